@@ -0,0 +1,61 @@
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Capture build metadata for `goose version`
+fn main() {
+    println!("cargo:rerun-if-env-changed=GOOSE_GIT_COMMIT");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    let git_dir = Path::new("../../.git");
+    let git_head = git_dir.join("HEAD");
+    if git_head.exists() {
+        println!("cargo:rerun-if-changed={}", git_head.display());
+    }
+    // HEAD only changes on checkout; a new commit moves the branch it points to, which lives
+    // either in its own file under refs/ or in packed-refs
+    if let Some(branch_ref) = std::fs::read_to_string(&git_head)
+        .ok()
+        .and_then(|head| head.trim().strip_prefix("ref: ").map(str::to_string))
+    {
+        let branch_ref = git_dir.join(branch_ref);
+        if branch_ref.exists() {
+            println!("cargo:rerun-if-changed={}", branch_ref.display());
+        }
+    }
+    let packed_refs = git_dir.join("packed-refs");
+    if packed_refs.exists() {
+        println!("cargo:rerun-if-changed={}", packed_refs.display());
+    }
+
+    let commit = std::env::var("GOOSE_GIT_COMMIT")
+        .ok()
+        .or_else(|| command_output("git", &["rev-parse", "HEAD"]))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let build_timestamp = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or_default()
+        });
+
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version =
+        command_output(&rustc, &["--version"]).unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=GOOSE_GIT_COMMIT={}", commit);
+    println!("cargo:rustc-env=GOOSE_BUILD_TIMESTAMP={}", build_timestamp);
+    println!("cargo:rustc-env=GOOSE_RUSTC_VERSION={}", rustc_version);
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
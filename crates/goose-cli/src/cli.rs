@@ -1,12 +1,16 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Args, Parser, Subcommand};
 
 use goose::config::{Config, ExtensionConfig};
 
 use crate::commands::acp::run_acp_agent;
+use crate::commands::ask::handle_ask;
+use crate::commands::batch::handle_batch;
 use crate::commands::bench::agent_generator;
 use crate::commands::configure::handle_configure;
+use crate::commands::extension::handle_extension_add;
 use crate::commands::info::handle_info;
+use crate::commands::keys::{handle_keys_clear, handle_keys_list};
 use crate::commands::project::{handle_project_default, handle_projects_interactive};
 use crate::commands::recipe::{handle_deeplink, handle_list, handle_open, handle_validate};
 
@@ -16,9 +20,12 @@ use crate::commands::schedule::{
     handle_schedule_sessions,
 };
 use crate::commands::session::{handle_session_list, handle_session_remove};
+use crate::commands::version::handle_version;
 use crate::recipes::extract_from_cli::extract_recipe_info_from_cli;
 use crate::recipes::recipe::{explain_recipe, render_recipe_as_yaml};
-use crate::session::{build_session, SessionBuilderConfig, SessionSettings};
+use crate::session::{
+    build_session, parse_message_history, termination_signal, SessionBuilderConfig, SessionSettings,
+};
 use goose::session::session_manager::SessionType;
 use goose::session::SessionManager;
 use goose_bench::bench_config::BenchRunConfig;
@@ -160,6 +167,39 @@ async fn lookup_session_id(identifier: Identifier) -> Result<String> {
     }
 }
 
+/// Find a session by ID, falling back to its name when no session has that ID
+async fn resolve_session_id(id_or_name: &str) -> Result<String> {
+    let sessions = SessionManager::list_sessions().await?;
+    sessions
+        .iter()
+        .find(|s| s.id == id_or_name)
+        .or_else(|| sessions.iter().find(|s| s.name == id_or_name))
+        .map(|s| s.id.clone())
+        .ok_or_else(|| anyhow::anyhow!("No session found with ID or name '{}'", id_or_name))
+}
+
+fn parse_compact_threshold(s: &str) -> Result<f64, String> {
+    let threshold: f64 = s.parse().map_err(|_| format!("invalid threshold: {}", s))?;
+    if threshold > 0.0 && threshold < 1.0 {
+        Ok(threshold)
+    } else {
+        Err(format!(
+            "threshold must be between 0 and 1 (exclusive), got {}",
+            threshold
+        ))
+    }
+}
+
+fn parse_context_limit(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(limit) if limit > 0 => Ok(limit),
+        _ => Err(format!(
+            "context limit must be a positive number of tokens, got {}",
+            s
+        )),
+    }
+}
+
 fn parse_key_val(s: &str) -> Result<(String, String), String> {
     match s.split_once('=') {
         Some((key, value)) => Ok((key.to_string(), value.to_string())),
@@ -229,6 +269,18 @@ enum SessionCommand {
         )]
         format: String,
     },
+    #[command(about = "Fork a session into a new, independent session")]
+    Fork {
+        #[arg(
+            long = "from",
+            value_name = "NAME",
+            help = "ID or name of the session to fork"
+        )]
+        from: String,
+
+        #[arg(long = "to", value_name = "NAME", help = "Name for the new session")]
+        to: String,
+    },
     #[command(name = "diagnostics")]
     Diagnostics {
         /// Session identifier for generating diagnostics
@@ -354,6 +406,78 @@ pub enum BenchCommand {
     },
 }
 
+#[derive(Subcommand)]
+enum ExtensionCommand {
+    /// Add an extension to the config without the interactive configure flow
+    #[command(about = "Add a stdio or SSE extension")]
+    Add {
+        /// Name of the extension
+        #[arg(long, help = "Name used to identify the extension")]
+        name: String,
+
+        /// Command that starts a stdio server
+        #[arg(
+            long,
+            value_name = "COMMAND",
+            help = "Command that starts a stdio server, quoted like a shell command (e.g. 'uvx mcp-server-git')",
+            required_unless_present = "sse",
+            conflicts_with = "sse"
+        )]
+        cmd: Option<String>,
+
+        /// Extra arguments for the stdio command
+        #[arg(
+            long = "arg",
+            value_name = "ARG",
+            help = "Argument appended to --cmd (can be specified multiple times)",
+            action = clap::ArgAction::Append,
+            allow_hyphen_values = true
+        )]
+        args: Vec<String>,
+
+        /// Environment variables for the extension
+        #[arg(
+            long = "env",
+            value_name = "KEY=VALUE",
+            help = "Environment variable for the extension, its value stored as a secret (can be specified multiple times)",
+            action = clap::ArgAction::Append,
+            value_parser = parse_key_val
+        )]
+        envs: Vec<(String, String)>,
+
+        /// URL of an SSE server
+        #[arg(long, value_name = "URL", help = "URL of an SSE server")]
+        sse: Option<String>,
+
+        /// Timeout in seconds
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            help = "Timeout for the extension in seconds"
+        )]
+        timeout: Option<u64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum KeysCommand {
+    /// List the names of stored keys
+    #[command(about = "List the names of keys goose has stored (values are never shown)")]
+    List {},
+
+    /// Remove a stored key
+    #[command(about = "Remove a key goose has stored")]
+    Clear {
+        /// Name of the key to remove
+        #[arg(help = "Name of the key to remove, as shown by `goose keys list`")]
+        name: String,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long, help = "Clear the key without asking for confirmation")]
+        yes: bool,
+    },
+}
+
 #[derive(Subcommand)]
 enum RecipeCommand {
     /// Validate a recipe file
@@ -418,6 +542,28 @@ enum Command {
         verbose: bool,
     },
 
+    /// Display version and build information
+    #[command(about = "Display goose version and build information")]
+    Version {
+        /// Output as JSON
+        #[arg(long, help = "Output version information as JSON")]
+        json: bool,
+    },
+
+    /// Manage extensions
+    #[command(about = "Manage goose extensions", alias = "system")]
+    Extension {
+        #[command(subcommand)]
+        command: ExtensionCommand,
+    },
+
+    /// Manage the keys goose stores in the keyring
+    #[command(about = "List or clear the keys goose stores in the keyring")]
+    Keys {
+        #[command(subcommand)]
+        command: KeysCommand,
+    },
+
     /// Manage system prompts and behaviors
     #[command(about = "Run one of the mcp servers bundled with goose")]
     Mcp { name: String },
@@ -520,6 +666,76 @@ enum Command {
             value_delimiter = ','
         )]
         builtins: Vec<String>,
+
+        /// End the session after a period without input
+        #[arg(
+            long = "idle-timeout",
+            value_name = "SECONDS",
+            help = "End the session after this many seconds without user input",
+            long_help = "Gracefully shut down the session, closing extensions, if no input arrives within the given number of seconds. Disabled by default."
+        )]
+        idle_timeout: Option<u64>,
+
+        /// Auto-compact threshold for this session
+        #[arg(
+            long = "compact-threshold",
+            value_name = "RATIO",
+            value_parser = parse_compact_threshold,
+            help = "Summarize the conversation once context usage crosses this ratio (e.g. 0.7)",
+            long_help = "Automatically compact the conversation between turns once context usage exceeds this fraction of the model's context limit. Overrides GOOSE_AUTO_COMPACT_THRESHOLD for this session."
+        )]
+        compact_threshold: Option<f64>,
+
+        /// Retry budget for each turn
+        #[arg(
+            long = "turn-budget",
+            value_name = "RETRIES",
+            help = "Maximum retries per turn across provider requests and recovery",
+            long_help = "Cap the total number of retries a single turn may spend on transient provider errors, context recovery, empty responses and recipe retries. Once spent, the turn ends with an error. Overrides GOOSE_TURN_RETRY_BUDGET for this session."
+        )]
+        turn_budget: Option<usize>,
+
+        /// Render assistant thinking blocks
+        #[arg(
+            long = "show-thinking",
+            help = "Show the model's thinking (dimmed) for reasoning models",
+            long_help = "Render thinking blocks from reasoning models, dimmed, alongside responses. Hidden by default; thinking is kept in the session history either way."
+        )]
+        show_thinking: bool,
+
+        /// Take provider settings from the environment only
+        #[arg(
+            long = "profile-from-env",
+            help = "Configure goose from environment variables only, ignoring the config file",
+            long_help = "Take the provider, model and the provider's keys from environment variables (GOOSE_PROVIDER, GOOSE_MODEL, e.g. OPENAI_API_KEY) without reading or writing the config file or keyring. This is picked automatically when no provider is configured and these variables are set."
+        )]
+        profile_from_env: bool,
+
+        /// Abbreviate tool descriptions sent to the provider
+        #[arg(
+            long = "compact-tool-descriptions",
+            help = "Send only the first sentence of each tool description to the model",
+            long_help = "Trim each tool description to its first sentence (at most 200 characters) in requests to the model to save context. Tools listed locally keep their full descriptions. Can also be enabled with GOOSE_COMPACT_TOOL_DESCRIPTIONS=true."
+        )]
+        compact_tool_descriptions: bool,
+
+        /// Wait for complete responses instead of streaming them
+        #[arg(
+            long = "no-stream",
+            help = "Show responses only once complete instead of streaming them",
+            long_help = "Use the provider's non-streaming completion so only whole messages are produced. Useful for terminals or pipelines that misbehave with partial output. Can also be enabled with GOOSE_NO_STREAM=true."
+        )]
+        no_stream: bool,
+
+        /// Override the model's context window
+        #[arg(
+            long = "context-limit",
+            value_name = "TOKENS",
+            value_parser = parse_context_limit,
+            help = "Context window of the model in tokens, overriding the detected limit",
+            long_help = "Set the model's context window for this session, for deployments such as gateways where the limit detected from the model name is wrong. Truncation and compaction budgets use this limit. Overrides GOOSE_CONTEXT_LIMIT."
+        )]
+        context_limit: Option<usize>,
     },
 
     /// Open the last project directory
@@ -556,6 +772,15 @@ enum Command {
         )]
         input_text: Option<String>,
 
+        /// Read a JSON array of messages from stdin
+        #[arg(
+            long = "stdin-json",
+            help = "Read a JSON array of messages from stdin as the conversation to continue",
+            long_help = "Parse stdin as a JSON array of goose messages, including prior tool calls and responses. The last message must be from the user; goose replies to it with the earlier messages as history.",
+            conflicts_with_all = ["instructions", "input_text", "recipe", "interactive"]
+        )]
+        stdin_json: bool,
+
         /// Additional system prompt to customize agent behavior
         #[arg(
             long = "system",
@@ -727,16 +952,28 @@ enum Command {
         )]
         additional_sub_recipes: Vec<String>,
 
-        /// Output format (text, json)
+        /// Output format (text, json, jsonl)
         #[arg(
             long = "output-format",
+            visible_alias = "output",
             value_name = "FORMAT",
-            help = "Output format (text, json)",
+            help = "Output format (text, json, jsonl)",
+            long_help = "Output format. json prints the whole conversation as one document when the run ends; jsonl prints each message as a JSON line as it arrives, followed by the turn's token usage.",
             default_value = "text",
-            value_parser = clap::builder::PossibleValuesParser::new(["text", "json"])
+            value_parser = clap::builder::PossibleValuesParser::new(["text", "json", "jsonl"])
         )]
         output_format: String,
 
+        /// Write only the final assistant message to a file
+        #[arg(
+            long = "output-file",
+            value_name = "FILE",
+            help = "Write the text of the final assistant message to FILE",
+            long_help = "Write just the text of the final assistant message to FILE, without tool calls or their results. Progress still streams to the console. Nothing is written if the run fails, is interrupted or ends without an answer.",
+            conflicts_with = "interactive"
+        )]
+        output_file: Option<PathBuf>,
+
         /// Provider to use for this run (overrides environment variable)
         #[arg(
             long = "provider",
@@ -754,6 +991,152 @@ enum Command {
             long_help = "Override the GOOSE_MODEL environment variable for this run. The model must be supported by the specified provider."
         )]
         model: Option<String>,
+
+        /// Auto-compact threshold for this session
+        #[arg(
+            long = "compact-threshold",
+            value_name = "RATIO",
+            value_parser = parse_compact_threshold,
+            help = "Summarize the conversation once context usage crosses this ratio (e.g. 0.7)",
+            long_help = "Automatically compact the conversation between turns once context usage exceeds this fraction of the model's context limit. Overrides GOOSE_AUTO_COMPACT_THRESHOLD for this session."
+        )]
+        compact_threshold: Option<f64>,
+
+        /// Retry budget for each turn
+        #[arg(
+            long = "turn-budget",
+            value_name = "RETRIES",
+            help = "Maximum retries per turn across provider requests and recovery",
+            long_help = "Cap the total number of retries a single turn may spend on transient provider errors, context recovery, empty responses and recipe retries. Once spent, the turn ends with an error. Overrides GOOSE_TURN_RETRY_BUDGET for this session."
+        )]
+        turn_budget: Option<usize>,
+
+        /// Render assistant thinking blocks
+        #[arg(
+            long = "show-thinking",
+            help = "Show the model's thinking (dimmed) for reasoning models",
+            long_help = "Render thinking blocks from reasoning models, dimmed, alongside responses. Hidden by default; thinking is kept in the session history either way."
+        )]
+        show_thinking: bool,
+
+        /// Take provider settings from the environment only
+        #[arg(
+            long = "profile-from-env",
+            help = "Configure goose from environment variables only, ignoring the config file",
+            long_help = "Take the provider, model and the provider's keys from environment variables (GOOSE_PROVIDER, GOOSE_MODEL, e.g. OPENAI_API_KEY) without reading or writing the config file or keyring. This is picked automatically when no provider is configured and these variables are set."
+        )]
+        profile_from_env: bool,
+
+        /// Abbreviate tool descriptions sent to the provider
+        #[arg(
+            long = "compact-tool-descriptions",
+            help = "Send only the first sentence of each tool description to the model",
+            long_help = "Trim each tool description to its first sentence (at most 200 characters) in requests to the model to save context. Tools listed locally keep their full descriptions. Can also be enabled with GOOSE_COMPACT_TOOL_DESCRIPTIONS=true."
+        )]
+        compact_tool_descriptions: bool,
+
+        /// Wait for complete responses instead of streaming them
+        #[arg(
+            long = "no-stream",
+            help = "Show responses only once complete instead of streaming them",
+            long_help = "Use the provider's non-streaming completion so only whole messages are produced. Useful for terminals or pipelines that misbehave with partial output. Can also be enabled with GOOSE_NO_STREAM=true."
+        )]
+        no_stream: bool,
+
+        /// Override the model's context window
+        #[arg(
+            long = "context-limit",
+            value_name = "TOKENS",
+            value_parser = parse_context_limit,
+            help = "Context window of the model in tokens, overriding the detected limit",
+            long_help = "Set the model's context window for this session, for deployments such as gateways where the limit detected from the model name is wrong. Truncation and compaction budgets use this limit. Overrides GOOSE_CONTEXT_LIMIT."
+        )]
+        context_limit: Option<usize>,
+
+        /// Keep the transcript when the run fails
+        #[arg(
+            long = "save-partial-on-error",
+            help = "Save the transcript so far to the session if the run fails",
+            long_help = "If the run fails part way, for example because the provider returns an error, save the messages so far to the session before exiting with the error, and print how to resume it. Has no effect with --no-session."
+        )]
+        save_partial_on_error: bool,
+
+        /// Serve repeated identical requests from a response cache
+        #[arg(
+            long = "cache-dir",
+            value_name = "DIR",
+            help = "Cache provider responses in DIR and reuse them for identical requests",
+            long_help = "Store each provider response on disk, keyed by the system prompt, messages, tools and model settings, and return it without calling the provider when the same request is made again. Useful for repeated CI or eval runs. Cached responses report zero token usage. Overrides GOOSE_RESPONSE_CACHE."
+        )]
+        cache_dir: Option<PathBuf>,
+
+        /// Print the rendered system prompt and exit
+        #[arg(
+            long = "dump-system-prompt",
+            help = "Print the fully rendered system prompt and exit",
+            long_help = "Build the agent as the run would, with the selected provider, extensions and --system instructions, then print the system prompt it would send and exit without calling the model. No session is saved and no instructions are required."
+        )]
+        dump_system_prompt: bool,
+    },
+
+    /// Ask a one-off question without keeping a session
+    #[command(
+        about = "Ask a one-off question and print the answer",
+        long_about = "Send a single message to a fresh agent, print the answer and exit. No extensions are loaded and no session is kept."
+    )]
+    Ask {
+        /// The question to ask
+        #[arg(value_name = "QUESTION", help = "The question to ask")]
+        prompt: String,
+
+        /// Provider to use for this question (overrides environment variable)
+        #[arg(
+            long = "provider",
+            value_name = "PROVIDER",
+            help = "Specify the LLM provider to use (e.g., 'openai', 'anthropic')"
+        )]
+        provider: Option<String>,
+
+        /// Model to use for this question (overrides environment variable)
+        #[arg(
+            long = "model",
+            value_name = "MODEL",
+            help = "Specify the model to use (e.g., 'gpt-4o', 'claude-sonnet-4-20250514')"
+        )]
+        model: Option<String>,
+    },
+
+    /// Run a batch of prompts from a JSONL file
+    #[command(about = "Run a batch of prompts from a JSONL file, each in a fresh session")]
+    Batch {
+        /// JSONL file with one task per line
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            help = "JSONL file with one task per line",
+            long_help = "JSONL file with one task per line, e.g. {\"id\": \"t1\", \"prompt\": \"...\"}. The id is optional and defaults to the line number."
+        )]
+        input: PathBuf,
+
+        /// File to write results to
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            help = "JSONL file to write results to as tasks complete"
+        )]
+        output: PathBuf,
+
+        /// Maximum number of tasks to run at once
+        #[arg(
+            short,
+            long,
+            default_value = "4",
+            value_parser = clap::value_parser!(u16).range(1..),
+            help = "Maximum number of tasks to run at once"
+        )]
+        concurrency: u16,
     },
 
     /// Recipe utilities for validation and deeplinking
@@ -827,6 +1210,7 @@ enum Command {
 #[derive(clap::ValueEnum, Clone, Debug)]
 enum CliProviderVariant {
     OpenAi,
+    AzureOpenAi,
     Databricks,
     Ollama,
 }
@@ -856,12 +1240,17 @@ pub async fn cli() -> anyhow::Result<()> {
     let command_name = match &cli.command {
         Some(Command::Configure {}) => "configure",
         Some(Command::Info { .. }) => "info",
+        Some(Command::Extension { .. }) => "extension",
+        Some(Command::Keys { .. }) => "keys",
+        Some(Command::Version { .. }) => "version",
         Some(Command::Mcp { .. }) => "mcp",
         Some(Command::Acp {}) => "acp",
         Some(Command::Session { .. }) => "session",
         Some(Command::Project {}) => "project",
         Some(Command::Projects) => "projects",
         Some(Command::Run { .. }) => "run",
+        Some(Command::Ask { .. }) => "ask",
+        Some(Command::Batch { .. }) => "batch",
         Some(Command::Schedule { .. }) => "schedule",
         Some(Command::Update { .. }) => "update",
         Some(Command::Bench { .. }) => "bench",
@@ -883,6 +1272,23 @@ pub async fn cli() -> anyhow::Result<()> {
         Some(Command::Info { verbose }) => {
             handle_info(verbose)?;
         }
+        Some(Command::Version { json }) => {
+            handle_version(json)?;
+        }
+        Some(Command::Extension { command }) => match command {
+            ExtensionCommand::Add {
+                name,
+                cmd,
+                args,
+                envs,
+                sse,
+                timeout,
+            } => handle_extension_add(name, cmd, args, envs, sse, timeout)?,
+        },
+        Some(Command::Keys { command }) => match command {
+            KeysCommand::List {} => handle_keys_list()?,
+            KeysCommand::Clear { name, yes } => handle_keys_clear(&name, yes)?,
+        },
         Some(Command::Mcp { name }) => {
             crate::logging::setup_logging(Some(&format!("mcp-{name}")), None)?;
             goose_mcp::mcp_server_runner::run_mcp_server(&name).await?;
@@ -902,6 +1308,14 @@ pub async fn cli() -> anyhow::Result<()> {
             remote_extensions,
             streamable_http_extensions,
             builtins,
+            idle_timeout,
+            compact_threshold,
+            turn_budget,
+            show_thinking,
+            profile_from_env,
+            compact_tool_descriptions,
+            no_stream,
+            context_limit,
         }) => {
             return match command {
                 Some(SessionCommand::List {
@@ -945,6 +1359,11 @@ pub async fn cli() -> anyhow::Result<()> {
                     .await?;
                     Ok(())
                 }
+                Some(SessionCommand::Fork { from, to }) => {
+                    let session_id = resolve_session_id(&from).await?;
+                    crate::commands::session::handle_session_fork(&session_id, to).await?;
+                    Ok(())
+                }
                 Some(SessionCommand::Diagnostics { identifier, output }) => {
                     let session_id = if let Some(id) = identifier {
                         lookup_session_id(id).await?
@@ -1009,6 +1428,16 @@ pub async fn cli() -> anyhow::Result<()> {
                         final_output_response: None,
                         retry_config: None,
                         output_format: "text".to_string(),
+                        idle_timeout,
+                        compact_threshold,
+                        turn_budget,
+                        show_thinking,
+                        profile_from_env,
+                        compact_tool_descriptions,
+                        no_stream,
+                        context_limit,
+                        save_partial_on_error: false,
+                        cache_dir: None,
                     })
                     .await;
 
@@ -1017,7 +1446,12 @@ pub async fn cli() -> anyhow::Result<()> {
                         session.render_message_history();
                     }
 
-                    let result = session.interactive(None).await;
+                    let result = tokio::select! {
+                        result = session.interactive(None) => result,
+                        signal = termination_signal() => Err(signal),
+                    };
+                    session.display_usage_report().await;
+                    session.shutdown().await;
 
                     let session_duration = session_start.elapsed();
                     let exit_type = if result.is_ok() { "normal" } else { "error" };
@@ -1070,6 +1504,7 @@ pub async fn cli() -> anyhow::Result<()> {
         Some(Command::Run {
             instructions,
             input_text,
+            stdin_json,
             recipe,
             system,
             interactive,
@@ -1090,15 +1525,39 @@ pub async fn cli() -> anyhow::Result<()> {
             quiet,
             additional_sub_recipes,
             output_format,
+            output_file,
             provider,
             model,
+            compact_threshold,
+            turn_budget,
+            show_thinking,
+            profile_from_env,
+            compact_tool_descriptions,
+            no_stream,
+            context_limit,
+            save_partial_on_error,
+            cache_dir,
+            dump_system_prompt,
         }) => {
+            // Dumping the prompt never talks to the model, so there is nothing worth saving
+            let no_session = no_session || dump_system_prompt;
+
+            let stdin_messages = if stdin_json {
+                let mut input = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut input)
+                    .context("Failed to read messages from stdin")?;
+                Some(parse_message_history(&input)?)
+            } else {
+                None
+            };
+
             let (input_config, recipe_info) = match (instructions, input_text, recipe) {
                 (Some(file), _, _) if file == "-" => {
                     let mut input = String::new();
                     std::io::stdin()
                         .read_to_string(&mut input)
-                        .expect("Failed to read from stdin");
+                        .context("Failed to read instructions from stdin")?;
 
                     let input_config = InputConfig {
                         contents: Some(input),
@@ -1178,8 +1637,24 @@ pub async fn cli() -> anyhow::Result<()> {
                     )?;
                     (input_config, Some(recipe_info))
                 }
+                (None, None, None) if stdin_messages.is_some() => {
+                    let input_config = InputConfig {
+                        contents: None,
+                        extensions_override: None,
+                        additional_system_prompt: system,
+                    };
+                    (input_config, None)
+                }
+                (None, None, None) if dump_system_prompt => {
+                    let input_config = InputConfig {
+                        contents: None,
+                        extensions_override: None,
+                        additional_system_prompt: system,
+                    };
+                    (input_config, None)
+                }
                 (None, None, None) => {
-                    eprintln!("Error: Must provide either --instructions (-i), --text (-t), or --recipe. Use -i - for stdin.");
+                    eprintln!("Error: Must provide either --instructions (-i), --text (-t), --stdin-json, or --recipe. Use -i - for stdin.");
                     std::process::exit(1);
                 }
             };
@@ -1224,12 +1699,35 @@ pub async fn cli() -> anyhow::Result<()> {
                     .and_then(|r| r.final_output_response.clone()),
                 retry_config: recipe_info.as_ref().and_then(|r| r.retry_config.clone()),
                 output_format,
+                idle_timeout: None,
+                compact_threshold,
+                turn_budget,
+                show_thinking,
+                profile_from_env,
+                compact_tool_descriptions,
+                no_stream,
+                context_limit,
+                save_partial_on_error,
+                cache_dir,
             })
             .await;
 
+            if dump_system_prompt {
+                let result = session.system_prompt().await;
+                session.shutdown().await;
+                println!("{}", result?);
+                return Ok(());
+            }
+
             if interactive {
-                session.interactive(input_config.contents).await?;
-            } else if let Some(contents) = input_config.contents {
+                let result = tokio::select! {
+                    result = session.interactive(input_config.contents) => result,
+                    signal = termination_signal() => Err(signal),
+                };
+                session.display_usage_report().await;
+                session.shutdown().await;
+                result?;
+            } else if stdin_messages.is_some() || input_config.contents.is_some() {
                 let session_start = std::time::Instant::now();
                 let session_type = if recipe_info.is_some() {
                     "recipe"
@@ -1244,7 +1742,26 @@ pub async fn cli() -> anyhow::Result<()> {
                     "Headless session started"
                 );
 
-                let result = session.headless(contents).await;
+                let run = async {
+                    match stdin_messages {
+                        Some(messages) => session.headless_messages(messages).await,
+                        None => {
+                            session
+                                .headless(input_config.contents.unwrap_or_default())
+                                .await
+                        }
+                    }
+                };
+                let result = tokio::select! {
+                    result = run => result,
+                    signal = termination_signal() => Err(signal),
+                };
+                let result = match (result, &output_file) {
+                    (Ok(()), Some(path)) => session.write_final_answer(path),
+                    (result, _) => result,
+                };
+                session.display_usage_report().await;
+                session.shutdown().await;
 
                 let session_duration = session_start.elapsed();
                 let exit_type = if result.is_ok() { "normal" } else { "error" };
@@ -1289,6 +1806,22 @@ pub async fn cli() -> anyhow::Result<()> {
 
             return Ok(());
         }
+        Some(Command::Ask {
+            prompt,
+            provider,
+            model,
+        }) => {
+            handle_ask(prompt, provider, model).await?;
+            return Ok(());
+        }
+        Some(Command::Batch {
+            input,
+            output,
+            concurrency,
+        }) => {
+            handle_batch(&input, &output, concurrency as usize).await?;
+            return Ok(());
+        }
         Some(Command::Schedule { command }) => {
             match command {
                 SchedulerCommand::Add {
@@ -1408,12 +1941,78 @@ pub async fn cli() -> anyhow::Result<()> {
                     final_output_response: None,
                     retry_config: None,
                     output_format: "text".to_string(),
+                    idle_timeout: None,
+                    compact_threshold: None,
+                    turn_budget: None,
+                    show_thinking: false,
+                    profile_from_env: false,
+                    compact_tool_descriptions: false,
+                    no_stream: false,
+                    context_limit: None,
+                    save_partial_on_error: false,
+                    cache_dir: None,
                 })
                 .await;
-                session.interactive(None).await?;
-                Ok(())
+                let result = tokio::select! {
+                    result = session.interactive(None) => result,
+                    signal = termination_signal() => Err(signal),
+                };
+                session.display_usage_report().await;
+                session.shutdown().await;
+                result
             };
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::isolate_goose_paths;
+
+    #[tokio::test]
+    async fn test_resolve_session_id_prefers_id_over_name() {
+        isolate_goose_paths();
+        let working_dir = std::env::temp_dir();
+        let original = SessionManager::create_session(
+            working_dir.clone(),
+            "original".into(),
+            SessionType::User,
+        )
+        .await
+        .unwrap();
+        // A session named after the other's ID must not shadow it
+        let impostor =
+            SessionManager::create_session(working_dir, original.id.clone(), SessionType::User)
+                .await
+                .unwrap();
+
+        assert_eq!(resolve_session_id(&original.id).await.unwrap(), original.id);
+        assert_eq!(resolve_session_id(&impostor.id).await.unwrap(), impostor.id);
+        assert!(resolve_session_id("no such session").await.is_err());
+
+        for session in [original, impostor] {
+            SessionManager::delete_session(&session.id).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_session_id_falls_back_to_name() {
+        isolate_goose_paths();
+        let session = SessionManager::create_session(
+            std::env::temp_dir(),
+            "fork me by name".into(),
+            SessionType::User,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            resolve_session_id("fork me by name").await.unwrap(),
+            session.id
+        );
+
+        SessionManager::delete_session(&session.id).await.unwrap();
+    }
+}
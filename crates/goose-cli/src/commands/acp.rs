@@ -227,6 +227,20 @@ impl GooseAcpAgent {
             toolshim: false,
             toolshim_model: None,
             fast_model: None,
+            fallback_model: None,
+            parallel_tool_calls: None,
+            strict_tools: false,
+            logit_bias: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            tool_result_join: None,
+            service_tier: None,
+            n: None,
+            prefill: None,
+            request_timeout: None,
+            requests_per_minute: None,
+            top_p: None,
+            stop: None,
         };
         let provider = create(&provider_name, model_config).await?;
 
@@ -670,6 +684,8 @@ impl acp::Agent for GooseAcpAgent {
             schedule_id: None,
             max_turns: None,
             retry_config: None,
+            compact_threshold: None,
+            turn_budget: None,
         };
 
         let mut stream = self
@@ -0,0 +1,111 @@
+use crate::commands::batch::collect_reply_text;
+use anyhow::Result;
+use futures::{stream, StreamExt};
+use goose::agents::Agent;
+use goose::config::Config;
+use goose::conversation::message::Message;
+use goose::model::ModelConfig;
+use goose::providers::create;
+
+/// Send `prompt` to the agent's provider as a single turn and return the final assistant
+/// text. The conversation only lives in memory, so nothing is added to the session list.
+pub async fn ask_once(agent: &Agent, prompt: &str) -> Result<String> {
+    let provider = agent.provider().await?;
+    let system_prompt = agent.get_system_prompt(&std::env::current_dir()?).await?;
+    let messages = [Message::user().with_text(prompt)];
+
+    if provider.supports_streaming() {
+        let chunks = provider.stream(&system_prompt, &messages, &[]).await?;
+        collect_reply_text(chunks.filter_map(|chunk| async move {
+            chunk
+                .map(|(message, _usage)| message)
+                .map_err(anyhow::Error::from)
+                .transpose()
+        }))
+        .await
+    } else {
+        let (message, _usage) = provider.complete(&system_prompt, &messages, &[]).await?;
+        collect_reply_text(stream::iter([anyhow::Ok(message)])).await
+    }
+}
+
+/// Answer a one-off question with the configured provider and model, or the given
+/// overrides, without extensions and without keeping a session.
+pub async fn handle_ask(
+    prompt: String,
+    provider: Option<String>,
+    model: Option<String>,
+) -> Result<()> {
+    let config = Config::global();
+    let provider_name = match provider {
+        Some(provider) => provider,
+        None => config.get_goose_provider()?,
+    };
+    let model_name = match model {
+        Some(model) => model,
+        None => config.get_goose_model()?,
+    };
+    let provider = create(&provider_name, ModelConfig::new(&model_name)?).await?;
+
+    let agent = Agent::new();
+    agent.update_provider(provider).await?;
+
+    let answer = ask_once(&agent, &prompt).await;
+    agent.shutdown().await;
+    println!("{}", answer?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{isolate_goose_paths, MockProvider};
+    use goose::providers::base::Usage;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn test_ask_once_answers_without_a_session() {
+        isolate_goose_paths();
+        // Records the session the request was made in, if any
+        let session_ids = Arc::new(Mutex::new(Vec::new()));
+        let provider = {
+            let session_ids = session_ids.clone();
+            MockProvider::new(move |messages| {
+                session_ids
+                    .lock()
+                    .unwrap()
+                    .push(goose::session_context::current_session_id());
+                let question = messages
+                    .last()
+                    .map(|m| m.as_concat_text())
+                    .unwrap_or_default();
+                Ok((
+                    Message::assistant().with_text(format!("answer to: {}", question)),
+                    Usage::default(),
+                ))
+            })
+        };
+        let agent = Agent::new();
+        agent.update_provider(Arc::new(provider)).await.unwrap();
+
+        let answer = ask_once(&agent, "what is 2 + 2?").await.unwrap();
+        assert_eq!(answer, "answer to: what is 2 + 2?");
+        assert_eq!(*session_ids.lock().unwrap(), vec![None]);
+    }
+
+    #[tokio::test]
+    async fn test_ask_once_joins_streamed_answer() {
+        isolate_goose_paths();
+        // Streams its answer in two pieces sharing one message id
+        let chunks = ["four, ", "as always"]
+            .map(|text| Message::assistant().with_text(text).with_id("msg_1"))
+            .to_vec();
+        let provider =
+            MockProvider::new(|_| unreachable!("the reply is streamed")).with_stream(chunks);
+        let agent = Agent::new();
+        agent.update_provider(Arc::new(provider)).await.unwrap();
+
+        let answer = ask_once(&agent, "what is 2 + 2?").await.unwrap();
+        assert_eq!(answer, "four, as always");
+    }
+}
@@ -0,0 +1,294 @@
+use crate::session::final_assistant_text;
+use anyhow::{Context, Result};
+use futures::{stream, Stream, StreamExt};
+use goose::agents::{Agent, AgentEvent, SessionConfig};
+use goose::config::{get_enabled_extensions, Config};
+use goose::conversation::message::Message;
+use goose::conversation::Conversation;
+use goose::model::ModelConfig;
+use goose::providers::create;
+use goose::session::session_manager::SessionType;
+use goose::session::SessionManager;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/// One line of the batch input file
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchTask {
+    /// Identifier echoed in the output; defaults to the 1-based line number
+    #[serde(default)]
+    pub id: Option<String>,
+    pub prompt: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BatchUsage {
+    pub input_tokens: Option<i32>,
+    pub output_tokens: Option<i32>,
+    pub total_tokens: Option<i32>,
+}
+
+/// What a task run produced on success
+#[derive(Debug, Clone, Default)]
+pub struct BatchOutcome {
+    pub session_id: Option<String>,
+    pub response: String,
+    pub usage: BatchUsage,
+}
+
+/// One line of the batch output file
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchResult {
+    pub id: String,
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub usage: BatchUsage,
+    pub duration_secs: f64,
+}
+
+fn read_tasks(input: &Path) -> Result<Vec<(String, BatchTask)>> {
+    let file = std::fs::File::open(input)
+        .with_context(|| format!("Failed to open batch input {}", input.display()))?;
+
+    let mut tasks = Vec::new();
+    for (idx, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let task: BatchTask = serde_json::from_str(&line)
+            .with_context(|| format!("Invalid task on line {} of {}", idx + 1, input.display()))?;
+        let id = task.id.clone().unwrap_or_else(|| (idx + 1).to_string());
+        tasks.push((id, task));
+    }
+    Ok(tasks)
+}
+
+/// Run `tasks` through `runner` with at most `concurrency` in flight, writing each result
+/// to `output` as soon as it completes. Failed tasks are recorded and do not stop the batch.
+pub async fn run_batch<F, Fut, W>(
+    tasks: Vec<(String, BatchTask)>,
+    concurrency: usize,
+    runner: F,
+    output: &mut W,
+) -> Result<Vec<BatchResult>>
+where
+    F: Fn(BatchTask) -> Fut,
+    Fut: Future<Output = Result<BatchOutcome>>,
+    W: Write,
+{
+    let mut results = stream::iter(tasks)
+        .map(|(id, task)| {
+            let run = runner(task);
+            async move {
+                let started = Instant::now();
+                let outcome = run.await;
+                let duration_secs = started.elapsed().as_secs_f64();
+                match outcome {
+                    Ok(outcome) => BatchResult {
+                        id,
+                        status: "ok",
+                        session_id: outcome.session_id,
+                        response: Some(outcome.response),
+                        error: None,
+                        usage: outcome.usage,
+                        duration_secs,
+                    },
+                    Err(e) => BatchResult {
+                        id,
+                        status: "error",
+                        session_id: None,
+                        response: None,
+                        error: Some(format!("{:#}", e)),
+                        usage: BatchUsage::default(),
+                        duration_secs,
+                    },
+                }
+            }
+        })
+        .buffer_unordered(concurrency.max(1));
+
+    let mut completed = Vec::new();
+    while let Some(result) = results.next().await {
+        writeln!(output, "{}", serde_json::to_string(&result)?)?;
+        output.flush()?;
+        completed.push(result);
+    }
+    Ok(completed)
+}
+
+/// Run a single prompt through a fresh agent, like `goose run --text`, collecting the reply.
+async fn run_task(task: BatchTask) -> Result<BatchOutcome> {
+    let config = Config::global();
+    let provider_name = config.get_goose_provider()?;
+    let model_name = config.get_goose_model()?;
+    let provider = create(&provider_name, ModelConfig::new(&model_name)?).await?;
+
+    let agent = Agent::new();
+    agent.update_provider(provider).await?;
+
+    // Every task starts its own extension processes, so close them whether or not it succeeded
+    let outcome = run_task_with_agent(&agent, &task).await;
+    agent.shutdown().await;
+    outcome
+}
+
+/// Join the messages of one reply and return the text of the last assistant message that
+/// has any. Streamed replies arrive in pieces sharing a message id, which push joins back up.
+pub async fn collect_reply_text(messages: impl Stream<Item = Result<Message>>) -> Result<String> {
+    let mut messages = std::pin::pin!(messages);
+    let mut conversation = Conversation::default();
+    while let Some(message) = messages.next().await {
+        conversation.push(message?);
+    }
+    Ok(final_assistant_text(conversation.messages()).unwrap_or_default())
+}
+
+async fn run_task_with_agent(agent: &Agent, task: &BatchTask) -> Result<BatchOutcome> {
+    for extension in get_enabled_extensions() {
+        agent.add_extension(extension).await?;
+    }
+
+    // Hidden keeps batch runs out of the user's session list
+    let session = SessionManager::create_session(
+        std::env::current_dir()?,
+        "Batch task".to_string(),
+        SessionType::Hidden,
+    )
+    .await?;
+    let session_config = SessionConfig {
+        id: session.id.clone(),
+        schedule_id: None,
+        max_turns: None,
+        retry_config: None,
+        compact_threshold: None,
+        turn_budget: None,
+    };
+
+    let user_message = Message::user().with_text(&task.prompt);
+    let stream = goose::session_context::with_session_id(Some(session.id.clone()), async {
+        agent.reply(user_message, session_config, None).await
+    })
+    .await?;
+    let response = collect_reply_text(stream.filter_map(|event| async move {
+        match event {
+            Ok(AgentEvent::Message(message)) => Some(Ok(message)),
+            Ok(_) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }))
+    .await?;
+
+    let session = SessionManager::get_session(&session.id, false).await?;
+    Ok(BatchOutcome {
+        session_id: Some(session.id),
+        response,
+        usage: BatchUsage {
+            input_tokens: session.accumulated_input_tokens,
+            output_tokens: session.accumulated_output_tokens,
+            total_tokens: session.accumulated_total_tokens,
+        },
+    })
+}
+
+pub async fn handle_batch(input: &Path, output: &Path, concurrency: usize) -> Result<()> {
+    let tasks = read_tasks(input)?;
+    let total = tasks.len();
+    let mut writer = std::io::BufWriter::new(
+        std::fs::File::create(output)
+            .with_context(|| format!("Failed to create batch output {}", output.display()))?,
+    );
+
+    let results = run_batch(tasks, concurrency, run_task, &mut writer).await?;
+    let failed = results.iter().filter(|r| r.status == "error").count();
+    eprintln!(
+        "Completed {} task(s), {} failed. Results written to {}",
+        total,
+        failed,
+        output.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_run_batch_bounds_concurrency_and_records_failures() {
+        let tasks: Vec<(String, BatchTask)> = (1..=6)
+            .map(|i| {
+                (
+                    i.to_string(),
+                    BatchTask {
+                        id: None,
+                        prompt: if i == 3 {
+                            "fail".into()
+                        } else {
+                            format!("task {i}")
+                        },
+                    },
+                )
+            })
+            .collect();
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let runner = |task: BatchTask| {
+            let in_flight = in_flight.clone();
+            let max_in_flight = max_in_flight.clone();
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                if task.prompt == "fail" {
+                    anyhow::bail!("provider unavailable");
+                }
+                Ok(BatchOutcome {
+                    session_id: None,
+                    response: format!("done: {}", task.prompt),
+                    usage: BatchUsage {
+                        input_tokens: Some(10),
+                        output_tokens: Some(5),
+                        total_tokens: Some(15),
+                    },
+                })
+            }
+        };
+
+        let mut output = Vec::new();
+        let results = run_batch(tasks, 2, runner, &mut output).await.unwrap();
+
+        assert_eq!(results.len(), 6);
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 6);
+
+        let failed = lines.iter().find(|l| l["id"] == "3").unwrap();
+        assert_eq!(failed["status"], "error");
+        assert_eq!(failed["error"], "provider unavailable");
+
+        let ok = lines.iter().find(|l| l["id"] == "1").unwrap();
+        assert_eq!(ok["status"], "ok");
+        assert_eq!(ok["response"], "done: task 1");
+        assert_eq!(ok["usage"]["total_tokens"], 15);
+    }
+}
@@ -56,6 +56,16 @@ pub async fn agent_generator(
         final_output_response: None,
         retry_config: None,
         output_format: "text".to_string(),
+        idle_timeout: None,
+        compact_threshold: None,
+        turn_budget: None,
+        show_thinking: false,
+        profile_from_env: false,
+        compact_tool_descriptions: false,
+        no_stream: false,
+        context_limit: None,
+        save_partial_on_error: false,
+        cache_dir: None,
     })
     .await;
 
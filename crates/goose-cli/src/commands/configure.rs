@@ -864,6 +864,7 @@ pub fn configure_extensions_dialog() -> anyhow::Result<()> {
                     timeout: Some(timeout),
                     bundled: None,
                     available_tools: Vec::new(),
+                    framing: Default::default(),
                 },
             });
 
@@ -0,0 +1,228 @@
+use anyhow::{bail, Result};
+use console::style;
+use goose::agents::extension::Envs;
+use goose::agents::ExtensionConfig;
+use goose::config::extensions::{get_extension_by_name, set_extension, ExtensionEntry};
+use goose::config::{Config, DEFAULT_EXTENSION_DESCRIPTION, DEFAULT_EXTENSION_TIMEOUT};
+use std::collections::HashMap;
+
+/// Build the extension described by `goose extension add` arguments: a stdio server when
+/// `cmd` is given (split like a shell would, its first word is the program, the rest and any
+/// `args` its arguments), or an SSE server at `sse`. `env_keys` names variables whose values
+/// are kept in the secret store.
+fn extension_config_from_args(
+    name: String,
+    cmd: Option<String>,
+    args: Vec<String>,
+    envs: Vec<(String, String)>,
+    env_keys: Vec<String>,
+    sse: Option<String>,
+    timeout: Option<u64>,
+) -> Result<ExtensionConfig> {
+    let envs = Envs::new(envs.into_iter().collect::<HashMap<_, _>>());
+    let timeout = Some(timeout.unwrap_or(DEFAULT_EXTENSION_TIMEOUT));
+    let description = DEFAULT_EXTENSION_DESCRIPTION.to_string();
+
+    match (cmd, sse) {
+        (Some(cmd), None) => {
+            let Some(words) = shlex::split(&cmd) else {
+                bail!("--cmd has unbalanced quotes");
+            };
+            let mut words = words.into_iter();
+            let Some(program) = words.next() else {
+                bail!("--cmd must not be empty");
+            };
+            Ok(ExtensionConfig::Stdio {
+                name,
+                description,
+                cmd: program,
+                args: words.chain(args).collect(),
+                envs,
+                env_keys,
+                timeout,
+                bundled: None,
+                available_tools: Vec::new(),
+                framing: Default::default(),
+            })
+        }
+        (None, Some(uri)) => {
+            if !args.is_empty() {
+                bail!("--arg can only be used with --cmd");
+            }
+            Ok(ExtensionConfig::Sse {
+                name,
+                description,
+                uri,
+                envs,
+                env_keys,
+                timeout,
+                bundled: None,
+                available_tools: Vec::new(),
+            })
+        }
+        _ => bail!("Specify exactly one of --cmd or --sse"),
+    }
+}
+
+/// Move `--env` values into the secret store, so they are not written to the config file in
+/// plain text. Returns the variables that could not be stored there, after warning about
+/// each, and the keys of those that were.
+fn store_env_secrets(envs: Vec<(String, String)>) -> (Vec<(String, String)>, Vec<String>) {
+    let config = Config::global();
+    let mut plain = Vec::new();
+    let mut env_keys = Vec::new();
+    for (key, value) in envs {
+        match config.set_secret(&key, &value) {
+            Ok(_) => env_keys.push(key),
+            Err(e) => {
+                eprintln!(
+                    "{}: could not store {} in the secret store ({}), saving it in plain text in the config file instead",
+                    style("Warning").yellow().italic(),
+                    key,
+                    e
+                );
+                plain.push((key, value));
+            }
+        }
+    }
+    (plain, env_keys)
+}
+
+pub fn handle_extension_add(
+    name: String,
+    cmd: Option<String>,
+    args: Vec<String>,
+    envs: Vec<(String, String)>,
+    sse: Option<String>,
+    timeout: Option<u64>,
+) -> Result<()> {
+    if get_extension_by_name(&name).is_some() {
+        bail!("An extension named '{}' already exists", name);
+    }
+    // Check the arguments before anything reaches the secret store, so a rejected command
+    // leaves no secrets behind
+    extension_config_from_args(
+        name.clone(),
+        cmd.clone(),
+        args.clone(),
+        Vec::new(),
+        Vec::new(),
+        sse.clone(),
+        timeout,
+    )?;
+    let (envs, env_keys) = store_env_secrets(envs);
+    let config = extension_config_from_args(name.clone(), cmd, args, envs, env_keys, sse, timeout)?;
+    set_extension(ExtensionEntry {
+        enabled: true,
+        config,
+    });
+    println!("Added extension {}", style(name).green());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stdio_extension_from_args() {
+        let config = extension_config_from_args(
+            "git".to_string(),
+            Some("uvx mcp-server-git --author 'goose bot'".to_string()),
+            vec!["--repository".to_string(), "/tmp/repo".to_string()],
+            vec![("GIT_AUTHOR".to_string(), "goose".to_string())],
+            vec!["GITHUB_TOKEN".to_string()],
+            None,
+            Some(60),
+        )
+        .unwrap();
+
+        match config {
+            ExtensionConfig::Stdio {
+                name,
+                cmd,
+                args,
+                envs,
+                env_keys,
+                timeout,
+                ..
+            } => {
+                assert_eq!(name, "git");
+                assert_eq!(cmd, "uvx");
+                assert_eq!(
+                    args,
+                    vec![
+                        "mcp-server-git",
+                        "--author",
+                        "goose bot",
+                        "--repository",
+                        "/tmp/repo"
+                    ]
+                );
+                assert_eq!(
+                    envs.get_env().get("GIT_AUTHOR").map(String::as_str),
+                    Some("goose")
+                );
+                assert_eq!(env_keys, vec!["GITHUB_TOKEN"]);
+                assert_eq!(timeout, Some(60));
+            }
+            other => panic!("Expected a stdio extension, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_sse_extension_from_args() {
+        let config = extension_config_from_args(
+            "remote".to_string(),
+            None,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            Some("http://localhost:8080/sse".to_string()),
+            None,
+        )
+        .unwrap();
+
+        match config {
+            ExtensionConfig::Sse {
+                name, uri, timeout, ..
+            } => {
+                assert_eq!(name, "remote");
+                assert_eq!(uri, "http://localhost:8080/sse");
+                assert_eq!(timeout, Some(DEFAULT_EXTENSION_TIMEOUT));
+            }
+            other => panic!("Expected an SSE extension, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_extension_from_args_requires_one_transport() {
+        let none = extension_config_from_args("x".into(), None, vec![], vec![], vec![], None, None);
+        assert!(none.is_err());
+
+        let sse_with_args = extension_config_from_args(
+            "x".into(),
+            None,
+            vec!["--flag".into()],
+            vec![],
+            vec![],
+            Some("http://localhost/sse".into()),
+            None,
+        );
+        assert!(sse_with_args
+            .unwrap_err()
+            .to_string()
+            .contains("--arg can only be used with --cmd"));
+
+        let unbalanced = extension_config_from_args(
+            "x".into(),
+            Some("npx 'server".into()),
+            vec![],
+            vec![],
+            vec![],
+            None,
+            None,
+        );
+        assert!(unbalanced.is_err());
+    }
+}
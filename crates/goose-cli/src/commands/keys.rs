@@ -0,0 +1,101 @@
+use anyhow::{bail, Result};
+use console::style;
+use goose::config::Config;
+
+/// Names of the secrets stored in `config`'s keyring (or secrets file)
+fn list_keys(config: &Config) -> Result<Vec<String>> {
+    Ok(config.secret_names()?)
+}
+
+/// Remove `name` from `config`'s secrets, failing if goose has no such key
+fn clear_key(config: &Config, name: &str) -> Result<()> {
+    if !config.secret_names()?.iter().any(|key| key == name) {
+        bail!("No stored key named '{}'", name);
+    }
+    config.delete_secret(name)?;
+    Ok(())
+}
+
+pub fn handle_keys_list() -> Result<()> {
+    let keys = list_keys(Config::global())?;
+    if keys.is_empty() {
+        println!("No keys stored.");
+        return Ok(());
+    }
+    for key in keys {
+        println!("{}", key);
+    }
+    Ok(())
+}
+
+pub fn handle_keys_clear(name: &str, yes: bool) -> Result<()> {
+    let config = Config::global();
+    if !list_keys(config)?.iter().any(|key| key == name) {
+        bail!("No stored key named '{}'", name);
+    }
+
+    if !yes
+        && !cliclack::confirm(format!("Clear the stored key '{}'?", name))
+            .initial_value(false)
+            .interact()?
+    {
+        println!("Key not cleared.");
+        return Ok(());
+    }
+
+    clear_key(config, name)?;
+    println!("Cleared key {}", style(name).green());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use tempfile::TempDir;
+
+    fn test_config(dir: &TempDir) -> Config {
+        Config::new_with_file_secrets(
+            dir.path().join("config.yaml"),
+            dir.path().join("secrets.yaml"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_list_keys_returns_names_only() {
+        let dir = TempDir::new().unwrap();
+        let config = test_config(&dir);
+        assert!(list_keys(&config).unwrap().is_empty());
+
+        config
+            .set_secret("OPENAI_API_KEY", &Value::String("sk-secret".into()))
+            .unwrap();
+        config
+            .set_secret("ANTHROPIC_API_KEY", &Value::String("sk-ant-secret".into()))
+            .unwrap();
+
+        let keys = list_keys(&config).unwrap();
+        assert_eq!(keys, vec!["ANTHROPIC_API_KEY", "OPENAI_API_KEY"]);
+        assert!(keys.iter().all(|key| !key.contains("secret")));
+    }
+
+    #[test]
+    fn test_clear_key() {
+        let dir = TempDir::new().unwrap();
+        let config = test_config(&dir);
+        config
+            .set_secret("OPENAI_API_KEY", &Value::String("sk-secret".into()))
+            .unwrap();
+        config
+            .set_secret("STALE_KEY", &Value::String("old".into()))
+            .unwrap();
+
+        clear_key(&config, "STALE_KEY").unwrap();
+        assert_eq!(list_keys(&config).unwrap(), vec!["OPENAI_API_KEY"]);
+
+        let err = clear_key(&config, "STALE_KEY").unwrap_err();
+        assert!(err.to_string().contains("No stored key named 'STALE_KEY'"));
+        assert_eq!(list_keys(&config).unwrap(), vec!["OPENAI_API_KEY"]);
+    }
+}
@@ -1,10 +1,15 @@
 pub mod acp;
+pub mod ask;
+pub mod batch;
 pub mod bench;
 pub mod configure;
+pub mod extension;
 pub mod info;
+pub mod keys;
 pub mod project;
 pub mod recipe;
 pub mod schedule;
 pub mod session;
 pub mod update;
+pub mod version;
 pub mod web;
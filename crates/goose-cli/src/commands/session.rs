@@ -216,6 +216,20 @@ pub async fn handle_session_export(
     Ok(())
 }
 
+pub async fn handle_session_fork(session_id: &str, name: String) -> Result<()> {
+    let session = SessionManager::fork_session(session_id, name)
+        .await
+        .with_context(|| format!("Failed to fork session '{}'", session_id))?;
+
+    println!(
+        "Forked session {} into {} ({})",
+        console::style(session_id).cyan(),
+        console::style(&session.name).green(),
+        session.id
+    );
+    Ok(())
+}
+
 pub async fn handle_diagnostics(session_id: &str, output_path: Option<PathBuf>) -> Result<()> {
     println!(
         "Generating diagnostics bundle for session '{}'...",
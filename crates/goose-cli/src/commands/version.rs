@@ -0,0 +1,66 @@
+use anyhow::Result;
+use chrono::DateTime;
+use console::style;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct VersionInfo {
+    pub version: &'static str,
+    pub commit: &'static str,
+    pub build_date: String,
+    pub rustc_version: &'static str,
+}
+
+impl VersionInfo {
+    pub fn current() -> Self {
+        let build_date = env!("GOOSE_BUILD_TIMESTAMP")
+            .parse::<i64>()
+            .ok()
+            .and_then(|ts| DateTime::from_timestamp(ts, 0))
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            commit: env!("GOOSE_GIT_COMMIT"),
+            build_date,
+            rustc_version: env!("GOOSE_RUSTC_VERSION"),
+        }
+    }
+}
+
+pub fn handle_version(json: bool) -> Result<()> {
+    let info = VersionInfo::current();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+
+    println!("{} {}", style("goose").cyan().bold(), info.version);
+    println!("  commit:     {}", info.commit);
+    println!("  built:      {}", info.build_date);
+    println!("  rustc:      {}", info.rustc_version);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_json_has_expected_keys() {
+        let value = serde_json::to_value(VersionInfo::current()).unwrap();
+        let object = value.as_object().unwrap();
+
+        for key in ["version", "commit", "build_date", "rustc_version"] {
+            let field = object.get(key).and_then(|v| v.as_str());
+            assert!(
+                field.is_some_and(|s| !s.is_empty()),
+                "missing or empty key: {}",
+                key
+            );
+        }
+        assert_eq!(object["version"], env!("CARGO_PKG_VERSION"));
+    }
+}
@@ -476,6 +476,8 @@ async fn process_message_streaming(
         schedule_id: None,
         max_turns: None,
         retry_config: None,
+        compact_threshold: None,
+        turn_budget: None,
     };
 
     match agent.reply(user_message, session_config, None).await {
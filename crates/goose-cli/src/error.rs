@@ -0,0 +1,278 @@
+use console::style;
+use goose::agents::extension::ExtensionError;
+use goose::config::ConfigError;
+use goose::providers::errors::ProviderError;
+use std::process::ExitCode;
+
+/// Exit code for failures that don't fit a more specific category
+pub const EXIT_FAILURE: u8 = 1;
+/// Exit code when goose is missing or has invalid configuration
+pub const EXIT_CONFIG: u8 = 2;
+/// Exit code when the model provider could not be reached or rejected the request
+pub const EXIT_PROVIDER: u8 = 3;
+/// Exit code when an extension failed to start or respond
+pub const EXIT_EXTENSION: u8 = 4;
+
+/// Map an error to the process exit code, based on the first recognised cause in its chain.
+pub fn exit_code_for(err: &anyhow::Error) -> u8 {
+    for cause in err.chain() {
+        if cause.is::<ConfigError>() {
+            return EXIT_CONFIG;
+        }
+        if cause.is::<ProviderError>() {
+            return EXIT_PROVIDER;
+        }
+        if cause.is::<ExtensionError>() {
+            return EXIT_EXTENSION;
+        }
+    }
+    EXIT_FAILURE
+}
+
+/// A single-line description of the error and its causes, suitable for the terminal.
+pub fn user_message(err: &anyhow::Error) -> String {
+    let message = format!("{:#}", err);
+    message
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+/// The first provider error in the chain of `err`, if any
+pub fn find_provider_error(err: &anyhow::Error) -> Option<&ProviderError> {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<ProviderError>())
+}
+
+/// The provider a failed request went to. Sessions attach it as context to the provider
+/// failures they pass on, so the report names the provider that was actually in use.
+#[derive(Debug)]
+pub struct ActiveProvider(pub String);
+
+impl std::fmt::Display for ActiveProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Request to provider {} failed", self.0)
+    }
+}
+
+/// The provider attached to `err` with [`ActiveProvider`], or "(unknown)"
+pub fn active_provider(err: &anyhow::Error) -> &str {
+    err.downcast_ref::<ActiveProvider>()
+        .map(|provider| provider.0.as_str())
+        .unwrap_or("(unknown)")
+}
+
+/// A concise description of a provider failure with what the user can do about it
+pub fn provider_error_message(err: &ProviderError, provider: &str) -> String {
+    match err {
+        ProviderError::Authentication(_) => format!(
+            "Invalid or missing API key for provider {} — run `goose configure` to update it",
+            provider
+        ),
+        ProviderError::ContextLengthExceeded { limit, .. } => format!(
+            "The conversation is too long for the model's context window{} — run /compact or start a new session",
+            limit
+                .map(|limit| format!(" of {} tokens", limit))
+                .unwrap_or_default()
+        ),
+        ProviderError::ModelNotFound(_) => format!(
+            "The model isn't available from provider {} — run `goose configure` to choose another, or set GOOSE_FALLBACK_MODEL",
+            provider
+        ),
+        ProviderError::RateLimitExceeded { .. } => format!(
+            "Rate limited by provider {} — automatic retries didn't get through, wait a moment and try again",
+            provider
+        ),
+        ProviderError::ServerError(_) | ProviderError::ServiceUnavailable { .. } => format!(
+            "Provider {} is having server problems — try again shortly",
+            provider
+        ),
+        ProviderError::RequestFailed(_) => format!(
+            "Provider {} rejected the request — see the details below",
+            provider
+        ),
+        ProviderError::NetworkError(_) => format!(
+            "Could not reach provider {} — check your network connection and the provider's host setting",
+            provider
+        ),
+        ProviderError::ExecutionError(_) => format!(
+            "Provider {} failed to handle the request — see the logs for details",
+            provider
+        ),
+        ProviderError::RetryBudgetExhausted(_) => {
+            "Gave up after using this turn's retry budget — try again, or raise --turn-budget"
+                .to_string()
+        }
+        ProviderError::UsageError(_) => format!(
+            "Provider {} returned usage data goose couldn't read — try again",
+            provider
+        ),
+        ProviderError::NotImplemented(_) => {
+            format!("Provider {} doesn't support this operation", provider)
+        }
+    }
+}
+
+/// Print a provider failure to stderr: the actionable summary, then the raw details dimmed
+pub fn render_provider_error(err: &ProviderError, provider: &str) {
+    eprintln!(
+        "{} {}",
+        style("error:").red().bold(),
+        provider_error_message(err, provider)
+    );
+    eprintln!("{}", style(err.to_string()).dim());
+}
+
+/// Log the full error to the log file, print a concise message to stderr and
+/// return the exit code the process should finish with.
+pub fn report(err: &anyhow::Error) -> ExitCode {
+    tracing::error!(error = ?err, "goose exited with an error");
+
+    match find_provider_error(err) {
+        Some(provider_err) => {
+            eprintln!(
+                "{} {}",
+                style("error:").red().bold(),
+                provider_error_message(provider_err, active_provider(err))
+            );
+            eprintln!("{}", style(user_message(err)).dim());
+        }
+        None => eprintln!("{} {}", style("error:").red().bold(), user_message(err)),
+    }
+    if let Ok(log_dir) = goose::logging::prepare_log_directory("cli", true) {
+        eprintln!(
+            "{}",
+            style(format!(
+                "Full details were written to the logs in {}",
+                log_dir.display()
+            ))
+            .dim()
+        );
+    }
+
+    ExitCode::from(exit_code_for(err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_exit_code_for_error_kinds() {
+        let config_err = anyhow::Error::new(ConfigError::NotFound("GOOSE_PROVIDER".into()))
+            .context("Failed to start session");
+        assert_eq!(exit_code_for(&config_err), EXIT_CONFIG);
+
+        let provider_err = anyhow::Error::new(ProviderError::RequestFailed("timeout".into()));
+        assert_eq!(exit_code_for(&provider_err), EXIT_PROVIDER);
+
+        let extension_err: anyhow::Result<()> =
+            Err(ExtensionError::SetupError("connection refused".into()))
+                .context("Failed to add extension 'fetch'");
+        assert_eq!(exit_code_for(&extension_err.unwrap_err()), EXIT_EXTENSION);
+
+        assert_eq!(exit_code_for(&anyhow::anyhow!("boom")), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn test_user_message_is_single_line_with_causes() {
+        let err = anyhow::Error::new(ProviderError::ServerError(
+            "bad gateway\nretry later".into(),
+        ))
+        .context("Failed to get a response");
+        assert_eq!(
+            user_message(&err),
+            "Failed to get a response: Server error: bad gateway"
+        );
+    }
+
+    #[test]
+    fn test_active_provider_is_read_from_the_error() {
+        let err = anyhow::Error::new(ProviderError::Authentication("401".into()));
+        assert_eq!(active_provider(&err), "(unknown)");
+
+        let err = err.context(ActiveProvider("lead_worker".into()));
+        assert_eq!(active_provider(&err), "lead_worker");
+        assert!(matches!(
+            find_provider_error(&err),
+            Some(ProviderError::Authentication(_))
+        ));
+    }
+
+    #[test]
+    fn test_provider_error_messages() {
+        let cases = [
+            (
+                ProviderError::Authentication("401".into()),
+                "Invalid or missing API key for provider openai — run `goose configure` to update it",
+            ),
+            (
+                ProviderError::context_length_exceeded("too long"),
+                "The conversation is too long for the model's context window — run /compact or start a new session",
+            ),
+            (
+                ProviderError::context_length_exceeded(
+                    "This model's maximum context length is 128000 tokens.",
+                ),
+                "The conversation is too long for the model's context window of 128000 tokens — run /compact or start a new session",
+            ),
+            (
+                ProviderError::ModelNotFound("gpt-9".into()),
+                "The model isn't available from provider openai — run `goose configure` to choose another, or set GOOSE_FALLBACK_MODEL",
+            ),
+            (
+                ProviderError::RateLimitExceeded {
+                    details: "slow down".into(),
+                    retry_delay: None,
+                },
+                "Rate limited by provider openai — automatic retries didn't get through, wait a moment and try again",
+            ),
+            (
+                ProviderError::ServerError("502".into()),
+                "Provider openai is having server problems — try again shortly",
+            ),
+            (
+                ProviderError::RequestFailed("invalid tool schema".into()),
+                "Provider openai rejected the request — see the details below",
+            ),
+            (
+                ProviderError::NetworkError("connection refused".into()),
+                "Could not reach provider openai — check your network connection and the provider's host setting",
+            ),
+            (
+                ProviderError::ExecutionError("boom".into()),
+                "Provider openai failed to handle the request — see the logs for details",
+            ),
+            (
+                ProviderError::RetryBudgetExhausted("3 retries".into()),
+                "Gave up after using this turn's retry budget — try again, or raise --turn-budget",
+            ),
+            (
+                ProviderError::UsageError("missing tokens".into()),
+                "Provider openai returned usage data goose couldn't read — try again",
+            ),
+            (
+                ProviderError::NotImplemented("embeddings".into()),
+                "Provider openai doesn't support this operation",
+            ),
+        ];
+
+        for (err, expected) in cases {
+            assert_eq!(provider_error_message(&err, "openai"), expected);
+        }
+    }
+
+    #[test]
+    fn test_find_provider_error_in_chain() {
+        let err = anyhow::Error::new(ProviderError::Authentication("401".into()))
+            .context("Failed to get a response");
+        assert!(matches!(
+            find_provider_error(&err),
+            Some(ProviderError::Authentication(_))
+        ));
+        assert!(find_provider_error(&anyhow::anyhow!("boom")).is_none());
+    }
+}
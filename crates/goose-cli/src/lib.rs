@@ -1,11 +1,14 @@
 pub mod cli;
 pub mod commands;
+pub mod error;
 pub mod logging;
 pub mod project_tracker;
 pub mod recipes;
 pub mod scenario_tests;
 pub mod session;
 pub mod signal;
+#[cfg(test)]
+pub(crate) mod test_support;
 
 // Re-export commonly used types
 pub use session::CliSession;
@@ -1,8 +1,8 @@
-use anyhow::Result;
 use goose_cli::cli::cli;
+use std::process::ExitCode;
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
     if let Err(e) = goose_cli::logging::setup_logging(None, None) {
         eprintln!("Warning: Failed to initialize logging: {}", e);
     }
@@ -33,5 +33,8 @@ async fn main() -> Result<()> {
         goose::tracing::shutdown_otlp();
     }
 
-    result
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => goose_cli::error::report(&e),
+    }
 }
@@ -156,6 +156,7 @@ mod tests {
                     description: "slack-mcp".to_string(),
                     bundled: None,
                     available_tools: Vec::new(),
+                    framing: Default::default(),
                 },
                 ExtensionConfig::Builtin {
                     name: "builtin-ext".to_string(),
@@ -251,6 +252,7 @@ mod tests {
                     description: "service-b".to_string(),
                     bundled: None,
                     available_tools: Vec::new(),
+                    framing: Default::default(),
                 },
             ]),
             settings: None,
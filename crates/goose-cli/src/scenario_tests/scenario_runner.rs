@@ -236,6 +236,10 @@ where
         None,
         None,
         "text".to_string(),
+        false,
+        None,
+        None,
+        None,
     )
     .await;
 
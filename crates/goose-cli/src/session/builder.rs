@@ -7,7 +7,10 @@ use goose::config::{
     extensions::{get_extension_by_name, set_extension, ExtensionEntry},
     get_all_extensions, get_enabled_extensions, Config, ExtensionConfig,
 };
+use goose::model::ModelConfig;
+use goose::providers::base::Provider;
 use goose::providers::create;
+use goose::providers::response_cache::CachingProvider;
 use goose::recipe::{Response, SubRecipe};
 
 use goose::agents::extension::PlatformExtensionContext;
@@ -16,6 +19,7 @@ use goose::session::SessionManager;
 use goose::session::{EnabledExtensionsState, ExtensionState};
 use rustyline::EditMode;
 use std::collections::HashSet;
+use std::path::PathBuf;
 use std::process;
 use std::sync::Arc;
 use tokio::task::JoinSet;
@@ -70,6 +74,25 @@ pub struct SessionBuilderConfig {
     pub retry_config: Option<RetryConfig>,
     /// Output format (text, json)
     pub output_format: String,
+    /// End an interactive session after this many seconds without user input
+    pub idle_timeout: Option<u64>,
+    /// Context usage ratio that triggers auto-compaction, overriding the configured threshold
+    pub compact_threshold: Option<f64>,
+    pub turn_budget: Option<usize>,
+    /// Render assistant thinking blocks instead of hiding them
+    pub show_thinking: bool,
+    /// Take provider settings from environment variables only, see `env_profile_available`
+    pub profile_from_env: bool,
+    /// Send abbreviated tool descriptions to the provider
+    pub compact_tool_descriptions: bool,
+    /// Use non-streaming completions so only whole messages are shown
+    pub no_stream: bool,
+    /// Context window in tokens, overriding the limit looked up for the model
+    pub context_limit: Option<usize>,
+    /// Save the transcript so far to the session when a headless run fails
+    pub save_partial_on_error: bool,
+    /// Directory for the on-disk provider response cache
+    pub cache_dir: Option<PathBuf>,
 }
 
 /// Manual implementation of Default to ensure proper initialization of output_format
@@ -99,6 +122,16 @@ impl Default for SessionBuilderConfig {
             final_output_response: None,
             retry_config: None,
             output_format: "text".to_string(),
+            idle_timeout: None,
+            compact_threshold: None,
+            turn_budget: None,
+            show_thinking: false,
+            profile_from_env: false,
+            compact_tool_descriptions: false,
+            no_stream: false,
+            context_limit: None,
+            save_partial_on_error: false,
+            cache_dir: None,
         }
     }
 }
@@ -181,6 +214,11 @@ async fn offer_extension_debugging_help(
         None,
         None,
         "text".to_string(),
+        false,
+        None,
+        None,
+        None,
+        false,
     )
     .await;
 
@@ -238,6 +276,28 @@ fn check_missing_extensions_or_exit(saved_extensions: &[ExtensionConfig]) {
     }
 }
 
+/// What `build_session` made the session's provider from, so it can be made again the same
+/// way, response cache included, after the user replaces a rejected key
+#[derive(Clone, Debug)]
+pub struct ProviderSettings {
+    pub provider_name: String,
+    pub model_config: ModelConfig,
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl ProviderSettings {
+    pub async fn create(&self) -> anyhow::Result<Arc<dyn Provider>> {
+        let provider = create(&self.provider_name, self.model_config.clone()).await?;
+        Ok(match &self.cache_dir {
+            Some(dir) => {
+                tracing::info!("Caching provider responses in {}", dir.display());
+                Arc::new(CachingProvider::new(provider, dir.clone()))
+            }
+            None => provider,
+        })
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct SessionSettings {
     pub goose_model: Option<String>,
@@ -245,9 +305,50 @@ pub struct SessionSettings {
     pub temperature: Option<f32>,
 }
 
+/// Whether goose can run from environment variables alone: no provider is configured, and
+/// GOOSE_PROVIDER, GOOSE_MODEL and the provider's required keys are all set
+async fn env_profile_available(config: &Config) -> bool {
+    if config.has_provider_configured() {
+        return false;
+    }
+    let (Ok(provider_name), Ok(_)) = (
+        std::env::var("GOOSE_PROVIDER"),
+        std::env::var("GOOSE_MODEL"),
+    ) else {
+        return false;
+    };
+
+    goose::providers::providers()
+        .await
+        .into_iter()
+        .find(|(metadata, _)| metadata.name == provider_name)
+        .is_some_and(|(metadata, _)| {
+            metadata
+                .config_keys
+                .iter()
+                .filter(|key| key.required && key.default.is_none())
+                .all(|key| std::env::var(&key.name).is_ok())
+        })
+}
+
+/// The session's model settings: `model_name` with any per-session overrides applied.
+fn session_model_config(
+    model_name: &str,
+    temperature: Option<f32>,
+    context_limit: Option<usize>,
+) -> Result<goose::model::ModelConfig, goose::model::ConfigError> {
+    Ok(goose::model::ModelConfig::new(model_name)?
+        .with_temperature(temperature)
+        .with_context_limit(context_limit))
+}
+
 pub async fn build_session(session_config: SessionBuilderConfig) -> CliSession {
     // Load config and get provider/model
     let config = Config::global();
+    if session_config.profile_from_env || env_profile_available(config).await {
+        tracing::info!("Using configuration from environment variables only");
+        config.use_env_only();
+    }
 
     let provider_name = session_config
         .provider
@@ -260,6 +361,8 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> CliSession {
         .or_else(|| config.get_goose_provider().ok())
         .expect("No provider configured. Run 'goose configure' first");
 
+    // A model given on the command line wins over the one a resumed session switched to
+    let model_overridden = session_config.model.is_some();
     let model_name = session_config
         .model
         .or_else(|| {
@@ -273,15 +376,22 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> CliSession {
 
     let temperature = session_config.settings.as_ref().and_then(|s| s.temperature);
 
-    let model_config = goose::model::ModelConfig::new(&model_name)
+    let model_config = session_model_config(&model_name, temperature, session_config.context_limit)
         .unwrap_or_else(|e| {
             output::render_error(&format!("Failed to create model configuration: {}", e));
             process::exit(1);
-        })
-        .with_temperature(temperature);
+        });
 
     // Create the agent
     let agent: Agent = Agent::new();
+    if session_config.compact_tool_descriptions {
+        agent.set_compact_tool_descriptions(true);
+    }
+    if session_config.no_stream {
+        agent.set_no_stream(true);
+    }
+    // The session reports provider failures itself and offers to fix a rejected API key
+    agent.set_error_on_provider_failure(true);
 
     agent
         .apply_recipe_components(
@@ -291,7 +401,17 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> CliSession {
         )
         .await;
 
-    let new_provider = match create(&provider_name, model_config).await {
+    let provider_settings = ProviderSettings {
+        provider_name: provider_name.clone(),
+        model_config,
+        cache_dir: session_config.cache_dir.clone().or_else(|| {
+            config
+                .get_param::<String>("GOOSE_RESPONSE_CACHE")
+                .ok()
+                .map(PathBuf::from)
+        }),
+    };
+    let new_provider = match provider_settings.create().await {
         Ok(provider) => provider,
         Err(e) => {
             output::render_error(&format!(
@@ -378,6 +498,12 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> CliSession {
                 output::render_error(&format!("Failed to read session metadata: {}", e));
                 process::exit(1);
             });
+        agent.restore_usage(&session).await;
+        if !model_overridden {
+            if let Err(e) = agent.restore_model(&session).await {
+                output::render_error(&format!("Failed to restore the session's model: {}", e));
+            }
+        }
 
         let current_workdir =
             std::env::current_dir().expect("Failed to get current working directory");
@@ -491,6 +617,9 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> CliSession {
         });
 
     let debug_mode = session_config.debug || config.get_param("GOOSE_DEBUG").unwrap_or(false);
+    if session_config.show_thinking {
+        output::set_show_thinking(true);
+    }
 
     // Create new session
     let mut session = CliSession::new(
@@ -502,8 +631,16 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> CliSession {
         edit_mode,
         session_config.retry_config.clone(),
         session_config.output_format.clone(),
+        session_config.quiet,
+        session_config
+            .idle_timeout
+            .map(std::time::Duration::from_secs),
+        session_config.compact_threshold,
+        session_config.turn_budget,
+        session_config.save_partial_on_error && !session_config.no_session,
     )
     .await;
+    session.provider_settings = Some(provider_settings);
 
     // Add stdio extensions if provided
     for extension_str in session_config.extensions {
@@ -617,6 +754,8 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> CliSession {
         schedule_id: None,
         max_turns: None,
         retry_config: None,
+        compact_threshold: None,
+        turn_budget: None,
     };
 
     if let Err(e) = session
@@ -627,23 +766,7 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> CliSession {
         tracing::warn!("Failed to save initial extension state: {}", e);
     }
 
-    // Add CLI-specific system prompt extension
-    session
-        .agent
-        .extend_system_prompt(super::prompt::get_cli_prompt())
-        .await;
-
-    if let Some(additional_prompt) = session_config.additional_system_prompt {
-        session.agent.extend_system_prompt(additional_prompt).await;
-    }
-
-    // Only override system prompt if a system override exists
-    let system_prompt_file: Option<String> = config.get_param("GOOSE_SYSTEM_PROMPT_FILE_PATH").ok();
-    if let Some(ref path) = system_prompt_file {
-        let override_prompt =
-            std::fs::read_to_string(path).expect("Failed to read system prompt file");
-        session.agent.override_system_prompt(override_prompt).await;
-    }
+    configure_system_prompt(&session.agent, session_config.additional_system_prompt).await;
 
     // Display session information unless in quiet mode
     if !session_config.quiet {
@@ -658,6 +781,31 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> CliSession {
     session
 }
 
+/// Add the CLI's instructions and `--system` text to the agent's system prompt, or replace it
+/// with the file in `GOOSE_SYSTEM_PROMPT_FILE_PATH`
+pub(crate) async fn configure_system_prompt(
+    agent: &Agent,
+    additional_system_prompt: Option<String>,
+) {
+    agent
+        .extend_system_prompt(super::prompt::get_cli_prompt())
+        .await;
+
+    if let Some(additional_prompt) = additional_system_prompt {
+        agent.extend_system_prompt(additional_prompt).await;
+    }
+
+    // Only override system prompt if a system override exists
+    let system_prompt_file: Option<String> = Config::global()
+        .get_param("GOOSE_SYSTEM_PROMPT_FILE_PATH")
+        .ok();
+    if let Some(ref path) = system_prompt_file {
+        let override_prompt =
+            std::fs::read_to_string(path).expect("Failed to read system prompt file");
+        agent.override_system_prompt(override_prompt).await;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -687,6 +835,16 @@ mod tests {
             final_output_response: None,
             retry_config: None,
             output_format: "text".to_string(),
+            idle_timeout: None,
+            compact_threshold: None,
+            turn_budget: None,
+            show_thinking: false,
+            profile_from_env: false,
+            compact_tool_descriptions: false,
+            no_stream: false,
+            context_limit: None,
+            save_partial_on_error: false,
+            cache_dir: None,
         };
 
         assert_eq!(config.extensions.len(), 1);
@@ -737,4 +895,68 @@ mod tests {
         assert_eq!(extension_name, "test-extension");
         assert_eq!(error_message, "test error");
     }
+
+    #[test]
+    fn test_context_limit_override_replaces_model_default() {
+        temp_env::with_var_unset("GOOSE_CONTEXT_LIMIT", || {
+            let default = session_model_config("gpt-4o", None, None).unwrap();
+            assert_eq!(default.context_limit(), 128_000);
+
+            let overridden = session_model_config("gpt-4o", None, Some(32_000)).unwrap();
+            assert_eq!(overridden.context_limit(), 32_000);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_build_session_from_env_only_config() {
+        crate::test_support::isolate_goose_paths();
+        let vars = [
+            ("GOOSE_PROVIDER", Some("openai")),
+            ("GOOSE_MODEL", Some("gpt-4o")),
+            ("OPENAI_API_KEY", Some("sk-test")),
+        ];
+        temp_env::async_with_vars(vars, async {
+            let config = Config::global();
+            assert!(!config.has_provider_configured());
+
+            let session = build_session(SessionBuilderConfig {
+                no_session: true,
+                extensions_override: Some(Vec::new()),
+                ..Default::default()
+            })
+            .await;
+
+            assert!(config.is_env_only());
+            let provider = session.agent.provider().await.unwrap();
+            assert_eq!(provider.get_name(), "openai");
+            assert_eq!(provider.get_model_config().model_name, "gpt-4o");
+            assert!(config.set_param("GOOSE_MODEL", "gpt-4.1").is_err());
+            assert!(!config.has_provider_configured());
+
+            SessionManager::delete_session(&session.session_id)
+                .await
+                .unwrap();
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_env_profile_needs_the_provider_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_with_file_secrets(
+            dir.path().join("config.yaml"),
+            dir.path().join("secrets.yaml"),
+        )
+        .unwrap();
+
+        let vars = [
+            ("GOOSE_PROVIDER", Some("openai")),
+            ("GOOSE_MODEL", Some("gpt-4o")),
+            ("OPENAI_API_KEY", None),
+        ];
+        temp_env::async_with_vars(vars, async {
+            assert!(!env_profile_available(&config).await);
+        })
+        .await;
+    }
 }
@@ -3,6 +3,7 @@ use anyhow::Result;
 use rustyline::Editor;
 use shlex;
 use std::collections::HashMap;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub enum InputResult {
@@ -54,6 +55,71 @@ impl rustyline::ConditionalEventHandler for CtrlCHandler {
     }
 }
 
+/// Run a blocking input read on its own thread so the wait can time out.
+/// Returns `None` if nothing arrived within `idle_timeout`; the reader thread is
+/// left behind since the session is about to shut down. It still holds the terminal in
+/// raw mode, so the terminal settings from before the read are put back.
+pub async fn read_with_idle_timeout<T, F>(read: F, idle_timeout: Duration) -> Result<Option<T>>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let terminal = TerminalSettings::save();
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(read());
+    });
+
+    match tokio::time::timeout(idle_timeout, rx).await {
+        Ok(Ok(result)) => result.map(Some),
+        Ok(Err(_)) => Err(anyhow::anyhow!("Input reader stopped unexpectedly")),
+        Err(_) => {
+            if let Some(terminal) = terminal {
+                terminal.restore();
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// The terminal's settings as saved by `stty -g`, for undoing the raw mode an abandoned
+/// line editor leaves behind
+struct TerminalSettings(String);
+
+impl TerminalSettings {
+    #[cfg(unix)]
+    fn save() -> Option<Self> {
+        use std::io::IsTerminal;
+        if !std::io::stdin().is_terminal() {
+            return None;
+        }
+        let output = std::process::Command::new("stty")
+            .arg("-g")
+            .stdin(std::process::Stdio::inherit())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())?;
+        Some(Self(
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        ))
+    }
+
+    #[cfg(not(unix))]
+    fn save() -> Option<Self> {
+        None
+    }
+
+    fn restore(&self) {
+        let restored = std::process::Command::new("stty")
+            .arg(&self.0)
+            .stdin(std::process::Stdio::inherit())
+            .status();
+        if !matches!(restored, Ok(status) if status.success()) {
+            tracing::warn!("Could not restore the terminal settings after the idle timeout");
+        }
+    }
+}
+
 pub fn get_input(
     editor: &mut Editor<GooseCompleter, rustyline::history::DefaultHistory>,
 ) -> Result<InputResult> {
@@ -328,6 +394,28 @@ Up/Down arrows - Navigate through command history"
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_read_with_idle_timeout() {
+        let result = read_with_idle_timeout(
+            || Ok(InputResult::Message("hello".to_string())),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(result, Some(InputResult::Message(m)) if m == "hello"));
+
+        let result = read_with_idle_timeout(
+            || {
+                std::thread::sleep(Duration::from_millis(500));
+                Ok(InputResult::Exit)
+            },
+            Duration::from_millis(50),
+        )
+        .await
+        .unwrap();
+        assert!(result.is_none());
+    }
+
     #[test]
     fn test_handle_slash_command() {
         // Test exit commands
@@ -0,0 +1,50 @@
+use goose::conversation::message::Message;
+use goose::session::Session;
+use serde::{Deserialize, Serialize};
+
+/// One line of `--output-format jsonl` output
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JsonlEvent {
+    /// A message from the agent as it streams in, including tool requests and responses.
+    /// Streamed text arrives in several messages sharing an id.
+    Message { message: Message },
+    /// Tokens used by the turn that just finished
+    Usage {
+        input_tokens: Option<i32>,
+        output_tokens: Option<i32>,
+        total_tokens: Option<i32>,
+    },
+    /// The turn failed with this error
+    Error { error: String },
+}
+
+/// Session token totals, to work out what a turn used
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenTotals {
+    input_tokens: Option<i32>,
+    output_tokens: Option<i32>,
+    total_tokens: Option<i32>,
+}
+
+impl TokenTotals {
+    pub fn of(session: &Session) -> Self {
+        Self {
+            input_tokens: session.accumulated_input_tokens,
+            output_tokens: session.accumulated_output_tokens,
+            total_tokens: session.accumulated_total_tokens,
+        }
+    }
+
+    /// The usage event for the tokens added since `before`
+    pub fn usage_since(&self, before: &TokenTotals) -> JsonlEvent {
+        let delta = |after: Option<i32>, before: Option<i32>| {
+            after.map(|after| after - before.unwrap_or(0))
+        };
+        JsonlEvent::Usage {
+            input_tokens: delta(self.input_tokens, before.input_tokens),
+            output_tokens: delta(self.output_tokens, before.output_tokens),
+            total_tokens: delta(self.total_tokens, before.total_tokens),
+        }
+    }
+}
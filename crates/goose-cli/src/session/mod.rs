@@ -2,8 +2,12 @@ mod builder;
 mod completion;
 mod export;
 mod input;
+mod jsonl;
 mod output;
 mod prompt;
+mod reauth;
+mod stdin_json;
+mod steer;
 mod task_execution_display;
 mod thinking;
 
@@ -11,13 +15,13 @@ use crate::session::task_execution_display::{
     format_task_execution_notification, TASK_EXECUTION_NOTIFICATION_TYPE,
 };
 use goose::conversation::Conversation;
-use std::io::Write;
+use std::io::{IsTerminal, Write};
 use std::str::FromStr;
 use tokio::signal::ctrl_c;
 use tokio_util::task::AbortOnDropHandle;
 
 pub use self::export::message_to_markdown;
-pub use builder::{build_session, SessionBuilderConfig, SessionSettings};
+pub use builder::{build_session, ProviderSettings, SessionBuilderConfig, SessionSettings};
 use console::Color;
 use goose::agents::AgentEvent;
 use goose::permission::permission_confirmation::PrincipalType;
@@ -25,6 +29,7 @@ use goose::permission::Permission;
 use goose::permission::PermissionConfirmation;
 use goose::providers::base::Provider;
 use goose::utils::safe_truncate;
+pub use stdin_json::parse_message_history;
 
 use anyhow::{Context, Result};
 use completion::GooseCompleter;
@@ -34,8 +39,11 @@ use goose::agents::{Agent, SessionConfig, MANUAL_COMPACT_TRIGGER};
 use goose::config::{Config, GooseMode};
 use goose::providers::pricing::initialize_pricing_cache;
 use goose::session::SessionManager;
+use goose::token_counter::create_token_counter;
 use input::InputResult;
+use jsonl::{JsonlEvent, TokenTotals};
 use rmcp::model::PromptMessage;
+use rmcp::model::Role;
 use rmcp::model::ServerNotification;
 use rmcp::model::{ErrorCode, ErrorData};
 
@@ -46,9 +54,9 @@ use rustyline::EditMode;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio;
 use tokio_util::sync::CancellationToken;
 use tracing::warn;
@@ -82,6 +90,14 @@ pub struct CliSession {
     edit_mode: Option<EditMode>,
     retry_config: Option<RetryConfig>,
     output_format: String,
+    quiet: bool,
+    idle_timeout: Option<Duration>,
+    compact_threshold: Option<f64>,
+    turn_budget: Option<usize>,
+    save_partial_on_error: bool,
+    reauth_attempted: bool,
+    jsonl_out: Arc<std::sync::Mutex<dyn Write + Send>>,
+    provider_settings: Option<ProviderSettings>,
 }
 
 // Cache structure for completion data
@@ -135,6 +151,29 @@ pub async fn classify_planner_response(
     }
 }
 
+/// Resolves with an error naming the signal when the process is asked to terminate, so the
+/// run it interrupts fails instead of looking successful. Ctrl-C is handled by the session
+/// loop itself, so this only listens for SIGTERM and SIGHUP and never resolves on other
+/// platforms.
+pub async fn termination_signal() -> anyhow::Error {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        if let (Ok(mut terminate), Ok(mut hangup)) = (
+            signal(SignalKind::terminate()),
+            signal(SignalKind::hangup()),
+        ) {
+            let name = tokio::select! {
+                _ = terminate.recv() => "SIGTERM",
+                _ = hangup.recv() => "SIGHUP",
+            };
+            return anyhow::anyhow!("Interrupted by {}", name);
+        }
+    }
+    std::future::pending().await
+}
+
 fn generate_extension_name(extension_command: &str) -> String {
     let cmd_name: String = extension_command
         .split([' ', '/'])
@@ -172,6 +211,11 @@ impl CliSession {
         edit_mode: Option<EditMode>,
         retry_config: Option<RetryConfig>,
         output_format: String,
+        quiet: bool,
+        idle_timeout: Option<Duration>,
+        compact_threshold: Option<f64>,
+        turn_budget: Option<usize>,
+        save_partial_on_error: bool,
     ) -> Self {
         let messages = SessionManager::get_session(&session_id, true)
             .await
@@ -190,6 +234,14 @@ impl CliSession {
             edit_mode,
             retry_config,
             output_format,
+            quiet,
+            idle_timeout,
+            compact_threshold,
+            turn_budget,
+            save_partial_on_error,
+            reauth_attempted: false,
+            jsonl_out: Arc::new(std::sync::Mutex::new(std::io::stdout())),
+            provider_settings: None,
         }
     }
 
@@ -233,6 +285,7 @@ impl CliSession {
             timeout: Some(goose::config::DEFAULT_EXTENSION_TIMEOUT),
             bundled: None,
             available_tools: Vec::new(),
+            framing: Default::default(),
         };
 
         self.agent
@@ -467,7 +520,32 @@ impl CliSession {
             // Display context usage before each prompt
             self.display_context_usage().await?;
 
-            match input::get_input(&mut editor)? {
+            let input = match self.idle_timeout {
+                None => input::get_input(&mut editor)?,
+                Some(idle_timeout) => {
+                    let mut reader = editor;
+                    let read = move || input::get_input(&mut reader).map(|input| (reader, input));
+                    match input::read_with_idle_timeout(read, idle_timeout).await? {
+                        Some((reader, input)) => {
+                            editor = reader;
+                            input
+                        }
+                        None => {
+                            println!(
+                                "\n{}",
+                                console::style(format!(
+                                    "No input for {}, ending session.",
+                                    format_elapsed_time(idle_timeout)
+                                ))
+                                .yellow()
+                            );
+                            break;
+                        }
+                    }
+                }
+            };
+
+            match input {
                 InputResult::Message(content) => {
                     match self.run_mode {
                         RunMode::Normal => {
@@ -802,9 +880,49 @@ impl CliSession {
     /// Process a single message and exit
     pub async fn headless(&mut self, prompt: String) -> Result<()> {
         let message = Message::user().with_text(&prompt);
-        self.process_message(message, CancellationToken::default())
-            .await?;
-        Ok(())
+        let result = self
+            .process_message(message, CancellationToken::default())
+            .await;
+        self.save_partial_transcript_on_error(result).await
+    }
+
+    /// Add `messages` to the session as history and reply to the final one, then exit
+    pub async fn headless_messages(&mut self, mut messages: Vec<Message>) -> Result<()> {
+        let message = messages
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("No messages provided"))?;
+        for history in messages {
+            SessionManager::add_message(&self.session_id, &history).await?;
+            self.push_message(history);
+        }
+        let result = self
+            .process_message(message, CancellationToken::default())
+            .await;
+        self.save_partial_transcript_on_error(result).await
+    }
+
+    /// With `--save-partial-on-error`, write the transcript so far to the session when a
+    /// headless run fails, then pass the result through
+    async fn save_partial_transcript_on_error(&self, result: Result<()>) -> Result<()> {
+        if result.is_err() && self.save_partial_on_error {
+            let transcript = partial_transcript(&self.messages);
+            match SessionManager::replace_conversation(&self.session_id, &transcript).await {
+                Ok(()) => eprintln!(
+                    "Saved the partial transcript ({} messages) to session {}. Resume it with: goose session --resume --session-id {}",
+                    transcript.len(),
+                    self.session_id,
+                    self.session_id
+                ),
+                Err(e) => eprintln!("Failed to save the partial transcript: {}", e),
+            }
+        }
+        result
+    }
+
+    /// Write the text of the final assistant message to `path`, leaving out tool calls
+    /// and their results
+    pub fn write_final_answer(&self, path: &Path) -> Result<()> {
+        write_final_answer(self.messages.messages(), path)
     }
 
     async fn process_agent_response(
@@ -814,12 +932,37 @@ impl CliSession {
     ) -> Result<()> {
         // Cache the output format check to avoid repeated string comparisons in the hot loop
         let is_json_mode = self.output_format == "json";
+        let is_jsonl_mode = self.output_format == "jsonl";
+        // Either machine-readable format replaces the rendered output
+        let is_machine_output = is_json_mode || is_jsonl_mode;
+        let show_token_rate = interactive
+            && !self.quiet
+            && !is_machine_output
+            // The status line is drawn on stderr
+            && std::io::stderr().is_terminal()
+            && Config::global()
+                .get_param::<bool>("GOOSE_CLI_SHOW_TOKEN_RATE")
+                .unwrap_or(false);
+        let token_counter = if show_token_rate {
+            create_token_counter().await.ok()
+        } else {
+            None
+        };
+        let mut token_rate = output::TokenRateTracker::new(Instant::now());
+        let mut token_rate_shown = false;
 
         let session_config = SessionConfig {
             id: self.session_id.clone(),
             schedule_id: self.scheduled_job_id.clone(),
             max_turns: self.max_turns,
             retry_config: self.retry_config.clone(),
+            compact_threshold: self.compact_threshold,
+            turn_budget: self.turn_budget,
+        };
+        let tokens_before = if is_jsonl_mode {
+            self.token_totals().await
+        } else {
+            TokenTotals::default()
         };
         let user_message = self
             .messages
@@ -845,11 +988,17 @@ impl CliSession {
 
         let mut progress_bars = output::McpSpinners::new();
         let cancel_token_clone = cancel_token.clone();
+        let mut steer_with = None;
+        let mut retry_turn = false;
 
         use futures::StreamExt;
         loop {
             tokio::select! {
                 result = stream.next() => {
+                    if token_rate_shown {
+                        output::clear_token_rate();
+                        token_rate_shown = false;
+                    }
                     match result {
                         Some(Ok(AgentEvent::Message(message))) => {
                             // If it's a confirmation request, get approval but otherwise do not render/persist
@@ -958,14 +1107,32 @@ impl CliSession {
                                 }
 
                                 self.messages.push(message.clone());
+                                if is_jsonl_mode {
+                                    self.emit_jsonl(&JsonlEvent::Message { message: message.clone() });
+                                }
 
                                 if interactive {output::hide_thinking()};
                                 let _ = progress_bars.hide();
 
                                 // Don't render in JSON mode
-                                if !is_json_mode {
+                                if !is_machine_output {
                                     output::render_message(&message, self.debug);
                                 }
+
+                                if let Some(counter) = &token_counter {
+                                    if message.role == rmcp::model::Role::Assistant {
+                                        token_rate.record(counter.count_tokens(&message.as_concat_text()), Instant::now());
+                                    }
+                                    // Only draw on a fresh line so we never clobber partial output
+                                    let at_line_start = match message.content.last() {
+                                        Some(MessageContent::Text(text)) => text.text.ends_with('\n'),
+                                        _ => true,
+                                    };
+                                    if at_line_start {
+                                        output::render_token_rate(&token_rate);
+                                        token_rate_shown = true;
+                                    }
+                                }
                             }
                         }
                         Some(Ok(AgentEvent::McpNotification((_id, message)))) => {
@@ -1037,21 +1204,21 @@ impl CliSession {
                                         // TODO: proper display for subagent notifications
                                         if interactive {
                                             let _ = progress_bars.hide();
-                                            if !is_json_mode {
+                                            if !is_machine_output {
                                                 println!("{}", console::style(&formatted_message).green().dim());
                                             }
-                                        } else if !is_json_mode {
+                                        } else if !is_machine_output {
                                             progress_bars.log(&formatted_message);
                                         }
                                     } else if let Some(ref notification_type) = message_notification_type {
                                         if notification_type == TASK_EXECUTION_NOTIFICATION_TYPE {
                                             if interactive {
                                                 let _ = progress_bars.hide();
-                                                if !is_json_mode {
+                                                if !is_machine_output {
                                                     print!("{}", formatted_message);
                                                     std::io::stdout().flush().unwrap();
                                                 }
-                                            } else if !is_json_mode {
+                                            } else if !is_machine_output {
                                                 print!("{}", formatted_message);
                                                 std::io::stdout().flush().unwrap();
                                             }
@@ -1089,22 +1256,66 @@ impl CliSession {
                         }
 
                         Some(Err(e)) => {
-                            // TODO(Douwe): Delete this
-                            // Check if it's a ProviderError::ContextLengthExceeded
-                            if e.downcast_ref::<goose::providers::errors::ProviderError>()
-                                .map(|provider_error| matches!(provider_error, goose::providers::errors::ProviderError::ContextLengthExceeded(_)))
-                                .unwrap_or(false) {
-
-                                output::render_text(
-                                    "Compaction requested. Should have happened in the agent!",
-                                    Some(Color::Yellow),
-                                    true
-                                );
-                                warn!("Compaction requested. Should have happened in the agent!");
+                            if is_jsonl_mode {
+                                self.emit_jsonl(&JsonlEvent::Error { error: format!("{:#}", e) });
                             }
-                            eprintln!("Error: {}", e);
+                            let auth_failed = matches!(
+                                crate::error::find_provider_error(&e),
+                                Some(goose::providers::errors::ProviderError::Authentication(_))
+                            );
+                            // Name the provider actually in use, which may not be the configured one
+                            let e = match crate::error::find_provider_error(&e) {
+                                Some(_) => {
+                                    let provider = self
+                                        .agent
+                                        .provider()
+                                        .await
+                                        .map(|p| p.get_name().to_string())
+                                        .unwrap_or_else(|_| "(unknown)".to_string());
+                                    e.context(crate::error::ActiveProvider(provider))
+                                }
+                                None => e,
+                            };
+                            if auth_failed && !interactive {
+                                // Nobody is there to enter a new key, so fail the run
+                                cancel_token_clone.cancel();
+                                drop(stream);
+                                return Err(e);
+                            }
+                            let provider_failed = match crate::error::find_provider_error(&e) {
+                                Some(provider_err) => {
+                                    crate::error::render_provider_error(
+                                        provider_err,
+                                        crate::error::active_provider(&e),
+                                    );
+                                    true
+                                }
+                                None => {
+                                    eprintln!("Error: {}", e);
+                                    false
+                                }
+                            };
                             cancel_token_clone.cancel();
                             drop(stream);
+                            if auth_failed && !self.reauth_attempted {
+                                match self.reauthenticate().await {
+                                    Ok(true) => {
+                                        retry_turn = true;
+                                        break;
+                                    }
+                                    Ok(false) => {}
+                                    Err(e) => eprintln!("Failed to update the API key: {}", e),
+                                }
+                            }
+                            if self.save_partial_on_error && !interactive {
+                                // Keep everything up to the failure so it can be saved
+                                return Err(e);
+                            }
+                            if provider_failed {
+                                // The agent already saved the turn up to the failure and
+                                // reported it, so the conversation can go on from here
+                                break;
+                            }
                             if let Err(e) = self.handle_interrupted_messages(false).await {
                                 eprintln!("Error handling interruption: {}", e);
                             } else {
@@ -1122,6 +1333,12 @@ impl CliSession {
                 }
                 _ = cancel_token_clone.cancelled() => {
                     drop(stream);
+                    if interactive && !is_machine_output {
+                        if let Some(partial) = steer::partial_reply(&self.messages) {
+                            steer_with = self.capture_partial_reply(partial).await?;
+                            break;
+                        }
+                    }
                     if let Err(e) = self.handle_interrupted_messages(true).await {
                         eprintln!("Error handling interruption: {}", e);
                     }
@@ -1130,6 +1347,14 @@ impl CliSession {
             }
         }
 
+        if token_rate_shown {
+            output::clear_token_rate();
+        }
+
+        if retry_turn {
+            return self.retry_turn(interactive).await;
+        }
+
         // Output JSON if requested
         if is_json_mode {
             let metadata = match SessionManager::get_session(&self.session_id, false).await {
@@ -1149,13 +1374,124 @@ impl CliSession {
             };
 
             println!("{}", serde_json::to_string_pretty(&json_output)?);
+        } else if is_jsonl_mode {
+            let usage = self.token_totals().await.usage_since(&tokens_before);
+            self.emit_jsonl(&usage);
         } else {
             println!();
         }
 
+        if let Some(message) = steer_with {
+            self.push_message(message);
+            return Box::pin(
+                self.process_agent_response(interactive, CancellationToken::default()),
+            )
+            .await;
+        }
+
         Ok(())
     }
 
+    async fn token_totals(&self) -> TokenTotals {
+        SessionManager::get_session(&self.session_id, false)
+            .await
+            .map(|session| TokenTotals::of(&session))
+            .unwrap_or_default()
+    }
+
+    /// Write one line of `--output-format jsonl` output
+    fn emit_jsonl(&self, event: &JsonlEvent) {
+        let line = match serde_json::to_string(event) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Failed to serialize output event: {}", e);
+                return;
+            }
+        };
+        let mut out = self.jsonl_out.lock().unwrap();
+        if let Err(e) = writeln!(out, "{}", line).and_then(|_| out.flush()) {
+            warn!("Failed to write output event: {}", e);
+        }
+    }
+
+    /// Ask for a new API key after the provider rejected the current one, and rebuild the
+    /// provider with it. Returns whether there is a new key to retry with.
+    async fn reauthenticate(&mut self) -> Result<bool> {
+        output::hide_thinking();
+        // Sessions from build_session rebuild their provider the way it was first made, so
+        // lead/worker, fallback and caching wrappers survive the new key
+        let settings = match &self.provider_settings {
+            Some(settings) => settings.clone(),
+            None => {
+                let provider = self.agent.provider().await?;
+                ProviderSettings {
+                    provider_name: provider.get_name().to_string(),
+                    model_config: provider.get_model_config(),
+                    cache_dir: None,
+                }
+            }
+        };
+        if !reauth::prompt_for_api_key(&settings.provider_name).await? {
+            return Ok(false);
+        }
+
+        self.agent.update_provider(settings.create().await?).await?;
+        Ok(true)
+    }
+
+    /// Send the message that started the failed turn again, once, after the key was replaced
+    async fn retry_turn(&mut self, interactive: bool) -> Result<()> {
+        let start = self
+            .messages
+            .iter()
+            .rposition(|msg| {
+                msg.role == Role::User
+                    && !msg
+                        .content
+                        .iter()
+                        .any(|content| matches!(content, MessageContent::ToolResponse(_)))
+            })
+            .ok_or_else(|| anyhow::anyhow!("No user message to retry"))?;
+        // Drop what the failed attempt produced. The agent stores the message again when it
+        // is resent, so the session goes back to what came before it.
+        self.messages.truncate(start + 1);
+        let previous = Conversation::new_unvalidated(self.messages.iter().take(start).cloned());
+        SessionManager::replace_conversation(&self.session_id, &previous).await?;
+
+        self.reauth_attempted = true;
+        let result =
+            Box::pin(self.process_agent_response(interactive, CancellationToken::default())).await;
+        self.reauth_attempted = false;
+        result
+    }
+
+    /// Keep a reply interrupted mid-generation in the history and ask the user how to steer.
+    /// Returns the message to start the next turn with, if the user typed one.
+    async fn capture_partial_reply(&mut self, partial: Message) -> Result<Option<Message>> {
+        output::hide_thinking();
+        self.messages.pop();
+        SessionManager::add_message(&self.session_id, &partial).await?;
+        self.push_message(partial);
+
+        println!();
+        let input = match cliclack::input(
+            "Interrupted. Steer goose (leave empty to return to the prompt):",
+        )
+        .required(false)
+        .interact::<String>()
+        {
+            Ok(input) => input,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let message = steer::steering_message(&input);
+        if message.is_some() {
+            output::show_thinking();
+        }
+        Ok(message)
+    }
+
     async fn handle_interrupted_messages(&mut self, interrupt: bool) -> Result<()> {
         // First, get any tool requests from the last message if it exists
         let tool_requests = self
@@ -1313,6 +1649,40 @@ impl CliSession {
         );
     }
 
+    /// Print this session's token usage per provider and model, with costs when
+    /// `GOOSE_CLI_SHOW_COST` is enabled
+    pub async fn display_usage_report(&self) {
+        if self.quiet || matches!(self.output_format.as_str(), "json" | "jsonl") {
+            return;
+        }
+        let report = self.agent.usage().await;
+        if report.is_empty() {
+            return;
+        }
+
+        let show_cost = Config::global()
+            .get_param::<bool>("GOOSE_CLI_SHOW_COST")
+            .unwrap_or(false);
+        if show_cost {
+            if let Err(e) = initialize_pricing_cache().await {
+                tracing::warn!("Failed to initialize pricing cache: {e}");
+            }
+        }
+        output::display_usage_report(report, show_cost).await;
+    }
+
+    /// The system prompt this session's agent would send, for debugging its behaviour
+    pub async fn system_prompt(&self) -> Result<String> {
+        self.agent
+            .get_system_prompt(&std::env::current_dir()?)
+            .await
+    }
+
+    /// Close the agent's extensions so their MCP server processes exit along with the CLI
+    pub async fn shutdown(&self) {
+        self.agent.shutdown().await;
+    }
+
     pub async fn get_session(&self) -> Result<goose::session::Session> {
         SessionManager::get_session(&self.session_id, false).await
     }
@@ -1487,6 +1857,22 @@ impl CliSession {
     }
 }
 
+/// The messages of `conversation` worth keeping, leaving out status notifications that were
+/// only shown while the reply was running
+fn partial_transcript(conversation: &Conversation) -> Conversation {
+    Conversation::new_unvalidated(
+        conversation
+            .iter()
+            .filter(|message| {
+                !message
+                    .content
+                    .iter()
+                    .all(|content| matches!(content, MessageContent::SystemNotification(_)))
+            })
+            .cloned(),
+    )
+}
+
 async fn get_reasoner() -> Result<Arc<dyn Provider>, anyhow::Error> {
     use goose::model::ModelConfig;
     use goose::providers::create;
@@ -1533,11 +1919,80 @@ fn format_elapsed_time(duration: std::time::Duration) -> String {
     }
 }
 
+/// Concatenated text of the last assistant message that has any text
+pub(crate) fn final_assistant_text(messages: &[Message]) -> Option<String> {
+    messages
+        .iter()
+        .rev()
+        .filter(|message| message.role == Role::Assistant)
+        .map(|message| message.as_concat_text())
+        .find(|text| !text.is_empty())
+}
+
+/// Write the final answer to `path`. A run that ended without one fails instead, leaving no
+/// file behind that could be mistaken for an empty answer.
+fn write_final_answer(messages: &[Message], path: &Path) -> Result<()> {
+    let text = final_assistant_text(messages).with_context(|| {
+        format!(
+            "The run ended without a final answer; not writing {}",
+            path.display()
+        )
+    })?;
+    std::fs::write(path, text)
+        .with_context(|| format!("Failed to write the final answer to {}", path.display()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::time::Duration;
 
+    #[test]
+    fn test_write_final_answer_contains_only_final_text() {
+        let messages = vec![
+            Message::user().with_text("List the files"),
+            Message::assistant()
+                .with_text("Let me check.")
+                .with_tool_request(
+                    "call_1",
+                    Ok(rmcp::model::CallToolRequestParam {
+                        name: "developer__shell".into(),
+                        arguments: None,
+                    }),
+                ),
+            Message::user()
+                .with_tool_response("call_1", Ok(vec![rmcp::model::Content::text("README.md")])),
+            Message::assistant()
+                .with_text("There is one file:")
+                .with_text("README.md"),
+            Message::assistant().with_tool_request(
+                "call_2",
+                Ok(rmcp::model::CallToolRequestParam {
+                    name: "todo__write".into(),
+                    arguments: None,
+                }),
+            ),
+        ];
+
+        assert_eq!(
+            final_assistant_text(&messages).as_deref(),
+            Some("There is one file:\nREADME.md")
+        );
+        assert_eq!(final_assistant_text(&messages[..1]), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("answer.txt");
+        write_final_answer(&messages, &path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "There is one file:\nREADME.md"
+        );
+
+        let missing = dir.path().join("missing.txt");
+        assert!(write_final_answer(&messages[..1], &missing).is_err());
+        assert!(!missing.exists());
+    }
+
     #[test]
     fn test_format_elapsed_time_under_60_seconds() {
         // Test sub-second duration
@@ -1603,4 +2058,235 @@ mod tests {
         let duration = Duration::from_millis(60500);
         assert_eq!(format_elapsed_time(duration), "1m 00s");
     }
+
+    /// A quiet headless CliSession over a fresh hidden session, answering with `provider`
+    async fn mock_session(
+        provider: crate::test_support::MockProvider,
+        output_format: &str,
+        save_partial_on_error: bool,
+    ) -> CliSession {
+        crate::test_support::isolate_goose_paths();
+        let session = SessionManager::create_session(
+            std::env::current_dir().unwrap(),
+            "Test session".to_string(),
+            goose::session::session_manager::SessionType::Hidden,
+        )
+        .await
+        .unwrap();
+
+        let agent = Agent::new();
+        agent.update_provider(Arc::new(provider)).await.unwrap();
+        CliSession::new(
+            agent,
+            session.id,
+            false,
+            None,
+            None,
+            None,
+            None,
+            output_format.to_string(),
+            true,
+            None,
+            None,
+            None,
+            save_partial_on_error,
+        )
+        .await
+    }
+
+    /// Shut down a session made by `mock_session` and delete what it saved
+    async fn finish(cli_session: CliSession) {
+        cli_session.shutdown().await;
+        SessionManager::delete_session(&cli_session.session_id)
+            .await
+            .unwrap();
+    }
+
+    async fn saved_conversation(cli_session: &CliSession) -> Conversation {
+        SessionManager::get_session(&cli_session.session_id, true)
+            .await
+            .unwrap()
+            .conversation
+            .unwrap_or_default()
+    }
+
+    mod partial_save {
+        use super::*;
+        use crate::test_support::MockProvider;
+        use goose::providers::base::Usage;
+        use goose::providers::errors::ProviderError;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[tokio::test]
+        async fn test_headless_failure_saves_partial_transcript() {
+            // Asks for a tool on the first call and fails on every call after that
+            let calls = AtomicUsize::new(0);
+            let provider = MockProvider::new(move |_| {
+                if calls.fetch_add(1, Ordering::SeqCst) > 0 {
+                    return Err(ProviderError::ServerError("upstream went away".to_string()));
+                }
+                Ok((
+                    Message::assistant().with_tool_request(
+                        "call_1",
+                        Ok(rmcp::model::CallToolRequestParam {
+                            name: "missing__tool".into(),
+                            arguments: None,
+                        }),
+                    ),
+                    Usage::default(),
+                ))
+            });
+            let mut cli_session = mock_session(provider, "text", true).await;
+            cli_session.agent.set_error_on_provider_failure(true);
+
+            assert!(cli_session
+                .headless("do the task".to_string())
+                .await
+                .is_err());
+
+            let saved = saved_conversation(&cli_session).await;
+            let has = |check: fn(&MessageContent) -> bool| {
+                saved
+                    .iter()
+                    .any(|message| message.content.iter().any(check))
+            };
+            assert_eq!(saved.first().unwrap().as_concat_text(), "do the task");
+            assert!(has(|c| matches!(c, MessageContent::ToolRequest(_))));
+            assert!(has(|c| matches!(c, MessageContent::ToolResponse(_))));
+            assert!(saved
+                .last()
+                .unwrap()
+                .as_concat_text()
+                .contains("upstream went away"));
+
+            finish(cli_session).await;
+        }
+    }
+
+    mod reauth_retry {
+        use super::*;
+        use crate::test_support::MockProvider;
+        use goose::providers::base::Usage;
+        use goose::providers::errors::ProviderError;
+        use std::sync::Mutex;
+
+        #[tokio::test]
+        async fn test_turn_succeeds_when_retried_after_key_update() {
+            let key = Arc::new(Mutex::new("expired-key".to_string()));
+            let current_key = key.clone();
+            // Rejects requests until its key is replaced
+            let provider = MockProvider::new(move |_| {
+                if *current_key.lock().unwrap() != "valid-key" {
+                    return Err(ProviderError::Authentication("401 invalid api key".into()));
+                }
+                Ok((
+                    Message::assistant().with_text("Hello again"),
+                    Usage::default(),
+                ))
+            });
+            let mut cli_session = mock_session(provider, "text", false).await;
+            cli_session.agent.set_error_on_provider_failure(true);
+
+            let err = cli_session.headless("hello".to_string()).await.unwrap_err();
+            assert!(matches!(
+                crate::error::find_provider_error(&err),
+                Some(ProviderError::Authentication(_))
+            ));
+
+            *key.lock().unwrap() = "valid-key".to_string();
+            cli_session.retry_turn(false).await.unwrap();
+
+            let saved = saved_conversation(&cli_session).await;
+            let texts: Vec<_> = saved.iter().map(|m| m.as_concat_text()).collect();
+            assert_eq!(texts, vec!["hello", "Hello again"]);
+            assert!(!cli_session.reauth_attempted);
+
+            finish(cli_session).await;
+        }
+    }
+
+    mod system_prompt {
+        use super::*;
+        use crate::test_support::MockProvider;
+        use goose::providers::base::Usage;
+
+        #[tokio::test]
+        async fn test_dumped_prompt_includes_extension_instructions_and_system_text() {
+            let cli_session = mock_session(
+                MockProvider::replying("ok", Usage::default()),
+                "text",
+                false,
+            )
+            .await;
+            cli_session
+                .agent
+                .add_extension(ExtensionConfig::Frontend {
+                    name: "notes".to_string(),
+                    description: "Notes kept by the frontend".to_string(),
+                    tools: vec![],
+                    instructions: Some("Write every finding to the notes panel.".to_string()),
+                    bundled: None,
+                    available_tools: vec![],
+                })
+                .await
+                .unwrap();
+            builder::configure_system_prompt(
+                &cli_session.agent,
+                Some("Always answer in haiku.".to_string()),
+            )
+            .await;
+
+            let prompt = cli_session.system_prompt().await.unwrap();
+            assert!(prompt.contains("Write every finding to the notes panel."));
+            assert!(prompt.contains("Always answer in haiku."));
+
+            finish(cli_session).await;
+        }
+    }
+
+    mod jsonl_output {
+        use super::*;
+        use crate::test_support::MockProvider;
+        use goose::providers::base::Usage;
+        use std::sync::Mutex;
+
+        #[tokio::test]
+        async fn test_headless_run_emits_parseable_jsonl() {
+            let provider =
+                MockProvider::replying("Hello from jsonl", Usage::new(Some(10), Some(5), Some(15)));
+            let mut cli_session = mock_session(provider, "jsonl", false).await;
+            let out = Arc::new(Mutex::new(Vec::<u8>::new()));
+            cli_session.jsonl_out = out.clone();
+
+            cli_session.headless("hello".to_string()).await.unwrap();
+
+            let out = String::from_utf8(out.lock().unwrap().clone()).unwrap();
+            let events: Vec<JsonlEvent> = out
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect();
+
+            let text: String = events
+                .iter()
+                .filter_map(|event| match event {
+                    JsonlEvent::Message { message } => Some(message.as_concat_text()),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(text, "Hello from jsonl");
+            match events.last() {
+                Some(JsonlEvent::Usage {
+                    input_tokens,
+                    output_tokens,
+                    ..
+                }) => {
+                    assert_eq!(*input_tokens, Some(10));
+                    assert_eq!(*output_tokens, Some(5));
+                }
+                other => panic!("expected a usage line last, got {:?}", other),
+            }
+
+            finish(cli_session).await;
+        }
+    }
 }
@@ -3,8 +3,11 @@ use bat::WrappingMode;
 use console::{measure_text_width, style, Color, Term};
 use goose::config::Config;
 use goose::conversation::message::{Message, MessageContent, ToolRequest, ToolResponse};
+use goose::providers::base::Usage;
 use goose::providers::pricing::get_model_pricing;
 use goose::providers::pricing::parse_model_id;
+use goose::providers::pricing::PricingInfo;
+use goose::providers::usage_report::UsageReport;
 use goose::utils::safe_truncate;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use regex::Regex;
@@ -14,8 +17,9 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::io::{Error, IsTerminal, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 // Re-export theme for use in main
 #[derive(Clone, Copy)]
@@ -88,6 +92,27 @@ pub fn get_theme() -> Theme {
     CURRENT_THEME.with(|t| *t.borrow())
 }
 
+static SHOW_THINKING: AtomicBool = AtomicBool::new(false);
+
+/// Enable rendering of thinking blocks (`--show-thinking`)
+pub fn set_show_thinking(show: bool) {
+    SHOW_THINKING.store(show, Ordering::Relaxed);
+}
+
+fn show_thinking_enabled() -> bool {
+    SHOW_THINKING.load(Ordering::Relaxed)
+        || (std::env::var("GOOSE_CLI_SHOW_THINKING").is_ok() && std::io::stdout().is_terminal())
+}
+
+/// Whether a content block should be rendered; thinking is only shown when enabled.
+/// Hidden thinking stays in the conversation history, this only affects display.
+fn should_render_content(content: &MessageContent, show_thinking: bool) -> bool {
+    match content {
+        MessageContent::Thinking(_) | MessageContent::RedactedThinking(_) => show_thinking,
+        _ => true,
+    }
+}
+
 // Simple wrapper around spinner to manage its state
 #[derive(Default)]
 pub struct ThinkingIndicator {
@@ -161,29 +186,92 @@ pub fn set_thinking_message(s: &String) {
     }
 }
 
+/// Tracks the tokens streamed during a turn so the CLI can report a generation rate
+pub struct TokenRateTracker {
+    started: Instant,
+    first_chunk: Option<(Instant, usize)>,
+    last_chunk: Option<Instant>,
+    tokens: usize,
+}
+
+impl TokenRateTracker {
+    pub fn new(started: Instant) -> Self {
+        Self {
+            started,
+            first_chunk: None,
+            last_chunk: None,
+            tokens: 0,
+        }
+    }
+
+    pub fn record(&mut self, tokens: usize, at: Instant) {
+        if tokens == 0 {
+            return;
+        }
+        if self.first_chunk.is_none() {
+            self.first_chunk = Some((at, tokens));
+        }
+        self.last_chunk = Some(at);
+        self.tokens += tokens;
+    }
+
+    /// Tokens per second between the first and the last chunk. The first chunk's
+    /// tokens are excluded since they cover time spent waiting on the model.
+    pub fn tokens_per_second(&self) -> Option<f64> {
+        let (first_at, first_tokens) = self.first_chunk?;
+        let elapsed = self.last_chunk?.duration_since(first_at).as_secs_f64();
+        if elapsed <= 0.0 {
+            return None;
+        }
+        Some((self.tokens - first_tokens) as f64 / elapsed)
+    }
+
+    pub fn status_line(&self, now: Instant) -> String {
+        let elapsed = now.duration_since(self.started).as_secs_f64();
+        match self.tokens_per_second() {
+            Some(rate) => format!(
+                "{} tokens · {:.1} tok/s · {:.1}s",
+                self.tokens, rate, elapsed
+            ),
+            None => format!("{} tokens · {:.1}s", self.tokens, elapsed),
+        }
+    }
+}
+
+pub fn render_token_rate(tracker: &TokenRateTracker) {
+    let term = Term::stderr();
+    let _ = term.clear_line();
+    let _ = term.write_str(&style(tracker.status_line(Instant::now())).dim().to_string());
+}
+
+pub fn clear_token_rate() {
+    let _ = Term::stderr().clear_line();
+}
+
 pub fn render_message(message: &Message, debug: bool) {
     let theme = get_theme();
+    let show_thinking = show_thinking_enabled();
 
-    for content in &message.content {
+    for content in message
+        .content
+        .iter()
+        .filter(|c| should_render_content(c, show_thinking))
+    {
         match content {
-            MessageContent::Text(text) => print_markdown(&text.text, theme),
+            MessageContent::Text(text) => print_highlighted(&text.text, "Markdown", theme),
             MessageContent::ToolRequest(req) => render_tool_request(req, theme, debug),
             MessageContent::ToolResponse(resp) => render_tool_response(resp, theme, debug),
             MessageContent::Image(image) => {
                 println!("Image: [data: {}, type: {}]", image.data, image.mime_type);
             }
             MessageContent::Thinking(thinking) => {
-                if std::env::var("GOOSE_CLI_SHOW_THINKING").is_ok()
-                    && std::io::stdout().is_terminal()
-                {
-                    println!("\n{}", style("Thinking:").dim().italic());
-                    print_markdown(&thinking.thinking, theme);
-                }
+                println!("\n{}", style("Thinking:").dim().italic());
+                println!("{}\n", style(&thinking.thinking).dim());
             }
             MessageContent::RedactedThinking(_) => {
                 // For redacted thinking, print thinking was redacted
                 println!("\n{}", style("Thinking:").dim().italic());
-                print_markdown("Thinking was redacted", theme);
+                println!("{}\n", style("Thinking was redacted").dim());
             }
             MessageContent::SystemNotification(notification) => {
                 use goose::conversation::message::SystemNotificationType;
@@ -264,7 +352,7 @@ fn render_tool_request(req: &ToolRequest, theme: Theme, debug: bool) {
             "todo__write" => render_todo_request(call, debug),
             _ => render_default_request(call, debug),
         },
-        Err(e) => print_markdown(&e.to_string(), theme),
+        Err(e) => print_highlighted(&e.to_string(), "Markdown", theme),
     }
 }
 
@@ -296,11 +384,16 @@ fn render_tool_response(resp: &ToolResponse, theme: Theme, debug: bool) {
                 if debug {
                     println!("{:#?}", content);
                 } else if let Some(text) = content.as_text() {
-                    print_markdown(&text.text, theme);
+                    let language = if is_unified_diff(&text.text) {
+                        "Diff"
+                    } else {
+                        "Markdown"
+                    };
+                    print_highlighted(&text.text, language, theme);
                 }
             }
         }
-        Err(e) => print_markdown(&e.to_string(), theme),
+        Err(e) => print_highlighted(&e.to_string(), "Markdown", theme),
     }
 }
 
@@ -545,13 +638,14 @@ pub fn env_no_color() -> bool {
     std::env::var_os("NO_COLOR").is_none()
 }
 
-fn print_markdown(content: &str, theme: Theme) {
+/// Print `content` highlighted as `language`; plain text when not on a terminal
+fn print_highlighted(content: &str, language: &str, theme: Theme) {
     if std::io::stdout().is_terminal() {
         bat::PrettyPrinter::new()
             .input(bat::Input::from_bytes(content.as_bytes()))
             .theme(theme.as_str())
             .colored_output(env_no_color())
-            .language("Markdown")
+            .language(language)
             .wrapping_mode(WrappingMode::NoWrapping(true))
             .print()
             .unwrap();
@@ -560,6 +654,29 @@ fn print_markdown(content: &str, theme: Theme) {
     }
 }
 
+/// Whether `text` looks like a unified diff: a `diff --git` header, or `---`/`+++` file
+/// headers followed by a `@@` hunk header
+fn is_unified_diff(text: &str) -> bool {
+    let mut saw_old_file = false;
+    let mut saw_new_file = false;
+    for line in text.lines().map(str::trim_end) {
+        if line.starts_with("diff --git ") {
+            return true;
+        }
+        if line.starts_with("--- ") {
+            saw_old_file = true;
+        } else if saw_old_file && line.starts_with("+++ ") {
+            saw_new_file = true;
+        } else if saw_new_file && line.starts_with("@@ -") && line[3..].contains(" @@") {
+            return true;
+        } else {
+            saw_old_file = false;
+            saw_new_file = false;
+        }
+    }
+    false
+}
+
 const INDENT: &str = "    ";
 
 fn print_value_with_prefix(prefix: &String, value: &Value, debug: bool) {
@@ -832,12 +949,7 @@ fn normalize_model_name(model: &str) -> String {
     result
 }
 
-async fn estimate_cost_usd(
-    provider: &str,
-    model: &str,
-    input_tokens: usize,
-    output_tokens: usize,
-) -> Option<f64> {
+async fn lookup_pricing(provider: &str, model: &str) -> Option<PricingInfo> {
     // For OpenRouter, parse the model name to extract real provider/model
     let openrouter_data = if provider == "openrouter" {
         parse_model_id(model)
@@ -852,16 +964,19 @@ async fn estimate_cost_usd(
 
     // Use the pricing module's get_model_pricing which handles model name mapping internally
     let cleaned_model = normalize_model_name(model_to_use);
-    let pricing_info = get_model_pricing(provider_to_use, &cleaned_model).await;
+    get_model_pricing(provider_to_use, &cleaned_model).await
+}
 
-    match pricing_info {
-        Some(pricing) => {
-            let input_cost = pricing.input_cost * input_tokens as f64;
-            let output_cost = pricing.output_cost * output_tokens as f64;
-            Some(input_cost + output_cost)
-        }
-        None => None,
-    }
+async fn estimate_cost_usd(
+    provider: &str,
+    model: &str,
+    input_tokens: usize,
+    output_tokens: usize,
+) -> Option<f64> {
+    let pricing_info = lookup_pricing(provider, model).await;
+
+    let usage = Usage::new(Some(input_tokens as i32), Some(output_tokens as i32), None);
+    pricing_info.map(|pricing| usage.cost(&pricing))
 }
 
 /// Display cost information, if price data is available.
@@ -883,6 +998,17 @@ pub async fn display_cost_usage(
     }
 }
 
+/// Display token usage broken down by provider and model, looking up pricing for each model
+/// when `with_pricing` is set.
+pub async fn display_usage_report(mut report: UsageReport, with_pricing: bool) {
+    if with_pricing {
+        for item in &mut report.items {
+            item.pricing = lookup_pricing(&item.provider, &item.model).await;
+        }
+    }
+    eprintln!("\n{}", report);
+}
+
 pub struct McpSpinners {
     bars: HashMap<String, ProgressBar>,
     log_spinner: Option<ProgressBar>,
@@ -952,6 +1078,71 @@ mod tests {
     use super::*;
     use std::env;
 
+    #[test]
+    fn test_thinking_filtered_by_show_thinking_flag() {
+        let message = Message::assistant()
+            .with_thinking("considering options", "sig")
+            .with_redacted_thinking("opaque")
+            .with_text("final answer");
+
+        let rendered = |show: bool| -> Vec<&MessageContent> {
+            message
+                .content
+                .iter()
+                .filter(|c| should_render_content(c, show))
+                .collect()
+        };
+
+        let hidden = rendered(false);
+        assert_eq!(hidden.len(), 1);
+        assert!(matches!(hidden[0], MessageContent::Text(_)));
+
+        let shown = rendered(true);
+        assert_eq!(shown.len(), 3);
+        assert!(matches!(shown[0], MessageContent::Thinking(_)));
+
+        // Filtering is display-only; the message still carries its thinking
+        assert_eq!(message.content.len(), 3);
+    }
+
+    #[test]
+    fn test_token_rate_from_timestamped_chunks() {
+        let start = Instant::now();
+        let mut tracker = TokenRateTracker::new(start);
+        assert_eq!(tracker.tokens_per_second(), None);
+
+        tracker.record(10, start + Duration::from_millis(500));
+        assert_eq!(tracker.tokens_per_second(), None);
+
+        tracker.record(0, start + Duration::from_millis(900));
+        tracker.record(20, start + Duration::from_millis(1500));
+        tracker.record(30, start + Duration::from_millis(2500));
+        assert_eq!(tracker.tokens_per_second(), Some(25.0));
+        assert_eq!(
+            tracker.status_line(start + Duration::from_secs(3)),
+            "60 tokens · 25.0 tok/s · 3.0s"
+        );
+    }
+
+    #[test]
+    fn test_is_unified_diff() {
+        let git_diff = "diff --git a/src/main.rs b/src/main.rs\nindex 1234..5678 100644\n";
+        assert!(is_unified_diff(git_diff));
+
+        let patch =
+            "Applied edit:\n--- a/notes.txt\n+++ b/notes.txt\n@@ -1,2 +1,2 @@\n-old\n+new\n same\n";
+        assert!(is_unified_diff(patch));
+
+        assert!(!is_unified_diff("Wrote 3 lines to notes.txt"));
+        assert!(!is_unified_diff(
+            "--- Summary ---\n+++ bonus points\nall done"
+        ));
+        assert!(!is_unified_diff("- removed item\n+ added item\n"));
+        assert!(!is_unified_diff(
+            "--- a/x\nsomething else\n+++ b/x\n@@ -1 +1 @@\n"
+        ));
+    }
+
     #[test]
     fn test_short_paths_unchanged() {
         assert_eq!(shorten_path("/usr/bin", false), "/usr/bin");
@@ -0,0 +1,77 @@
+use anyhow::Result;
+use goose::config::Config;
+use goose::providers::base::ProviderMetadata;
+
+use super::output;
+
+/// The secret holding the provider's API key, if it is configured with one
+pub fn api_key_name(metadata: &ProviderMetadata) -> Option<&str> {
+    metadata
+        .config_keys
+        .iter()
+        .find(|key| key.secret && key.required && !key.oauth_flow)
+        .map(|key| key.name.as_str())
+}
+
+/// Ask for a replacement API key for `provider` and store it in the keyring.
+///
+/// Returns false when there is no key to replace here: the provider doesn't use one, the
+/// key is set through an environment variable, or the user skipped the prompt.
+pub async fn prompt_for_api_key(provider: &str) -> Result<bool> {
+    let metadata = goose::providers::providers()
+        .await
+        .into_iter()
+        .map(|(metadata, _)| metadata)
+        .find(|metadata| metadata.name == provider);
+    let Some(key_name) = metadata.as_ref().and_then(api_key_name) else {
+        return Ok(false);
+    };
+
+    // Environment variables take precedence over the keyring, so a stored key would be ignored
+    if std::env::var(key_name.to_uppercase()).is_ok() {
+        output::render_error(&format!(
+            "{} is set in the environment — update it there and restart goose",
+            key_name
+        ));
+        return Ok(false);
+    }
+
+    let value = cliclack::password(format!(
+        "Enter a new {} for {} - press ESC to skip",
+        key_name, provider
+    ))
+    .mask('▪')
+    .interact()
+    .unwrap_or_default();
+    if value.trim().is_empty() {
+        return Ok(false);
+    }
+
+    Config::global().set_secret(key_name, &value)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goose::providers::base::ConfigKey;
+
+    #[test]
+    fn test_api_key_name_skips_plain_and_oauth_keys() {
+        let metadata = ProviderMetadata::new(
+            "mock",
+            "Mock",
+            "",
+            "mock-model",
+            vec![],
+            "",
+            vec![
+                ConfigKey::new("MOCK_HOST", true, false, None),
+                ConfigKey::new_oauth("MOCK_TOKEN", true, true, None),
+                ConfigKey::new("MOCK_API_KEY", true, true, None),
+            ],
+        );
+        assert_eq!(api_key_name(&metadata), Some("MOCK_API_KEY"));
+        assert_eq!(api_key_name(&ProviderMetadata::empty()), None);
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::{bail, Context, Result};
+use goose::conversation::message::Message;
+use goose::conversation::Conversation;
+use rmcp::model::Role;
+
+/// Parse the `--stdin-json` input: a JSON array of messages ending with the user message to
+/// reply to. Earlier messages, including tool requests and responses, become the history.
+pub fn parse_message_history(input: &str) -> Result<Vec<Message>> {
+    let messages: Vec<Message> = serde_json::from_str(input)
+        .context("Invalid --stdin-json input: expected a JSON array of messages")?;
+
+    let Some(last) = messages.last() else {
+        bail!("Invalid --stdin-json input: the message array is empty");
+    };
+    if last.role != Role::User {
+        bail!("Invalid --stdin-json input: the last message must have role \"user\"");
+    }
+
+    let conversation = Conversation::new(messages)
+        .map_err(|e| anyhow::anyhow!("Invalid --stdin-json input: {}", e))?;
+    Ok(conversation.messages().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goose::conversation::message::MessageContent;
+
+    const HISTORY: &str = r#"[
+        {"role": "user", "created": 1700000000, "metadata": {"userVisible": true, "agentVisible": true},
+         "content": [{"type": "text", "text": "What files are here?"}]},
+        {"role": "assistant", "created": 1700000001, "metadata": {"userVisible": true, "agentVisible": true},
+         "content": [{"type": "toolRequest", "id": "call_1",
+                      "toolCall": {"status": "success", "value": {"name": "developer__shell", "arguments": {"command": "ls"}}}}]},
+        {"role": "user", "created": 1700000002, "metadata": {"userVisible": true, "agentVisible": true},
+         "content": [{"type": "toolResponse", "id": "call_1",
+                      "toolResult": {"status": "success", "value": [{"type": "text", "text": "README.md"}]}}]},
+        {"role": "assistant", "created": 1700000003, "metadata": {"userVisible": true, "agentVisible": true},
+         "content": [{"type": "text", "text": "There is a README.md."}]},
+        {"role": "user", "created": 1700000004, "metadata": {"userVisible": true, "agentVisible": true},
+         "content": [{"type": "text", "text": "Summarize it"}]}
+    ]"#;
+
+    #[test]
+    fn test_parse_message_history() {
+        let messages = parse_message_history(HISTORY).unwrap();
+        assert_eq!(messages.len(), 5);
+        assert!(matches!(
+            &messages[1].content[0],
+            MessageContent::ToolRequest(req) if req.id == "call_1"
+        ));
+        assert!(matches!(
+            &messages[2].content[0],
+            MessageContent::ToolResponse(resp) if resp.id == "call_1"
+        ));
+        assert_eq!(messages[4].role, Role::User);
+        assert_eq!(messages[4].as_concat_text(), "Summarize it");
+    }
+
+    #[test]
+    fn test_parse_message_history_rejects_malformed_input() {
+        let err = parse_message_history(r#"{"role": "user"}"#).unwrap_err();
+        assert!(format!("{:#}", err).contains("expected a JSON array of messages"));
+
+        let err = parse_message_history(r#"[{"role": "user", "content": []}]"#).unwrap_err();
+        assert!(format!("{:#}", err).contains("missing field"));
+
+        let err = parse_message_history("[]").unwrap_err();
+        assert!(err.to_string().contains("empty"));
+
+        let trailing_assistant = r#"[
+            {"role": "user", "created": 1, "metadata": {"userVisible": true, "agentVisible": true},
+             "content": [{"type": "text", "text": "hi"}]},
+            {"role": "assistant", "created": 2, "metadata": {"userVisible": true, "agentVisible": true},
+             "content": [{"type": "text", "text": "hello"}]}
+        ]"#;
+        let err = parse_message_history(trailing_assistant).unwrap_err();
+        assert!(err.to_string().contains("last message must have role"));
+    }
+}
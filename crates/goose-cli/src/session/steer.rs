@@ -0,0 +1,113 @@
+use goose::conversation::message::{Message, MessageContent};
+use goose::conversation::Conversation;
+use rmcp::model::Role;
+
+/// Appended to a reply that was cut short, so the model knows it did not finish
+pub const INTERRUPTED_MARKER: &str = "[interrupted by the user]";
+
+/// The partially streamed assistant reply at the end of `messages`, marked as interrupted.
+///
+/// Replies that already contain tool requests are not partial text and are left to the
+/// regular interruption handling.
+pub fn partial_reply(messages: &Conversation) -> Option<Message> {
+    let last = messages.last()?;
+    if last.role != Role::Assistant {
+        return None;
+    }
+    let only_text = last
+        .content
+        .iter()
+        .all(|content| matches!(content, MessageContent::Text(_)));
+    if !only_text || last.as_concat_text().trim().is_empty() {
+        return None;
+    }
+
+    let mut partial = last.clone();
+    partial
+        .content
+        .push(MessageContent::text(format!("\n\n{}", INTERRUPTED_MARKER)));
+    Some(partial)
+}
+
+/// The user message that starts the next turn, or None when nothing was typed.
+pub fn steering_message(input: &str) -> Option<Message> {
+    let input = input.trim();
+    if input.is_empty() {
+        None
+    } else {
+        Some(Message::user().with_text(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use goose::agents::AgentEvent;
+    use tokio_util::sync::CancellationToken;
+
+    #[tokio::test]
+    async fn test_interrupt_captures_partial_reply_and_injects_message() {
+        let chunks = ["Refactoring the ", "parser to use ", "a state machine"];
+        let events = futures::stream::iter(chunks.map(|chunk| {
+            AgentEvent::Message(Message::assistant().with_id("reply-1").with_text(chunk))
+        }));
+        let mut stream = Box::pin(events);
+
+        let mut messages =
+            Conversation::new_unvalidated(vec![Message::user().with_text("Clean up the parser")]);
+        let cancel_token = CancellationToken::new();
+
+        let mut received = 0;
+        loop {
+            tokio::select! {
+                biased;
+                _ = cancel_token.cancelled() => break,
+                event = stream.next() => match event {
+                    Some(AgentEvent::Message(message)) => {
+                        messages.push(message);
+                        received += 1;
+                        if received == 2 {
+                            cancel_token.cancel();
+                        }
+                    }
+                    _ => break,
+                },
+            }
+        }
+
+        let partial = partial_reply(&messages).expect("partial reply");
+        let text = partial.as_concat_text();
+        assert!(text.starts_with("Refactoring the parser to use "));
+        assert!(!text.contains("state machine"));
+        assert!(text.ends_with(INTERRUPTED_MARKER));
+
+        assert!(steering_message("   ").is_none());
+        let steer = steering_message(" use a recursive descent parser instead ").unwrap();
+        assert_eq!(steer.role, Role::User);
+        assert_eq!(
+            steer.as_concat_text(),
+            "use a recursive descent parser instead"
+        );
+    }
+
+    #[test]
+    fn test_no_partial_reply_for_tool_requests_or_user_turns() {
+        let user_only = Conversation::new_unvalidated(vec![Message::user().with_text("hello")]);
+        assert!(partial_reply(&user_only).is_none());
+
+        let tool_request = Conversation::new_unvalidated(vec![
+            Message::user().with_text("list files"),
+            Message::assistant()
+                .with_text("Listing files")
+                .with_tool_request(
+                    "call-1",
+                    Ok(rmcp::model::CallToolRequestParam {
+                        name: "developer__shell".into(),
+                        arguments: None,
+                    }),
+                ),
+        ]);
+        assert!(partial_reply(&tool_request).is_none());
+    }
+}
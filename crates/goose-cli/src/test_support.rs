@@ -0,0 +1,102 @@
+use async_trait::async_trait;
+use goose::conversation::message::Message;
+use goose::conversation::Conversation;
+use goose::model::ModelConfig;
+use goose::providers::base::{MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage};
+use goose::providers::errors::ProviderError;
+use rmcp::model::Tool;
+use std::sync::OnceLock;
+use tempfile::TempDir;
+
+/// Point goose's config, data and state directories at a temporary root shared by the test
+/// process, so tests that go through the global SessionManager never write to the user's
+/// session database. Call it before the test first touches SessionManager.
+pub fn isolate_goose_paths() {
+    static ROOT: OnceLock<TempDir> = OnceLock::new();
+    let root = ROOT.get_or_init(|| TempDir::new().expect("failed to create a test root"));
+    std::env::set_var("GOOSE_PATH_ROOT", root.path());
+}
+
+type Respond = dyn Fn(&[Message]) -> Result<(Message, Usage), ProviderError> + Send + Sync;
+
+/// A provider that answers each request with whatever `respond` returns for the messages sent
+pub struct MockProvider {
+    model_config: ModelConfig,
+    respond: Box<Respond>,
+    stream_chunks: Vec<Message>,
+}
+
+impl MockProvider {
+    pub fn new(
+        respond: impl Fn(&[Message]) -> Result<(Message, Usage), ProviderError> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            model_config: ModelConfig::new("mock-model").unwrap(),
+            respond: Box::new(respond),
+            stream_chunks: Vec::new(),
+        }
+    }
+
+    /// Answer every request with the same text and usage
+    pub fn replying(text: &str, usage: Usage) -> Self {
+        let text = text.to_string();
+        Self::new(move |_| Ok((Message::assistant().with_text(&text), usage)))
+    }
+
+    /// Stream `chunks` when asked to stream, rather than the whole reply
+    pub fn with_stream(mut self, chunks: Vec<Message>) -> Self {
+        self.stream_chunks = chunks;
+        self
+    }
+}
+
+#[async_trait]
+impl Provider for MockProvider {
+    fn metadata() -> ProviderMetadata {
+        ProviderMetadata::empty()
+    }
+
+    fn get_name(&self) -> &str {
+        "mock"
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.model_config.clone()
+    }
+
+    async fn complete_with_model(
+        &self,
+        _model_config: &ModelConfig,
+        _system: &str,
+        messages: &[Message],
+        _tools: &[Tool],
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let (message, usage) = (self.respond)(messages)?;
+        Ok((message, ProviderUsage::new("mock".to_string(), usage)))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        !self.stream_chunks.is_empty()
+    }
+
+    async fn stream(
+        &self,
+        _system: &str,
+        _messages: &[Message],
+        _tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let chunks: Vec<_> = self
+            .stream_chunks
+            .iter()
+            .map(|chunk| Ok((Some(chunk.clone()), None)))
+            .collect();
+        Ok(Box::pin(futures::stream::iter(chunks)))
+    }
+
+    async fn generate_session_name(
+        &self,
+        _messages: &Conversation,
+    ) -> Result<String, ProviderError> {
+        Ok("Mock session".to_string())
+    }
+}
@@ -1,4 +1,5 @@
 use base64::Engine;
+use goose::utils::open_code_fence;
 use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use include_dir::{include_dir, Dir};
 use indoc::{formatdoc, indoc};
@@ -21,6 +22,7 @@ use std::{
     io::Cursor,
     path::{Path, PathBuf},
     sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 use xcap::{Monitor, Window};
 
@@ -40,6 +42,23 @@ use super::text_editor::{
     text_editor_insert, text_editor_replace, text_editor_undo, text_editor_view, text_editor_write,
 };
 
+/// JSON-RPC error code for a request the client cancelled (matches LSP's `RequestCancelled`)
+pub const REQUEST_CANCELLED: ErrorCode = ErrorCode(-32800);
+
+/// Default cap on characters kept per shell output stream, overridable with
+/// `GOOSE_SHELL_MAX_OUTPUT_CHARS`
+const DEFAULT_SHELL_MAX_OUTPUT_CHARS: usize = 400_000;
+
+/// Captured result of a completed shell command
+#[derive(Debug, Default)]
+struct ShellOutput {
+    stdout: String,
+    stderr: String,
+    /// `None` when the process was terminated by a signal
+    exit_code: Option<i32>,
+    duration: Duration,
+}
+
 /// Parameters for the screen_capture tool
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ScreenCaptureParams {
@@ -166,6 +185,69 @@ fn load_prompt_files() -> HashMap<String, Prompt> {
     prompts
 }
 
+/// Maximum characters kept per shell output stream.
+fn shell_max_output_chars() -> usize {
+    std::env::var("GOOSE_SHELL_MAX_OUTPUT_CHARS")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|&v| v > 0)
+        .unwrap_or(DEFAULT_SHELL_MAX_OUTPUT_CHARS)
+}
+
+/// Keep the last `max_chars` characters of `output`, noting how much was dropped.
+/// A code fence opened in the dropped part is reopened so the tail stays valid markdown.
+fn truncate_shell_output(output: &str, max_chars: usize) -> String {
+    let char_count = output.chars().count();
+    if char_count <= max_chars {
+        return output.to_string();
+    }
+
+    let omitted = char_count - max_chars;
+    let tail_start = output
+        .char_indices()
+        .nth(omitted)
+        .map_or(output.len(), |(idx, _)| idx);
+    let reopened = open_code_fence(&output[..tail_start])
+        .map(|opening| format!("{}\n", opening))
+        .unwrap_or_default();
+    format!(
+        "[output truncated: first {} of {} characters omitted]\n{}{}",
+        omitted,
+        char_count,
+        reopened,
+        &output[tail_start..]
+    )
+}
+
+fn format_shell_status(exit_code: Option<i32>, duration: Duration) -> String {
+    let code = exit_code.map_or_else(
+        || "none (terminated by signal)".to_string(),
+        |c| c.to_string(),
+    );
+    format!(
+        "exit code: {}, duration: {:.2}s",
+        code,
+        duration.as_secs_f64()
+    )
+}
+
+/// Lay out stdout, then a labelled stderr section if present, then the exit status.
+fn format_shell_result(stdout: &str, stderr: &str, status: &str) -> String {
+    let mut result = stdout.to_string();
+    if !stderr.is_empty() {
+        if !result.is_empty() && !result.ends_with('\n') {
+            result.push('\n');
+        }
+        result.push_str("stderr:\n");
+        result.push_str(stderr);
+    }
+    if !result.is_empty() && !result.ends_with('\n') {
+        result.push('\n');
+    }
+    result.push_str(&format!("[{}]", status));
+    result
+}
+
 /// Developer MCP Server using official RMCP SDK
 #[derive(Clone)]
 pub struct DeveloperServer {
@@ -873,13 +955,18 @@ impl DeveloperServer {
             }
         }
 
-        let output_str = output_result?;
+        let output = output_result?;
 
-        // Validate output size
-        self.validate_shell_output_size(command, &output_str)?;
+        // Cap each stream, then apply line truncation separately so stdout and stderr stay apart
+        let max_chars = shell_max_output_chars();
+        let stdout = truncate_shell_output(&output.stdout, max_chars);
+        let stderr = truncate_shell_output(&output.stderr, max_chars);
+        let (stdout_final, stdout_user) = self.process_shell_output(&stdout)?;
+        let (stderr_final, stderr_user) = self.process_shell_output(&stderr)?;
 
-        // Process and format the output
-        let (final_output, user_output) = self.process_shell_output(&output_str)?;
+        let status = format_shell_status(output.exit_code, output.duration);
+        let final_output = format_shell_result(&stdout_final, &stderr_final, &status);
+        let user_output = format_shell_result(&stdout_user, &stderr_user, &status);
 
         Ok(CallToolResult::success(vec![
             Content::text(final_output).with_audience(vec![Role::Assistant]),
@@ -933,7 +1020,7 @@ impl DeveloperServer {
         Ok(())
     }
 
-    /// Execute a shell command and return the combined output.
+    /// Execute a shell command and return its output, exit code and duration.
     ///
     /// Streams output in real-time to the client using logging notifications.
     async fn execute_shell_command(
@@ -941,9 +1028,10 @@ impl DeveloperServer {
         command: &str,
         peer: &rmcp::service::Peer<RoleServer>,
         cancellation_token: CancellationToken,
-    ) -> Result<String, ErrorData> {
+    ) -> Result<ShellOutput, ErrorData> {
         // Get platform-specific shell configuration
         let shell_config = get_shell_config();
+        let started = Instant::now();
 
         let mut child = configure_shell_command(&shell_config, command)
             .spawn()
@@ -965,9 +1053,15 @@ impl DeveloperServer {
 
         tokio::select! {
             output_result = output_task => {
+                let (stdout, stderr) = output_result?;
                 // Wait for the process to complete
-                let _exit_status = child.wait().await.map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
-                output_result
+                let exit_status = child.wait().await.map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+                Ok(ShellOutput {
+                    stdout,
+                    stderr,
+                    exit_code: exit_status.code(),
+                    duration: started.elapsed(),
+                })
             }
             _ = cancellation_token.cancelled() => {
                 tracing::info!("Cancellation token triggered! Attempting to kill process and all child processes");
@@ -983,7 +1077,7 @@ impl DeveloperServer {
                 }
 
                 Err(ErrorData::new(
-                    ErrorCode::INTERNAL_ERROR,
+                    REQUEST_CANCELLED,
                     "Shell command was cancelled by user".to_string(),
                     None,
                 ))
@@ -991,7 +1085,7 @@ impl DeveloperServer {
         }
     }
 
-    /// Stream shell output in real-time and return the combined output.
+    /// Stream shell output in real-time and return the collected `(stdout, stderr)`.
     ///
     /// Merges stdout and stderr streams and sends each line as a logging notification.
     async fn stream_shell_output(
@@ -999,12 +1093,13 @@ impl DeveloperServer {
         stdout: tokio::process::ChildStdout,
         stderr: tokio::process::ChildStderr,
         peer: rmcp::service::Peer<RoleServer>,
-    ) -> Result<String, ErrorData> {
+    ) -> Result<(String, String), ErrorData> {
         let stdout = BufReader::new(stdout);
         let stderr = BufReader::new(stderr);
 
         let output_task = tokio::spawn(async move {
-            let mut combined_output = String::new();
+            let mut stdout_output = String::new();
+            let mut stderr_output = String::new();
 
             // Merge stdout and stderr streams
             // ref https://blog.yoshuawuyts.com/futures-concurrency-3
@@ -1019,7 +1114,11 @@ impl DeveloperServer {
                 // Convert to UTF-8 to avoid corrupted output
                 let line_str = String::from_utf8_lossy(&line);
 
-                combined_output.push_str(&line_str);
+                if stream_type == "stdout" {
+                    stdout_output.push_str(&line_str);
+                } else {
+                    stderr_output.push_str(&line_str);
+                }
 
                 // Stream each line back to the client in real-time
                 let trimmed_line = line_str.trim();
@@ -1042,7 +1141,7 @@ impl DeveloperServer {
                     }
                 }
             }
-            Ok::<_, std::io::Error>(combined_output)
+            Ok::<_, std::io::Error>((stdout_output, stderr_output))
         });
 
         match output_task.await {
@@ -1057,27 +1156,6 @@ impl DeveloperServer {
         }
     }
 
-    /// Validate that shell output doesn't exceed size limits.
-    fn validate_shell_output_size(&self, command: &str, output: &str) -> Result<(), ErrorData> {
-        const MAX_CHAR_COUNT: usize = 400_000; // 400KB
-        let char_count = output.chars().count();
-
-        if char_count > MAX_CHAR_COUNT {
-            return Err(ErrorData::new(
-                ErrorCode::INTERNAL_ERROR,
-                format!(
-                    "Shell output from command '{}' has too many characters ({}). Maximum character count is {}.",
-                    command,
-                    char_count,
-                    MAX_CHAR_COUNT
-                ),
-                None,
-            ));
-        }
-
-        Ok(())
-    }
-
     /// Analyze code structure and relationships.
     ///
     /// Automatically selects the appropriate analysis:
@@ -3345,6 +3423,8 @@ mod tests {
             assert!(result.is_ok(), "Shell task should complete within timeout");
             let task_result = result.unwrap();
             assert!(task_result.is_ok(), "Shell task should not panic");
+            let shell_error = task_result.unwrap().unwrap_err();
+            assert_eq!(shell_error.code, REQUEST_CANCELLED);
 
             // Verify the command was cancelled quickly (much less than 30 seconds)
             assert!(
@@ -3507,7 +3587,112 @@ mod tests {
                 "Process should be cleaned up after completion"
             );
 
+            drop(processes);
+
+            let text = assistant_text(&result.unwrap());
+            assert!(text.contains("Hello, World!"));
+            assert!(text.contains("exit code: 0, duration: "));
+            assert!(!text.contains("stderr:"));
+
             cleanup_test_service(running_service, peer);
         });
     }
+
+    fn assistant_text(result: &CallToolResult) -> String {
+        result
+            .content
+            .iter()
+            .find(|c| {
+                c.audience()
+                    .is_some_and(|roles| roles.contains(&Role::Assistant))
+            })
+            .and_then(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .expect("shell result should have assistant text")
+    }
+
+    #[test]
+    #[serial]
+    #[cfg(unix)]
+    fn test_shell_nonzero_exit_separates_stderr() {
+        run_shell_test(|| async {
+            let server = create_test_server();
+            let running_service = serve_directly(server.clone(), create_test_transport(), None);
+            let peer = running_service.peer().clone();
+
+            let result = server
+                .shell(
+                    Parameters(ShellParams {
+                        command: "echo to-stdout; echo to-stderr >&2; exit 3".to_string(),
+                    }),
+                    RequestContext {
+                        ct: Default::default(),
+                        id: NumberOrString::Number(1),
+                        meta: Default::default(),
+                        extensions: Default::default(),
+                        peer: peer.clone(),
+                    },
+                )
+                .await
+                .expect("non-zero exit should still return a result");
+
+            let text = assistant_text(&result);
+            assert!(text.starts_with("to-stdout\n"), "got: {}", text);
+            assert!(text.contains("stderr:\nto-stderr\n"), "got: {}", text);
+            assert!(text.contains("exit code: 3, duration: "), "got: {}", text);
+
+            cleanup_test_service(running_service, peer);
+        });
+    }
+
+    #[test]
+    #[serial]
+    #[cfg(unix)]
+    fn test_shell_output_exceeding_cap_is_truncated() {
+        run_shell_test(|| async {
+            std::env::set_var("GOOSE_SHELL_MAX_OUTPUT_CHARS", "100");
+
+            let server = create_test_server();
+            let running_service = serve_directly(server.clone(), create_test_transport(), None);
+            let peer = running_service.peer().clone();
+
+            let result = server
+                .shell(
+                    Parameters(ShellParams {
+                        command: "printf 'a%.0s' $(seq 1 500); echo END".to_string(),
+                    }),
+                    RequestContext {
+                        ct: Default::default(),
+                        id: NumberOrString::Number(1),
+                        meta: Default::default(),
+                        extensions: Default::default(),
+                        peer: peer.clone(),
+                    },
+                )
+                .await;
+
+            std::env::remove_var("GOOSE_SHELL_MAX_OUTPUT_CHARS");
+
+            let text = assistant_text(&result.expect("oversized output should be truncated"));
+            assert!(
+                text.starts_with("[output truncated: first 404 of 504 characters omitted]"),
+                "got: {}",
+                text
+            );
+            assert!(text.contains("aEND\n"));
+            assert!(text.contains("exit code: 0"));
+
+            cleanup_test_service(running_service, peer);
+        });
+    }
+
+    #[test]
+    fn test_truncate_shell_output_reopens_code_fence() {
+        let output = format!("```json\n{}\n```\n", "x".repeat(50));
+        let truncated = truncate_shell_output(&output, 10);
+        assert_eq!(
+            truncated,
+            "[output truncated: first 53 of 63 characters omitted]\n```json\nxxxxx\n```\n"
+        );
+    }
 }
@@ -1,4 +1,5 @@
 use goose::agents::extension::Envs;
+use goose::agents::extension::StdioFraming;
 use goose::agents::extension::ToolInfo;
 use goose::agents::ExtensionConfig;
 use goose::config::permission::PermissionLevel;
@@ -440,6 +441,7 @@ derive_utoipa!(Icon as IconSchema);
         ExtensionConfig,
         ConfigKey,
         Envs,
+        StdioFraming,
         ToolSchema,
         ToolAnnotationsSchema,
         ToolInfo,
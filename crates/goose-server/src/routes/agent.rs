@@ -210,6 +210,7 @@ async fn resume_agent(
                 message: "Failed to get agent for route".into(),
                 status: code,
             })?;
+        agent.restore_usage(&session).await;
 
         let config = Config::global();
 
@@ -0,0 +1,53 @@
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::{routing::get, Router};
+use goose::providers::usage_metrics::render_prometheus;
+
+/// Token and cost counters per provider and model, for Prometheus to scrape
+async fn metrics() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        render_prometheus(),
+    )
+}
+
+pub fn routes() -> Router {
+    Router::new().route("/metrics", get(metrics))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, http::StatusCode};
+    use goose::providers::base::{ProviderUsage, Usage};
+    use goose::providers::usage_metrics;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_reports_recorded_usage() {
+        let usage = ProviderUsage::new(
+            "scrape-model".to_string(),
+            Usage::new(Some(50), Some(10), Some(60)),
+        )
+        .with_provider("scrape-provider");
+        usage_metrics::record(&usage).await;
+
+        let request = Request::builder()
+            .uri("/metrics")
+            .body(Body::empty())
+            .unwrap();
+        let response = routes().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains(
+            "goose_input_tokens_total{provider=\"scrape-provider\",model=\"scrape-model\"} 50"
+        ));
+        assert!(body.contains(
+            "goose_output_tokens_total{provider=\"scrape-provider\",model=\"scrape-model\"} 10"
+        ));
+    }
+}
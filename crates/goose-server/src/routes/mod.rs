@@ -2,6 +2,8 @@ pub mod agent;
 pub mod audio;
 pub mod config_management;
 pub mod errors;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod recipe;
 pub mod recipe_utils;
 pub mod reply;
@@ -27,4 +29,15 @@ pub fn configure(state: Arc<crate::state::AppState>) -> Router {
         .merge(session::routes(state.clone()))
         .merge(schedule::routes(state.clone()))
         .merge(setup::routes(state.clone()))
+        .merge(metrics_routes())
+}
+
+#[cfg(feature = "metrics")]
+fn metrics_routes() -> Router {
+    metrics::routes()
+}
+
+#[cfg(not(feature = "metrics"))]
+fn metrics_routes() -> Router {
+    Router::new()
 }
@@ -282,6 +282,8 @@ pub async fn reply(
             schedule_id: session.schedule_id.clone(),
             max_turns: None,
             retry_config: None,
+            compact_threshold: None,
+            turn_budget: None,
         };
 
         let user_message = match messages.last() {
@@ -47,6 +47,8 @@ async fn main() {
         schedule_id: None,
         max_turns: None,
         retry_config: None,
+        compact_threshold: None,
+        turn_budget: None,
     };
 
     let user_message = Message::user()
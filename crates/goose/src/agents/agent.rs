@@ -1,6 +1,8 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
@@ -13,7 +15,8 @@ use crate::agents::extension_manager::{get_parameter_names, ExtensionManager};
 use crate::agents::extension_manager_extension::MANAGE_EXTENSIONS_TOOL_NAME_COMPLETE;
 use crate::agents::final_output_tool::{FINAL_OUTPUT_CONTINUATION_MESSAGE, FINAL_OUTPUT_TOOL_NAME};
 use crate::agents::platform_tools::PLATFORM_MANAGE_SCHEDULE_TOOL_NAME;
-use crate::agents::prompt_manager::PromptManager;
+use crate::agents::post_processors::PostProcessors;
+use crate::agents::prompt_manager::{budget_extension_instructions, PromptManager};
 use crate::agents::recipe_tools::dynamic_task_tools::{
     create_dynamic_task, create_dynamic_task_tool, DYNAMIC_TASK_TOOL_NAME_PREFIX,
 };
@@ -31,18 +34,19 @@ use crate::agents::types::SessionConfig;
 use crate::agents::types::{FrontendTool, SharedProvider, ToolResultReceiver};
 use crate::config::{get_enabled_extensions, Config, GooseMode};
 use crate::context_mgmt::{
-    check_if_compaction_needed, compact_messages, DEFAULT_COMPACTION_THRESHOLD,
+    check_if_compaction_needed, compact_messages, limit_message_count, DEFAULT_COMPACTION_THRESHOLD,
 };
 use crate::conversation::{debug_conversation_fix, fix_conversation, Conversation};
 use crate::mcp_utils::ToolResult;
 use crate::permission::permission_inspector::PermissionInspector;
 use crate::permission::permission_judge::PermissionCheckResult;
 use crate::permission::PermissionConfirmation;
-use crate::providers::base::Provider;
+use crate::providers::base::{CompletionOptions, Provider};
 use crate::providers::errors::ProviderError;
+use crate::providers::usage_report::UsageReport;
 use crate::recipe::{Author, Recipe, Response, Settings, SubRecipe};
 use crate::security::security_inspector::SecurityInspector;
-use crate::tool_inspection::ToolInspectionManager;
+use crate::tool_inspection::{get_flagged_security_findings, ToolInspectionManager};
 use crate::tool_monitor::RepetitionInspector;
 use crate::utils::is_token_cancelled;
 use regex::Regex;
@@ -58,17 +62,38 @@ use tracing::{debug, error, info, instrument, warn};
 use super::final_output_tool::FinalOutputTool;
 use super::model_selector::autopilot::AutoPilot;
 use super::platform_tools;
-use super::tool_execution::{ToolCallResult, CHAT_MODE_TOOL_SKIPPED_RESPONSE, DECLINED_RESPONSE};
-use crate::agents::subagent_task_config::TaskConfig;
+use super::tool_execution::{
+    dedupe_tool_requests, with_duplicate_tool_responses, ToolCallResult,
+    CHAT_MODE_TOOL_SKIPPED_RESPONSE, DECLINED_RESPONSE,
+};
+use super::tool_span::ToolSpanFields;
+use crate::agents::subagent_task_config::{
+    max_agent_depth, TaskConfig, GOOSE_MAX_AGENT_DEPTH_ENV_VAR,
+};
 use crate::conversation::message::{Message, MessageContent, SystemNotificationType, ToolRequest};
+use crate::retry_budget::{with_retry_budget, RetryBudget};
 use crate::scheduler_trait::SchedulerTrait;
-use crate::session::extension_data::{EnabledExtensionsState, ExtensionState};
+use crate::session::extension_data::{
+    EnabledExtensionsState, ExtensionState, ModelState, UsageState,
+};
 use crate::session::{Session, SessionManager};
 
 const DEFAULT_MAX_TURNS: u32 = 1000;
 const COMPACTION_THINKING_TEXT: &str = "goose is compacting the conversation...";
+const EMPTY_RESPONSE_NUDGE: &str = "Your previous response was empty. Please respond to the last message, either with text or by calling a tool.";
+const EMPTY_RESPONSE_NOTE: &str =
+    "The model returned an empty response. Please try rephrasing your request.";
+const CONTEXT_RECOVERY_FAILED_NOTE: &str =
+    "The conversation is still too long for the model's context window after compacting. Please start a new session or shorten your request.";
 pub const MANUAL_COMPACT_TRIGGER: &str = "Please compact this conversation";
 
+fn retry_budget_exhausted_note(budget: &RetryBudget) -> String {
+    format!(
+        "Stopped this turn after using its retry budget of {} retries. Please try again, or raise --turn-budget (GOOSE_TURN_RETRY_BUDGET) if the failures are expected to clear.",
+        budget.limit()
+    )
+}
+
 /// Context needed for the reply function
 pub struct ReplyContext {
     pub conversation: Conversation,
@@ -106,6 +131,15 @@ pub struct Agent {
     pub(super) retry_manager: RetryManager,
     pub(super) tool_inspection_manager: ToolInspectionManager,
     pub(super) autopilot: Mutex<AutoPilot>,
+    pub(super) usage_report: Mutex<UsageReport>,
+    /// Send only the first sentence of each tool description to the provider
+    pub(super) compact_tool_descriptions: AtomicBool,
+    /// Request complete responses from the provider instead of streaming
+    pub(super) no_stream: AtomicBool,
+    /// End the reply with an error when the provider fails instead of only reporting it
+    pub(super) error_on_provider_failure: AtomicBool,
+    /// Rewrites applied to assistant text, overriding the configured ones when set
+    pub(super) post_processors: Mutex<Option<Arc<PostProcessors>>>,
 }
 
 #[derive(Clone, Debug)]
@@ -132,12 +166,17 @@ pub type ToolStream = Pin<Box<dyn Stream<Item = ToolStreamItem<ToolResult<Vec<Co
 // tool_stream combines a stream of ServerNotifications with a future representing the
 // final result of the tool call. MCP notifications are not request-scoped, but
 // this lets us capture all notifications emitted during the tool call for
-// simpler consumption
+// simpler consumption. A panic in the result future becomes an error result for that
+// tool alone, so the other tools running alongside it still report back.
 pub fn tool_stream<S, F>(rx: S, done: F) -> ToolStream
 where
     S: Stream<Item = ServerNotification> + Send + Unpin + 'static,
     F: Future<Output = ToolResult<Vec<Content>>> + Send + 'static,
 {
+    let done = std::panic::AssertUnwindSafe(done)
+        .catch_unwind()
+        .map(|result| result.unwrap_or_else(|panic| Err(tool_panic_error(panic))));
+
     Box::pin(async_stream::stream! {
         tokio::pin!(done);
         let mut rx = rx;
@@ -156,6 +195,20 @@ where
     })
 }
 
+fn tool_panic_error(panic: Box<dyn std::any::Any + Send>) -> ErrorData {
+    let reason = panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    error!("Tool call panicked: {}", reason);
+    ErrorData::new(
+        ErrorCode::INTERNAL_ERROR,
+        format!("Tool execution panicked: {}", reason),
+        None,
+    )
+}
+
 impl Agent {
     pub fn new() -> Self {
         // Create channels with buffer size 32 (adjust if needed)
@@ -181,6 +234,11 @@ impl Agent {
             retry_manager: RetryManager::new(),
             tool_inspection_manager: Self::create_default_tool_inspection_manager(),
             autopilot: Mutex::new(AutoPilot::new()),
+            usage_report: Mutex::new(UsageReport::new()),
+            compact_tool_descriptions: AtomicBool::new(false),
+            no_stream: AtomicBool::new(false),
+            error_on_provider_failure: AtomicBool::new(false),
+            post_processors: Mutex::new(None),
         }
     }
 
@@ -414,6 +472,11 @@ impl Agent {
         cancellation_token: Option<CancellationToken>,
         session: &Session,
     ) -> (String, Result<ToolCallResult, ErrorData>) {
+        let span_fields = ToolSpanFields::from_config();
+        if let Some(span_fields) = &span_fields {
+            span_fields.record_arguments(&tracing::Span::current(), tool_call.arguments.as_ref());
+        }
+
         if session.session_type == crate::session::SessionType::SubAgent
             && (tool_call.name == DYNAMIC_TASK_TOOL_NAME_PREFIX
                 || tool_call.name == SUBAGENT_EXECUTE_TASK_TOOL_NAME)
@@ -428,6 +491,24 @@ impl Agent {
             );
         }
 
+        let max_depth = max_agent_depth();
+        if self.depth() >= max_depth
+            && (tool_call.name == DYNAMIC_TASK_TOOL_NAME_PREFIX
+                || tool_call.name == SUBAGENT_EXECUTE_TASK_TOOL_NAME)
+        {
+            return (
+                request_id,
+                Err(ErrorData::new(
+                    ErrorCode::INVALID_REQUEST,
+                    format!(
+                        "Maximum agent depth of {} reached; cannot start another sub-agent",
+                        max_depth
+                    ),
+                    None,
+                )),
+            );
+        }
+
         if tool_call.name == PLATFORM_MANAGE_SCHEDULE_TOOL_NAME {
             let arguments = tool_call
                 .arguments
@@ -496,7 +577,8 @@ impl Agent {
             let extensions = self.get_extension_configs().await;
 
             let task_config =
-                TaskConfig::new(provider, &session.id, &session.working_dir, extensions);
+                TaskConfig::new(provider, &session.id, &session.working_dir, extensions)
+                    .with_depth(self.depth() + 1);
 
             let arguments = match tool_call.arguments.clone() {
                 Some(args) => Value::Object(args),
@@ -602,15 +684,17 @@ impl Agent {
 
         debug!("WAITING_TOOL_END: {}", tool_call.name);
 
+        let span = tracing::Span::current();
         (
             request_id,
             Ok(ToolCallResult {
                 notification_stream: result.notification_stream,
-                result: Box::new(
-                    result
-                        .result
-                        .map(super::large_response_handler::process_tool_response),
-                ),
+                result: Box::new(result.result.map(move |result| {
+                    if let Some(span_fields) = &span_fields {
+                        span_fields.record_result(&span, &result);
+                    }
+                    super::large_response_handler::process_tool_response(result)
+                })),
             }),
         )
     }
@@ -750,6 +834,75 @@ impl Agent {
         Ok(())
     }
 
+    /// How many agents deep this agent runs: 0 for a user's agent, +1 for each agent started
+    /// by another agent's tools
+    pub fn depth(&self) -> usize {
+        self.extension_manager.depth()
+    }
+
+    /// Set the depth, which extensions started from now on are launched one level below
+    pub fn set_depth(&self, depth: usize) {
+        self.extension_manager.set_depth(depth);
+    }
+
+    /// Abbreviate tool descriptions in requests to the provider. Tools listed locally keep
+    /// their full descriptions. `GOOSE_COMPACT_TOOL_DESCRIPTIONS` turns this on as well.
+    pub fn set_compact_tool_descriptions(&self, compact: bool) {
+        self.compact_tool_descriptions
+            .store(compact, Ordering::Relaxed);
+    }
+
+    /// Use the provider's non-streaming completion even when it supports streaming, so only
+    /// whole messages are produced. `GOOSE_NO_STREAM` turns this on as well.
+    pub fn set_no_stream(&self, no_stream: bool) {
+        self.no_stream.store(no_stream, Ordering::Relaxed);
+    }
+
+    /// List extensions' tools in this order, overriding `GOOSE_EXTENSION_ORDER`
+    pub async fn set_extension_order(&self, order: Vec<String>) {
+        self.extension_manager.set_extension_order(order).await;
+    }
+
+    /// Make a failed provider call end the reply stream with an error after the failure is
+    /// reported, so callers can tell an interrupted reply from a finished one. The error wraps
+    /// the `ProviderError`, so callers can downcast it to see what went wrong.
+    pub fn set_error_on_provider_failure(&self, error: bool) {
+        self.error_on_provider_failure
+            .store(error, Ordering::Relaxed);
+    }
+
+    /// Rewrite assistant text with these rules before it is yielded, overriding
+    /// `GOOSE_OUTPUT_POST_PROCESSORS`
+    pub async fn set_post_processors(&self, post_processors: PostProcessors) {
+        *self.post_processors.lock().await = Some(Arc::new(post_processors));
+    }
+
+    async fn post_processors(&self) -> Result<Arc<PostProcessors>> {
+        if let Some(post_processors) = self.post_processors.lock().await.clone() {
+            return Ok(post_processors);
+        }
+        Ok(Arc::new(PostProcessors::from_config()?))
+    }
+
+    /// Token usage of this agent so far, broken down by provider and model. Pricing is left
+    /// for the caller to fill in.
+    pub async fn usage(&self) -> UsageReport {
+        self.usage_report.lock().await.clone()
+    }
+
+    /// Load the usage saved with `session`, so [`Agent::usage`] covers the whole conversation
+    /// after resuming it
+    pub async fn restore_usage(&self, session: &Session) {
+        if let Some(state) = UsageState::from_extension_data(&session.extension_data) {
+            *self.usage_report.lock().await = UsageReport::from_usage(&state.usage);
+        }
+    }
+
+    /// Close every extension, terminating any MCP server processes the agent started
+    pub async fn shutdown(&self) {
+        self.extension_manager.shutdown().await;
+    }
+
     pub async fn list_extensions(&self) -> Vec<String> {
         self.extension_manager
             .list_extensions()
@@ -779,6 +932,16 @@ impl Agent {
         session_config: SessionConfig,
         cancel_token: Option<CancellationToken>,
     ) -> Result<BoxStream<'_, Result<AgentEvent>>> {
+        let max_depth = max_agent_depth();
+        if self.depth() > max_depth {
+            return Err(anyhow!(
+                "Agent depth {} exceeds the maximum of {} ({}); refusing to start a nested agent",
+                self.depth(),
+                max_depth,
+                GOOSE_MAX_AGENT_DEPTH_ENV_VAR
+            ));
+        }
+
         let is_manual_compact = user_message.content.iter().any(|c| {
             if let MessageContent::Text(text) = c {
                 text.text.trim() == MANUAL_COMPACT_TRIGGER
@@ -799,7 +962,7 @@ impl Agent {
             && check_if_compaction_needed(
                 self.provider().await?.as_ref(),
                 &conversation,
-                None,
+                session_config.compact_threshold,
                 &session,
             )
             .await?;
@@ -812,9 +975,11 @@ impl Agent {
             } else {
                 if !is_manual_compact {
                     let config = crate::config::Config::global();
-                    let threshold = config
-                        .get_param::<f64>("GOOSE_AUTO_COMPACT_THRESHOLD")
-                        .unwrap_or(DEFAULT_COMPACTION_THRESHOLD);
+                    let threshold = session_config.compact_threshold.unwrap_or_else(|| {
+                        config
+                            .get_param::<f64>("GOOSE_AUTO_COMPACT_THRESHOLD")
+                            .unwrap_or(DEFAULT_COMPACTION_THRESHOLD)
+                    });
                     let threshold_percentage = (threshold * 100.0) as u32;
 
                     let inline_msg = format!(
@@ -840,7 +1005,7 @@ impl Agent {
                 match compact_messages(self.provider().await?.as_ref(), &conversation_to_compact, is_manual_compact).await {
                     Ok((compacted_conversation, summarization_usage)) => {
                         SessionManager::replace_conversation(&session_config.id, &compacted_conversation).await?;
-                        Self::update_session_metrics(&session_config, &summarization_usage, true).await?;
+                        self.update_session_metrics(&session_config, &summarization_usage, true).await?;
 
                         yield AgentEvent::HistoryReplaced(compacted_conversation.clone());
 
@@ -893,11 +1058,25 @@ impl Agent {
         } = context;
         let reply_span = tracing::Span::current();
         self.reset_retry_attempts().await;
+        let retry_budget = session_config
+            .turn_budget
+            .or_else(|| {
+                Config::global()
+                    .get_param::<usize>("GOOSE_TURN_RETRY_BUDGET")
+                    .ok()
+            })
+            .map(|limit| Arc::new(RetryBudget::new(limit)));
+        let max_messages = Config::global()
+            .get_param::<usize>("GOOSE_MAX_MESSAGES")
+            .ok();
+        let post_processors = self.post_processors().await?;
+        // The context size as last reported, passed on so providers needn't recount it
+        let mut input_tokens_hint = session.total_tokens.map(|tokens| tokens as usize);
 
         let provider = self.provider().await?;
         let session_id = session_config.id.clone();
         let working_dir = session.working_dir.clone();
-        tokio::spawn(async move {
+        crate::clock::spawn(async move {
             if let Err(e) = SessionManager::maybe_update_name(&session_id, provider).await {
                 warn!("Failed to generate session description: {}", e);
             }
@@ -905,7 +1084,19 @@ impl Agent {
 
         Ok(Box::pin(async_stream::try_stream! {
             let _ = reply_span.enter();
+            // Agent-level retries draw from the same budget as provider request retries
+            let retry_allowed = || {
+                retry_budget
+                    .as_ref()
+                    .is_none_or(|budget| budget.try_consume())
+            };
             let mut turns_taken = 0u32;
+            let mut empty_response_retried = false;
+            // Set after compacting to recover from a context-length error; a second one before
+            // any response means compaction could not shrink the request enough
+            let mut context_recovery_retried = false;
+            // The fallback model is tried at most once per reply
+            let mut model_fallback_used = false;
             let max_turns = session_config.max_turns.unwrap_or(DEFAULT_MAX_TURNS);
 
             loop {
@@ -951,18 +1142,35 @@ impl Agent {
                     &self.extension_manager,
                 ).await;
 
-                let mut stream = Self::stream_response_from_provider(
-                    self.provider().await?,
-                    &system_prompt,
-                    conversation_with_moim.messages(),
-                    &tools,
-                    &toolshim_tools,
+                // Some providers cap the number of messages regardless of their size
+                let messages_for_provider = match max_messages {
+                    Some(max_messages) => limit_message_count(conversation_with_moim.messages(), max_messages),
+                    None => Cow::Borrowed(conversation_with_moim.messages().as_slice()),
+                };
+
+                let mut stream = with_retry_budget(
+                    retry_budget.clone(),
+                    Self::stream_response_from_provider(
+                        self.provider().await?,
+                        &system_prompt,
+                        &messages_for_provider,
+                        &tools,
+                        &toolshim_tools,
+                        &CompletionOptions {
+                            input_tokens: input_tokens_hint,
+                            no_stream: self.no_stream_enabled(),
+                        },
+                    ),
                 ).await?;
 
                 let mut no_tools_called = true;
+                let mut received_content = false;
                 let mut messages_to_add = Conversation::default();
                 let mut tools_updated = false;
                 let mut did_recovery_compact_this_iteration = false;
+                // Set when a provider error ends the turn, so it is not mistaken for an empty reply
+                let mut provider_error: Option<ProviderError> = None;
+                let mut retry_with_fallback_model = false;
 
                 while let Some(next) = stream.next().await {
                     if is_token_cancelled(&cancel_token) {
@@ -993,16 +1201,29 @@ impl Agent {
                             }
 
                             if let Some(ref usage) = usage {
-                                Self::update_session_metrics(&session_config, usage, false).await?;
+                                self.update_session_metrics(&session_config, usage, false).await?;
+                                if let Some(total_tokens) = usage.usage.total_tokens {
+                                    input_tokens_hint = Some(total_tokens as usize);
+                                }
                             }
 
                             if let Some(response) = response {
-                                messages_to_add.push(response.clone());
+                                if is_empty_response(&response) {
+                                    continue;
+                                }
+                                context_recovery_retried = false;
+                                received_content = true;
+                                let processed = post_processors.apply(&response);
+                                if post_processors.redact_history() {
+                                    messages_to_add.push(processed.clone());
+                                } else {
+                                    messages_to_add.push(response.clone());
+                                }
                                 let ToolCategorizeResult {
                                     frontend_requests,
                                     remaining_requests,
                                     filtered_response,
-                                } = self.categorize_tools(&response, &tools).await;
+                                } = self.categorize_tools(&processed, &tools).await;
                                 let requests_to_record: Vec<ToolRequest> = frontend_requests.iter().chain(remaining_requests.iter()).cloned().collect();
                                 self.tool_route_manager
                                     .record_tool_requests(&requests_to_record)
@@ -1039,6 +1260,10 @@ impl Agent {
                                         );
                                     }
                                 } else {
+                                    // Identical calls in one turn run once and share the result
+                                    let (remaining_requests, duplicate_requests) =
+                                        dedupe_tool_requests(remaining_requests);
+
                                     // Run all tool inspectors (security, repetition, permission, etc.)
                                     let inspection_results = self.tool_inspection_manager
                                         .inspect_tools(
@@ -1047,6 +1272,22 @@ impl Agent {
                                         )
                                         .await?;
 
+                                    // Calls the security check flagged but let through still run, so tell the user
+                                    for (request_id, reason) in get_flagged_security_findings(&inspection_results) {
+                                        let tool_name = remaining_requests
+                                            .iter()
+                                            .find(|request| request.id == request_id)
+                                            .and_then(|request| request.tool_call.as_ref().ok())
+                                            .map(|tool_call| tool_call.name.to_string())
+                                            .unwrap_or_default();
+                                        yield AgentEvent::Message(
+                                            Message::assistant().with_system_notification(
+                                                SystemNotificationType::InlineMessage,
+                                                format!("Security check flagged {tool_name} and allowed it: {reason}"),
+                                            )
+                                        );
+                                    }
+
                                     // Process inspection results into permission decisions using the permission inspector
                                     let permission_check_result = self.tool_inspection_manager
                                         .process_inspection_results_with_permission_inspector(
@@ -1134,6 +1375,14 @@ impl Agent {
                                         }
                                     }
 
+                                    if !duplicate_requests.is_empty() {
+                                        let mut response = message_tool_response.lock().await;
+                                        *response = with_duplicate_tool_responses(
+                                            response.clone(),
+                                            &duplicate_requests,
+                                        );
+                                    }
+
                                     if all_install_successful && !enable_extension_request_ids.is_empty() {
                                         if let Err(e) = self.save_extension_state(&session_config).await {
                                             warn!("Failed to save extension state after runtime changes: {}", e);
@@ -1149,7 +1398,27 @@ impl Agent {
                                 messages_to_add.push(final_message_tool_resp);
                             }
                         }
-                        Err(ProviderError::ContextLengthExceeded(_error_msg)) => {
+                        Err(ProviderError::ContextLengthExceeded { details: error_msg, limit }) => {
+                            if context_recovery_retried {
+                                error!("Context length still exceeded after compacting: {}", error_msg);
+                                yield AgentEvent::Message(
+                                    Message::assistant().with_text(CONTEXT_RECOVERY_FAILED_NOTE)
+                                );
+                                provider_error = Some(ProviderError::ContextLengthExceeded { details: error_msg, limit });
+                                break;
+                            }
+                            if !retry_allowed() {
+                                warn!("Retry budget exhausted, not compacting to recover: {}", error_msg);
+                                if let Some(budget) = &retry_budget {
+                                    yield AgentEvent::Message(
+                                        Message::assistant().with_text(retry_budget_exhausted_note(budget))
+                                    );
+                                }
+                                provider_error = Some(ProviderError::ContextLengthExceeded { details: error_msg, limit });
+                                break;
+                            }
+                            context_recovery_retried = true;
+
                             yield AgentEvent::Message(
                                 Message::assistant().with_system_notification(
                                     SystemNotificationType::InlineMessage,
@@ -1166,7 +1435,7 @@ impl Agent {
                             match compact_messages(self.provider().await?.as_ref(), &conversation, false).await {
                                 Ok((compacted_conversation, usage)) => {
                                     SessionManager::replace_conversation(&session_config.id, &compacted_conversation).await?;
-                                    Self::update_session_metrics(&session_config, &usage, true).await?;
+                                    self.update_session_metrics(&session_config, &usage, true).await?;
                                     conversation = compacted_conversation;
                                     did_recovery_compact_this_iteration = true;
                                     yield AgentEvent::HistoryReplaced(conversation.clone());
@@ -1179,10 +1448,63 @@ impl Agent {
                                             format!("Ran into this error trying to compact: {e}.\n\nPlease retry if you think this is a transient or recoverable error.")
                                         )
                                     );
+                                    provider_error = Some(ProviderError::ContextLengthExceeded { details: error_msg, limit });
                                     break;
                                 }
                             }
                         }
+                        Err(ProviderError::ModelNotFound(error_msg)) if !model_fallback_used => {
+                            model_fallback_used = true;
+                            if !retry_allowed() {
+                                warn!("Retry budget exhausted, not switching to the fallback model: {}", error_msg);
+                                if let Some(budget) = &retry_budget {
+                                    yield AgentEvent::Message(
+                                        Message::assistant().with_text(retry_budget_exhausted_note(budget))
+                                    );
+                                }
+                                provider_error = Some(ProviderError::ModelNotFound(error_msg));
+                                break;
+                            }
+                            match self.switch_to_fallback_model(&session_config.id).await {
+                                Ok(Some(model)) => {
+                                    warn!("Model not found ({}), retrying with fallback model {}", error_msg, model);
+                                    yield AgentEvent::ModelChange {
+                                        model,
+                                        mode: "fallback".to_string(),
+                                    };
+                                    retry_with_fallback_model = true;
+                                }
+                                Ok(None) => {
+                                    error!("Model not found and no fallback model configured: {}", error_msg);
+                                    yield AgentEvent::Message(
+                                        Message::assistant().with_text(
+                                            format!("Ran into this error: Model not found: {error_msg}.\n\nSet GOOSE_FALLBACK_MODEL to switch models automatically when this happens.")
+                                        )
+                                    );
+                                    provider_error = Some(ProviderError::ModelNotFound(error_msg));
+                                }
+                                Err(e) => {
+                                    error!("Failed to switch to the fallback model: {}", e);
+                                    yield AgentEvent::Message(
+                                        Message::assistant().with_text(
+                                            format!("Ran into this error: Model not found: {error_msg}. Switching to the fallback model failed: {e}.")
+                                        )
+                                    );
+                                    provider_error = Some(ProviderError::ModelNotFound(error_msg));
+                                }
+                            }
+                            break;
+                        }
+                        Err(ProviderError::RetryBudgetExhausted(error_msg)) => {
+                            error!("Retry budget exhausted: {}", error_msg);
+                            if let Some(budget) = &retry_budget {
+                                yield AgentEvent::Message(
+                                    Message::assistant().with_text(retry_budget_exhausted_note(budget))
+                                );
+                            }
+                            provider_error = Some(ProviderError::RetryBudgetExhausted(error_msg));
+                            break;
+                        }
                         Err(e) => {
                             error!("Error: {}", e);
                             yield AgentEvent::Message(
@@ -1190,17 +1512,57 @@ impl Agent {
                                     format!("Ran into this error: {e}.\n\nPlease retry if you think this is a transient or recoverable error.")
                                 )
                             );
+                            provider_error = Some(e);
                             break;
                         }
                     }
                 }
+                if let Some(error) = provider_error {
+                    // Keep what the turn produced before the failure, so tool requests that
+                    // already ran are not left without their responses on resume
+                    for msg in &messages_to_add {
+                        SessionManager::add_message(&session_config.id, msg).await?;
+                    }
+                    conversation.extend(messages_to_add);
+                    if self.error_on_provider_failure.load(Ordering::Relaxed) {
+                        Err(anyhow::Error::new(error)
+                            .context("The provider failed before the reply was complete"))?;
+                    }
+                    break;
+                }
+                if retry_with_fallback_model {
+                    continue;
+                }
                 if tools_updated {
                     (tools, toolshim_tools, system_prompt) =
                         self.prepare_tools_and_prompt(&working_dir).await?;
                 }
                 let mut exit_chat = false;
                 if no_tools_called {
-                    if let Some(final_output_tool) = self.final_output_tool.lock().await.as_ref() {
+                    if !received_content
+                        && !did_recovery_compact_this_iteration
+                        && !is_token_cancelled(&cancel_token)
+                    {
+                        if empty_response_retried {
+                            warn!("Provider returned an empty response again, ending the turn");
+                            yield AgentEvent::Message(Message::assistant().with_text(EMPTY_RESPONSE_NOTE));
+                            exit_chat = true;
+                        } else if !retry_allowed() {
+                            warn!("Provider returned an empty response and the retry budget is exhausted");
+                            if let Some(budget) = &retry_budget {
+                                yield AgentEvent::Message(
+                                    Message::assistant().with_text(retry_budget_exhausted_note(budget))
+                                );
+                            }
+                            exit_chat = true;
+                        } else {
+                            warn!("Provider returned an empty response, retrying with a nudge");
+                            empty_response_retried = true;
+                            messages_to_add.push(
+                                Message::user().with_text(EMPTY_RESPONSE_NUDGE).agent_only()
+                            );
+                        }
+                    } else if let Some(final_output_tool) = self.final_output_tool.lock().await.as_ref() {
                         if final_output_tool.final_output.is_none() {
                             warn!("Final output tool has not been called yet. Continuing agent loop.");
                             let message = Message::user().with_text(FINAL_OUTPUT_CONTINUATION_MESSAGE);
@@ -1217,7 +1579,15 @@ impl Agent {
                     } else {
                         match self.handle_retry_logic(&mut conversation, &session_config, &initial_messages).await {
                             Ok(should_retry) => {
-                                if should_retry {
+                                if should_retry && !retry_allowed() {
+                                    warn!("Retry logic triggered but the retry budget is exhausted");
+                                    if let Some(budget) = &retry_budget {
+                                        yield AgentEvent::Message(
+                                            Message::assistant().with_text(retry_budget_exhausted_note(budget))
+                                        );
+                                    }
+                                    exit_chat = true;
+                                } else if should_retry {
                                     info!("Retry logic triggered, restarting agent loop");
                                 } else {
                                     exit_chat = true;
@@ -1254,6 +1624,42 @@ impl Agent {
         prompt_manager.add_system_prompt_extra(instruction);
     }
 
+    /// Replace the provider with one using its configured fallback model and record the
+    /// switch in the session. Returns the new model name, or `None` when no fallback is
+    /// configured.
+    async fn switch_to_fallback_model(&self, session_id: &str) -> Result<Option<String>> {
+        let provider = self.provider().await?;
+        let Some(model_config) = provider.get_model_config().use_fallback_model() else {
+            return Ok(None);
+        };
+        let model = model_config.model_name.clone();
+        let fallback = provider.with_model(model_config).await?;
+        self.update_provider(fallback).await?;
+
+        let mut session = SessionManager::get_session(session_id, false).await?;
+        ModelState::new(model.clone()).to_extension_data(&mut session.extension_data)?;
+        SessionManager::update_session(session_id)
+            .extension_data(session.extension_data)
+            .apply()
+            .await?;
+        Ok(Some(model))
+    }
+
+    /// Switch to the model the session last ran with, if it changed models mid-run
+    pub async fn restore_model(&self, session: &Session) -> Result<()> {
+        let Some(state) = ModelState::from_extension_data(&session.extension_data) else {
+            return Ok(());
+        };
+        let provider = self.provider().await?;
+        let mut model_config = provider.get_model_config();
+        if model_config.model_name == state.model {
+            return Ok(());
+        }
+        model_config.model_name = state.model;
+        self.update_provider(provider.with_model(model_config).await?)
+            .await
+    }
+
     pub async fn update_provider(&self, provider: Arc<dyn Provider>) -> Result<()> {
         let mut current_provider = self.provider.lock().await;
         *current_provider = Some(provider.clone());
@@ -1346,7 +1752,8 @@ impl Agent {
     pub async fn create_recipe(&self, mut messages: Conversation) -> Result<Recipe> {
         tracing::info!("Starting recipe creation with {} messages", messages.len());
 
-        let extensions_info = self.extension_manager.get_extensions_info().await;
+        let extensions_info =
+            budget_extension_instructions(self.extension_manager.get_extensions_info().await).await;
         tracing::debug!("Retrieved {} extensions info", extensions_info.len());
         let (extension_count, tool_count) =
             self.extension_manager.get_extension_and_tool_counts().await;
@@ -1551,6 +1958,14 @@ impl Agent {
     }
 }
 
+/// A response with no content, or only blank text, carries nothing to show or act on
+fn is_empty_response(message: &Message) -> bool {
+    message
+        .content
+        .iter()
+        .all(|c| c.as_text().is_some_and(|text| text.trim().is_empty()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1613,4 +2028,75 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_agent_depth_limit_stops_recursive_sub_agents() {
+        use crate::agents::subagent_task_config::DEFAULT_MAX_AGENT_DEPTH;
+
+        // Stands in for a tool that starts a goose sub-agent: each level asks its agent to run
+        // a sub-agent and, unless the depth limit refuses, recurses one agent deeper.
+        let session = Session::default();
+        let mut depth = 0;
+        let error = loop {
+            let agent = Agent::new();
+            agent.set_depth(depth);
+            let tool_call = CallToolRequestParam {
+                name: SUBAGENT_EXECUTE_TASK_TOOL_NAME.into(),
+                arguments: Some(serde_json::Map::new()),
+            };
+            let (_, result) = agent
+                .dispatch_tool_call(tool_call, "request".to_string(), None, &session)
+                .await;
+            match result {
+                Err(error) if error.message.contains("Maximum agent depth") => break error,
+                _ => depth += 1,
+            }
+            assert!(
+                depth <= DEFAULT_MAX_AGENT_DEPTH,
+                "depth limit never applied"
+            );
+        };
+
+        assert_eq!(depth, DEFAULT_MAX_AGENT_DEPTH);
+        assert_eq!(error.code, ErrorCode::INVALID_REQUEST);
+    }
+
+    async fn panicking_tool() -> ToolResult<Vec<Content>> {
+        panic!("tool blew up")
+    }
+
+    #[tokio::test]
+    async fn test_panicking_tool_does_not_drop_other_results() {
+        let ok = |text: &'static str| {
+            tool_stream(
+                stream::empty(),
+                futures::future::ready(Ok(vec![Content::text(text)])),
+            )
+        };
+        let panicking = tool_stream(stream::empty(), panicking_tool());
+
+        let streams = vec![("a", ok("first")), ("b", panicking), ("c", ok("third"))]
+            .into_iter()
+            .map(|(id, stream)| stream.map(move |item| (id, item)));
+        let mut results: Vec<(&str, ToolResult<Vec<Content>>)> = stream::select_all(streams)
+            .filter_map(|(id, item)| async move {
+                match item {
+                    ToolStreamItem::Result(result) => Some((id, result)),
+                    ToolStreamItem::Message(_) => None,
+                }
+            })
+            .collect()
+            .await;
+        results.sort_by_key(|(id, _)| *id);
+
+        assert_eq!(results.len(), 3);
+        let text = |result: &ToolResult<Vec<Content>>| {
+            result.as_ref().unwrap()[0].as_text().unwrap().text.clone()
+        };
+        assert_eq!(text(&results[0].1), "first");
+        assert_eq!(text(&results[2].1), "third");
+        let error = results[1].1.as_ref().unwrap_err();
+        assert_eq!(error.code, ErrorCode::INTERNAL_ERROR);
+        assert!(error.message.contains("tool blew up"));
+    }
 }
@@ -0,0 +1,217 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use rmcp::model::{Content, ErrorData, JsonObject};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::Config;
+
+/// Argument names whose values are redacted when `GOOSE_AUDIT_LOG_REDACT` is not set
+const DEFAULT_REDACTED_ARGUMENTS: &[&str] = &[
+    "password",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "authorization",
+];
+const REDACTED: &str = "[REDACTED]";
+
+// Serializes appends so lines from concurrent tool calls never interleave
+static WRITE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// Argument names to redact: `GOOSE_AUDIT_LOG_REDACT` as a comma-separated list, replacing
+/// the defaults, lowercased for case-insensitive matching
+pub(crate) fn redacted_argument_names() -> Vec<String> {
+    Config::global()
+        .get_param::<String>("GOOSE_AUDIT_LOG_REDACT")
+        .map(|names| {
+            names
+                .split(',')
+                .map(|name| name.trim().to_lowercase())
+                .filter(|name| !name.is_empty())
+                .collect()
+        })
+        .unwrap_or_else(|_| {
+            DEFAULT_REDACTED_ARGUMENTS
+                .iter()
+                .map(|name| name.to_string())
+                .collect()
+        })
+}
+
+/// Replace the values of arguments named in `redacted` (lowercase), at any depth
+pub(crate) fn redact_arguments(value: &Value, redacted: &[String]) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let value = if redacted.contains(&key.to_lowercase()) {
+                        Value::String(REDACTED.to_string())
+                    } else {
+                        redact_arguments(value, redacted)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|v| redact_arguments(v, redacted))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct AuditRecord<'a> {
+    timestamp: String,
+    session_id: Option<&'a str>,
+    tool: &'a str,
+    arguments: Value,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+    duration_ms: u128,
+}
+
+/// Append-only JSONL log of tool invocations, enabled by setting `GOOSE_AUDIT_LOG` to a path.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+    redacted_arguments: Vec<String>,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>, redacted_arguments: Vec<String>) -> Self {
+        Self {
+            path: path.into(),
+            redacted_arguments: redacted_arguments
+                .into_iter()
+                .map(|name| name.to_lowercase())
+                .collect(),
+        }
+    }
+
+    /// The configured audit log, if `GOOSE_AUDIT_LOG` is set. See [`redacted_argument_names`]
+    /// for which arguments are redacted.
+    pub fn from_config() -> Option<Self> {
+        let path: String = Config::global().get_param("GOOSE_AUDIT_LOG").ok()?;
+        if path.trim().is_empty() {
+            return None;
+        }
+        Some(Self::new(path, redacted_argument_names()))
+    }
+
+    fn redact(&self, value: &Value) -> Value {
+        redact_arguments(value, &self.redacted_arguments)
+    }
+
+    /// Append one invocation to the log. Failures are logged and otherwise ignored so
+    /// auditing never interrupts the tool call itself.
+    pub fn record(
+        &self,
+        session_id: Option<&str>,
+        tool: &str,
+        arguments: Option<&JsonObject>,
+        result: &Result<Vec<Content>, ErrorData>,
+        duration: Duration,
+    ) {
+        let arguments = arguments
+            .map(|args| self.redact(&Value::Object(args.clone())))
+            .unwrap_or(Value::Null);
+        let record = AuditRecord {
+            timestamp: crate::clock::now().to_rfc3339(),
+            session_id,
+            tool,
+            arguments,
+            status: if result.is_ok() { "success" } else { "error" },
+            error: result.as_ref().err().map(|e| e.message.as_ref()),
+            duration_ms: duration.as_millis(),
+        };
+
+        if let Err(e) = self.append(&record) {
+            tracing::warn!(
+                "Failed to write tool audit log to {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+
+    fn append(&self, record: &AuditRecord) -> anyhow::Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::ErrorCode;
+    use rmcp::object;
+
+    #[test]
+    fn test_record_redacts_arguments_and_appends_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let audit = AuditLog::new(&path, vec!["Token".to_string()]);
+
+        let args = object!({"url": "https://example.com", "headers": {"token": "abc"}});
+        audit.record(
+            Some("session-1"),
+            "fetch__get",
+            Some(&args),
+            &Ok(vec![Content::text("ok")]),
+            Duration::from_millis(42),
+        );
+        audit.record(
+            None,
+            "developer__shell",
+            None,
+            &Err(ErrorData::new(ErrorCode::INTERNAL_ERROR, "boom", None)),
+            Duration::from_millis(1),
+        );
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+
+        assert_eq!(lines[0]["session_id"], "session-1");
+        assert_eq!(lines[0]["tool"], "fetch__get");
+        assert_eq!(lines[0]["status"], "success");
+        assert_eq!(lines[0]["duration_ms"], 42);
+        assert_eq!(lines[0]["arguments"]["url"], "https://example.com");
+        assert_eq!(lines[0]["arguments"]["headers"]["token"], REDACTED);
+
+        assert_eq!(lines[1]["status"], "error");
+        assert_eq!(lines[1]["error"], "boom");
+        assert!(lines[1]["session_id"].is_null());
+    }
+
+    #[test]
+    fn test_unwritable_path_does_not_panic() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing").join("audit.jsonl");
+        let audit = AuditLog::new(&path, vec![]);
+        audit.record(None, "tool", None, &Ok(vec![]), Duration::ZERO);
+        assert!(!path.exists());
+    }
+}
@@ -1,12 +1,12 @@
 use crate::agents::extension::PlatformExtensionContext;
-use crate::agents::mcp_client::{Error, McpClientTrait};
+use crate::agents::mcp_client::{Error, McpClientTrait, MCP_PROTOCOL_VERSION};
 use crate::session::SessionManager;
 use anyhow::Result;
 use async_trait::async_trait;
 use indoc::indoc;
 use rmcp::model::{
     CallToolResult, Content, GetPromptResult, Implementation, InitializeResult, JsonObject,
-    ListPromptsResult, ListResourcesResult, ListToolsResult, ProtocolVersion, ReadResourceResult,
+    ListPromptsResult, ListResourcesResult, ListToolsResult, ReadResourceResult,
     ServerCapabilities, ServerNotification, Tool, ToolAnnotations, ToolsCapability,
 };
 use schemars::{schema_for, JsonSchema};
@@ -45,7 +45,7 @@ pub struct ChatRecallClient {
 impl ChatRecallClient {
     pub fn new(context: PlatformExtensionContext) -> Result<Self> {
         let info = InitializeResult {
-            protocol_version: ProtocolVersion::V_2025_03_26,
+            protocol_version: MCP_PROTOCOL_VERSION,
             capabilities: ServerCapabilities {
                 tools: Some(ToolsCapability {
                     list_changed: Some(false),
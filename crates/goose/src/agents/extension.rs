@@ -110,6 +110,8 @@ pub enum ExtensionError {
     TaskJoinError(#[from] tokio::task::JoinError),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+    #[error("failed to connect to extension: {0}")]
+    ConnectError(String),
     #[error("failed to initialize MCP client: {0}")]
     InitializeError(#[from] ClientInitializeError),
     #[error("{0}")]
@@ -207,6 +209,23 @@ impl Envs {
     }
 }
 
+/// How messages are delimited on a stdio extension's stdin and stdout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StdioFraming {
+    /// One JSON message per line, as the MCP spec describes
+    #[default]
+    NewlineDelimited,
+    /// A `Content-Length` header before each message, as used by LSP-derived servers
+    ContentLength,
+}
+
+impl StdioFraming {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 /// Represents the different types of MCP extensions that can be added to the manager
 #[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
 #[serde(tag = "type")]
@@ -253,6 +272,8 @@ pub enum ExtensionConfig {
         bundled: Option<bool>,
         #[serde(default)]
         available_tools: Vec<String>,
+        #[serde(default, skip_serializing_if = "StdioFraming::is_default")]
+        framing: StdioFraming,
     },
     /// Built-in extension that is part of the bundled goose MCP server
     #[serde(rename = "builtin")]
@@ -405,6 +426,7 @@ impl ExtensionConfig {
             timeout: Some(timeout.into()),
             bundled: None,
             available_tools: Vec::new(),
+            framing: StdioFraming::default(),
         }
     }
 
@@ -439,6 +461,7 @@ impl ExtensionConfig {
                 description,
                 bundled,
                 available_tools,
+                framing,
                 ..
             } => Self::Stdio {
                 name,
@@ -450,6 +473,7 @@ impl ExtensionConfig {
                 timeout,
                 bundled,
                 available_tools,
+                framing,
             },
             other => other,
         }
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use axum::http::{HeaderMap, HeaderName};
 use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
 use futures::stream::{FuturesUnordered, StreamExt};
 use futures::{future, FutureExt};
 use rmcp::service::{ClientInitializeError, ServiceError};
@@ -11,29 +12,33 @@ use rmcp::transport::{
     ConfigureCommandExt, DynamicTransportError, SseClientTransport, StreamableHttpClientTransport,
     TokioChildProcess,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::option::Option;
 use std::process::Stdio;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
 use tempfile::{tempdir, TempDir};
 use tokio::io::AsyncReadExt;
-use tokio::process::Command;
+use tokio::process::{ChildStderr, Command};
 use tokio::sync::Mutex;
 use tokio::task;
 use tokio_stream::wrappers::ReceiverStream;
-use tokio_util::sync::CancellationToken;
+use tokio_util::sync::{CancellationToken, DropGuard};
 use tracing::{error, warn};
 
+use super::audit_log::AuditLog;
 use super::extension::{
     ExtensionConfig, ExtensionError, ExtensionInfo, ExtensionResult, PlatformExtensionContext,
-    ToolInfo, PLATFORM_EXTENSIONS,
+    StdioFraming, ToolInfo, PLATFORM_EXTENSIONS,
 };
+use super::subagent_task_config::{process_agent_depth, GOOSE_AGENT_DEPTH_ENV_VAR};
 use super::tool_execution::ToolCallResult;
 use super::types::SharedProvider;
 use crate::agents::extension::{Envs, ProcessExit};
 use crate::agents::extension_malware_check;
 use crate::agents::mcp_client::{McpClient, McpClientTrait};
+use crate::agents::mcp_transport::ContentLengthChildProcess;
 use crate::config::search_path::SearchPaths;
 use crate::config::{get_all_extensions, Config};
 use crate::oauth::oauth_flow;
@@ -55,6 +60,8 @@ struct Extension {
     client: McpClientBox,
     server_info: Option<ServerInfo>,
     _temp_dir: Option<tempfile::TempDir>,
+    // Stops the keepalive task when the extension is removed
+    _keepalive: Option<DropGuard>,
 }
 
 impl Extension {
@@ -69,6 +76,7 @@ impl Extension {
             config,
             server_info,
             _temp_dir: temp_dir,
+            _keepalive: None,
         }
     }
 
@@ -93,8 +101,12 @@ impl Extension {
 /// Manages goose extensions / MCP clients and their interactions
 pub struct ExtensionManager {
     extensions: Mutex<HashMap<String, Extension>>,
+    extension_order: Mutex<Option<Vec<String>>>,
     context: Mutex<PlatformExtensionContext>,
     provider: SharedProvider,
+    /// How many agents deep the owning agent runs: 0 for a user's agent, +1 for each agent
+    /// started by another agent's tools. Extension processes run one level deeper.
+    depth: AtomicUsize,
 }
 
 /// A flattened representation of a resource used by the agent to prepare inference
@@ -130,6 +142,25 @@ impl ResourceItem {
     }
 }
 
+/// Extension names from a comma-separated list such as `GOOSE_EXTENSION_ORDER`
+fn parse_extension_order(order: &str) -> Vec<String> {
+    order
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| normalize(name.to_string()))
+        .collect()
+}
+
+/// Sort key placing extensions in `order` first, then the rest by name
+fn extension_rank(order: &[String], name: &str) -> (usize, String) {
+    let position = order
+        .iter()
+        .position(|ordered| ordered == name)
+        .unwrap_or(order.len());
+    (position, name.to_string())
+}
+
 /// Sanitizes a string by replacing invalid characters with underscores.
 /// Valid characters match [a-zA-Z0-9_-]
 fn normalize(input: String) -> String {
@@ -162,6 +193,56 @@ fn require_str_parameter<'a>(v: &'a serde_json::Value, name: &str) -> Result<&'a
     }
 }
 
+fn optional_usize_parameter(v: &serde_json::Value, name: &str) -> Result<Option<usize>, ErrorData> {
+    match v.get(name) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(value) => value.as_u64().map(|n| Some(n as usize)).ok_or_else(|| {
+            ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("The parameter {name} must be a non-negative integer"),
+                None,
+            )
+        }),
+    }
+}
+
+/// Byte range of a resource to return instead of its full text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ResourceRange {
+    offset: usize,
+    length: Option<usize>,
+}
+
+impl ResourceRange {
+    fn from_params(params: &serde_json::Value) -> Result<Option<Self>, ErrorData> {
+        let offset = optional_usize_parameter(params, "offset")?;
+        let length = optional_usize_parameter(params, "length")?;
+        if offset.is_none() && length.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            offset: offset.unwrap_or(0),
+            length,
+        }))
+    }
+
+    /// The requested slice of `text`, widened to UTF-8 character boundaries,
+    /// with its actual start and end byte offsets.
+    fn slice<'a>(&self, text: &'a str) -> (&'a str, usize, usize) {
+        let mut start = self.offset.min(text.len());
+        while !text.is_char_boundary(start) {
+            start -= 1;
+        }
+        let mut end = self.length.map_or(text.len(), |length| {
+            start.saturating_add(length).min(text.len())
+        });
+        while !text.is_char_boundary(end) {
+            end += 1;
+        }
+        (&text[start..end], start, end)
+    }
+}
+
 pub fn get_parameter_names(tool: &Tool) -> Vec<String> {
     tool.input_schema
         .get("properties")
@@ -179,35 +260,43 @@ impl Default for ExtensionManager {
 async fn child_process_client(
     mut command: Command,
     timeout: &Option<u64>,
+    framing: StdioFraming,
     provider: SharedProvider,
+    agent_depth: usize,
 ) -> ExtensionResult<McpClient> {
     #[cfg(unix)]
     command.process_group(0);
+    // Reap the server if its client is dropped without an explicit shutdown
+    command.kill_on_drop(true);
     configure_command_no_window(&mut command);
 
     if let Ok(path) = SearchPaths::builder().path() {
         command.env("PATH", path);
     }
-
-    let (transport, mut stderr) = TokioChildProcess::builder(command)
-        .stderr(Stdio::piped())
-        .spawn()?;
-    let mut stderr = stderr.take().ok_or_else(|| {
-        ExtensionError::SetupError("failed to attach child process stderr".to_owned())
-    })?;
-
-    let stderr_task = tokio::spawn(async move {
-        let mut all_stderr = Vec::new();
-        stderr.read_to_end(&mut all_stderr).await?;
-        Ok::<String, std::io::Error>(String::from_utf8_lossy(&all_stderr).into())
-    });
-
-    let client_result = McpClient::connect(
-        transport,
-        Duration::from_secs(timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT)),
-        provider,
-    )
-    .await;
+    // A goose started from one of this extension's tools runs one level deeper
+    command.env(GOOSE_AGENT_DEPTH_ENV_VAR, (agent_depth + 1).to_string());
+
+    let timeout = Duration::from_secs(timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT));
+    let (client_result, stderr_task) = match framing {
+        StdioFraming::NewlineDelimited => {
+            let (transport, stderr) = TokioChildProcess::builder(command)
+                .stderr(Stdio::piped())
+                .spawn()?;
+            let stderr_task = collect_stderr(stderr)?;
+            (
+                McpClient::connect(transport, timeout, provider).await,
+                stderr_task,
+            )
+        }
+        StdioFraming::ContentLength => {
+            let (transport, stderr) = ContentLengthChildProcess::spawn(command)?;
+            let stderr_task = collect_stderr(stderr)?;
+            (
+                McpClient::connect(transport, timeout, provider).await,
+                stderr_task,
+            )
+        }
+    };
 
     match client_result {
         Ok(client) => Ok(client),
@@ -221,6 +310,20 @@ async fn child_process_client(
     }
 }
 
+/// Read a child's stderr in the background so it can be reported if the connection fails
+fn collect_stderr(
+    stderr: Option<ChildStderr>,
+) -> ExtensionResult<task::JoinHandle<std::io::Result<String>>> {
+    let mut stderr = stderr.ok_or_else(|| {
+        ExtensionError::SetupError("failed to attach child process stderr".to_owned())
+    })?;
+    Ok(tokio::spawn(async move {
+        let mut all_stderr = Vec::new();
+        stderr.read_to_end(&mut all_stderr).await?;
+        Ok(String::from_utf8_lossy(&all_stderr).into())
+    }))
+}
+
 fn extract_auth_error(
     res: &Result<McpClient, ClientInitializeError>,
 ) -> Option<&AuthRequiredError> {
@@ -243,19 +346,294 @@ fn extract_auth_error(
     }
 }
 
+/// Merge environment variables from direct envs and keychain-stored env_keys
+async fn merge_environments(
+    envs: &Envs,
+    env_keys: &[String],
+    ext_name: &str,
+) -> Result<HashMap<String, String>, ExtensionError> {
+    let mut all_envs = envs.get_env();
+    let config_instance = Config::global();
+
+    for key in env_keys {
+        // If the Envs payload already contains the key, prefer that value
+        // over looking into the keychain/secret store
+        if all_envs.contains_key(key) {
+            continue;
+        }
+
+        match config_instance.get(key, true) {
+            Ok(value) => {
+                if value.is_null() {
+                    warn!(
+                        key = %key,
+                        ext_name = %ext_name,
+                        "Secret key not found in config (returned null)."
+                    );
+                    continue;
+                }
+
+                // Try to get string value
+                if let Some(str_val) = value.as_str() {
+                    all_envs.insert(key.clone(), str_val.to_string());
+                } else {
+                    warn!(
+                        key = %key,
+                        ext_name = %ext_name,
+                        value_type = %value.get("type").and_then(|t| t.as_str()).unwrap_or("unknown"),
+                        "Secret value is not a string; skipping."
+                    );
+                }
+            }
+            Err(e) => {
+                error!(
+                    key = %key,
+                    ext_name = %ext_name,
+                    error = %e,
+                    "Failed to fetch secret from config."
+                );
+                return Err(ExtensionError::ConfigError(format!(
+                    "Failed to fetch secret '{}' from config: {}",
+                    key, e
+                )));
+            }
+        }
+    }
+
+    Ok(all_envs)
+}
+
+/// Start the process behind a stdio or builtin extension and connect to it
+async fn connect_child_process_extension(
+    config: &ExtensionConfig,
+    provider: SharedProvider,
+    agent_depth: usize,
+) -> ExtensionResult<McpClient> {
+    match config {
+        ExtensionConfig::Stdio {
+            cmd,
+            args,
+            envs,
+            env_keys,
+            timeout,
+            framing,
+            ..
+        } => {
+            let ext_name = normalize(config.key().to_string());
+            let all_envs = merge_environments(envs, env_keys, &ext_name).await?;
+            let command = Command::new(cmd).configure(|command| {
+                command.args(args).envs(all_envs);
+            });
+
+            // Check for malicious packages before launching the process
+            extension_malware_check::deny_if_malicious_cmd_args(cmd, args).await?;
+
+            child_process_client(command, timeout, *framing, provider, agent_depth).await
+        }
+        ExtensionConfig::Builtin { name, timeout, .. } => {
+            let cmd = std::env::current_exe()
+                .and_then(|path| {
+                    path.to_str().map(|s| s.to_string()).ok_or_else(|| {
+                        std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            "Invalid UTF-8 in executable path",
+                        )
+                    })
+                })
+                .map_err(|e| {
+                    ExtensionError::ConfigError(format!("Failed to resolve executable path: {}", e))
+                })?;
+            let command = Command::new(cmd).configure(|command| {
+                command.arg("mcp").arg(name);
+            });
+            child_process_client(
+                command,
+                timeout,
+                StdioFraming::default(),
+                provider,
+                agent_depth,
+            )
+            .await
+        }
+        _ => Err(ExtensionError::ConfigError(format!(
+            "Extension '{}' does not run as a child process",
+            config.key()
+        ))),
+    }
+}
+
+/// Longest we wait for an SSE server to send its `endpoint` event before giving up.
+const SSE_ENDPOINT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Open an SSE transport to `uri`. The server must announce its message endpoint within
+/// `timeout` (capped at [`SSE_ENDPOINT_TIMEOUT`]), otherwise the connect fails.
+async fn start_sse_transport(
+    uri: &str,
+    timeout: Duration,
+) -> ExtensionResult<SseClientTransport<reqwest::Client>> {
+    let timeout = timeout.min(SSE_ENDPOINT_TIMEOUT);
+    match tokio::time::timeout(timeout, SseClientTransport::start(uri.to_string())).await {
+        Ok(transport) => transport.map_err(|transport_error| {
+            ClientInitializeError::transport::<SseClientTransport<reqwest::Client>>(
+                transport_error,
+                "connect",
+            )
+            .into()
+        }),
+        Err(_) => Err(ExtensionError::ConnectError(format!(
+            "SSE server at {} did not send its endpoint event within {}s",
+            uri,
+            timeout.as_secs_f64()
+        ))),
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a_char != *b_char);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Most tool names an unknown-tool error lists when none is close to the requested name
+const MAX_LISTED_TOOLS: usize = 20;
+
+/// Up to `max` of `known` within a third of `name`'s length in edits, closest first.
+fn closest_tool_names<'a>(name: &str, known: &'a [String], max: usize) -> Vec<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    let mut candidates: Vec<(usize, &str)> = known
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate.as_str()))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    candidates.sort();
+    candidates
+        .into_iter()
+        .take(max)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// `names` joined for an error message, cut off after `max` with a count of the rest
+fn list_tool_names(names: &[String], max: usize) -> String {
+    let listed = names[..names.len().min(max)].join(", ");
+    match names.len().saturating_sub(max) {
+        0 => listed,
+        rest => format!("{} and {} more", listed, rest),
+    }
+}
+
+/// Interval between keepalive pings to stdio extensions, from `GOOSE_EXTENSION_PING_INTERVAL`
+/// in seconds. Unset or 0 (the default) disables keepalive.
+fn extension_ping_interval() -> Option<Duration> {
+    Config::global()
+        .get_param::<u64>("GOOSE_EXTENSION_PING_INTERVAL")
+        .ok()
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
+}
+
+/// Ping `client` every `interval` until `cancel_token` is cancelled. When a ping fails the
+/// server is assumed gone and `reconnect` re-registers the extension. The re-registered
+/// extension runs its own keepalive, so this one stops once a reconnect succeeds.
+async fn run_keepalive<F, Fut>(
+    name: String,
+    client: McpClientBox,
+    interval: Duration,
+    cancel_token: CancellationToken,
+    reconnect: F,
+) where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = ExtensionResult<()>>,
+{
+    let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = cancel_token.cancelled() => return,
+            _ = ticker.tick() => {}
+        }
+
+        let ping = {
+            let client = client.lock().await;
+            // No point waiting on a ping when the transport is already known to be closed
+            if client.is_connected().await {
+                client.ping(cancel_token.clone()).await
+            } else {
+                Err(ServiceError::TransportClosed)
+            }
+        };
+        if let Err(e) = ping {
+            if cancel_token.is_cancelled() {
+                return;
+            }
+            warn!(extension = %name, error = %e, "Extension keepalive ping failed; restarting it");
+            // Removing the extension mid-restart must not bring it back
+            tokio::select! {
+                _ = cancel_token.cancelled() => return,
+                result = reconnect() => match result {
+                    Ok(()) => return,
+                    Err(e) => warn!(extension = %name, error = %e, "Failed to restart extension"),
+                },
+            }
+        }
+    }
+}
+
+/// Start a fresh process for `config` and register it as `name` through `add_client`, so the
+/// extension's server info is refreshed along with its client.
+fn reconnect_extension(
+    manager: Weak<ExtensionManager>,
+    name: String,
+    config: ExtensionConfig,
+) -> BoxFuture<'static, ExtensionResult<()>> {
+    Box::pin(async move {
+        let Some(manager) = manager.upgrade() else {
+            return Ok(());
+        };
+        let client =
+            connect_child_process_extension(&config, manager.provider.clone(), manager.depth())
+                .await?;
+        let info = client.get_info().cloned();
+        let client: Box<dyn McpClientTrait> = Box::new(client);
+        manager
+            .add_client(name, config, Arc::new(Mutex::new(client)), info, None)
+            .await;
+        Ok(())
+    })
+}
+
 impl ExtensionManager {
     pub fn new(provider: SharedProvider) -> Self {
         Self {
             extensions: Mutex::new(HashMap::new()),
+            extension_order: Mutex::new(None),
             context: Mutex::new(PlatformExtensionContext {
                 session_id: None,
                 extension_manager: None,
                 tool_route_manager: None,
             }),
             provider,
+            depth: AtomicUsize::new(process_agent_depth()),
         }
     }
 
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+
+    pub fn set_depth(&self, depth: usize) {
+        self.depth.store(depth, Ordering::Relaxed);
+    }
+
     /// Create a new ExtensionManager with no provider (useful for tests)
     pub fn new_without_provider() -> Self {
         Self::new(Arc::new(Mutex::new(None)))
@@ -282,83 +660,13 @@ impl ExtensionManager {
         let sanitized_name = normalize(config_name.clone());
         let mut temp_dir = None;
 
-        /// Helper function to merge environment variables from direct envs and keychain-stored env_keys
-        async fn merge_environments(
-            envs: &Envs,
-            env_keys: &[String],
-            ext_name: &str,
-        ) -> Result<HashMap<String, String>, ExtensionError> {
-            let mut all_envs = envs.get_env();
-            let config_instance = Config::global();
-
-            for key in env_keys {
-                // If the Envs payload already contains the key, prefer that value
-                // over looking into the keychain/secret store
-                if all_envs.contains_key(key) {
-                    continue;
-                }
-
-                match config_instance.get(key, true) {
-                    Ok(value) => {
-                        if value.is_null() {
-                            warn!(
-                                key = %key,
-                                ext_name = %ext_name,
-                                "Secret key not found in config (returned null)."
-                            );
-                            continue;
-                        }
-
-                        // Try to get string value
-                        if let Some(str_val) = value.as_str() {
-                            all_envs.insert(key.clone(), str_val.to_string());
-                        } else {
-                            warn!(
-                                key = %key,
-                                ext_name = %ext_name,
-                                value_type = %value.get("type").and_then(|t| t.as_str()).unwrap_or("unknown"),
-                                "Secret value is not a string; skipping."
-                            );
-                        }
-                    }
-                    Err(e) => {
-                        error!(
-                            key = %key,
-                            ext_name = %ext_name,
-                            error = %e,
-                            "Failed to fetch secret from config."
-                        );
-                        return Err(ExtensionError::ConfigError(format!(
-                            "Failed to fetch secret '{}' from config: {}",
-                            key, e
-                        )));
-                    }
-                }
-            }
-
-            Ok(all_envs)
-        }
-
         let client: Box<dyn McpClientTrait> = match &config {
             ExtensionConfig::Sse { uri, timeout, .. } => {
-                let transport = SseClientTransport::start(uri.to_string()).await.map_err(
-                    |transport_error| {
-                        ClientInitializeError::transport::<SseClientTransport<reqwest::Client>>(
-                            transport_error,
-                            "connect",
-                        )
-                    },
-                )?;
-                Box::new(
-                    McpClient::connect(
-                        transport,
-                        Duration::from_secs(
-                            timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
-                        ),
-                        self.provider.clone(),
-                    )
-                    .await?,
-                )
+                let timeout = Duration::from_secs(
+                    timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
+                );
+                let transport = start_sse_transport(uri, timeout).await?;
+                Box::new(McpClient::connect(transport, timeout, self.provider.clone()).await?)
             }
             ExtensionConfig::StreamableHttp {
                 uri,
@@ -465,54 +773,10 @@ impl ExtensionManager {
                 };
                 Box::new(client)
             }
-            ExtensionConfig::Stdio {
-                cmd,
-                args,
-                envs,
-                env_keys,
-                timeout,
-                ..
-            } => {
-                let all_envs = merge_environments(envs, env_keys, &sanitized_name).await?;
-                let command = Command::new(cmd).configure(|command| {
-                    command.args(args).envs(all_envs);
-                });
-
-                // Check for malicious packages before launching the process
-                extension_malware_check::deny_if_malicious_cmd_args(cmd, args).await?;
-
-                let client = child_process_client(command, timeout, self.provider.clone()).await?;
-                Box::new(client)
-            }
-            ExtensionConfig::Builtin {
-                name,
-                display_name: _,
-                description: _,
-                timeout,
-                bundled: _,
-                available_tools: _,
-            } => {
-                let cmd = std::env::current_exe()
-                    .and_then(|path| {
-                        path.to_str().map(|s| s.to_string()).ok_or_else(|| {
-                            std::io::Error::new(
-                                std::io::ErrorKind::InvalidData,
-                                "Invalid UTF-8 in executable path",
-                            )
-                        })
-                    })
-                    .map_err(|e| {
-                        ExtensionError::ConfigError(format!(
-                            "Failed to resolve executable path: {}",
-                            e
-                        ))
-                    })?;
-                let command = Command::new(cmd).configure(|command| {
-                    command.arg("mcp").arg(name);
-                });
-                let client = child_process_client(command, timeout, self.provider.clone()).await?;
-                Box::new(client)
-            }
+            ExtensionConfig::Stdio { .. } | ExtensionConfig::Builtin { .. } => Box::new(
+                connect_child_process_extension(&config, self.provider.clone(), self.depth())
+                    .await?,
+            ),
             ExtensionConfig::Platform { name, .. } => {
                 // Normalize the name to match the key used in PLATFORM_EXTENSIONS
                 let normalized_key = normalize(name.clone());
@@ -546,7 +810,14 @@ impl ExtensionManager {
                     command.arg("python").arg(file_path.to_str().unwrap());
                 });
 
-                let client = child_process_client(command, timeout, self.provider.clone()).await?;
+                let client = child_process_client(
+                    command,
+                    timeout,
+                    StdioFraming::default(),
+                    self.provider.clone(),
+                    self.depth(),
+                )
+                .await?;
 
                 Box::new(client)
             }
@@ -578,10 +849,46 @@ impl ExtensionManager {
         info: Option<ServerInfo>,
         temp_dir: Option<TempDir>,
     ) {
-        self.extensions
-            .lock()
-            .await
-            .insert(name, Extension::new(config, client, info, temp_dir));
+        let keepalive = self.spawn_keepalive(&name, &config, &client).await;
+        let mut extension = Extension::new(config, client, info, temp_dir);
+        extension._keepalive = keepalive;
+        self.extensions.lock().await.insert(name, extension);
+    }
+
+    /// Keep stdio and builtin extensions alive when `GOOSE_EXTENSION_PING_INTERVAL` is set,
+    /// restarting them through [`reconnect_extension`] when they stop answering pings.
+    async fn spawn_keepalive(
+        &self,
+        name: &str,
+        config: &ExtensionConfig,
+        client: &McpClientBox,
+    ) -> Option<DropGuard> {
+        if !matches!(
+            config,
+            ExtensionConfig::Stdio { .. } | ExtensionConfig::Builtin { .. }
+        ) {
+            return None;
+        }
+        let interval = extension_ping_interval()?;
+        let manager = self.get_context().await.extension_manager?;
+
+        let cancel_token = CancellationToken::new();
+        let reconnect_name = name.to_string();
+        let reconnect_config = config.clone();
+        tokio::spawn(run_keepalive(
+            name.to_string(),
+            client.clone(),
+            interval,
+            cancel_token.clone(),
+            move || {
+                reconnect_extension(
+                    manager.clone(),
+                    reconnect_name.clone(),
+                    reconnect_config.clone(),
+                )
+            },
+        ));
+        Some(cancel_token.drop_guard())
     }
 
     /// Get extensions info
@@ -607,6 +914,20 @@ impl ExtensionManager {
         Ok(())
     }
 
+    /// Remove every extension and close its connection, waiting for the processes behind
+    /// stdio extensions to exit. Call this before the process exits so no children outlive it.
+    pub async fn shutdown(&self) {
+        let extensions: Vec<(String, Extension)> = self.extensions.lock().await.drain().collect();
+
+        future::join_all(extensions.into_iter().map(|(name, extension)| async move {
+            // Stop the keepalive first so it does not reconnect a client we are closing
+            drop(extension._keepalive);
+            extension.client.lock().await.shutdown().await;
+            tracing::debug!("Shut down extension {}", name);
+        }))
+        .await;
+    }
+
     pub async fn get_extension_and_tool_counts(&self) -> (usize, usize) {
         let enabled_extensions_count = self.extensions.lock().await.len();
 
@@ -632,13 +953,46 @@ impl ExtensionManager {
             .collect()
     }
 
+    /// Set the order extensions are listed in, overriding `GOOSE_EXTENSION_ORDER`.
+    ///
+    /// Prefixed tool names always identify a single extension; the order decides how tools
+    /// are listed and which extension wins when a name could belong to more than one.
+    /// Extensions not in the list come after those that are, sorted by name.
+    pub async fn set_extension_order(&self, order: Vec<String>) {
+        *self.extension_order.lock().await = Some(order.into_iter().map(normalize).collect());
+    }
+
+    async fn extension_order(&self) -> Vec<String> {
+        if let Some(order) = self.extension_order.lock().await.clone() {
+            return order;
+        }
+        Config::global()
+            .get_param::<String>("GOOSE_EXTENSION_ORDER")
+            .map(|order| parse_extension_order(&order))
+            .unwrap_or_default()
+    }
+
+    /// Sort `tools` by the extension order, then by name. Tools of extensions outside the
+    /// order, and platform and frontend tools, keep plain name order among themselves.
+    pub async fn sort_tools(&self, tools: &mut [Tool]) {
+        let order = self.extension_order().await;
+        tools.sort_by_cached_key(|tool| {
+            let rank = order
+                .iter()
+                .position(|extension| tool.name.starts_with(&format!("{extension}__")))
+                .unwrap_or(order.len());
+            (rank, tool.name.to_string())
+        });
+    }
+
     /// Get all tools from all clients with proper prefixing
     pub async fn get_prefixed_tools(
         &self,
         extension_name: Option<String>,
     ) -> ExtensionResult<Vec<Tool>> {
+        let order = self.extension_order().await;
         // Filter clients based on the provided extension_name or include all if None
-        let filtered_clients: Vec<_> = self
+        let mut filtered_clients: Vec<_> = self
             .extensions
             .lock()
             .await
@@ -652,6 +1006,7 @@ impl ExtensionManager {
             })
             .map(|(name, ext)| (name.clone(), ext.config.clone(), ext.get_client()))
             .collect();
+        filtered_clients.sort_by_key(|(name, _, _)| extension_rank(&order, name));
 
         let cancel_token = CancellationToken::default();
         let client_futures = filtered_clients.into_iter().map(|(name, config, client)| {
@@ -717,13 +1072,44 @@ impl ExtensionManager {
         prompt_template::render_global_file("plan.md", &context).expect("Prompt should render")
     }
 
+    /// Error for a call to a tool no extension provides, naming the closest known tools (or the
+    /// first `MAX_LISTED_TOOLS` of them when nothing is close) so the model can correct itself.
+    async fn unknown_tool_error(&self, name: &str) -> ErrorData {
+        let known: Vec<String> = self
+            .get_prefixed_tools(None)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|tool| tool.name.to_string())
+            .collect();
+        let closest = closest_tool_names(name, &known, 3);
+        let message = if !closest.is_empty() {
+            format!(
+                "Tool '{}' not found. Did you mean: {}?",
+                name,
+                closest.join(", ")
+            )
+        } else if !known.is_empty() {
+            format!(
+                "Tool '{}' not found. Available tools: {}",
+                name,
+                list_tool_names(&known, MAX_LISTED_TOOLS)
+            )
+        } else {
+            format!("Tool '{}' not found. No tools are available.", name)
+        };
+        ErrorData::new(ErrorCode::RESOURCE_NOT_FOUND, message, None)
+    }
+
     /// Find and return a reference to the appropriate client for a tool call
     async fn get_client_for_tool(&self, prefixed_name: &str) -> Option<(String, McpClientBox)> {
+        let order = self.extension_order().await;
         self.extensions
             .lock()
             .await
             .iter()
-            .find(|(key, _)| prefixed_name.starts_with(*key))
+            .filter(|(key, _)| prefixed_name.starts_with(&format!("{key}__")))
+            .min_by_key(|(key, _)| extension_rank(&order, key))
             .map(|(name, extension)| (name.clone(), extension.get_client()))
     }
 
@@ -734,6 +1120,7 @@ impl ExtensionManager {
         cancellation_token: CancellationToken,
     ) -> Result<Vec<Content>, ErrorData> {
         let uri = require_str_parameter(&params, "uri")?;
+        let range = ResourceRange::from_params(&params)?;
 
         let extension_name = params.get("extension_name").and_then(|v| v.as_str());
 
@@ -743,6 +1130,7 @@ impl ExtensionManager {
                 .read_resource_from_extension(
                     uri,
                     extension_name.unwrap(),
+                    range,
                     cancellation_token.clone(),
                 )
                 .await?;
@@ -759,7 +1147,12 @@ impl ExtensionManager {
 
         for extension_name in extension_names {
             let result = self
-                .read_resource_from_extension(uri, &extension_name, cancellation_token.clone())
+                .read_resource_from_extension(
+                    uri,
+                    &extension_name,
+                    range,
+                    cancellation_token.clone(),
+                )
                 .await;
             match result {
                 Ok(result) => return Ok(result),
@@ -792,6 +1185,7 @@ impl ExtensionManager {
         &self,
         uri: &str,
         extension_name: &str,
+        range: Option<ResourceRange>,
         cancellation_token: CancellationToken,
     ) -> Result<Vec<Content>, ErrorData> {
         let available_extensions = self
@@ -828,7 +1222,21 @@ impl ExtensionManager {
         for content in read_result.contents {
             // Only reading the text resource content; skipping the blob content cause it's too long
             if let ResourceContents::TextResourceContents { text, .. } = content {
-                let content_str = format!("{}\n\n{}", uri, text);
+                // MCP reads have no range parameter, so ranges are applied to the full text here
+                let content_str = match range {
+                    Some(range) => {
+                        let (slice, start, end) = range.slice(&text);
+                        format!(
+                            "{}\n[bytes {}-{} of {}]\n\n{}",
+                            uri,
+                            start,
+                            end,
+                            text.len(),
+                            slice
+                        )
+                    }
+                    None => format!("{}\n\n{}", uri, text),
+                };
                 result.push(Content::text(content_str));
             }
         }
@@ -853,26 +1261,69 @@ impl ExtensionManager {
             })?;
 
         let client_guard = client.lock().await;
-        client_guard
-            .list_resources(None, cancellation_token)
+        let mut resources = Vec::new();
+        let mut cursor = None;
+        let mut seen_cursors = HashSet::new();
+
+        // Follow next_cursor until the server reports no more pages
+        loop {
+            let page = client_guard
+                .list_resources(cursor, cancellation_token.clone())
+                .await
+                .map_err(|e| {
+                    ErrorData::new(
+                        ErrorCode::INTERNAL_ERROR,
+                        format!("Unable to list resources for {}, {:?}", extension_name, e),
+                        None,
+                    )
+                })?;
+            resources.extend(page.resources);
+
+            cursor = page.next_cursor;
+            match &cursor {
+                None => break,
+                // A server handing back a cursor it already gave would keep us paging forever
+                Some(next) if !seen_cursors.insert(next.clone()) => {
+                    tracing::warn!(
+                        "{} repeated resource cursor {}, stopping pagination",
+                        extension_name,
+                        next
+                    );
+                    break;
+                }
+                Some(_) => {}
+            }
+        }
+
+        // Templates are optional for servers, so a failure here only hides them
+        let templates = match client_guard
+            .list_resource_templates(None, cancellation_token.clone())
             .await
-            .map_err(|e| {
-                ErrorData::new(
-                    ErrorCode::INTERNAL_ERROR,
-                    format!("Unable to list resources for {}, {:?}", extension_name, e),
-                    None,
+        {
+            Ok(page) => page.resource_templates,
+            Err(e) => {
+                tracing::debug!(
+                    "Unable to list resource templates for {}: {:?}",
+                    extension_name,
+                    e
+                );
+                Vec::new()
+            }
+        };
+
+        let resource_list = resources
+            .into_iter()
+            .map(|r| format!("{} - {}, uri: ({})", extension_name, r.name, r.uri))
+            .chain(templates.into_iter().map(|t| {
+                format!(
+                    "{} - {}, uri template: ({})",
+                    extension_name, t.name, t.uri_template
                 )
-            })
-            .map(|lr| {
-                let resource_list = lr
-                    .resources
-                    .into_iter()
-                    .map(|r| format!("{} - {}, uri: ({})", extension_name, r.name, r.uri))
-                    .collect::<Vec<String>>()
-                    .join("\n");
+            }))
+            .collect::<Vec<String>>()
+            .join("\n");
 
-                vec![Content::text(resource_list)]
-            })
+        Ok(vec![Content::text(resource_list)])
     }
 
     pub async fn list_resources(
@@ -944,22 +1395,19 @@ impl ExtensionManager {
         cancellation_token: CancellationToken,
     ) -> Result<ToolCallResult> {
         // Dispatch tool call based on the prefix naming convention
-        let (client_name, client) =
-            self.get_client_for_tool(&tool_call.name)
-                .await
-                .ok_or_else(|| {
-                    ErrorData::new(ErrorCode::RESOURCE_NOT_FOUND, tool_call.name.clone(), None)
-                })?;
+        let Some((client_name, client)) = self.get_client_for_tool(&tool_call.name).await else {
+            return Err(self.unknown_tool_error(&tool_call.name).await.into());
+        };
 
         // rsplit returns the iterator in reverse, tool_name is then at 0
-        let tool_name = tool_call
+        let Some(tool_name) = tool_call
             .name
             .strip_prefix(client_name.as_str())
             .and_then(|s| s.strip_prefix("__"))
-            .ok_or_else(|| {
-                ErrorData::new(ErrorCode::RESOURCE_NOT_FOUND, tool_call.name.clone(), None)
-            })?
-            .to_string();
+            .map(str::to_string)
+        else {
+            return Err(self.unknown_tool_error(&tool_call.name).await.into());
+        };
 
         if let Some(extension) = self.extensions.lock().await.get(&client_name) {
             if !extension.config.is_tool_available(&tool_name) {
@@ -978,11 +1426,14 @@ impl ExtensionManager {
         let arguments = tool_call.arguments.clone();
         let client = client.clone();
         let notifications_receiver = client.lock().await.subscribe().await;
+        let audit_log = AuditLog::from_config();
+        let session_id = crate::session_context::current_session_id();
 
         let fut = async move {
+            let started = Instant::now();
             let client_guard = client.lock().await;
-            client_guard
-                .call_tool(&tool_name, arguments, cancellation_token)
+            let result = client_guard
+                .call_tool(&tool_name, arguments.clone(), cancellation_token)
                 .await
                 .map(|call| call.content)
                 .map_err(|e| match e {
@@ -990,7 +1441,17 @@ impl ExtensionManager {
                     _ => {
                         ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), e.maybe_to_value())
                     }
-                })
+                });
+            if let Some(audit_log) = audit_log {
+                audit_log.record(
+                    session_id.as_deref(),
+                    &tool_call.name,
+                    arguments.as_ref(),
+                    &result,
+                    started.elapsed(),
+                );
+            }
+            result
         };
 
         Ok(ToolCallResult {
@@ -1016,17 +1477,39 @@ impl ExtensionManager {
             })?;
 
         let client_guard = client.lock().await;
-        client_guard
-            .list_prompts(None, cancellation_token)
-            .await
-            .map_err(|e| {
-                ErrorData::new(
-                    ErrorCode::INTERNAL_ERROR,
-                    format!("Unable to list prompts for {}, {:?}", extension_name, e),
-                    None,
-                )
-            })
-            .map(|lp| lp.prompts)
+        let mut prompts = Vec::new();
+        let mut cursor = None;
+        let mut seen_cursors = HashSet::new();
+
+        loop {
+            let page = client_guard
+                .list_prompts(cursor, cancellation_token.clone())
+                .await
+                .map_err(|e| {
+                    ErrorData::new(
+                        ErrorCode::INTERNAL_ERROR,
+                        format!("Unable to list prompts for {}, {:?}", extension_name, e),
+                        None,
+                    )
+                })?;
+            prompts.extend(page.prompts);
+
+            cursor = page.next_cursor;
+            match &cursor {
+                None => break,
+                Some(next) if !seen_cursors.insert(next.clone()) => {
+                    tracing::warn!(
+                        "{} repeated prompt cursor {}, stopping pagination",
+                        extension_name,
+                        next
+                    );
+                    break;
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(prompts)
     }
 
     pub async fn list_prompts(
@@ -1200,6 +1683,7 @@ mod tests {
     use rmcp::model::ListToolsResult;
     use rmcp::model::ReadResourceResult;
     use rmcp::model::ServerNotification;
+    use rmcp::model::{AnnotateAble, RawResource};
     use serde_json::json;
     use tokio::sync::mpsc;
 
@@ -1242,18 +1726,38 @@ mod tests {
 
         async fn list_resources(
             &self,
-            _next_cursor: Option<String>,
+            next_cursor: Option<String>,
             _cancellation_token: CancellationToken,
         ) -> Result<ListResourcesResult, Error> {
-            Err(Error::TransportClosed)
+            // Serve two pages so callers have to follow the cursor
+            let (name, next_cursor) = match next_cursor.as_deref() {
+                None => ("first", Some("page-2".to_string())),
+                Some("page-2") => ("second", None),
+                Some(_) => return Err(Error::TransportClosed),
+            };
+            let resource = RawResource::new(format!("file:///{}", name), name).no_annotation();
+            Ok(ListResourcesResult {
+                resources: vec![resource],
+                next_cursor,
+            })
         }
 
         async fn read_resource(
             &self,
-            _uri: &str,
+            uri: &str,
             _cancellation_token: CancellationToken,
         ) -> Result<ReadResourceResult, Error> {
-            Err(Error::TransportClosed)
+            if uri != "file:///first" {
+                return Err(Error::TransportClosed);
+            }
+            Ok(ReadResourceResult {
+                contents: vec![ResourceContents::TextResourceContents {
+                    uri: uri.to_string(),
+                    mime_type: Some("text/plain".to_string()),
+                    text: "0123456789abcdefghij".to_string(),
+                    meta: None,
+                }],
+            })
         }
 
         async fn list_tools(
@@ -1304,10 +1808,19 @@ mod tests {
 
         async fn list_prompts(
             &self,
-            _next_cursor: Option<String>,
+            next_cursor: Option<String>,
             _cancellation_token: CancellationToken,
         ) -> Result<ListPromptsResult, Error> {
-            Err(Error::TransportClosed)
+            // The second page points back at itself, like a server stuck on one cursor
+            let name = match next_cursor.as_deref() {
+                None => "first",
+                Some("page-2") => "second",
+                Some(_) => return Err(Error::TransportClosed),
+            };
+            Ok(ListPromptsResult {
+                prompts: vec![Prompt::new(name, None::<String>, None)],
+                next_cursor: Some("page-2".to_string()),
+            })
         }
 
         async fn get_prompt(
@@ -1324,6 +1837,205 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_sse_connect_fails_without_endpoint_event() {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await;
+            socket
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ncache-control: no-cache\r\n\r\n: connected\n\n",
+                )
+                .await
+                .unwrap();
+            // Keep the stream open without ever announcing an endpoint.
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        });
+
+        let extension_manager = ExtensionManager::new_without_provider();
+        let start = Instant::now();
+        let result = extension_manager
+            .add_extension(ExtensionConfig::sse(
+                "silent",
+                format!("http://{}/sse", addr).as_str(),
+                "",
+                1u64,
+            ))
+            .await;
+
+        assert!(start.elapsed() < Duration::from_secs(10));
+        match result {
+            Err(ExtensionError::ConnectError(msg)) => {
+                assert!(msg.contains("did not send its endpoint event"), "{}", msg)
+            }
+            other => panic!("expected a connect error, got {:?}", other.err()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_resources_follows_pagination() {
+        let extension_manager = ExtensionManager::new_without_provider();
+        extension_manager
+            .add_mock_extension(
+                "test_client".to_string(),
+                Arc::new(Mutex::new(Box::new(MockClient {}))),
+            )
+            .await;
+
+        let content = extension_manager
+            .list_resources(
+                json!({"extension": "test_client"}),
+                CancellationToken::default(),
+            )
+            .await
+            .unwrap();
+
+        let text = content[0].as_text().unwrap().text.clone();
+        assert_eq!(
+            text,
+            "test_client - first, uri: (file:///first)\ntest_client - second, uri: (file:///second)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_prompts_stops_at_repeated_cursor() {
+        let extension_manager = ExtensionManager::new_without_provider();
+        extension_manager
+            .add_mock_extension(
+                "test_client".to_string(),
+                Arc::new(Mutex::new(Box::new(MockClient {}))),
+            )
+            .await;
+
+        let prompts = extension_manager
+            .list_prompts_from_extension("test_client", CancellationToken::default())
+            .await
+            .unwrap();
+
+        let names: Vec<_> = prompts.iter().map(|prompt| prompt.name.as_str()).collect();
+        assert_eq!(names, vec!["first", "second"]);
+    }
+
+    #[tokio::test]
+    async fn test_read_resource_byte_range() {
+        let extension_manager = ExtensionManager::new_without_provider();
+        extension_manager
+            .add_mock_extension(
+                "test_client".to_string(),
+                Arc::new(Mutex::new(Box::new(MockClient {}))),
+            )
+            .await;
+
+        let read = |params: Value| {
+            let extension_manager = &extension_manager;
+            async move {
+                let content = extension_manager
+                    .read_resource(params, CancellationToken::default())
+                    .await
+                    .unwrap();
+                content[0].as_text().unwrap().text.clone()
+            }
+        };
+
+        let full = read(json!({"uri": "file:///first", "extension_name": "test_client"})).await;
+        assert_eq!(full, "file:///first\n\n0123456789abcdefghij");
+
+        let slice = read(json!({"uri": "file:///first", "offset": 5, "length": 4})).await;
+        assert_eq!(slice, "file:///first\n[bytes 5-9 of 20]\n\n5678");
+
+        let tail = read(json!({"uri": "file:///first", "offset": 15})).await;
+        assert_eq!(tail, "file:///first\n[bytes 15-20 of 20]\n\nfghij");
+
+        let past_end = read(json!({"uri": "file:///first", "offset": 100, "length": 4})).await;
+        assert_eq!(past_end, "file:///first\n[bytes 20-20 of 20]\n\n");
+    }
+
+    #[test]
+    fn test_resource_range_respects_char_boundaries() {
+        let text = "héllo";
+        let range = ResourceRange {
+            offset: 2,
+            length: Some(1),
+        };
+        // 'é' spans bytes 1..3, so the slice widens to include all of it
+        assert_eq!(range.slice(text), ("é", 1, 3));
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_dispatch_tool_call_writes_audit_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit_path = dir.path().join("audit.jsonl");
+        std::env::set_var("GOOSE_AUDIT_LOG", &audit_path);
+
+        let extension_manager = ExtensionManager::new_without_provider();
+        extension_manager
+            .add_mock_extension(
+                "test_client".to_string(),
+                Arc::new(Mutex::new(Box::new(MockClient {}))),
+            )
+            .await;
+
+        let tool_call = CallToolRequestParam {
+            name: "test_client__tool".to_string().into(),
+            arguments: Some(object!({"path": "notes.txt", "password": "hunter2"})),
+        };
+        let result = crate::session_context::with_session_id(Some("audit-session".into()), async {
+            extension_manager
+                .dispatch_tool_call(tool_call, CancellationToken::default())
+                .await
+        })
+        .await
+        .unwrap();
+        result.result.await.unwrap();
+        std::env::remove_var("GOOSE_AUDIT_LOG");
+
+        let contents = std::fs::read_to_string(&audit_path).unwrap();
+        let lines: Vec<Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["tool"], "test_client__tool");
+        assert_eq!(lines[0]["session_id"], "audit-session");
+        assert_eq!(lines[0]["status"], "success");
+        assert_eq!(lines[0]["arguments"]["path"], "notes.txt");
+        assert_eq!(lines[0]["arguments"]["password"], "[REDACTED]");
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_unknown_tool_suggests_close_name() {
+        let extension_manager = ExtensionManager::new_without_provider();
+        extension_manager
+            .add_mock_extension(
+                "test_client".to_string(),
+                Arc::new(Mutex::new(Box::new(MockClient {}))),
+            )
+            .await;
+
+        let tool_call = CallToolRequestParam {
+            name: "test_clent__available_tool".to_string().into(),
+            arguments: Some(object!({})),
+        };
+        let err = match extension_manager
+            .dispatch_tool_call(tool_call, CancellationToken::default())
+            .await
+        {
+            Ok(_) => panic!("expected an unknown tool error"),
+            Err(e) => e.to_string(),
+        };
+        assert!(
+            err.contains("Did you mean: test_client__available_tool"),
+            "{}",
+            err
+        );
+    }
+
     #[tokio::test]
     async fn test_get_client_for_tool() {
         let extension_manager = ExtensionManager::new_without_provider();
@@ -1380,6 +2092,21 @@ mod tests {
             .get_client_for_tool("client___tool")
             .await
             .is_some());
+
+        // An extension whose name is a prefix of another's doesn't claim its tools
+        for name in ["dev", "developer"] {
+            extension_manager
+                .add_mock_extension(
+                    name.to_string(),
+                    Arc::new(Mutex::new(Box::new(MockClient {}))),
+                )
+                .await;
+        }
+        let (name, _) = extension_manager
+            .get_client_for_tool("developer__shell")
+            .await
+            .unwrap();
+        assert_eq!(name, "developer");
     }
 
     #[tokio::test]
@@ -1529,6 +2256,68 @@ mod tests {
         assert!(tool_names.len() == 1);
     }
 
+    #[tokio::test]
+    async fn test_tools_follow_configured_extension_order() {
+        let extension_manager = ExtensionManager::new_without_provider();
+        for name in ["alpha", "beta", "gamma"] {
+            extension_manager
+                .add_mock_extension_with_tools(
+                    name.to_string(),
+                    Arc::new(Mutex::new(Box::new(MockClient {}))),
+                    vec!["tool".to_string()],
+                )
+                .await;
+        }
+
+        let extension_order = |tools: Vec<Tool>| -> Vec<String> {
+            tools
+                .iter()
+                .map(|tool| tool.name.split("__").next().unwrap().to_string())
+                .collect()
+        };
+
+        extension_manager
+            .set_extension_order(vec!["gamma".to_string(), "alpha".to_string()])
+            .await;
+        let tools = extension_manager.get_prefixed_tools(None).await.unwrap();
+        assert_eq!(extension_order(tools), vec!["gamma", "alpha", "beta"]);
+
+        extension_manager
+            .set_extension_order(vec!["beta".to_string()])
+            .await;
+        let tools = extension_manager.get_prefixed_tools(None).await.unwrap();
+        assert_eq!(extension_order(tools), vec!["beta", "alpha", "gamma"]);
+
+        // Sorting the tools sent to the model keeps the order
+        let tool =
+            |name: &str| Tool::new(name.to_string(), String::new(), Arc::new(JsonObject::new()));
+        let mut tools = vec![
+            tool("alpha__tool"),
+            tool("platform__tool"),
+            tool("beta__tool"),
+        ];
+        extension_manager.sort_tools(&mut tools).await;
+        assert_eq!(extension_order(tools), vec!["beta", "alpha", "platform"]);
+    }
+
+    #[test]
+    fn test_list_tool_names_is_capped() {
+        let names: Vec<String> = (0..5).map(|i| format!("ext__tool{}", i)).collect();
+        assert_eq!(
+            list_tool_names(&names, 3),
+            "ext__tool0, ext__tool1, ext__tool2 and 2 more"
+        );
+        assert_eq!(list_tool_names(&names[..2], 3), "ext__tool0, ext__tool1");
+    }
+
+    #[test]
+    fn test_parse_extension_order() {
+        assert_eq!(
+            parse_extension_order(" Developer, memory ,,computercontroller"),
+            vec!["developer", "memory", "computercontroller"]
+        );
+    }
+
     #[tokio::test]
     async fn test_tool_availability_defaults_to_available() {
         let extension_manager = ExtensionManager::new_without_provider();
@@ -1667,4 +2456,227 @@ mod tests {
         );
         assert_eq!(result, "Authorization: Bearer secret123 and API key456");
     }
+
+    struct PingClient {
+        pings: mpsc::UnboundedSender<()>,
+        healthy: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl McpClientTrait for PingClient {
+        fn get_info(&self) -> Option<&InitializeResult> {
+            None
+        }
+
+        async fn list_resources(
+            &self,
+            _next_cursor: Option<String>,
+            _cancellation_token: CancellationToken,
+        ) -> Result<ListResourcesResult, Error> {
+            Err(Error::TransportClosed)
+        }
+
+        async fn read_resource(
+            &self,
+            _uri: &str,
+            _cancellation_token: CancellationToken,
+        ) -> Result<ReadResourceResult, Error> {
+            Err(Error::TransportClosed)
+        }
+
+        async fn list_tools(
+            &self,
+            _next_cursor: Option<String>,
+            _cancellation_token: CancellationToken,
+        ) -> Result<ListToolsResult, Error> {
+            Err(Error::TransportClosed)
+        }
+
+        async fn call_tool(
+            &self,
+            _name: &str,
+            _arguments: Option<JsonObject>,
+            _cancellation_token: CancellationToken,
+        ) -> Result<CallToolResult, Error> {
+            Err(Error::TransportClosed)
+        }
+
+        async fn list_prompts(
+            &self,
+            _next_cursor: Option<String>,
+            _cancellation_token: CancellationToken,
+        ) -> Result<ListPromptsResult, Error> {
+            Err(Error::TransportClosed)
+        }
+
+        async fn get_prompt(
+            &self,
+            _name: &str,
+            _arguments: Value,
+            _cancellation_token: CancellationToken,
+        ) -> Result<GetPromptResult, Error> {
+            Err(Error::TransportClosed)
+        }
+
+        async fn subscribe(&self) -> mpsc::Receiver<ServerNotification> {
+            mpsc::channel(1).1
+        }
+
+        async fn ping(&self, _cancel_token: CancellationToken) -> Result<(), Error> {
+            let _ = self.pings.send(());
+            if self.healthy {
+                Ok(())
+            } else {
+                Err(ServiceError::TransportClosed)
+            }
+        }
+    }
+
+    fn ping_client(healthy: bool) -> (McpClientBox, mpsc::UnboundedReceiver<()>) {
+        let (pings, rx) = mpsc::unbounded_channel();
+        let client: Box<dyn McpClientTrait> = Box::new(PingClient { pings, healthy });
+        (Arc::new(Mutex::new(client)), rx)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_keepalive_pings_at_configured_interval() {
+        let (client, mut pings) = ping_client(true);
+        let interval = Duration::from_millis(100);
+        let cancel_token = CancellationToken::new();
+        let start = tokio::time::Instant::now();
+        let task = tokio::spawn(run_keepalive(
+            "stdio".to_string(),
+            client,
+            interval,
+            cancel_token.clone(),
+            || async { Err(ExtensionError::SetupError("unexpected restart".to_string())) },
+        ));
+
+        for expected in 1..=3 {
+            pings.recv().await.unwrap();
+            let elapsed = start.elapsed();
+            assert!(
+                elapsed >= interval * expected && elapsed < interval * expected + interval / 2,
+                "ping {expected} sent after {elapsed:?}"
+            );
+        }
+
+        cancel_token.cancel();
+        task.await.unwrap();
+        assert!(pings.try_recv().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_keepalive_reconnects_after_failed_ping() {
+        let (client, mut failed_pings) = ping_client(false);
+        let restarts = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let cancel_token = CancellationToken::new();
+
+        // The first restart fails, so the keepalive keeps pinging and tries again
+        let reconnect = {
+            let restarts = restarts.clone();
+            move || {
+                let attempt = restarts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                async move {
+                    if attempt == 0 {
+                        Err(ExtensionError::SetupError("still starting".to_string()))
+                    } else {
+                        Ok(())
+                    }
+                }
+            }
+        };
+        let task = tokio::spawn(run_keepalive(
+            "stdio".to_string(),
+            client,
+            Duration::from_millis(50),
+            cancel_token.clone(),
+            reconnect,
+        ));
+
+        // A successful restart hands over to the re-registered extension's keepalive
+        task.await.unwrap();
+
+        assert!(!cancel_token.is_cancelled());
+        assert_eq!(restarts.load(std::sync::atomic::Ordering::SeqCst), 2);
+        let mut sent = 0;
+        while failed_pings.try_recv().is_ok() {
+            sent += 1;
+        }
+        assert_eq!(sent, 2);
+    }
+
+    /// Count live processes with `arg` in their command line, ignoring zombies
+    #[cfg(target_os = "linux")]
+    fn live_processes_with_arg(arg: &str) -> usize {
+        std::fs::read_dir("/proc")
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                let dir = entry.path();
+                let cmdline = std::fs::read(dir.join("cmdline")).unwrap_or_default();
+                let stat = std::fs::read_to_string(dir.join("stat")).unwrap_or_default();
+                let zombie = stat
+                    .rsplit_once(')')
+                    .is_some_and(|(_, rest)| rest.trim_start().starts_with('Z'));
+                !zombie
+                    && cmdline
+                        .split(|b| *b == 0)
+                        .any(|part| part == arg.as_bytes())
+            })
+            .count()
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_shutdown_terminates_child_processes() {
+        // A minimal server: answer initialize, then hold the process open without reading
+        // stdin so only an explicit shutdown can end it.
+        let marker = format!("600.{}", std::process::id());
+        let script = format!(
+            r#"read -r _; echo '{{"jsonrpc":"2.0","id":0,"result":{{"protocolVersion":"2025-03-26","capabilities":{{}},"serverInfo":{{"name":"sleeper","version":"0.0.0"}}}}}}'; exec sleep {marker}"#
+        );
+        let config = ExtensionConfig::Stdio {
+            name: "sleeper".to_string(),
+            description: "Test".to_string(),
+            cmd: "sh".to_string(),
+            args: vec!["-c".to_string(), script],
+            envs: Default::default(),
+            env_keys: vec![],
+            timeout: Some(10),
+            bundled: None,
+            available_tools: vec![],
+            framing: Default::default(),
+        };
+
+        let extension_manager = ExtensionManager::new_without_provider();
+        let client = connect_child_process_extension(&config, Arc::new(Mutex::new(None)), 0)
+            .await
+            .unwrap();
+        extension_manager
+            .add_mock_extension(
+                "sleeper".to_string(),
+                Arc::new(Mutex::new(Box::new(client))),
+            )
+            .await;
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while live_processes_with_arg(&marker) == 0 && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(live_processes_with_arg(&marker), 1);
+
+        extension_manager.shutdown().await;
+        assert!(extension_manager
+            .list_extensions()
+            .await
+            .unwrap()
+            .is_empty());
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+        while live_processes_with_arg(&marker) > 0 && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(live_processes_with_arg(&marker), 0);
+    }
 }
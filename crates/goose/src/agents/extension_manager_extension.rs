@@ -1,5 +1,5 @@
 use crate::agents::extension::PlatformExtensionContext;
-use crate::agents::mcp_client::{Error, McpClientTrait};
+use crate::agents::mcp_client::{Error, McpClientTrait, MCP_PROTOCOL_VERSION};
 use crate::agents::tool_router_index_manager::ToolRouterIndexManager;
 use crate::config::get_extension_by_name;
 use anyhow::Result;
@@ -8,8 +8,8 @@ use indoc::indoc;
 use rmcp::model::{
     CallToolResult, Content, ErrorCode, ErrorData, GetPromptResult, Implementation,
     InitializeResult, JsonObject, ListPromptsResult, ListResourcesResult, ListToolsResult,
-    ProtocolVersion, ReadResourceResult, ServerCapabilities, ServerNotification, Tool,
-    ToolAnnotations, ToolsCapability,
+    ReadResourceResult, ServerCapabilities, ServerNotification, Tool, ToolAnnotations,
+    ToolsCapability,
 };
 use schemars::{schema_for, JsonSchema};
 use serde::{Deserialize, Serialize};
@@ -61,6 +61,12 @@ pub struct ReadResourceParams {
     pub uri: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub extension_name: Option<String>,
+    /// Byte offset to start reading from, for large resources
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    /// Maximum number of bytes to read from the offset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -84,7 +90,7 @@ pub struct ExtensionManagerClient {
 impl ExtensionManagerClient {
     pub fn new(context: PlatformExtensionContext) -> Result<Self> {
         let info = InitializeResult {
-            protocol_version: ProtocolVersion::V_2025_03_26,
+            protocol_version: MCP_PROTOCOL_VERSION,
             capabilities: ServerCapabilities {
                 tools: Some(ToolsCapability {
                     list_changed: Some(false),
@@ -430,6 +436,9 @@ impl ExtensionManagerClient {
             files, database schemas, or application-specific information. This tool searches for the
             resource URI in the provided extension, and reads in the resource content. If no extension
             is provided, the tool will search all extensions for the resource.
+
+            For large resources, pass an offset and/or length in bytes to read only part of it; the
+            result reports which bytes were returned and the total size.
         "#}.to_string(),
                             Arc::new(
                                 serde_json::to_value(schema_for!(ReadResourceParams))
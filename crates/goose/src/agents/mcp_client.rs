@@ -8,11 +8,13 @@ use rmcp::{
         CancelledNotificationMethod, CancelledNotificationParam, ClientCapabilities, ClientInfo,
         ClientRequest, CreateMessageRequestParam, CreateMessageResult, GetPromptRequest,
         GetPromptRequestParam, GetPromptResult, Implementation, InitializeResult,
-        ListPromptsRequest, ListPromptsResult, ListResourcesRequest, ListResourcesResult,
-        ListToolsRequest, ListToolsResult, LoggingMessageNotification,
-        LoggingMessageNotificationMethod, PaginatedRequestParam, ProgressNotification,
-        ProgressNotificationMethod, ProtocolVersion, ReadResourceRequest, ReadResourceRequestParam,
-        ReadResourceResult, RequestId, Role, SamplingMessage, ServerNotification, ServerResult,
+        ListPromptsRequest, ListPromptsResult, ListResourceTemplatesRequest,
+        ListResourceTemplatesResult, ListResourcesRequest, ListResourcesResult, ListToolsRequest,
+        ListToolsResult, LoggingLevel, LoggingMessageNotification,
+        LoggingMessageNotificationMethod, LoggingMessageNotificationParam, PaginatedRequestParam,
+        PingRequest, ProgressNotification, ProgressNotificationMethod, ProtocolVersion,
+        ReadResourceRequest, ReadResourceRequestParam, ReadResourceResult, RequestId, Role,
+        SamplingMessage, ServerNotification, ServerResult, SetLevelRequest, SetLevelRequestParam,
     },
     service::{
         ClientInitializeError, PeerRequestOptions, RequestContext, RequestHandle, RunningService,
@@ -22,10 +24,10 @@ use rmcp::{
     ClientHandler, ErrorData, Peer, RoleClient, ServiceError, ServiceExt,
 };
 use serde_json::Value;
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 use tokio::sync::{
     mpsc::{self, Sender},
-    Mutex,
+    oneshot, Mutex,
 };
 use tokio_util::sync::CancellationToken;
 
@@ -33,6 +35,60 @@ pub type BoxError = Box<dyn std::error::Error + Sync + Send>;
 
 pub type Error = rmcp::ServiceError;
 
+/// The MCP protocol version goose requests when initializing a connection. Servers may
+/// answer with a different version they support, which is what the session then uses.
+pub const MCP_PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion::V_2025_03_26;
+
+/// `_meta` key a server sets to "pending" on a `tools/call` result to say the real result
+/// will follow in a notification
+pub const TOOL_RESULT_STATUS_META_KEY: &str = "goose/status";
+
+/// `_meta` key of a progress notification carrying the deferred result of the `tools/call`
+/// whose request id matches the notification's progress token
+pub const DEFERRED_TOOL_RESULT_META_KEY: &str = "goose/toolResult";
+
+/// `tools/call` requests waiting on a deferred result
+#[derive(Default)]
+struct PendingToolCalls {
+    /// Senders for the calls' deferred results, by request id
+    waiters: HashMap<RequestId, oneshot::Sender<CallToolResult>>,
+    /// Calls sent whose request id isn't in `waiters` yet
+    unregistered: usize,
+    /// Deferred results that arrived while a call was still unregistered
+    early: HashMap<RequestId, CallToolResult>,
+}
+
+type DeferredToolResults = Arc<Mutex<PendingToolCalls>>;
+
+fn is_pending(result: &CallToolResult) -> bool {
+    result
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.get(TOOL_RESULT_STATUS_META_KEY))
+        .and_then(Value::as_str)
+        == Some("pending")
+}
+
+/// Write a server's `notifications/message` entry to our log at the matching level
+fn log_server_message(params: &LoggingMessageNotificationParam) {
+    let logger = params.logger.as_deref().unwrap_or_default();
+    let data = match &params.data {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+    match params.level {
+        LoggingLevel::Debug => tracing::debug!(target: "mcp_server", logger, "{}", data),
+        LoggingLevel::Info | LoggingLevel::Notice => {
+            tracing::info!(target: "mcp_server", logger, "{}", data)
+        }
+        LoggingLevel::Warning => tracing::warn!(target: "mcp_server", logger, "{}", data),
+        LoggingLevel::Error
+        | LoggingLevel::Critical
+        | LoggingLevel::Alert
+        | LoggingLevel::Emergency => tracing::error!(target: "mcp_server", logger, "{}", data),
+    }
+}
+
 #[async_trait::async_trait]
 pub trait McpClientTrait: Send + Sync {
     async fn list_resources(
@@ -47,6 +103,16 @@ pub trait McpClientTrait: Send + Sync {
         cancel_token: CancellationToken,
     ) -> Result<ReadResourceResult, Error>;
 
+    /// Parameterized resource URIs (e.g. `file:///{path}`) the server can read once filled
+    /// in. Clients without templates return an empty list.
+    async fn list_resource_templates(
+        &self,
+        _next_cursor: Option<String>,
+        _cancel_token: CancellationToken,
+    ) -> Result<ListResourceTemplatesResult, Error> {
+        Ok(ListResourceTemplatesResult::default())
+    }
+
     async fn list_tools(
         &self,
         next_cursor: Option<String>,
@@ -77,14 +143,46 @@ pub trait McpClientTrait: Send + Sync {
 
     fn get_info(&self) -> Option<&InitializeResult>;
 
+    /// The protocol version negotiated with the server during initialize
+    fn protocol_version(&self) -> Option<&ProtocolVersion> {
+        self.get_info().map(|info| &info.protocol_version)
+    }
+
     async fn get_moim(&self) -> Option<String> {
         None
     }
+
+    /// Check that the server is still responding. Clients without a connection to check
+    /// always succeed.
+    async fn ping(&self, _cancel_token: CancellationToken) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Ask the server to send log messages at `level` and above. Clients without a server
+    /// to ask ignore it.
+    async fn set_log_level(
+        &self,
+        _level: LoggingLevel,
+        _cancel_token: CancellationToken,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Whether the connection to the server is still open. Lets callers skip requests that
+    /// would fail and reconnect instead; clients without a connection are always connected.
+    async fn is_connected(&self) -> bool {
+        true
+    }
+
+    /// Close the connection, terminating the server process if this client spawned one.
+    /// Requests made after shutdown fail with a closed transport.
+    async fn shutdown(&self) {}
 }
 
 pub struct GooseClient {
     notification_handlers: Arc<Mutex<Vec<Sender<ServerNotification>>>>,
     provider: SharedProvider,
+    deferred_results: DeferredToolResults,
 }
 
 impl GooseClient {
@@ -95,8 +193,41 @@ impl GooseClient {
         GooseClient {
             notification_handlers: handlers,
             provider,
+            deferred_results: Default::default(),
         }
     }
+
+    /// Hand a deferred tool result to the `tools/call` waiting on it. Returns false when the
+    /// notification carries no result, so it is forwarded as ordinary progress instead.
+    async fn complete_deferred_result(
+        &self,
+        params: &rmcp::model::ProgressNotificationParam,
+        meta: &rmcp::model::Meta,
+    ) -> bool {
+        let Some(result) = meta.get(DEFERRED_TOOL_RESULT_META_KEY) else {
+            return false;
+        };
+        let request_id = &params.progress_token.0;
+        let result = match serde_json::from_value::<CallToolResult>(result.clone()) {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::warn!("Invalid deferred tool result for {:?}: {}", request_id, e);
+                return true;
+            }
+        };
+        let mut pending = self.deferred_results.lock().await;
+        match pending.waiters.remove(request_id) {
+            Some(waiter) => {
+                let _ = waiter.send(result);
+            }
+            // It may belong to a call that went out but isn't registered yet
+            None if pending.unregistered > 0 => {
+                pending.early.insert(request_id.clone(), result);
+            }
+            None => tracing::debug!("No tool call waiting on deferred result {:?}", request_id),
+        }
+        true
+    }
 }
 
 impl ClientHandler for GooseClient {
@@ -105,6 +236,9 @@ impl ClientHandler for GooseClient {
         params: rmcp::model::ProgressNotificationParam,
         context: rmcp::service::NotificationContext<rmcp::RoleClient>,
     ) {
+        if self.complete_deferred_result(&params, &context.meta).await {
+            return;
+        }
         self.notification_handlers
             .lock()
             .await
@@ -122,9 +256,10 @@ impl ClientHandler for GooseClient {
 
     async fn on_logging_message(
         &self,
-        params: rmcp::model::LoggingMessageNotificationParam,
+        params: LoggingMessageNotificationParam,
         context: rmcp::service::NotificationContext<rmcp::RoleClient>,
     ) {
+        log_server_message(&params);
         self.notification_handlers
             .lock()
             .await
@@ -220,7 +355,7 @@ impl ClientHandler for GooseClient {
 
     fn get_info(&self) -> ClientInfo {
         ClientInfo {
-            protocol_version: ProtocolVersion::V_2025_03_26,
+            protocol_version: MCP_PROTOCOL_VERSION,
             capabilities: ClientCapabilities::builder().enable_sampling().build(),
             client_info: Implementation {
                 name: "goose".to_string(),
@@ -236,8 +371,9 @@ impl ClientHandler for GooseClient {
 
 /// The MCP client is the interface for MCP operations.
 pub struct McpClient {
-    client: Mutex<RunningService<RoleClient, GooseClient>>,
+    client: Mutex<Option<RunningService<RoleClient, GooseClient>>>,
     notification_subscribers: Arc<Mutex<Vec<mpsc::Sender<ServerNotification>>>>,
+    deferred_results: DeferredToolResults,
     server_info: Option<InitializeResult>,
     timeout: std::time::Duration,
 }
@@ -256,13 +392,26 @@ impl McpClient {
             Arc::new(Mutex::new(Vec::<mpsc::Sender<ServerNotification>>::new()));
 
         let client = GooseClient::new(notification_subscribers.clone(), provider);
+        let deferred_results = client.deferred_results.clone();
         let client: rmcp::service::RunningService<rmcp::RoleClient, GooseClient> =
             client.serve(transport).await?;
         let server_info = client.peer_info().cloned();
 
+        if let Some(info) = &server_info {
+            if info.protocol_version != MCP_PROTOCOL_VERSION {
+                tracing::info!(
+                    "MCP server {} negotiated protocol version {} (requested {})",
+                    info.server_info.name,
+                    info.protocol_version,
+                    MCP_PROTOCOL_VERSION
+                );
+            }
+        }
+
         Ok(Self {
-            client: Mutex::new(client),
+            client: Mutex::new(Some(client)),
             notification_subscribers,
+            deferred_results,
             server_info,
             timeout,
         })
@@ -277,11 +426,100 @@ impl McpClient {
             .client
             .lock()
             .await
+            .as_ref()
+            .ok_or(ServiceError::TransportClosed)?
             .send_cancellable_request(request, PeerRequestOptions::no_options())
             .await?;
 
         await_response(handle, self.timeout, &cancel_token).await
     }
+
+    /// Send a `tools/call`, waiting for the notification that completes it if the server
+    /// answers with a pending result
+    async fn send_tool_call(
+        &self,
+        request: ClientRequest,
+        cancel_token: CancellationToken,
+    ) -> Result<CallToolResult, Error> {
+        // Count the call as pending before it goes out, so a deferred result notified before
+        // we know its request id is kept for us instead of dropped
+        self.deferred_results.lock().await.unregistered += 1;
+        let sent = match self.client.lock().await.as_ref() {
+            Some(client) => {
+                client
+                    .send_cancellable_request(request, PeerRequestOptions::no_options())
+                    .await
+            }
+            None => Err(ServiceError::TransportClosed),
+        };
+
+        let (tx, deferred) = oneshot::channel();
+        let handle = {
+            let mut pending = self.deferred_results.lock().await;
+            pending.unregistered -= 1;
+            let registered = sent.map(|handle| {
+                match pending.early.remove(&handle.id) {
+                    Some(result) => {
+                        let _ = tx.send(result);
+                    }
+                    None => {
+                        pending.waiters.insert(handle.id.clone(), tx);
+                    }
+                }
+                handle
+            });
+            if pending.unregistered == 0 {
+                pending.early.clear();
+            }
+            registered?
+        };
+        let request_id = handle.id.clone();
+        let peer = handle.peer.clone();
+
+        let result = match await_response(handle, self.timeout, &cancel_token).await {
+            Ok(ServerResult::CallToolResult(result)) if is_pending(&result) => {
+                await_deferred_result(
+                    deferred,
+                    &peer,
+                    request_id.clone(),
+                    self.timeout,
+                    &cancel_token,
+                )
+                .await
+            }
+            Ok(ServerResult::CallToolResult(result)) => Ok(result),
+            Ok(_) => Err(ServiceError::UnexpectedResponse),
+            Err(e) => Err(e),
+        };
+        self.deferred_results
+            .lock()
+            .await
+            .waiters
+            .remove(&request_id);
+        result
+    }
+}
+
+async fn await_deferred_result(
+    deferred: oneshot::Receiver<CallToolResult>,
+    peer: &Peer<RoleClient>,
+    request_id: RequestId,
+    timeout: Duration,
+    cancel_token: &CancellationToken,
+) -> Result<CallToolResult, ServiceError> {
+    tokio::select! {
+        result = deferred => {
+            result.map_err(|_e| ServiceError::TransportClosed)
+        }
+        _ = tokio::time::sleep(timeout) => {
+            send_cancel_message(peer, request_id, Some("timed out".to_owned())).await?;
+            Err(ServiceError::Timeout{timeout})
+        }
+        _ = cancel_token.cancelled() => {
+            send_cancel_message(peer, request_id, Some("operation cancelled".to_owned())).await?;
+            Err(ServiceError::Cancelled { reason: None })
+        }
+    }
 }
 
 async fn await_response(
@@ -329,6 +567,52 @@ impl McpClientTrait for McpClient {
         self.server_info.as_ref()
     }
 
+    async fn ping(&self, cancel_token: CancellationToken) -> Result<(), Error> {
+        self.send_request(
+            ClientRequest::PingRequest(PingRequest {
+                method: Default::default(),
+                extensions: Default::default(),
+            }),
+            cancel_token,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn set_log_level(
+        &self,
+        level: LoggingLevel,
+        cancel_token: CancellationToken,
+    ) -> Result<(), Error> {
+        self.send_request(
+            ClientRequest::SetLevelRequest(SetLevelRequest {
+                method: Default::default(),
+                params: SetLevelRequestParam { level },
+                extensions: Default::default(),
+            }),
+            cancel_token,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn is_connected(&self) -> bool {
+        self.client
+            .lock()
+            .await
+            .as_ref()
+            .is_some_and(|client| !client.is_transport_closed())
+    }
+
+    async fn shutdown(&self) {
+        let Some(client) = self.client.lock().await.take() else {
+            return;
+        };
+        if let Err(e) = client.cancel().await {
+            tracing::warn!("Failed to shut down MCP client cleanly: {}", e);
+        }
+    }
+
     async fn list_resources(
         &self,
         cursor: Option<String>,
@@ -375,14 +659,14 @@ impl McpClientTrait for McpClient {
         }
     }
 
-    async fn list_tools(
+    async fn list_resource_templates(
         &self,
         cursor: Option<String>,
         cancel_token: CancellationToken,
-    ) -> Result<ListToolsResult, Error> {
+    ) -> Result<ListResourceTemplatesResult, Error> {
         let res = self
             .send_request(
-                ClientRequest::ListToolsRequest(ListToolsRequest {
+                ClientRequest::ListResourceTemplatesRequest(ListResourceTemplatesRequest {
                     params: Some(PaginatedRequestParam { cursor }),
                     method: Default::default(),
                     extensions: inject_session_into_extensions(Default::default()),
@@ -392,24 +676,20 @@ impl McpClientTrait for McpClient {
             .await?;
 
         match res {
-            ServerResult::ListToolsResult(result) => Ok(result),
+            ServerResult::ListResourceTemplatesResult(result) => Ok(result),
             _ => Err(ServiceError::UnexpectedResponse),
         }
     }
 
-    async fn call_tool(
+    async fn list_tools(
         &self,
-        name: &str,
-        arguments: Option<JsonObject>,
+        cursor: Option<String>,
         cancel_token: CancellationToken,
-    ) -> Result<CallToolResult, Error> {
+    ) -> Result<ListToolsResult, Error> {
         let res = self
             .send_request(
-                ClientRequest::CallToolRequest(CallToolRequest {
-                    params: CallToolRequestParam {
-                        name: name.to_string().into(),
-                        arguments,
-                    },
+                ClientRequest::ListToolsRequest(ListToolsRequest {
+                    params: Some(PaginatedRequestParam { cursor }),
                     method: Default::default(),
                     extensions: inject_session_into_extensions(Default::default()),
                 }),
@@ -418,11 +698,31 @@ impl McpClientTrait for McpClient {
             .await?;
 
         match res {
-            ServerResult::CallToolResult(result) => Ok(result),
+            ServerResult::ListToolsResult(result) => Ok(result),
             _ => Err(ServiceError::UnexpectedResponse),
         }
     }
 
+    async fn call_tool(
+        &self,
+        name: &str,
+        arguments: Option<JsonObject>,
+        cancel_token: CancellationToken,
+    ) -> Result<CallToolResult, Error> {
+        self.send_tool_call(
+            ClientRequest::CallToolRequest(CallToolRequest {
+                params: CallToolRequestParam {
+                    name: name.to_string().into(),
+                    arguments,
+                },
+                method: Default::default(),
+                extensions: inject_session_into_extensions(Default::default()),
+            }),
+            cancel_token,
+        )
+        .await
+    }
+
     async fn list_prompts(
         &self,
         cursor: Option<String>,
@@ -509,6 +809,252 @@ fn inject_session_into_extensions(
 mod tests {
     use super::*;
     use rmcp::model::Meta;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::task::JoinHandle;
+
+    type Handler = Box<dyn Fn(&Value) -> Vec<Value> + Send>;
+
+    /// Per-method request handlers for `spawn_mock_server`, each returning the messages
+    /// to send back for a request
+    #[derive(Default)]
+    struct MockHandlers(HashMap<&'static str, Handler>);
+
+    impl MockHandlers {
+        fn on(
+            mut self,
+            method: &'static str,
+            handler: impl Fn(&Value) -> Vec<Value> + Send + 'static,
+        ) -> Self {
+            self.0.insert(method, Box::new(handler));
+            self
+        }
+    }
+
+    /// A response to `request` carrying `result`
+    fn reply(request: &Value, result: Value) -> Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "result": result
+        })
+    }
+
+    /// Serve MCP over an in-memory pipe and connect a client to it. The server answers
+    /// `initialize` with `protocol_version` and `capabilities`, and any other request with
+    /// the handler for its method, leaving the rest unanswered. Aborting the returned task
+    /// hangs up on the client.
+    async fn spawn_mock_server(
+        protocol_version: &'static str,
+        capabilities: Value,
+        handlers: MockHandlers,
+    ) -> (McpClient, JoinHandle<()>) {
+        let (client_io, server_io) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (read, mut write) = tokio::io::split(server_io);
+            let mut lines = BufReader::new(read).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let request: Value = serde_json::from_str(&line).unwrap();
+                let messages = match request["method"].as_str() {
+                    Some("initialize") => vec![reply(
+                        &request,
+                        serde_json::json!({
+                            "protocolVersion": protocol_version,
+                            "capabilities": capabilities,
+                            "serverInfo": {"name": "mock-server", "version": "0.1.0"}
+                        }),
+                    )],
+                    Some(method) => match handlers.0.get(method) {
+                        Some(handler) => handler(&request),
+                        None => continue,
+                    },
+                    None => continue,
+                };
+                for message in messages {
+                    write
+                        .write_all(format!("{}\n", message).as_bytes())
+                        .await
+                        .unwrap();
+                }
+            }
+        });
+
+        let client = McpClient::connect(
+            client_io,
+            Duration::from_secs(5),
+            Arc::new(Mutex::new(None)),
+        )
+        .await
+        .unwrap();
+        (client, server)
+    }
+
+    #[tokio::test]
+    async fn test_negotiated_protocol_version_is_stored() {
+        let (client, _server) =
+            spawn_mock_server("2024-11-05", serde_json::json!({}), MockHandlers::default()).await;
+
+        assert_eq!(
+            client.protocol_version(),
+            Some(&ProtocolVersion::V_2024_11_05)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_is_connected_tracks_connection_state() {
+        async fn connect_to_server() -> (McpClient, JoinHandle<()>) {
+            spawn_mock_server("2025-03-26", serde_json::json!({}), MockHandlers::default()).await
+        }
+
+        // Closed by the client
+        let (client, _server) = connect_to_server().await;
+        assert!(client.is_connected().await);
+        client.shutdown().await;
+        assert!(!client.is_connected().await);
+        let err = client.ping(CancellationToken::new()).await.unwrap_err();
+        assert!(matches!(err, ServiceError::TransportClosed));
+
+        // Closed by the server, whose side of the transport drops with its task
+        let (client, server) = connect_to_server().await;
+        assert!(client.is_connected().await);
+        server.abort();
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while client.is_connected().await && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!client.is_connected().await);
+    }
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_server_log_message_forwarded_to_tracing() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        // The client handler runs on this test's single thread
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let handlers = MockHandlers::default().on("logging/setLevel", |request| {
+            assert_eq!(request["params"]["level"], "warning");
+            vec![
+                reply(request, serde_json::json!({})),
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/message",
+                    "params": {"level": "warning", "logger": "db", "data": "disk almost full"}
+                }),
+            ]
+        });
+        let (client, _server) =
+            spawn_mock_server("2025-03-26", serde_json::json!({"logging": {}}), handlers).await;
+        client
+            .set_log_level(LoggingLevel::Warning, CancellationToken::new())
+            .await
+            .unwrap();
+
+        let logged = || String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while !logged().contains("disk almost full") && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let output = logged();
+        let line = output
+            .lines()
+            .find(|line| line.contains("disk almost full"))
+            .unwrap();
+        assert!(line.contains("WARN"));
+        assert!(line.contains("mcp_server"));
+        assert!(line.contains("logger=\"db\""));
+    }
+
+    #[tokio::test]
+    async fn test_list_resource_templates() {
+        let handlers = MockHandlers::default().on("resources/templates/list", |request| {
+            vec![reply(
+                request,
+                serde_json::json!({
+                    "resourceTemplates": [{
+                        "uriTemplate": "file:///{path}",
+                        "name": "file",
+                        "description": "A file on disk",
+                        "mimeType": "text/plain"
+                    }]
+                }),
+            )]
+        });
+        let (client, _server) =
+            spawn_mock_server("2025-03-26", serde_json::json!({"resources": {}}), handlers).await;
+
+        let result = client
+            .list_resource_templates(None, CancellationToken::new())
+            .await
+            .unwrap();
+        assert!(result.next_cursor.is_none());
+        assert_eq!(result.resource_templates.len(), 1);
+        let template = &result.resource_templates[0];
+        assert_eq!(template.uri_template, "file:///{path}");
+        assert_eq!(template.name, "file");
+        assert_eq!(template.mime_type.as_deref(), Some("text/plain"));
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_completed_by_deferred_result() {
+        // Answer pending, then deliver the real result later by request id
+        let handlers = MockHandlers::default().on("tools/call", |request| {
+            vec![
+                reply(
+                    request,
+                    serde_json::json!({
+                        "content": [],
+                        "_meta": {TOOL_RESULT_STATUS_META_KEY: "pending"}
+                    }),
+                ),
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/progress",
+                    "params": {
+                        "progressToken": request["id"],
+                        "progress": 1,
+                        "_meta": {
+                            DEFERRED_TOOL_RESULT_META_KEY: {
+                                "content": [{"type": "text", "text": "build finished"}]
+                            }
+                        }
+                    }
+                }),
+            ]
+        });
+        let (client, _server) =
+            spawn_mock_server("2025-03-26", serde_json::json!({"tools": {}}), handlers).await;
+
+        let result = client
+            .call_tool("build", None, CancellationToken::new())
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content[0].as_text().map(|t| t.text.as_str()),
+            Some("build finished")
+        );
+        let pending = client.deferred_results.lock().await;
+        assert!(pending.waiters.is_empty());
+        assert_eq!(pending.unregistered, 0);
+    }
 
     #[tokio::test]
     async fn test_session_id_in_mcp_meta() {
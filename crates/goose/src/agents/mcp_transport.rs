@@ -0,0 +1,440 @@
+//! Middleware for MCP client transports.
+//!
+//! A [`TransportLayer`] wraps a transport in another transport, the way a tower layer wraps a
+//! service, so logging, timeouts or auth refresh can be composed around any transport without
+//! changing it. [`McpClient::connect`](super::mcp_client::McpClient::connect) accepts the
+//! wrapped transport like any other. For example, to fail sends that take longer than 30
+//! seconds:
+//!
+//! ```ignore
+//! use goose::agents::mcp_transport::{TimeoutLayer, TransportLayerExt};
+//!
+//! let transport = TokioChildProcess::new(command)?
+//!     .with_layer(TimeoutLayer::new(Duration::from_secs(30)));
+//! let client = McpClient::connect(transport, timeout, provider).await?;
+//! ```
+
+use std::future::Future;
+use std::marker::PhantomData;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use rmcp::service::{RxJsonRpcMessage, ServiceRole, TxJsonRpcMessage};
+use rmcp::transport::Transport;
+use rmcp::RoleClient;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+use tokio_util::bytes::{Buf, BytesMut};
+use tokio_util::codec::{Decoder, Encoder, FramedRead, FramedWrite};
+
+/// Wraps a transport of type `T` in middleware
+pub trait TransportLayer<T> {
+    type Transport;
+
+    fn layer(&self, inner: T) -> Self::Transport;
+}
+
+/// Adds `with_layer` to every transport so layers can be chained
+pub trait TransportLayerExt: Sized {
+    fn with_layer<L: TransportLayer<Self>>(self, layer: L) -> L::Transport {
+        layer.layer(self)
+    }
+}
+
+impl<T> TransportLayerExt for T {}
+
+/// Fails any send that does not complete within `timeout`
+#[derive(Debug, Clone, Copy)]
+pub struct TimeoutLayer {
+    timeout: Duration,
+}
+
+impl TimeoutLayer {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+impl<T> TransportLayer<T> for TimeoutLayer {
+    type Transport = TimeoutTransport<T>;
+
+    fn layer(&self, inner: T) -> Self::Transport {
+        TimeoutTransport {
+            inner,
+            timeout: self.timeout,
+        }
+    }
+}
+
+/// Transport produced by [`TimeoutLayer`]. Receives are left alone, since servers may stay
+/// quiet for as long as they like.
+pub struct TimeoutTransport<T> {
+    inner: T,
+    timeout: Duration,
+}
+
+impl<R, T> Transport<R> for TimeoutTransport<T>
+where
+    R: ServiceRole,
+    T: Transport<R>,
+    T::Error: From<std::io::Error>,
+{
+    type Error = T::Error;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<R>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let send = self.inner.send(item);
+        let timeout = self.timeout;
+        async move {
+            tokio::time::timeout(timeout, send)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(std::io::Error::new(
+                        std::io::ErrorKind::TimedOut,
+                        format!("MCP transport send timed out after {:?}", timeout),
+                    )
+                    .into())
+                })
+        }
+    }
+
+    fn receive(&mut self) -> impl Future<Output = Option<RxJsonRpcMessage<R>>> + Send {
+        self.inner.receive()
+    }
+
+    fn close(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        self.inner.close()
+    }
+}
+
+/// Frames each message with an LSP-style `Content-Length` header instead of a trailing newline
+#[derive(Debug, Clone)]
+pub struct ContentLengthCodec<T> {
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for ContentLengthCodec<T> {
+    fn default() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+const HEADER_END: &[u8] = b"\r\n\r\n";
+
+/// Largest frame body accepted, so a bad `Content-Length` can't make us buffer without bound
+const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+
+fn invalid_data(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, error)
+}
+
+impl<T: DeserializeOwned> Decoder for ContentLengthCodec<T> {
+    type Item = T;
+    type Error = std::io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>, Self::Error> {
+        let Some(header_len) = src
+            .windows(HEADER_END.len())
+            .position(|window| window == HEADER_END)
+        else {
+            if src.len() > MAX_FRAME_LEN {
+                return Err(invalid_data(
+                    "frame headers exceed the maximum frame length",
+                ));
+            }
+            return Ok(None);
+        };
+        let headers = std::str::from_utf8(&src[..header_len]).map_err(invalid_data)?;
+        let content_length = headers
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.trim()
+                    .eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse::<usize>())
+            })
+            .ok_or_else(|| invalid_data("missing Content-Length header"))?
+            .map_err(invalid_data)?;
+        if content_length > MAX_FRAME_LEN {
+            return Err(invalid_data(format!(
+                "frame of {} bytes exceeds the maximum of {} bytes",
+                content_length, MAX_FRAME_LEN
+            )));
+        }
+
+        let body_start = header_len + HEADER_END.len();
+        if src.len() < body_start + content_length {
+            src.reserve(body_start + content_length - src.len());
+            return Ok(None);
+        }
+        src.advance(body_start);
+        let body = src.split_to(content_length);
+        serde_json::from_slice(&body)
+            .map(Some)
+            .map_err(invalid_data)
+    }
+}
+
+impl<T: Serialize> Encoder<T> for ContentLengthCodec<T> {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let body = serde_json::to_vec(&item).map_err(invalid_data)?;
+        dst.extend_from_slice(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes());
+        dst.extend_from_slice(&body);
+        Ok(())
+    }
+}
+
+/// Transport over a reader and writer using [`ContentLengthCodec`] framing, for servers
+/// derived from LSP implementations
+pub struct ContentLengthTransport<Role: ServiceRole, R, W> {
+    read: FramedRead<R, ContentLengthCodec<RxJsonRpcMessage<Role>>>,
+    write: Arc<Mutex<Option<FramedWrite<W, ContentLengthCodec<TxJsonRpcMessage<Role>>>>>>,
+}
+
+impl<Role: ServiceRole, R: AsyncRead, W: AsyncWrite> ContentLengthTransport<Role, R, W> {
+    pub fn new(read: R, write: W) -> Self {
+        Self {
+            read: FramedRead::new(read, ContentLengthCodec::default()),
+            write: Arc::new(Mutex::new(Some(FramedWrite::new(
+                write,
+                ContentLengthCodec::default(),
+            )))),
+        }
+    }
+}
+
+impl<Role, R, W> Transport<Role> for ContentLengthTransport<Role, R, W>
+where
+    Role: ServiceRole,
+    R: AsyncRead + Send + Unpin,
+    W: AsyncWrite + Send + Unpin + 'static,
+{
+    type Error = std::io::Error;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<Role>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let write = self.write.clone();
+        async move {
+            match write.lock().await.as_mut() {
+                Some(write) => write.send(item).await,
+                None => Err(std::io::Error::new(
+                    std::io::ErrorKind::NotConnected,
+                    "Transport is closed",
+                )),
+            }
+        }
+    }
+
+    fn receive(&mut self) -> impl Future<Output = Option<RxJsonRpcMessage<Role>>> + Send {
+        let next = self.read.next();
+        async {
+            next.await.and_then(|message| {
+                message
+                    .inspect_err(|e| tracing::error!("Error reading from stream: {}", e))
+                    .ok()
+            })
+        }
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        drop(self.write.lock().await.take());
+        Ok(())
+    }
+}
+
+/// A child process spoken to with `Content-Length` framing on its stdin and stdout, the
+/// counterpart of rmcp's newline-delimited `TokioChildProcess`. The process is killed when
+/// this is dropped.
+pub struct ContentLengthChildProcess {
+    child: Child,
+    transport: ContentLengthTransport<RoleClient, ChildStdout, ChildStdin>,
+}
+
+impl ContentLengthChildProcess {
+    /// Spawns `command` with piped stdio, returning its stderr for the caller to drain
+    pub fn spawn(mut command: Command) -> std::io::Result<(Self, Option<ChildStderr>)> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| std::io::Error::other("stdin was already taken"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| std::io::Error::other("stdout was already taken"))?;
+        let stderr = child.stderr.take();
+        let transport = ContentLengthTransport::new(stdout, stdin);
+        Ok((Self { child, transport }, stderr))
+    }
+}
+
+impl Transport<RoleClient> for ContentLengthChildProcess {
+    type Error = std::io::Error;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<RoleClient>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        self.transport.send(item)
+    }
+
+    fn receive(&mut self) -> impl Future<Output = Option<RxJsonRpcMessage<RoleClient>>> + Send {
+        self.transport.receive()
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        Transport::<RoleClient>::close(&mut self.transport).await?;
+        // Closing stdin asks the server to exit; give it a moment before killing it
+        if tokio::time::timeout(Duration::from_secs(3), self.child.wait())
+            .await
+            .is_err()
+        {
+            self.child.kill().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::{
+        ClientJsonRpcMessage, ClientRequest, NumberOrString, PingRequest, ServerJsonRpcMessage,
+        ServerResult,
+    };
+
+    fn ping() -> ClientJsonRpcMessage {
+        ClientJsonRpcMessage::request(
+            ClientRequest::PingRequest(PingRequest {
+                method: Default::default(),
+                extensions: Default::default(),
+            }),
+            NumberOrString::Number(1),
+        )
+    }
+
+    /// Takes far longer to send than any sensible timeout
+    struct SlowTransport;
+
+    impl Transport<RoleClient> for SlowTransport {
+        type Error = std::io::Error;
+
+        fn send(
+            &mut self,
+            _item: TxJsonRpcMessage<RoleClient>,
+        ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+            async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok(())
+            }
+        }
+
+        fn receive(&mut self) -> impl Future<Output = Option<RxJsonRpcMessage<RoleClient>>> + Send {
+            std::future::pending()
+        }
+
+        async fn close(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout_layer_fails_slow_send() {
+        let mut transport = SlowTransport.with_layer(TimeoutLayer::new(Duration::from_millis(50)));
+        let err = Transport::<RoleClient>::send(&mut transport, ping())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+    }
+
+    #[tokio::test]
+    async fn test_content_length_framing_round_trip() {
+        let (client_io, server_io) = tokio::io::duplex(4096);
+        let (client_read, client_write) = tokio::io::split(client_io);
+        let mut client = ContentLengthTransport::<RoleClient, _, _>::new(client_read, client_write);
+        let (server_read, server_write) = tokio::io::split(server_io);
+        let mut server_read = FramedRead::new(
+            server_read,
+            ContentLengthCodec::<ClientJsonRpcMessage>::default(),
+        );
+        let mut server_write = FramedWrite::new(
+            server_write,
+            ContentLengthCodec::<ServerJsonRpcMessage>::default(),
+        );
+
+        Transport::<RoleClient>::send(&mut client, ping())
+            .await
+            .unwrap();
+        let (_, id) = server_read
+            .next()
+            .await
+            .unwrap()
+            .unwrap()
+            .into_request()
+            .unwrap();
+
+        server_write
+            .send(ServerJsonRpcMessage::response(ServerResult::empty(()), id))
+            .await
+            .unwrap();
+
+        let (_, id) = Transport::<RoleClient>::receive(&mut client)
+            .await
+            .unwrap()
+            .into_response()
+            .unwrap();
+        assert_eq!(id, NumberOrString::Number(1));
+    }
+
+    #[test]
+    fn test_content_length_codec_waits_for_whole_body() {
+        let body = r#"{"jsonrpc":"2.0","id":7,"result":{}}"#;
+        let frame = format!(
+            "Content-Type: application/vscode-jsonrpc; charset=utf-8\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let mut codec = ContentLengthCodec::<ServerJsonRpcMessage>::default();
+        let mut buf = BytesMut::from(&frame.as_bytes()[..frame.len() - 5]);
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+
+        buf.extend_from_slice(&frame.as_bytes()[frame.len() - 5..]);
+        let (_, id) = codec
+            .decode(&mut buf)
+            .unwrap()
+            .unwrap()
+            .into_response()
+            .unwrap();
+        assert_eq!(id, NumberOrString::Number(7));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_decode_rejects_oversized_frame() {
+        let frame = format!("Content-Length: {}\r\n\r\n{{", MAX_FRAME_LEN + 1);
+        let mut codec = ContentLengthCodec::<ServerJsonRpcMessage>::default();
+        let mut buf = BytesMut::from(frame.as_bytes());
+
+        let err = codec.decode(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(buf.capacity() < MAX_FRAME_LEN);
+    }
+}
@@ -1,4 +1,5 @@
 mod agent;
+mod audit_log;
 pub(crate) mod chatrecall_extension;
 pub mod extension;
 pub mod extension_malware_check;
@@ -7,9 +8,11 @@ pub mod extension_manager_extension;
 pub mod final_output_tool;
 mod large_response_handler;
 pub mod mcp_client;
+pub mod mcp_transport;
 pub mod model_selector;
 pub mod moim;
 pub mod platform_tools;
+mod post_processors;
 pub mod prompt_manager;
 pub mod recipe_tools;
 mod reply_parts;
@@ -22,14 +25,17 @@ pub mod subagent_execution_tool;
 pub mod subagent_handler;
 mod subagent_task_config;
 pub(crate) mod todo_extension;
+mod tool_budget;
 mod tool_execution;
 mod tool_route_manager;
 mod tool_router_index_manager;
+mod tool_span;
 pub mod types;
 
 pub use agent::{Agent, AgentEvent, MANUAL_COMPACT_TRIGGER};
 pub use extension::ExtensionConfig;
 pub use extension_manager::ExtensionManager;
+pub use post_processors::{PostProcessorRule, PostProcessors};
 pub use prompt_manager::PromptManager;
 pub use subagent_task_config::TaskConfig;
 pub use types::{FrontendTool, RetryConfig, SessionConfig, SuccessCheck};
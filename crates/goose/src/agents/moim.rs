@@ -134,4 +134,46 @@ mod tests {
             "MOIM should be in message before latest assistant message"
         );
     }
+
+    #[tokio::test]
+    async fn test_moim_injection_adds_no_tool_exchange() {
+        let em = ExtensionManager::new_without_provider();
+
+        let conv = Conversation::new_unvalidated(vec![
+            Message::user().with_text("List files"),
+            Message::assistant().with_tool_request(
+                "ls_1",
+                Ok(CallToolRequestParam {
+                    name: "shell".into(),
+                    arguments: None,
+                }),
+            ),
+            Message::user().with_tool_response("ls_1", Ok(vec![])),
+            Message::assistant().with_text("Done"),
+            Message::user().with_text("Thanks"),
+        ]);
+
+        let tool_ids = |conversation: &Conversation| {
+            conversation
+                .messages()
+                .iter()
+                .flat_map(|m| m.content.iter())
+                .filter_map(|c| {
+                    c.as_tool_request()
+                        .map(|r| r.id.clone())
+                        .or_else(|| c.as_tool_response().map(|r| r.id.clone()))
+                })
+                .collect::<Vec<_>>()
+        };
+        let before = tool_ids(&conv);
+        let result = inject_moim(conv, &em).await;
+
+        // The status goes in as plain user text, never as a synthetic tool call that strict
+        // providers would reject for lacking a matching tool definition
+        assert_eq!(tool_ids(&result), before);
+        assert!(result.messages().iter().any(|m| m.role == Role::User
+            && m.content
+                .iter()
+                .any(|c| c.as_text().is_some_and(|t| t.contains("<info-msg>")))));
+    }
 }
@@ -0,0 +1,128 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use rmcp::model::Role;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::conversation::message::{Message, MessageContent};
+
+/// A regex replacement applied to assistant text, e.g. to mask internal URLs or secrets.
+/// The replacement may refer to capture groups as `$1` or `${name}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostProcessorRule {
+    pub pattern: String,
+    #[serde(default)]
+    pub replacement: String,
+}
+
+/// Rules applied to the assistant's text before it is shown.
+///
+/// Rules run on each message as it arrives, so with streaming a match split across two
+/// chunks is missed; turn streaming off when the rules must always apply.
+#[derive(Debug, Clone, Default)]
+pub struct PostProcessors {
+    rules: Vec<(Regex, String)>,
+    redact_history: bool,
+}
+
+impl PostProcessors {
+    /// Compile `rules`. With `redact_history` the processed text is also what gets saved to
+    /// the session and sent back to the model; otherwise only what is shown changes.
+    pub fn new(rules: &[PostProcessorRule], redact_history: bool) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let regex = Regex::new(&rule.pattern)
+                    .with_context(|| format!("Invalid post-processor pattern {}", rule.pattern))?;
+                Ok((regex, rule.replacement.clone()))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            rules,
+            redact_history,
+        })
+    }
+
+    /// Rules from `GOOSE_OUTPUT_POST_PROCESSORS`, a list of `{pattern, replacement}`, and
+    /// `GOOSE_REDACT_HISTORY`
+    pub fn from_config() -> Result<Self> {
+        let config = Config::global();
+        let rules: Vec<PostProcessorRule> = config
+            .get_param("GOOSE_OUTPUT_POST_PROCESSORS")
+            .unwrap_or_default();
+        let redact_history = config
+            .get_param::<bool>("GOOSE_REDACT_HISTORY")
+            .unwrap_or(false);
+        Self::new(&rules, redact_history)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn redact_history(&self) -> bool {
+        self.redact_history
+    }
+
+    /// The message with every rule applied to its text, if it is from the assistant
+    pub fn apply(&self, message: &Message) -> Message {
+        let mut message = message.clone();
+        if self.is_empty() || message.role != Role::Assistant {
+            return message;
+        }
+        for content in &mut message.content {
+            if let MessageContent::Text(text) = content {
+                for (regex, replacement) in &self.rules {
+                    if let std::borrow::Cow::Owned(replaced) =
+                        regex.replace_all(&text.text, replacement.as_str())
+                    {
+                        text.text = replaced;
+                    }
+                }
+            }
+        }
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, replacement: &str) -> PostProcessorRule {
+        PostProcessorRule {
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_redaction_masks_assistant_text() {
+        let processors = PostProcessors::new(
+            &[
+                rule(r"https?://[\w.-]+\.internal\S*", "[internal link]"),
+                rule(r"(api_key=)\w+", "${1}****"),
+            ],
+            false,
+        )
+        .unwrap();
+
+        let message = Message::assistant()
+            .with_text("See https://wiki.corp.internal/page and use api_key=abc123.")
+            .with_thinking("https://wiki.corp.internal", "sig");
+        let processed = processors.apply(&message);
+        assert_eq!(
+            processed.content[0].as_text(),
+            Some("See [internal link] and use api_key=****.")
+        );
+        assert_eq!(processed.content[1], message.content[1]);
+
+        let user = Message::user().with_text("api_key=abc123");
+        assert_eq!(processors.apply(&user), user);
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_rejected() {
+        assert!(PostProcessors::new(&[rule("(unclosed", "")], false).is_err());
+    }
+}
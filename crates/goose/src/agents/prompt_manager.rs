@@ -1,14 +1,19 @@
-#[cfg(test)]
-use chrono::DateTime;
-use chrono::Utc;
+use chrono::{DateTime, Local, Utc};
+use lru::LruCache;
+use once_cell::sync::Lazy;
 use serde::Serialize;
 use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
 
 use crate::agents::extension::ExtensionInfo;
 use crate::agents::recipe_tools::dynamic_task_tools::should_enabled_subagents;
 use crate::agents::router_tools::llm_search_tool_prompt;
 use crate::hints::load_hints::{load_hint_files, AGENTS_MD_FILENAME, GOOSE_HINTS_FILENAME};
+use crate::token_counter::create_token_counter;
 use crate::{
     config::{Config, GooseMode},
     prompt_template,
@@ -19,6 +24,192 @@ use std::path::Path;
 const MAX_EXTENSIONS: usize = 5;
 const MAX_TOOLS: usize = 50;
 
+/// Default cap on the tokens each extension's instructions may take in the system prompt
+pub const DEFAULT_MAX_INSTRUCTION_TOKENS: usize = 2000;
+
+/// Default cap on the tokens all extensions' instructions may take together
+pub const DEFAULT_MAX_TOTAL_INSTRUCTION_TOKENS: usize = 8000;
+
+/// Cut `instructions` to the longest prefix that fits within `max_tokens` together with a
+/// marker saying how much was dropped. Returns None when they already fit.
+pub fn clamp_instructions<F>(
+    instructions: &str,
+    max_tokens: usize,
+    count_tokens: F,
+) -> Option<String>
+where
+    F: Fn(&str) -> usize,
+{
+    let total = count_tokens(instructions);
+    if total <= max_tokens {
+        return None;
+    }
+
+    let boundaries: Vec<usize> = instructions
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(instructions.len()))
+        .collect();
+    let truncated = |end: usize| {
+        let kept = instructions[..end].trim_end();
+        format!(
+            "{}\n[... truncated {} of {} tokens of instructions]",
+            kept,
+            total - count_tokens(kept),
+            total
+        )
+    };
+    let (mut lo, mut hi) = (0, boundaries.len() - 1);
+    while lo < hi {
+        let mid = (lo + hi).div_ceil(2);
+        if count_tokens(&truncated(boundaries[mid])) <= max_tokens {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+
+    Some(truncated(boundaries[lo]))
+}
+
+const CLAMPED_INSTRUCTIONS_CAPACITY: usize = 64;
+
+/// Instructions already measured against a token limit, keyed by a hash of the instructions
+/// and the limit. The prompt is rebuilt every turn, so this keeps the counting and the
+/// truncation warning to once per distinct instructions; the least recently used entries are
+/// dropped once extensions have changed often enough to fill it.
+static CLAMPED_INSTRUCTIONS: Lazy<Mutex<LruCache<(u64, usize), ClampedInstructions>>> =
+    Lazy::new(|| {
+        Mutex::new(LruCache::new(
+            NonZeroUsize::new(CLAMPED_INSTRUCTIONS_CAPACITY).unwrap(),
+        ))
+    });
+
+#[derive(Clone)]
+struct ClampedInstructions {
+    /// The truncated text, or None when the instructions fit
+    text: Option<String>,
+    tokens: usize,
+}
+
+/// [`clamp_instructions`] through [`CLAMPED_INSTRUCTIONS`], logging a truncation only the
+/// first time it happens
+fn clamp_instructions_cached<F>(
+    extension: &str,
+    instructions: &str,
+    max_tokens: usize,
+    count_tokens: F,
+) -> ClampedInstructions
+where
+    F: Fn(&str) -> usize,
+{
+    let mut hasher = DefaultHasher::new();
+    instructions.hash(&mut hasher);
+    let key = (hasher.finish(), max_tokens);
+    if let Some(clamped) = CLAMPED_INSTRUCTIONS.lock().unwrap().get(&key) {
+        return clamped.clone();
+    }
+
+    let text = clamp_instructions(instructions, max_tokens, &count_tokens);
+    if text.is_some() {
+        tracing::warn!(
+            "Truncated instructions from extension {} to {} tokens",
+            extension,
+            max_tokens
+        );
+    }
+    let clamped = ClampedInstructions {
+        tokens: count_tokens(text.as_deref().unwrap_or(instructions)),
+        text,
+    };
+    CLAMPED_INSTRUCTIONS
+        .lock()
+        .unwrap()
+        .put(key, clamped.clone());
+    clamped
+}
+
+/// Token limits that split `total` between extensions whose instructions take `tokens`.
+/// Extensions within an equal share keep everything and leave the rest to the others.
+fn share_instruction_budget(tokens: &[usize], total: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..tokens.len()).collect();
+    order.sort_by_key(|&i| tokens[i]);
+
+    let mut limits = vec![0; tokens.len()];
+    let mut remaining = total;
+    for (position, &i) in order.iter().enumerate() {
+        let share = remaining / (order.len() - position);
+        limits[i] = tokens[i].min(share);
+        remaining -= limits[i];
+    }
+    limits
+}
+
+/// Clamp each extension's instructions to `GOOSE_EXTENSION_INSTRUCTIONS_MAX_TOKENS`, then all
+/// of them together to `GOOSE_EXTENSION_INSTRUCTIONS_TOTAL_MAX_TOKENS` (0 turns either off),
+/// so verbose servers can't crowd out the rest of the system prompt.
+pub async fn budget_extension_instructions(
+    mut extensions: Vec<ExtensionInfo>,
+) -> Vec<ExtensionInfo> {
+    let config = Config::global();
+    let max_tokens = config
+        .get_param::<usize>("GOOSE_EXTENSION_INSTRUCTIONS_MAX_TOKENS")
+        .unwrap_or(DEFAULT_MAX_INSTRUCTION_TOKENS);
+    let max_total_tokens = config
+        .get_param::<usize>("GOOSE_EXTENSION_INSTRUCTIONS_TOTAL_MAX_TOKENS")
+        .unwrap_or(DEFAULT_MAX_TOTAL_INSTRUCTION_TOKENS);
+    if max_tokens == 0 && max_total_tokens == 0 {
+        return extensions;
+    }
+
+    let token_counter = match create_token_counter().await {
+        Ok(counter) => counter,
+        Err(e) => {
+            tracing::warn!("Skipping extension instruction budget: {}", e);
+            return extensions;
+        }
+    };
+    let count_tokens = |text: &str| token_counter.count_tokens(text);
+
+    let max_tokens = if max_tokens == 0 {
+        usize::MAX
+    } else {
+        max_tokens
+    };
+    let mut tokens = Vec::with_capacity(extensions.len());
+    for extension in &mut extensions {
+        let clamped = clamp_instructions_cached(
+            &extension.name,
+            &extension.instructions,
+            max_tokens,
+            count_tokens,
+        );
+        if let Some(text) = clamped.text {
+            extension.instructions = text;
+        }
+        tokens.push(clamped.tokens);
+    }
+
+    if max_total_tokens == 0 || tokens.iter().sum::<usize>() <= max_total_tokens {
+        return extensions;
+    }
+    let limits = share_instruction_budget(&tokens, max_total_tokens);
+    for (extension, (limit, tokens)) in extensions.iter_mut().zip(limits.into_iter().zip(tokens)) {
+        if limit < tokens {
+            let clamped = clamp_instructions_cached(
+                &extension.name,
+                &extension.instructions,
+                limit,
+                count_tokens,
+            );
+            if let Some(text) = clamped.text {
+                extension.instructions = text;
+            }
+        }
+    }
+    extensions
+}
+
 pub struct PromptManager {
     system_prompt_override: Option<String>,
     system_prompt_extras: Vec<String>,
@@ -44,6 +235,65 @@ struct SystemPromptContext {
     enable_subagents: bool,
     max_extensions: usize,
     max_tools: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cwd: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git_branch: Option<String>,
+}
+
+/// Values exposed to the prompt for the working directory, each of which can be turned off
+/// in config. The date is only flagged here and read from the clock when the prompt is built.
+#[derive(Default)]
+struct DynamicContext {
+    current_date: bool,
+    cwd: Option<String>,
+    git_branch: Option<String>,
+}
+
+impl DynamicContext {
+    fn collect(working_dir: &Path) -> Self {
+        let config = Config::global();
+        let enabled = |key: &str| config.get_param::<bool>(key).unwrap_or(true);
+
+        DynamicContext {
+            current_date: enabled("GOOSE_PROMPT_CURRENT_DATE"),
+            cwd: enabled("GOOSE_PROMPT_CWD").then(|| working_dir.display().to_string()),
+            git_branch: enabled("GOOSE_PROMPT_GIT_BRANCH")
+                .then(|| current_git_branch(working_dir))
+                .flatten(),
+        }
+    }
+}
+
+fn format_current_date(now: DateTime<Utc>) -> String {
+    now.with_timezone(&Local)
+        .format("%Y-%m-%d (%A)")
+        .to_string()
+}
+
+/// The checked-out branch of the repository containing `dir`, read from its HEAD file.
+/// Returns None outside a repository or when HEAD is detached.
+fn current_git_branch(dir: &Path) -> Option<String> {
+    let dot_git = dir
+        .ancestors()
+        .map(|ancestor| ancestor.join(".git"))
+        .find(|candidate| candidate.exists())?;
+
+    let git_dir = if dot_git.is_file() {
+        // Worktrees and submodules point at their git directory from a `.git` file
+        let pointer = std::fs::read_to_string(&dot_git).ok()?;
+        let target = pointer.trim().strip_prefix("gitdir:")?.trim();
+        dot_git.parent()?.join(target)
+    } else {
+        dot_git
+    };
+
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref: refs/heads/")
+        .map(|branch| branch.to_string())
 }
 
 pub struct SystemPromptBuilder<'a, M> {
@@ -55,6 +305,7 @@ pub struct SystemPromptBuilder<'a, M> {
     extension_tool_count: Option<(usize, usize)>,
     router_enabled: bool,
     hints: Option<String>,
+    dynamic_context: DynamicContext,
 }
 
 impl<'a> SystemPromptBuilder<'a, PromptManager> {
@@ -116,6 +367,14 @@ impl<'a> SystemPromptBuilder<'a, PromptManager> {
         self
     }
 
+    /// Expose the current date, working directory and git branch to the prompt template as
+    /// `current_date`, `cwd` and `git_branch`. Each is on unless `GOOSE_PROMPT_CURRENT_DATE`,
+    /// `GOOSE_PROMPT_CWD` or `GOOSE_PROMPT_GIT_BRANCH` is set to false.
+    pub fn with_dynamic_context(mut self, working_dir: &Path) -> Self {
+        self.dynamic_context = DynamicContext::collect(working_dir);
+        self
+    }
+
     pub fn build(self) -> String {
         let mut extensions_info = self.extensions_info;
 
@@ -155,6 +414,12 @@ impl<'a> SystemPromptBuilder<'a, PromptManager> {
             enable_subagents: should_enabled_subagents(self.model_name.as_str()),
             max_extensions: MAX_EXTENSIONS,
             max_tools: MAX_TOOLS,
+            current_date: self
+                .dynamic_context
+                .current_date
+                .then(|| format_current_date(crate::clock::now())),
+            cwd: self.dynamic_context.cwd,
+            git_branch: self.dynamic_context.git_branch,
         };
 
         let base_prompt = if let Some(override_prompt) = &self.manager.system_prompt_override {
@@ -200,12 +465,13 @@ impl<'a> SystemPromptBuilder<'a, PromptManager> {
 
 impl PromptManager {
     pub fn new() -> Self {
+        let now = crate::clock::now();
         PromptManager {
             system_prompt_override: None,
             system_prompt_extras: Vec::new(),
             // Use the fixed current date time so that prompt cache can be used.
             // Filtering to an hour to balance user time accuracy and multi session prompt cache hits.
-            current_date_timestamp: Utc::now().format("%Y-%m-%d %H:00").to_string(),
+            current_date_timestamp: now.format("%Y-%m-%d %H:00").to_string(),
         }
     }
 
@@ -238,6 +504,7 @@ impl PromptManager {
             extension_tool_count: None,
             router_enabled: false,
             hints: None,
+            dynamic_context: DynamicContext::default(),
         }
     }
 
@@ -251,8 +518,10 @@ impl PromptManager {
 #[cfg(test)]
 mod tests {
     use insta::assert_snapshot;
+    use std::sync::Arc;
 
     use super::*;
+    use crate::clock::{with_clock_sync, Clock, FixedClock};
 
     #[test]
     fn test_build_system_prompt_sanitizes_override() {
@@ -315,6 +584,50 @@ mod tests {
         assert!(result.contains("emojis"));
     }
 
+    #[test]
+    fn test_clamp_instructions_truncates_with_marker() {
+        let count_words = |text: &str| text.split_whitespace().count();
+        let instructions = (0..100)
+            .map(|i| format!("w{}", i))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        assert!(clamp_instructions("short help", 10, count_words).is_none());
+
+        let clamped = clamp_instructions(&instructions, 10, count_words).unwrap();
+        assert_eq!(count_words(&clamped), 10);
+        assert_eq!(
+            clamped,
+            "w0 w1\n[... truncated 98 of 100 tokens of instructions]"
+        );
+    }
+
+    #[test]
+    fn test_share_instruction_budget() {
+        assert_eq!(
+            share_instruction_budget(&[100, 5000, 3000], 4000),
+            vec![100, 1950, 1950]
+        );
+        assert_eq!(share_instruction_budget(&[10, 20], 4000), vec![10, 20]);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_extension_instructions_are_clamped() {
+        let verbose = "Always follow these rules carefully. ".repeat(2000);
+        let extensions = budget_extension_instructions(vec![
+            ExtensionInfo::new("verbose", &verbose, false),
+            ExtensionInfo::new("terse", "Use the search tool.", false),
+        ])
+        .await;
+
+        let counter = create_token_counter().await.unwrap();
+        let clamped = &extensions[0].instructions;
+        assert!(clamped.starts_with("Always follow these rules carefully."));
+        assert!(clamped.contains("[... truncated"));
+        assert!(counter.count_tokens(clamped) <= DEFAULT_MAX_INSTRUCTION_TOKENS);
+        assert_eq!(extensions[1].instructions, "Use the search tool.");
+    }
+
     #[test]
     fn test_build_system_prompt_sanitizes_extension_instructions() {
         let manager = PromptManager::new();
@@ -336,6 +649,86 @@ mod tests {
         assert!(result.contains("hidden instructions"));
     }
 
+    #[test]
+    fn test_dynamic_context_in_system_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        std::fs::write(
+            dir.path().join(".git/HEAD"),
+            "ref: refs/heads/feature/login\n",
+        )
+        .unwrap();
+        let nested = dir.path().join("src");
+        std::fs::create_dir(&nested).unwrap();
+
+        // The manager is created a day before the prompt is rendered, so the date must come
+        // from the clock at build time rather than from the manager's timestamp
+        let created = DateTime::<Utc>::from_timestamp(1_710_504_000, 0).unwrap();
+        let manager = PromptManager::with_timestamp(created);
+        let rendered_at = created + chrono::Duration::days(1);
+        let clock: Arc<dyn Clock> = Arc::new(FixedClock(rendered_at));
+        let today = format_current_date(rendered_at);
+        let yesterday = format_current_date(created);
+
+        let system_prompt = temp_env::with_vars_unset(
+            [
+                "GOOSE_PROMPT_CURRENT_DATE",
+                "GOOSE_PROMPT_CWD",
+                "GOOSE_PROMPT_GIT_BRANCH",
+            ],
+            || {
+                with_clock_sync(clock.clone(), || {
+                    manager
+                        .builder("gpt-4o")
+                        .with_dynamic_context(&nested)
+                        .build()
+                })
+            },
+        );
+        assert!(system_prompt.contains("# Current Context"));
+        assert!(system_prompt.contains(&format!("- Current date: {}", today)));
+        assert!(system_prompt.contains(&format!("- Working directory: {}", nested.display())));
+        assert!(system_prompt.contains("- Git branch: feature/login"));
+        assert!(!system_prompt.contains(&format!("- Current date: {}", yesterday)));
+
+        let system_prompt = temp_env::with_vars(
+            [
+                ("GOOSE_PROMPT_CURRENT_DATE", Some("false")),
+                ("GOOSE_PROMPT_CWD", Some("false")),
+                ("GOOSE_PROMPT_GIT_BRANCH", Some("false")),
+            ],
+            || {
+                manager
+                    .builder("gpt-4o")
+                    .with_dynamic_context(&nested)
+                    .build()
+            },
+        );
+        assert!(!system_prompt.contains("# Current Context"));
+        assert!(!system_prompt.contains(&today));
+    }
+
+    #[test]
+    fn test_current_git_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(current_git_branch(dir.path()), None);
+
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        std::fs::write(dir.path().join(".git/HEAD"), "3f2a9c1e\n").unwrap();
+        assert_eq!(current_git_branch(dir.path()), None);
+
+        let worktree = dir.path().join("worktree");
+        std::fs::create_dir_all(dir.path().join(".git/worktrees/wt")).unwrap();
+        std::fs::create_dir(&worktree).unwrap();
+        std::fs::write(worktree.join(".git"), "gitdir: ../.git/worktrees/wt\n").unwrap();
+        std::fs::write(
+            dir.path().join(".git/worktrees/wt/HEAD"),
+            "ref: refs/heads/main\n",
+        )
+        .unwrap();
+        assert_eq!(current_git_branch(&worktree), Some("main".to_string()));
+    }
+
     #[test]
     fn test_basic() {
         let manager = PromptManager::with_timestamp(DateTime::<Utc>::from_timestamp(0, 0).unwrap());
@@ -9,15 +9,26 @@ use tracing::debug;
 use super::super::agents::Agent;
 use crate::conversation::message::{Message, MessageContent, ToolRequest};
 use crate::conversation::Conversation;
-use crate::providers::base::{stream_from_single_message, MessageStream, Provider, ProviderUsage};
+use crate::providers::base::{
+    stream_from_single_message, CompletionOptions, MessageStream, Provider, ProviderUsage,
+};
 use crate::providers::errors::ProviderError;
 use crate::providers::toolshim::{
     augment_message_with_tool_calls, convert_tool_messages_to_text,
     modify_system_prompt_for_tool_json, OllamaInterpreter,
 };
+use crate::providers::usage_metrics;
+use crate::providers::usage_report::UsageReport;
 
+use crate::agents::prompt_manager::budget_extension_instructions;
 use crate::agents::recipe_tools::dynamic_task_tools::should_enabled_subagents;
-use crate::session::SessionManager;
+use crate::agents::tool_budget::{
+    omitted_tools_note, trim_tools_to_budget, DEFAULT_TOOL_BUDGET_FRACTION,
+    DEFAULT_TOOL_BUDGET_MIN_TOOLS,
+};
+use crate::config::Config;
+use crate::session::{ExtensionState, SessionManager, UsageState};
+use crate::token_counter::create_token_counter;
 use rmcp::model::Tool;
 
 fn coerce_value(s: &str, schema: &Value) -> Value {
@@ -106,7 +117,41 @@ async fn toolshim_postprocess(
         .map_err(|e| ProviderError::ExecutionError(format!("Failed to augment message: {}", e)))
 }
 
+/// Longest tool description sent to the provider when descriptions are compacted.
+const COMPACT_TOOL_DESCRIPTION_CHARS: usize = 200;
+
+/// The first sentence or paragraph of `description`, cut to `max_chars` characters.
+fn compact_description(description: &str, max_chars: usize) -> String {
+    let description = description.trim();
+    let end = [". ", ".\n", "\n\n"]
+        .iter()
+        .filter_map(|sep| description.find(sep).map(|i| i + 1))
+        .min()
+        .unwrap_or(description.len());
+    let summary = description[..end].trim_end();
+    if summary.chars().count() <= max_chars {
+        return summary.to_string();
+    }
+    let cut: String = summary.chars().take(max_chars).collect();
+    format!("{}...", cut.trim_end())
+}
+
+fn compact_tool_descriptions(tools: &mut [Tool]) {
+    for tool in tools {
+        if let Some(description) = &tool.description {
+            let compact = compact_description(description, COMPACT_TOOL_DESCRIPTION_CHARS);
+            tool.description = Some(compact.into());
+        }
+    }
+}
+
 impl Agent {
+    /// The fully rendered system prompt the next reply in `working_dir` would be sent with
+    pub async fn get_system_prompt(&self, working_dir: &std::path::Path) -> Result<String> {
+        let (_, _, system_prompt) = self.prepare_tools_and_prompt(working_dir).await?;
+        Ok(system_prompt)
+    }
+
     pub async fn prepare_tools_and_prompt(
         &self,
         working_dir: &std::path::Path,
@@ -138,12 +183,20 @@ impl Agent {
         }
 
         if !router_enabled {
-            // Stable tool ordering is important for multi session prompt caching.
-            tools.sort_by(|a, b| a.name.cmp(&b.name));
+            // Stable tool ordering is important for multi session prompt caching. Extensions
+            // in the configured order come first.
+            self.extension_manager.sort_tools(&mut tools).await;
         }
 
+        if self.compact_tool_descriptions_enabled() {
+            compact_tool_descriptions(&mut tools);
+        }
+
+        let omitted_tools = self.apply_tool_budget(&mut tools).await?;
+
         // Prepare system prompt
-        let extensions_info = self.extension_manager.get_extensions_info().await;
+        let extensions_info =
+            budget_extension_instructions(self.extension_manager.get_extensions_info().await).await;
         let (extension_count, tool_count) =
             self.extension_manager.get_extension_and_tool_counts().await;
 
@@ -160,8 +213,14 @@ impl Agent {
             .with_extension_and_tool_counts(extension_count, tool_count)
             .with_router_enabled(router_enabled)
             .with_hints(working_dir)
+            .with_dynamic_context(working_dir)
             .build();
 
+        if !omitted_tools.is_empty() {
+            system_prompt.push_str("\n\n");
+            system_prompt.push_str(&omitted_tools_note(&omitted_tools));
+        }
+
         // Handle toolshim if enabled
         let mut toolshim_tools = vec![];
         if model_config.toolshim {
@@ -176,14 +235,77 @@ impl Agent {
         Ok((tools, toolshim_tools, system_prompt))
     }
 
+    /// Whether to use the non-streaming completion path, set by the agent or `GOOSE_NO_STREAM`.
+    pub(crate) fn no_stream_enabled(&self) -> bool {
+        self.no_stream.load(std::sync::atomic::Ordering::Relaxed)
+            || Config::global()
+                .get_param::<bool>("GOOSE_NO_STREAM")
+                .unwrap_or(false)
+    }
+
+    fn compact_tool_descriptions_enabled(&self) -> bool {
+        self.compact_tool_descriptions
+            .load(std::sync::atomic::Ordering::Relaxed)
+            || Config::global()
+                .get_param::<bool>("GOOSE_COMPACT_TOOL_DESCRIPTIONS")
+                .unwrap_or(false)
+    }
+
+    /// Keep tool definitions within `GOOSE_TOOL_BUDGET_FRACTION` of the context window by
+    /// dropping least-recently-used tools, returning the names of those dropped.
+    async fn apply_tool_budget(&self, tools: &mut Vec<Tool>) -> Result<Vec<String>> {
+        let config = Config::global();
+        let fraction: f64 = config
+            .get_param("GOOSE_TOOL_BUDGET_FRACTION")
+            .unwrap_or(DEFAULT_TOOL_BUDGET_FRACTION);
+        let min_tools: usize = config
+            .get_param("GOOSE_TOOL_BUDGET_MIN_TOOLS")
+            .unwrap_or(DEFAULT_TOOL_BUDGET_MIN_TOOLS);
+        if tools.len() <= min_tools || !(0.0..1.0).contains(&fraction) {
+            return Ok(Vec::new());
+        }
+
+        let token_counter = match create_token_counter().await {
+            Ok(counter) => counter,
+            Err(e) => {
+                tracing::warn!("Skipping tool budget check: {}", e);
+                return Ok(Vec::new());
+            }
+        };
+        let context_limit = self.provider().await?.get_model_config().context_limit();
+        let budget_tokens = (context_limit as f64 * fraction) as usize;
+        let last_used = self.tool_route_manager.tool_last_used().await;
+
+        let (kept, omitted) = trim_tools_to_budget(
+            std::mem::take(tools),
+            &last_used,
+            budget_tokens,
+            min_tools,
+            |tool| token_counter.count_tokens_for_tools(std::slice::from_ref(tool)),
+        );
+        *tools = kept;
+
+        if !omitted.is_empty() {
+            tracing::info!(
+                "Omitted {} tools to fit tool definitions in {} tokens",
+                omitted.len(),
+                budget_tokens
+            );
+        }
+        Ok(omitted)
+    }
+
     /// Stream a response from the LLM provider.
-    /// Handles toolshim transformations if needed
+    /// Handles toolshim transformations if needed. `options` reach providers that don't
+    /// stream, or every provider when `options.no_stream` is set; streaming providers use
+    /// their own settings.
     pub(crate) async fn stream_response_from_provider(
         provider: Arc<dyn Provider>,
         system_prompt: &str,
         messages: &[Message],
         tools: &[Tool],
         toolshim_tools: &[Tool],
+        options: &CompletionOptions,
     ) -> Result<MessageStream, ProviderError> {
         let config = provider.get_model_config();
 
@@ -202,7 +324,7 @@ impl Agent {
 
         // Capture errors during stream creation and return them as part of the stream
         // so they can be handled by the existing error handling logic in the agent
-        let stream_result = if provider.supports_streaming() {
+        let stream_result = if provider.supports_streaming() && !options.no_stream {
             debug!("WAITING_LLM_STREAM_START");
             let result = provider
                 .stream(
@@ -216,10 +338,11 @@ impl Agent {
         } else {
             debug!("WAITING_LLM_START");
             let complete_result = provider
-                .complete(
+                .complete_with_options(
                     system_prompt.as_str(),
                     messages_for_provider.messages(),
                     &tools,
+                    options,
                 )
                 .await;
             debug!("WAITING_LLM_END");
@@ -244,6 +367,7 @@ impl Agent {
 
         Ok(Box::pin(try_stream! {
             while let Some(Ok((mut message, usage))) = stream.next().await {
+                let usage = usage.map(|usage| usage.with_provider(provider.get_name()));
                 // Store the model information in the global store
                 if let Some(usage) = usage.as_ref() {
                     crate::providers::base::set_current_model(&usage.model);
@@ -343,10 +467,14 @@ impl Agent {
     }
 
     pub(crate) async fn update_session_metrics(
+        &self,
         session_config: &crate::agents::types::SessionConfig,
         usage: &ProviderUsage,
         is_compaction_usage: bool,
     ) -> Result<()> {
+        self.usage_report.lock().await.record(usage);
+        usage_metrics::record(usage).await;
+
         let session_id = session_config.id.as_str();
         let session = SessionManager::get_session(session_id, false).await?;
 
@@ -364,6 +492,16 @@ impl Agent {
         let accumulated_output =
             accumulate(session.accumulated_output_tokens, usage.usage.output_tokens);
 
+        // Keep the per-model breakdown with the session so resuming it restores the totals
+        let mut extension_data = session.extension_data;
+        let mut saved_usage = UsageReport::from_usage(
+            &UsageState::from_extension_data(&extension_data)
+                .unwrap_or_default()
+                .usage,
+        );
+        saved_usage.record(usage);
+        UsageState::new(saved_usage.to_usage()).to_extension_data(&mut extension_data)?;
+
         let (current_total, current_input, current_output) = if is_compaction_usage {
             // After compaction: summary output becomes new input context
             let new_input = usage.usage.output_tokens;
@@ -384,6 +522,7 @@ impl Agent {
             .accumulated_total_tokens(accumulated_total)
             .accumulated_input_tokens(accumulated_input)
             .accumulated_output_tokens(accumulated_output)
+            .extension_data(extension_data)
             .apply()
             .await?;
 
@@ -489,4 +628,58 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_compact_description_keeps_first_sentence() {
+        assert_eq!(
+            compact_description("Reads a file. Supports ranges and globs.", 200),
+            "Reads a file."
+        );
+        assert_eq!(
+            compact_description("List tools\n\nLonger explanation", 200),
+            "List tools"
+        );
+        assert_eq!(compact_description("abcdefghij", 4), "abcd...");
+    }
+
+    #[tokio::test]
+    async fn test_compact_tool_descriptions_only_in_outgoing_tools() -> anyhow::Result<()> {
+        let agent = crate::agents::Agent::new();
+        let model_config = ModelConfig::new("test-model").unwrap();
+        agent
+            .update_provider(std::sync::Arc::new(MockProvider { model_config }))
+            .await?;
+        agent.disable_router_for_recipe().await;
+        agent.set_compact_tool_descriptions(true);
+
+        let full = "Search the docs. Matches titles first, then bodies, and returns links.";
+        agent
+            .add_extension(crate::agents::extension::ExtensionConfig::Frontend {
+                name: "frontend".to_string(),
+                description: "desc".to_string(),
+                tools: vec![Tool::new(
+                    "frontend__search".to_string(),
+                    full.to_string(),
+                    object!({ "type": "object", "properties": { } }),
+                )],
+                instructions: None,
+                bundled: None,
+                available_tools: vec![],
+            })
+            .await
+            .unwrap();
+
+        let working_dir = std::env::current_dir()?;
+        let (tools, _toolshim_tools, _system_prompt) =
+            agent.prepare_tools_and_prompt(&working_dir).await?;
+        let sent = tools.iter().find(|t| t.name == "frontend__search").unwrap();
+        assert_eq!(sent.description.as_deref(), Some("Search the docs."));
+
+        let local = agent.frontend_tools.lock().await;
+        assert_eq!(
+            local["frontend__search"].tool.description.as_deref(),
+            Some(full)
+        );
+        Ok(())
+    }
 }
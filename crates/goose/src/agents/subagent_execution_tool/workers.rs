@@ -15,7 +15,7 @@ pub fn spawn_worker(
 ) -> tokio::task::JoinHandle<()> {
     state.increment_active_workers();
 
-    tokio::spawn(async move {
+    crate::clock::spawn(async move {
         worker_loop(state, worker_id, task_config).await;
     })
 }
@@ -116,6 +116,7 @@ fn get_agent_messages(
             .await
             .map_err(|e| anyhow!("Failed to get sub agent session file path: {}", e))?;
 
+        agent.set_depth(task_config.depth);
         agent
             .update_provider(task_config.provider)
             .await
@@ -149,6 +150,8 @@ fn get_agent_messages(
             schedule_id: None,
             max_turns: task_config.max_turns.map(|v| v as u32),
             retry_config: recipe.retry,
+            compact_threshold: None,
+            turn_budget: None,
         };
 
         let mut stream = crate::session_context::with_session_id(Some(session_id.clone()), async {
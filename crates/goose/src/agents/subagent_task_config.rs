@@ -11,6 +11,31 @@ pub const DEFAULT_SUBAGENT_MAX_TURNS: usize = 25;
 /// Environment variable name for configuring max turns
 pub const GOOSE_SUBAGENT_MAX_TURNS_ENV_VAR: &str = "GOOSE_SUBAGENT_MAX_TURNS";
 
+/// Default maximum nesting of agents started by other agents' tools
+pub const DEFAULT_MAX_AGENT_DEPTH: usize = 3;
+
+/// Environment variable name for configuring the maximum agent depth
+pub const GOOSE_MAX_AGENT_DEPTH_ENV_VAR: &str = "GOOSE_MAX_AGENT_DEPTH";
+
+/// Environment variable carrying the depth of the agent that launched this process, passed to
+/// extension processes so a goose started from a tool knows how deep it is
+pub const GOOSE_AGENT_DEPTH_ENV_VAR: &str = "GOOSE_AGENT_DEPTH";
+
+/// How many agents deep this process is; 0 for a goose started directly by a user
+pub fn process_agent_depth() -> usize {
+    env::var(GOOSE_AGENT_DEPTH_ENV_VAR)
+        .ok()
+        .and_then(|val| val.parse::<usize>().ok())
+        .unwrap_or(0)
+}
+
+pub fn max_agent_depth() -> usize {
+    env::var(GOOSE_MAX_AGENT_DEPTH_ENV_VAR)
+        .ok()
+        .and_then(|val| val.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_AGENT_DEPTH)
+}
+
 /// Configuration for task execution with all necessary dependencies
 #[derive(Clone)]
 pub struct TaskConfig {
@@ -19,6 +44,8 @@ pub struct TaskConfig {
     pub parent_working_dir: PathBuf,
     pub extensions: Vec<ExtensionConfig>,
     pub max_turns: Option<usize>,
+    /// Depth of the sub-agent that runs the task
+    pub depth: usize,
 }
 
 impl fmt::Debug for TaskConfig {
@@ -28,6 +55,7 @@ impl fmt::Debug for TaskConfig {
             .field("parent_session_id", &self.parent_session_id)
             .field("parent_working_dir", &self.parent_working_dir)
             .field("max_turns", &self.max_turns)
+            .field("depth", &self.depth)
             .field("extensions", &self.extensions)
             .finish()
     }
@@ -51,6 +79,12 @@ impl TaskConfig {
                     .and_then(|val| val.parse::<usize>().ok())
                     .unwrap_or(DEFAULT_SUBAGENT_MAX_TURNS),
             ),
+            depth: process_agent_depth() + 1,
         }
     }
+
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
 }
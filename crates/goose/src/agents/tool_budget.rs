@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use rmcp::model::Tool;
+
+/// Default share of the context window tool definitions may occupy
+pub const DEFAULT_TOOL_BUDGET_FRACTION: f64 = 0.5;
+/// Default number of tools always kept, regardless of budget
+pub const DEFAULT_TOOL_BUDGET_MIN_TOOLS: usize = 10;
+
+/// Tracks when each tool was last requested, as a monotonically increasing sequence.
+#[derive(Debug, Default)]
+pub struct ToolUsageTracker {
+    seq: u64,
+    last_used: HashMap<String, u64>,
+}
+
+impl ToolUsageTracker {
+    pub fn record(&mut self, tool_name: &str) {
+        self.seq += 1;
+        self.last_used.insert(tool_name.to_string(), self.seq);
+    }
+
+    pub fn last_used(&self) -> &HashMap<String, u64> {
+        &self.last_used
+    }
+}
+
+/// Drop least-recently-used tools until the remaining definitions fit in `budget_tokens`.
+///
+/// Tools that were never used go first, then the oldest used; `min_tools` are always kept.
+/// Returns the kept tools in their original order and the names of the omitted ones.
+pub fn trim_tools_to_budget<F>(
+    tools: Vec<Tool>,
+    last_used: &HashMap<String, u64>,
+    budget_tokens: usize,
+    min_tools: usize,
+    count_tokens: F,
+) -> (Vec<Tool>, Vec<String>)
+where
+    F: Fn(&Tool) -> usize,
+{
+    let costs: Vec<usize> = tools.iter().map(&count_tokens).collect();
+    let mut total: usize = costs.iter().sum();
+    if total <= budget_tokens {
+        return (tools, Vec::new());
+    }
+
+    let mut eviction_order: Vec<usize> = (0..tools.len()).collect();
+    eviction_order.sort_by(|&a, &b| {
+        let used_a = last_used.get(tools[a].name.as_ref()).copied().unwrap_or(0);
+        let used_b = last_used.get(tools[b].name.as_ref()).copied().unwrap_or(0);
+        used_a
+            .cmp(&used_b)
+            .then_with(|| tools[a].name.cmp(&tools[b].name))
+    });
+
+    let mut omit = vec![false; tools.len()];
+    let mut remaining = tools.len();
+    for idx in eviction_order {
+        if total <= budget_tokens || remaining <= min_tools {
+            break;
+        }
+        omit[idx] = true;
+        total -= costs[idx];
+        remaining -= 1;
+    }
+
+    let mut kept = Vec::with_capacity(remaining);
+    let mut omitted = Vec::new();
+    for (tool, omitted_flag) in tools.into_iter().zip(omit) {
+        if omitted_flag {
+            omitted.push(tool.name.to_string());
+        } else {
+            kept.push(tool);
+        }
+    }
+    (kept, omitted)
+}
+
+/// System prompt note telling the model which tools were left out.
+pub fn omitted_tools_note(omitted: &[String]) -> String {
+    format!(
+        "Note: {} tool(s) were omitted to keep tool definitions within the context budget: {}. \
+        Ask the user to disable unused extensions if you need one of them.",
+        omitted.len(),
+        omitted.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::object;
+    use std::sync::Arc;
+
+    fn large_tool(name: &str) -> Tool {
+        let properties: serde_json::Map<String, serde_json::Value> = (0..50)
+            .map(|i| {
+                (
+                    format!("field_{i}"),
+                    serde_json::json!({"type": "string", "description": "a".repeat(40)}),
+                )
+            })
+            .collect();
+        Tool::new(
+            name.to_string(),
+            "A tool with a large schema".to_string(),
+            Arc::new(object!({"type": "object", "properties": properties})),
+        )
+    }
+
+    fn schema_len(tool: &Tool) -> usize {
+        serde_json::to_string(&tool.input_schema).unwrap().len()
+    }
+
+    #[test]
+    fn test_trims_least_recently_used_tools() {
+        let tools: Vec<Tool> = (0..20)
+            .map(|i| large_tool(&format!("tool_{i:02}")))
+            .collect();
+        let per_tool = schema_len(&tools[0]);
+
+        let mut usage = ToolUsageTracker::default();
+        usage.record("tool_05");
+        usage.record("tool_00");
+
+        let (kept, omitted) =
+            trim_tools_to_budget(tools, usage.last_used(), per_tool * 4, 2, schema_len);
+
+        assert_eq!(kept.len(), 4);
+        assert_eq!(omitted.len(), 16);
+        let kept_names: Vec<&str> = kept.iter().map(|t| t.name.as_ref()).collect();
+        assert!(kept_names.contains(&"tool_00"));
+        assert!(kept_names.contains(&"tool_05"));
+        assert!(omitted_tools_note(&omitted).starts_with("Note: 16 tool(s) were omitted"));
+    }
+
+    #[test]
+    fn test_keeps_minimum_and_untouched_when_under_budget() {
+        let tools: Vec<Tool> = (0..20)
+            .map(|i| large_tool(&format!("tool_{i:02}")))
+            .collect();
+
+        let (kept, omitted) =
+            trim_tools_to_budget(tools.clone(), &HashMap::new(), 1, 5, schema_len);
+        assert_eq!(kept.len(), 5);
+        assert_eq!(omitted.len(), 15);
+
+        let (kept, omitted) =
+            trim_tools_to_budget(tools, &HashMap::new(), usize::MAX, 5, schema_len);
+        assert_eq!(kept.len(), 20);
+        assert!(omitted.is_empty());
+    }
+}
@@ -30,7 +30,7 @@ impl From<ToolResult<Vec<Content>>> for ToolCallResult {
 
 use super::agent::{tool_stream, ToolStream};
 use crate::agents::Agent;
-use crate::conversation::message::{Message, ToolRequest};
+use crate::conversation::message::{Message, MessageContent, ToolRequest};
 use crate::session::Session;
 use crate::tool_inspection::get_security_finding_id_from_results;
 
@@ -47,6 +47,51 @@ pub const CHAT_MODE_TOOL_SKIPPED_RESPONSE: &str = "Let the user know the tool ca
                                         2. **Outline Steps** - Break down the steps.\n \
                                         If needed, adjust the explanation based on user preferences or questions.";
 
+/// Split off requests that repeat an earlier request's tool and arguments in the same turn.
+/// Returns the requests to dispatch and, for each duplicate, its id and the id it repeats.
+pub(crate) fn dedupe_tool_requests(
+    requests: Vec<ToolRequest>,
+) -> (Vec<ToolRequest>, Vec<(String, String)>) {
+    let mut unique: Vec<ToolRequest> = Vec::new();
+    let mut duplicates = Vec::new();
+    for request in requests {
+        let original_id = request.tool_call.as_ref().ok().and_then(|call| {
+            unique
+                .iter()
+                .find(|seen| {
+                    seen.tool_call.as_ref().is_ok_and(|seen_call| {
+                        seen_call.name == call.name && seen_call.arguments == call.arguments
+                    })
+                })
+                .map(|seen| seen.id.clone())
+        });
+        match original_id {
+            Some(original_id) => duplicates.push((request.id, original_id)),
+            None => unique.push(request),
+        }
+    }
+    (unique, duplicates)
+}
+
+/// Answer each duplicate request with the response already recorded for the request it repeats.
+pub(crate) fn with_duplicate_tool_responses(
+    mut message: Message,
+    duplicates: &[(String, String)],
+) -> Message {
+    for (duplicate_id, original_id) in duplicates {
+        let result = message.content.iter().find_map(|content| match content {
+            MessageContent::ToolResponse(response) if &response.id == original_id => {
+                Some(response.tool_result.clone())
+            }
+            _ => None,
+        });
+        if let Some(result) = result {
+            message = message.with_tool_response(duplicate_id.clone(), result);
+        }
+    }
+    message
+}
+
 impl Agent {
     pub(crate) fn handle_approval_tool_requests<'a>(
         &'a self,
@@ -155,3 +200,50 @@ impl Agent {
         .boxed()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::CallToolRequestParam;
+    use rmcp::object;
+
+    fn request(id: &str, path: &str) -> ToolRequest {
+        ToolRequest {
+            id: id.to_string(),
+            tool_call: Ok(CallToolRequestParam {
+                name: "developer__read".into(),
+                arguments: Some(object!({ "path": path })),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_identical_tool_requests_dispatch_once() {
+        let (unique, duplicates) = dedupe_tool_requests(vec![
+            request("a", "README.md"),
+            request("b", "Cargo.toml"),
+            request("c", "README.md"),
+        ]);
+
+        let ids: Vec<&str> = unique.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+        assert_eq!(duplicates, vec![("c".to_string(), "a".to_string())]);
+
+        let message = Message::user()
+            .with_tool_response("a", Ok(vec![Content::text("readme")]))
+            .with_tool_response("b", Ok(vec![Content::text("manifest")]));
+        let message = with_duplicate_tool_responses(message, &duplicates);
+
+        let duplicate = message
+            .content
+            .iter()
+            .find_map(|content| match content {
+                MessageContent::ToolResponse(response) if response.id == "c" => {
+                    Some(response.tool_result.clone())
+                }
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(duplicate.unwrap(), vec![Content::text("readme")]);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::agents::extension_manager::ExtensionManager;
 use crate::agents::router_tool_selector::{create_tool_selector, RouterToolSelector};
 use crate::agents::router_tools::{self};
+use crate::agents::tool_budget::ToolUsageTracker;
 use crate::agents::tool_execution::ToolCallResult;
 use crate::agents::tool_router_index_manager::ToolRouterIndexManager;
 use crate::config::Config;
@@ -8,6 +9,7 @@ use crate::conversation::message::ToolRequest;
 use crate::providers::base::Provider;
 use anyhow::{anyhow, Result};
 use rmcp::model::{ErrorCode, ErrorData, JsonObject, Tool};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::error;
@@ -15,6 +17,7 @@ use tracing::error;
 pub struct ToolRouteManager {
     router_tool_selector: Mutex<Option<Arc<Box<dyn RouterToolSelector>>>>,
     router_disabled_override: Mutex<bool>,
+    tool_usage: Mutex<ToolUsageTracker>,
 }
 
 impl Default for ToolRouteManager {
@@ -28,6 +31,7 @@ impl ToolRouteManager {
         Self {
             router_tool_selector: Mutex::new(None),
             router_disabled_override: Mutex::new(false),
+            tool_usage: Mutex::new(ToolUsageTracker::default()),
         }
     }
 
@@ -37,6 +41,15 @@ impl ToolRouteManager {
     }
 
     pub async fn record_tool_requests(&self, requests: &[ToolRequest]) {
+        {
+            let mut usage = self.tool_usage.lock().await;
+            for request in requests {
+                if let Ok(tool_call) = &request.tool_call {
+                    usage.record(&tool_call.name);
+                }
+            }
+        }
+
         let selector = self.router_tool_selector.lock().await.clone();
         if let Some(selector) = selector {
             for request in requests {
@@ -49,6 +62,10 @@ impl ToolRouteManager {
         }
     }
 
+    pub async fn tool_last_used(&self) -> HashMap<String, u64> {
+        self.tool_usage.lock().await.last_used().clone()
+    }
+
     pub async fn dispatch_route_search_tool(
         &self,
         arguments: JsonObject,
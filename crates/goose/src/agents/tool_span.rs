@@ -0,0 +1,180 @@
+use rmcp::model::{Content, ErrorData, JsonObject};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::Span;
+
+use super::audit_log::{redact_arguments, redacted_argument_names};
+use crate::config::Config;
+
+const DEFAULT_MAX_ARGUMENT_BYTES: usize = 2048;
+
+/// Records tool call arguments and a result hash on the `dispatch_tool_call` span so they
+/// reach Langfuse/OTLP. Arguments are redacted like the audit log and capped at
+/// `GOOSE_TRACE_TOOL_ARGUMENTS_MAX_BYTES`; set `GOOSE_TRACE_TOOL_ARGUMENTS=false` to turn
+/// this off entirely.
+#[derive(Debug, Clone)]
+pub(crate) struct ToolSpanFields {
+    max_bytes: usize,
+    redacted_arguments: Vec<String>,
+}
+
+impl ToolSpanFields {
+    pub fn new(max_bytes: usize, redacted_arguments: Vec<String>) -> Self {
+        Self {
+            max_bytes,
+            redacted_arguments,
+        }
+    }
+
+    pub fn from_config() -> Option<Self> {
+        let config = Config::global();
+        if !config
+            .get_param::<bool>("GOOSE_TRACE_TOOL_ARGUMENTS")
+            .unwrap_or(true)
+        {
+            return None;
+        }
+        let max_bytes = config
+            .get_param("GOOSE_TRACE_TOOL_ARGUMENTS_MAX_BYTES")
+            .unwrap_or(DEFAULT_MAX_ARGUMENT_BYTES);
+        Some(Self::new(max_bytes, redacted_argument_names()))
+    }
+
+    /// Redacted arguments as JSON. Anything over the cap is cut at a char boundary and
+    /// tagged with the length and hash of the full text for correlation.
+    pub fn arguments(&self, arguments: Option<&JsonObject>) -> String {
+        let redacted = arguments
+            .map(|args| redact_arguments(&Value::Object(args.clone()), &self.redacted_arguments))
+            .unwrap_or(Value::Null);
+        let text = redacted.to_string();
+        if text.len() <= self.max_bytes {
+            return text;
+        }
+
+        let mut end = self.max_bytes;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!(
+            "{}... [truncated {} bytes, {}]",
+            &text[..end],
+            text.len(),
+            sha256(text.as_bytes())
+        )
+    }
+
+    pub fn record_arguments(&self, span: &Span, arguments: Option<&JsonObject>) {
+        span.record("input", self.arguments(arguments).as_str());
+    }
+
+    pub fn record_result(&self, span: &Span, result: &Result<Vec<Content>, ErrorData>) {
+        span.record("output", result_hash(result).as_str());
+    }
+}
+
+/// Hash of a tool result, so identical results can be matched across traces
+pub(crate) fn result_hash(result: &Result<Vec<Content>, ErrorData>) -> String {
+    let serialized = match result {
+        Ok(content) => serde_json::to_vec(content),
+        Err(error) => serde_json::to_vec(error),
+    }
+    .unwrap_or_default();
+    sha256(&serialized)
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::Layer;
+
+    #[derive(Default)]
+    struct Recorded(Vec<(String, String)>);
+
+    impl Visit for Recorded {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.push((field.name().to_string(), value.to_string()));
+        }
+
+        fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+    }
+
+    struct CaptureLayer(Arc<Mutex<Recorded>>);
+
+    impl<S: tracing::Subscriber> Layer<S> for CaptureLayer {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            attrs.record(&mut *self.0.lock().unwrap());
+        }
+
+        fn on_record(&self, _id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+            values.record(&mut *self.0.lock().unwrap());
+        }
+    }
+
+    fn object(value: Value) -> JsonObject {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_arguments_redacted_and_capped() {
+        let fields = ToolSpanFields::new(64, vec!["token".to_string()]);
+
+        let small = object(json!({"command": "ls", "token": "hunter2"}));
+        let text = fields.arguments(Some(&small));
+        assert_eq!(text, r#"{"command":"ls","token":"[REDACTED]"}"#);
+
+        let large = object(json!({"content": "é".repeat(100)}));
+        let text = fields.arguments(Some(&large));
+        let (prefix, suffix) = text.split_once("... [truncated ").unwrap();
+        assert!(prefix.len() <= 64);
+        assert!(suffix.starts_with("214 bytes, sha256:"));
+        assert_eq!(text, fields.arguments(Some(&large)));
+
+        assert_eq!(fields.arguments(None), "null");
+    }
+
+    #[test]
+    fn test_span_carries_capped_arguments_and_result_hash() {
+        let recorded = Arc::new(Mutex::new(Recorded::default()));
+        let subscriber = tracing_subscriber::registry().with(CaptureLayer(recorded.clone()));
+        let fields = ToolSpanFields::new(32, vec!["api_key".to_string()]);
+        let arguments = object(json!({"api_key": "sk-123", "query": "x".repeat(100)}));
+        let result: Result<Vec<Content>, ErrorData> = Ok(vec![Content::text("done")]);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!(
+                "dispatch_tool_call",
+                input = tracing::field::Empty,
+                output = tracing::field::Empty
+            );
+            fields.record_arguments(&span, Some(&arguments));
+            fields.record_result(&span, &result);
+        });
+
+        let recorded = recorded.lock().unwrap();
+        let input = &recorded
+            .0
+            .iter()
+            .find(|(name, _)| name == "input")
+            .unwrap()
+            .1;
+        assert!(input.starts_with(r#"{"api_key":"[REDACTED]""#));
+        assert!(input.contains("... [truncated"));
+        assert!(!input.contains("sk-123"));
+        let output = &recorded
+            .0
+            .iter()
+            .find(|(name, _)| name == "output")
+            .unwrap()
+            .1;
+        assert_eq!(output, &result_hash(&result));
+    }
+}
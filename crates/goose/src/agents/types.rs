@@ -92,4 +92,12 @@ pub struct SessionConfig {
     /// Retry configuration for automated validation and recovery
     #[serde(skip_serializing_if = "Option::is_none")]
     pub retry_config: Option<RetryConfig>,
+    /// Context usage ratio that triggers auto-compaction before a reply, overriding
+    /// GOOSE_AUTO_COMPACT_THRESHOLD for this session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compact_threshold: Option<f64>,
+    /// Retries allowed per turn across provider requests and the agent's own recovery,
+    /// overriding GOOSE_TURN_RETRY_BUDGET for this session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turn_budget: Option<usize>,
 }
@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tokio::task_local;
+
+/// Source of the current time for timestamps goose generates (message `created`, session
+/// ids, trace events). Defaults to the system clock; tests can swap in a [`FixedClock`] with
+/// [`with_clock`] to get reproducible output.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that always reports the same instant
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl FixedClock {
+    pub fn from_timestamp(secs: i64) -> Self {
+        Self(DateTime::from_timestamp(secs, 0).expect("timestamp out of range"))
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+task_local! {
+    static CLOCK: Arc<dyn Clock>;
+}
+
+/// Run `f` with `clock` as the source of [`now`]
+pub async fn with_clock<F>(clock: Arc<dyn Clock>, f: F) -> F::Output
+where
+    F: std::future::Future,
+{
+    CLOCK.scope(clock, f).await
+}
+
+/// Synchronous variant of [`with_clock`]
+pub fn with_clock_sync<R>(clock: Arc<dyn Clock>, f: impl FnOnce() -> R) -> R {
+    CLOCK.sync_scope(clock, f)
+}
+
+/// Spawn `future` as a task that keeps the clock in scope, since task-locals do not carry
+/// over into spawned tasks
+pub fn spawn<F>(future: F) -> tokio::task::JoinHandle<F::Output>
+where
+    F: std::future::Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    match CLOCK.try_with(Arc::clone) {
+        Ok(clock) => tokio::spawn(CLOCK.scope(clock, future)),
+        Err(_) => tokio::spawn(future),
+    }
+}
+
+/// The current time from the clock in scope, or the system clock outside any scope
+pub fn now() -> DateTime<Utc> {
+    CLOCK
+        .try_with(|clock| clock.now())
+        .unwrap_or_else(|_| SystemClock.now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::message::Message;
+
+    #[test]
+    fn test_now_defaults_to_system_clock() {
+        let before = Utc::now();
+        let now = now();
+        assert!(now >= before && now <= Utc::now());
+    }
+
+    #[tokio::test]
+    async fn test_fixed_clock_makes_messages_deterministic() {
+        let clock: Arc<dyn Clock> = Arc::new(FixedClock::from_timestamp(1_700_000_000));
+
+        let render = || async {
+            let message = Message::user().with_text("hello");
+            tokio::task::yield_now().await;
+            let reply = Message::assistant().with_text("hi");
+            serde_json::to_string(&[message, reply]).unwrap()
+        };
+
+        let first = with_clock(clock.clone(), render()).await;
+        let second = with_clock(clock.clone(), render()).await;
+        assert_eq!(first, second);
+        assert_eq!(first.matches("\"created\":1700000000").count(), 2);
+
+        let sync = with_clock_sync(clock, || Message::user().created);
+        assert_eq!(sync, 1_700_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_spawned_tasks_keep_the_clock() {
+        let clock: Arc<dyn Clock> = Arc::new(FixedClock::from_timestamp(1_700_000_000));
+
+        let created = with_clock(clock, async {
+            spawn(async { Message::user().created }).await.unwrap()
+        })
+        .await;
+        assert_eq!(created, 1_700_000_000);
+    }
+}
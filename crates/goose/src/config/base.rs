@@ -12,12 +12,16 @@ use std::ffi::OsString;
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
+use std::time::Duration;
 use thiserror::Error;
 
 const KEYRING_SERVICE: &str = "goose";
 const KEYRING_USERNAME: &str = "secrets";
 pub const CONFIG_YAML_NAME: &str = "config.yaml";
+const KEYRING_MAX_ATTEMPTS: u32 = 3;
+const KEYRING_RETRY_DELAY: Duration = Duration::from_millis(50);
 
 #[cfg(test)]
 const TEST_KEYRING_SERVICE: &str = "goose-test";
@@ -36,6 +40,8 @@ pub enum ConfigError {
     KeyringError(String),
     #[error("Failed to lock config file: {0}")]
     LockError(String),
+    #[error("Configuration is taken from environment variables only; can't store {0}")]
+    EnvOnly(String),
 }
 
 impl From<serde_json::Error> for ConfigError {
@@ -56,6 +62,47 @@ impl From<keyring::Error> for ConfigError {
     }
 }
 
+/// Errors worth retrying, e.g. a locked macOS keychain. A missing entry is not one of them.
+fn is_transient_keyring_error(err: &keyring::Error) -> bool {
+    matches!(
+        err,
+        keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_)
+    )
+}
+
+/// Run a keyring operation, retrying a couple of times on transient access errors.
+///
+/// The wait between attempts blocks the calling thread, as the keyring calls themselves do.
+/// Secrets are read through synchronous getters that async code calls directly, so the delay
+/// is kept short enough (at most 100ms in total) not to stall a runtime worker noticeably.
+fn with_keyring_retry<T>(mut op: impl FnMut() -> keyring::Result<T>) -> keyring::Result<T> {
+    let mut attempt = 1;
+    loop {
+        match op() {
+            Err(e) if attempt < KEYRING_MAX_ATTEMPTS && is_transient_keyring_error(&e) => {
+                tracing::debug!(
+                    "Keyring access failed (attempt {}/{}), retrying: {}",
+                    attempt,
+                    KEYRING_MAX_ATTEMPTS,
+                    e
+                );
+                std::thread::sleep(KEYRING_RETRY_DELAY);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// The keyring entry holding all of goose's secrets for `service`
+fn keyring_entry(service: &str) -> keyring::Result<Entry> {
+    #[cfg(test)]
+    if let Some(credential) = tests::mock_keyring_credential(service) {
+        return Ok(Entry::new_with_credential(credential));
+    }
+    Entry::new(service, KEYRING_USERNAME)
+}
+
 /// Configuration management for goose.
 ///
 /// This module provides a flexible configuration system that supports:
@@ -106,6 +153,7 @@ pub struct Config {
     config_path: PathBuf,
     secrets: SecretStorage,
     guard: Mutex<()>,
+    env_only: AtomicBool,
 }
 
 enum SecretStorage {
@@ -134,6 +182,7 @@ impl Default for Config {
             config_path,
             secrets,
             guard: Mutex::new(()),
+            env_only: AtomicBool::new(false),
         }
     }
 }
@@ -237,6 +286,7 @@ impl Config {
                 service: service.to_string(),
             },
             guard: Mutex::new(()),
+            env_only: AtomicBool::new(false),
         })
     }
 
@@ -254,6 +304,7 @@ impl Config {
                 path: secrets_path.as_ref().to_path_buf(),
             },
             guard: Mutex::new(()),
+            env_only: AtomicBool::new(false),
         })
     }
 
@@ -261,6 +312,25 @@ impl Config {
         self.config_path.exists()
     }
 
+    /// Take all values and secrets from environment variables only. The config file and
+    /// secret storage are neither read nor written, and attempts to change them fail with
+    /// [`ConfigError::EnvOnly`].
+    pub fn use_env_only(&self) {
+        self.env_only.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_env_only(&self) -> bool {
+        self.env_only.load(Ordering::SeqCst)
+    }
+
+    /// Whether the config file sets up a provider. Doesn't create the file when it is missing.
+    pub fn has_provider_configured(&self) -> bool {
+        self.exists()
+            && self
+                .load()
+                .is_ok_and(|values| values.contains_key("GOOSE_PROVIDER"))
+    }
+
     pub fn clear(&self) -> Result<(), ConfigError> {
         Ok(std::fs::remove_file(&self.config_path)?)
     }
@@ -270,6 +340,9 @@ impl Config {
     }
 
     fn load(&self) -> Result<Mapping, ConfigError> {
+        if self.is_env_only() {
+            return Ok(Mapping::new());
+        }
         if self.config_path.exists() {
             self.load_values_with_recovery()
         } else {
@@ -426,6 +499,10 @@ impl Config {
     }
 
     fn save_values(&self, values: Mapping) -> Result<(), ConfigError> {
+        if self.is_env_only() {
+            return Ok(());
+        }
+
         // Create backup before writing new config
         self.create_backup_if_needed()?;
 
@@ -541,11 +618,14 @@ impl Config {
     }
 
     pub fn all_secrets(&self) -> Result<HashMap<String, Value>, ConfigError> {
+        if self.is_env_only() {
+            return Ok(HashMap::new());
+        }
         match &self.secrets {
             SecretStorage::Keyring { service } => {
-                let entry = Entry::new(service, KEYRING_USERNAME)?;
+                let entry = keyring_entry(service)?;
 
-                match entry.get_password() {
+                match with_keyring_retry(|| entry.get_password()) {
                     Ok(content) => {
                         let values: HashMap<String, Value> = serde_json::from_str(&content)?;
                         Ok(values)
@@ -570,6 +650,13 @@ impl Config {
         }
     }
 
+    /// Names of the secrets goose has stored, sorted. Values are never returned.
+    pub fn secret_names(&self) -> Result<Vec<String>, ConfigError> {
+        let mut names: Vec<String> = self.all_secrets()?.into_keys().collect();
+        names.sort();
+        Ok(names)
+    }
+
     /// Parse an environment variable value into a JSON Value.
     ///
     /// This function tries to intelligently parse environment variable values:
@@ -669,6 +756,9 @@ impl Config {
     /// - There is an error serializing the value
     pub fn set_param<V: Serialize>(&self, key: &str, value: V) -> Result<(), ConfigError> {
         let _guard = self.guard.lock().unwrap();
+        if self.is_env_only() {
+            return Err(ConfigError::EnvOnly(key.to_string()));
+        }
         let mut values = self.load()?;
         values.insert(serde_yaml::to_value(key)?, serde_yaml::to_value(value)?);
         self.save_values(values)
@@ -690,6 +780,9 @@ impl Config {
     pub fn delete(&self, key: &str) -> Result<(), ConfigError> {
         // Lock before reading to prevent race condition.
         let _guard = self.guard.lock().unwrap();
+        if self.is_env_only() {
+            return Err(ConfigError::EnvOnly(key.to_string()));
+        }
 
         let mut values = self.load()?;
         values.shift_remove(key);
@@ -750,14 +843,18 @@ impl Config {
         // Lock before reading to prevent race condition.
         let _guard = self.guard.lock().unwrap();
 
+        if self.is_env_only() {
+            return Err(ConfigError::EnvOnly(key.to_string()));
+        }
+
         let mut values = self.all_secrets()?;
         values.insert(key.to_string(), serde_json::to_value(value)?);
 
         match &self.secrets {
             SecretStorage::Keyring { service } => {
                 let json_value = serde_json::to_string(&values)?;
-                let entry = Entry::new(service, KEYRING_USERNAME)?;
-                entry.set_password(&json_value)?;
+                let entry = keyring_entry(service)?;
+                with_keyring_retry(|| entry.set_password(&json_value))?;
             }
             SecretStorage::File { path } => {
                 let yaml_value = serde_yaml::to_string(&values)?;
@@ -781,14 +878,18 @@ impl Config {
         // Lock before reading to prevent race condition.
         let _guard = self.guard.lock().unwrap();
 
+        if self.is_env_only() {
+            return Err(ConfigError::EnvOnly(key.to_string()));
+        }
+
         let mut values = self.all_secrets()?;
         values.remove(key);
 
         match &self.secrets {
             SecretStorage::Keyring { service } => {
                 let json_value = serde_json::to_string(&values)?;
-                let entry = Entry::new(service, KEYRING_USERNAME)?;
-                entry.set_password(&json_value)?;
+                let entry = keyring_entry(service)?;
+                with_keyring_retry(|| entry.set_password(&json_value))?;
             }
             SecretStorage::File { path } => {
                 let yaml_value = serde_yaml::to_string(&values)?;
@@ -849,9 +950,78 @@ pub fn load_init_config_from_workspace() -> Result<Mapping, ConfigError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use keyring::credential::{Credential, CredentialApi};
     use serial_test::serial;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
     use tempfile::NamedTempFile;
 
+    /// Keyring credentials that tests install for a service in place of the platform store
+    static MOCK_KEYRING: Mutex<Vec<(String, FlakyCredential)>> = Mutex::new(Vec::new());
+
+    pub(super) fn mock_keyring_credential(service: &str) -> Option<Box<Credential>> {
+        MOCK_KEYRING
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(name, _)| name == service)
+            .map(|(_, credential)| Box::new(credential.clone()) as Box<Credential>)
+    }
+
+    /// An in-memory credential, shared by every entry built for its service, whose next
+    /// `failures` reads fail as if the keychain were locked
+    #[derive(Clone, Default)]
+    struct FlakyCredential {
+        secret: Arc<Mutex<Option<Vec<u8>>>>,
+        failures: Arc<AtomicUsize>,
+        reads: Arc<AtomicUsize>,
+    }
+
+    impl CredentialApi for FlakyCredential {
+        fn set_password(&self, password: &str) -> keyring::Result<()> {
+            self.set_secret(password.as_bytes())
+        }
+
+        fn set_secret(&self, secret: &[u8]) -> keyring::Result<()> {
+            *self.secret.lock().unwrap() = Some(secret.to_vec());
+            Ok(())
+        }
+
+        fn get_password(&self) -> keyring::Result<String> {
+            String::from_utf8(self.get_secret()?)
+                .map_err(|e| keyring::Error::BadEncoding(e.into_bytes()))
+        }
+
+        fn get_secret(&self) -> keyring::Result<Vec<u8>> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            let failing = self
+                .failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            if failing {
+                return Err(keyring::Error::NoStorageAccess("keychain locked".into()));
+            }
+            self.secret
+                .lock()
+                .unwrap()
+                .clone()
+                .ok_or(keyring::Error::NoEntry)
+        }
+
+        fn delete_credential(&self) -> keyring::Result<()> {
+            self.secret
+                .lock()
+                .unwrap()
+                .take()
+                .map(|_| ())
+                .ok_or(keyring::Error::NoEntry)
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
     fn cleanup_keyring() -> Result<(), ConfigError> {
         let entry = Entry::new(TEST_KEYRING_SERVICE, KEYRING_USERNAME)?;
         match entry.delete_credential() {
@@ -861,6 +1031,69 @@ mod tests {
         }
     }
 
+    fn mock_keyring_entry() -> Entry {
+        let credential = keyring::mock::default_credential_builder()
+            .build(None, TEST_KEYRING_SERVICE, KEYRING_USERNAME)
+            .unwrap();
+        Entry::new_with_credential(credential)
+    }
+
+    #[test]
+    fn test_keyring_retries_transient_error() {
+        let entry = mock_keyring_entry();
+        entry.set_password("{}").unwrap();
+
+        let mock: &keyring::mock::MockCredential = entry.get_credential().downcast_ref().unwrap();
+        mock.set_error(keyring::Error::NoStorageAccess("keychain locked".into()));
+
+        let mut calls = 0;
+        let result = with_keyring_retry(|| {
+            calls += 1;
+            entry.get_password()
+        });
+        assert_eq!(result.unwrap(), "{}");
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_get_secret_retries_locked_keyring() -> Result<(), ConfigError> {
+        let service = "goose-test-flaky-keyring";
+        let credential = FlakyCredential::default();
+        MOCK_KEYRING
+            .lock()
+            .unwrap()
+            .push((service.to_string(), credential.clone()));
+
+        let temp_file = NamedTempFile::new().unwrap();
+        let config = Config::new(temp_file.path(), service)?;
+        config.set_secret("flaky_api_key", &"secret")?;
+
+        credential.failures.store(1, Ordering::SeqCst);
+        credential.reads.store(0, Ordering::SeqCst);
+        let value: String = config.get_secret("flaky_api_key")?;
+        assert_eq!(value, "secret");
+        assert_eq!(credential.reads.load(Ordering::SeqCst), 2);
+
+        MOCK_KEYRING
+            .lock()
+            .unwrap()
+            .retain(|(name, _)| name != service);
+        Ok(())
+    }
+
+    #[test]
+    fn test_keyring_does_not_retry_missing_entry() {
+        let entry = mock_keyring_entry();
+
+        let mut calls = 0;
+        let result = with_keyring_retry(|| {
+            calls += 1;
+            entry.get_password()
+        });
+        assert!(matches!(result, Err(keyring::Error::NoEntry)));
+        assert_eq!(calls, 1);
+    }
+
     #[test]
     fn test_basic_config() -> Result<(), ConfigError> {
         let temp_file = NamedTempFile::new().unwrap();
@@ -881,6 +1114,46 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_env_only_config_leaves_files_alone() -> Result<(), ConfigError> {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("config.yaml");
+        let secrets_path = dir.path().join("secrets.yaml");
+        let config = Config::new_with_file_secrets(&config_path, &secrets_path)?;
+        config.use_env_only();
+
+        temp_env::with_vars(
+            [
+                ("GOOSE_PROVIDER", Some("openai")),
+                ("ENV_ONLY_TEST_API_KEY", Some("sk-test")),
+            ],
+            || -> Result<(), ConfigError> {
+                assert_eq!(config.get_goose_provider()?, "openai");
+                let key: String = config.get_secret("ENV_ONLY_TEST_API_KEY")?;
+                assert_eq!(key, "sk-test");
+                assert!(matches!(
+                    config.get_param::<String>("GOOSE_MODEL"),
+                    Err(ConfigError::NotFound(_))
+                ));
+
+                assert!(matches!(
+                    config.set_param("GOOSE_MODEL", "gpt-4o"),
+                    Err(ConfigError::EnvOnly(_))
+                ));
+                assert!(matches!(
+                    config.set_secret("OTHER_KEY", &Value::String("secret".into())),
+                    Err(ConfigError::EnvOnly(_))
+                ));
+                Ok(())
+            },
+        )?;
+
+        assert!(!config.has_provider_configured());
+        assert!(!config_path.exists());
+        assert!(!secrets_path.exists());
+        Ok(())
+    }
+
     #[test]
     fn test_complex_type() -> Result<(), ConfigError> {
         #[derive(Deserialize, Debug, PartialEq)]
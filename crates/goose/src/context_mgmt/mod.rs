@@ -8,10 +8,14 @@ use crate::{config::Config, token_counter::create_token_counter};
 use anyhow::Result;
 use rmcp::model::Role;
 use serde::Serialize;
+use std::borrow::{Borrow, Cow};
 use tracing::{debug, info};
 
 pub const DEFAULT_COMPACTION_THRESHOLD: f64 = 0.8;
 
+/// Number of most recent turns whose tool responses are never dropped while compacting
+pub const DEFAULT_MIN_RETAINED_TURNS: usize = 1;
+
 const CONVERSATION_CONTINUATION_TEXT: &str =
     "The previous message contains a summary that was prepared because a context limit was reached.
 Do not mention that you read a summary or that conversation summarization occurred.
@@ -219,21 +223,109 @@ pub async fn check_if_compaction_needed(
     Ok(needs_compaction)
 }
 
-fn filter_tool_responses<'a>(messages: &[&'a Message], remove_percent: u32) -> Vec<&'a Message> {
-    fn has_tool_response(msg: &Message) -> bool {
-        msg.content
-            .iter()
-            .any(|c| matches!(c, MessageContent::ToolResponse(_)))
+fn has_tool_response(msg: &Message) -> bool {
+    msg.content
+        .iter()
+        .any(|c| matches!(c, MessageContent::ToolResponse(_)))
+}
+
+/// A user message that is not a tool response, i.e. one that opens a turn
+fn is_prompt(msg: &Message) -> bool {
+    msg.role == Role::User && !has_tool_response(msg)
+}
+
+/// Indices, ascending, where a history can begin without parting a tool response from its
+/// request. A turn starts at each user message, so in a tool loop every tool response opens
+/// a new turn; such a turn begins at the assistant message that made the calls.
+fn turn_boundaries<M: Borrow<Message>>(messages: &[M]) -> Vec<usize> {
+    let mut boundaries: Vec<usize> = messages
+        .iter()
+        .enumerate()
+        .filter(|(_, msg)| msg.borrow().role == Role::User)
+        .map(|(i, msg)| {
+            if has_tool_response(msg.borrow()) {
+                i.saturating_sub(1)
+            } else {
+                i
+            }
+        })
+        .collect();
+    boundaries.dedup();
+    boundaries
+}
+
+/// The most recent messages of `messages` that fit in `max_messages`, dropping the oldest
+/// turns. When the history has to start inside a turn, it starts at a tool call, so no tool
+/// response loses its request, and the prompt that opened the turn is kept in front of it.
+/// If even the latest tool exchange doesn't fit, it is kept anyway.
+pub fn limit_message_count(messages: &[Message], max_messages: usize) -> Cow<'_, [Message]> {
+    if messages.len() <= max_messages {
+        return Cow::Borrowed(messages);
     }
 
+    // The prompt of the turn `start` falls inside of, if it doesn't start one itself
+    let opening_prompt = |start: usize| {
+        if is_prompt(&messages[start]) {
+            None
+        } else {
+            (0..start).rev().find(|&i| is_prompt(&messages[i]))
+        }
+    };
+    let kept = |start: usize| messages.len() - start + usize::from(opening_prompt(start).is_some());
+    let boundaries = turn_boundaries(messages);
+    let start = boundaries
+        .iter()
+        .copied()
+        .find(|&start| kept(start) <= max_messages)
+        .or(boundaries.last().copied())
+        .unwrap_or(0);
+
+    debug!(
+        "Keeping {} of {} messages to stay within {} messages",
+        kept(start),
+        messages.len(),
+        max_messages
+    );
+    match opening_prompt(start) {
+        Some(prompt) => Cow::Owned(
+            std::iter::once(&messages[prompt])
+                .chain(&messages[start..])
+                .cloned()
+                .collect(),
+        ),
+        None => Cow::Borrowed(&messages[start..]),
+    }
+}
+
+/// Index of the first message belonging to the most recent `min_retained_turns` turns, as
+/// delimited by `turn_boundaries`.
+fn retained_turns_start(messages: &[&Message], min_retained_turns: usize) -> usize {
+    if min_retained_turns == 0 {
+        return messages.len();
+    }
+
+    turn_boundaries(messages)
+        .into_iter()
+        .rev()
+        .nth(min_retained_turns - 1)
+        .unwrap_or(0)
+}
+
+fn filter_tool_responses<'a>(
+    messages: &[&'a Message],
+    remove_percent: u32,
+    min_retained_turns: usize,
+) -> Vec<&'a Message> {
     if remove_percent == 0 {
         return messages.to_vec();
     }
 
+    let retained_start = retained_turns_start(messages, min_retained_turns);
+
     let tool_indices: Vec<usize> = messages
         .iter()
         .enumerate()
-        .filter(|(_, msg)| has_tool_response(msg))
+        .filter(|(i, msg)| *i < retained_start && has_tool_response(msg))
         .map(|(i, _)| i)
         .collect();
 
@@ -278,11 +370,16 @@ async fn do_compact(
         .filter(|msg| msg.is_agent_visible())
         .collect();
 
+    let min_retained_turns = Config::global()
+        .get_param::<usize>("GOOSE_COMPACTION_MIN_RETAINED_TURNS")
+        .unwrap_or(DEFAULT_MIN_RETAINED_TURNS);
+
     // Try progressively removing more tool response messages from the middle to reduce context length
     let removal_percentages = [0, 10, 20, 50, 100];
 
     for (attempt, &remove_percent) in removal_percentages.iter().enumerate() {
-        let filtered_messages = filter_tool_responses(&agent_visible_messages, remove_percent);
+        let filtered_messages =
+            filter_tool_responses(&agent_visible_messages, remove_percent, min_retained_turns);
 
         let messages_text = filtered_messages
             .iter()
@@ -315,13 +412,15 @@ async fn do_compact(
                 return Ok((response, provider_usage));
             }
             Err(e) => {
-                if matches!(e, ProviderError::ContextLengthExceeded(_)) {
+                if matches!(e, ProviderError::ContextLengthExceeded { .. }) {
                     if attempt < removal_percentages.len() - 1 {
                         continue;
                     } else {
                         return Err(anyhow::anyhow!(
-                            "Failed to compact messages: context length still exceeded after {} attempts with maximum removal",
-                            removal_percentages.len()
+                            "Failed to compact messages: context length still exceeded after {} attempts with maximum removal \
+                            (the {} most recent turn(s) are always retained and may alone exceed the limit)",
+                            removal_percentages.len(),
+                            min_retained_turns
                         ));
                     }
                 }
@@ -433,6 +532,20 @@ mod tests {
                     toolshim: false,
                     toolshim_model: None,
                     fast_model: None,
+                    fallback_model: None,
+                    parallel_tool_calls: None,
+                    strict_tools: false,
+                    logit_bias: None,
+                    frequency_penalty: None,
+                    presence_penalty: None,
+                    tool_result_join: None,
+                    service_tier: None,
+                    n: None,
+                    prefill: None,
+                    request_timeout: None,
+                    requests_per_minute: None,
+                    top_p: None,
+                    stop: None,
                 },
                 max_tool_responses: None,
             }
@@ -473,10 +586,13 @@ mod tests {
                     .count();
 
                 if tool_response_count > max {
-                    return Err(ProviderError::ContextLengthExceeded(format!(
-                        "Too many tool responses: {} > {}",
-                        tool_response_count, max
-                    )));
+                    return Err(ProviderError::ContextLengthExceeded {
+                        details: format!(
+                            "Too many tool responses: {} > {}",
+                            tool_response_count, max
+                        ),
+                        limit: None,
+                    });
                 }
             }
 
@@ -521,6 +637,107 @@ mod tests {
             .expect("compaction should produce a valid conversation");
     }
 
+    #[test]
+    fn test_filter_tool_responses_retains_recent_turns() {
+        let mut messages = vec![Message::user().with_text("start")];
+        for i in 0..4 {
+            messages.push(Message::assistant().with_tool_request(
+                format!("tool_{}", i),
+                Ok(CallToolRequestParam {
+                    name: "read_file".into(),
+                    arguments: None,
+                }),
+            ));
+            messages.push(Message::user().with_tool_response(
+                format!("tool_{}", i),
+                Ok(vec![
+                    RawContent::text(format!("response{}", i)).no_annotation(),
+                ]),
+            ));
+        }
+        let refs: Vec<&Message> = messages.iter().collect();
+        let last = *refs.last().unwrap();
+
+        let trimmed = filter_tool_responses(&refs, 100, 0);
+        assert!(!trimmed.iter().any(|m| std::ptr::eq(*m, last)));
+
+        let retained = filter_tool_responses(&refs, 100, 1);
+        assert!(retained.iter().any(|m| std::ptr::eq(*m, last)));
+        assert!(retained.len() < refs.len());
+
+        let second_last = refs[refs.len() - 3];
+        let retained_two = filter_tool_responses(&refs, 100, 2);
+        assert!(retained_two.iter().any(|m| std::ptr::eq(*m, last)));
+        assert!(retained_two.iter().any(|m| std::ptr::eq(*m, second_last)));
+    }
+
+    #[test]
+    fn test_limit_message_count_drops_oldest_turns() {
+        let mut messages = Vec::new();
+        for i in 0..3 {
+            messages.push(Message::user().with_text(format!("question {}", i)));
+            messages.push(Message::assistant().with_tool_request(
+                format!("tool_{}", i),
+                Ok(CallToolRequestParam {
+                    name: "read_file".into(),
+                    arguments: None,
+                }),
+            ));
+            messages.push(Message::user().with_tool_response(
+                format!("tool_{}", i),
+                Ok(vec![RawContent::text("contents").no_annotation()]),
+            ));
+            messages.push(Message::assistant().with_text(format!("answer {}", i)));
+        }
+
+        assert_eq!(limit_message_count(&messages, 12).len(), 12);
+
+        // Seven messages would start mid-turn, so only the last whole turn is kept
+        let limited = limit_message_count(&messages, 7);
+        assert_eq!(limited.len(), 4);
+        assert_eq!(limited[0].as_concat_text(), "question 2");
+
+        let limited = limit_message_count(&messages, 8);
+        assert_eq!(limited.len(), 8);
+        assert_eq!(limited[0].as_concat_text(), "question 1");
+
+        // A turn longer than the limit keeps its prompt and latest tool exchange
+        let limited = limit_message_count(&messages, 2);
+        assert_eq!(limited.len(), 4);
+        assert_eq!(limited[0].as_concat_text(), "question 2");
+    }
+
+    #[test]
+    fn test_limit_message_count_trims_within_a_turn() {
+        let mut messages = vec![
+            Message::user().with_text("question 0"),
+            Message::assistant().with_text("answer 0"),
+            Message::user().with_text("question 1"),
+        ];
+        for i in 0..3 {
+            messages.push(Message::assistant().with_tool_request(
+                format!("tool_{}", i),
+                Ok(CallToolRequestParam {
+                    name: "read_file".into(),
+                    arguments: None,
+                }),
+            ));
+            messages.push(Message::user().with_tool_response(
+                format!("tool_{}", i),
+                Ok(vec![RawContent::text("contents").no_annotation()]),
+            ));
+        }
+        messages.push(Message::assistant().with_text("answer 1"));
+
+        let limited = limit_message_count(&messages, 5);
+        assert_eq!(limited.len(), 4);
+        assert_eq!(limited[0].as_concat_text(), "question 1");
+        assert!(limited[1].is_tool_call());
+        assert!(has_tool_response(&limited[2]));
+        assert_eq!(limited[3].as_concat_text(), "answer 1");
+        Conversation::new(limited.into_owned()).expect("no tool response loses its request");
+    }
+
     #[tokio::test]
     async fn test_progressive_removal_on_context_exceeded() {
         let response_message = Message::assistant().with_text("<mock summary>");
@@ -1,5 +1,4 @@
 use crate::mcp_utils::ToolResult;
-use chrono::Utc;
 use rmcp::model::{
     AnnotateAble, CallToolRequestParam, Content, ImageContent, JsonObject, PromptMessage,
     PromptMessageContent, PromptMessageRole, RawContent, RawImageContent, RawTextContent,
@@ -13,6 +12,8 @@ use utoipa::ToSchema;
 use crate::conversation::tool_result_serde;
 use crate::utils::sanitize_unicode_tags;
 
+const ALTERNATION_PLACEHOLDER: &str = "Continue.";
+
 #[derive(ToSchema)]
 pub enum ToolCallResult<T> {
     Success { value: T },
@@ -328,6 +329,41 @@ impl MessageContent {
     }
 }
 
+/// Fence language for a text resource's mime type, if it is code or structured data.
+fn fence_language(mime_type: &str) -> Option<&str> {
+    let essence = mime_type.split(';').next().unwrap_or_default().trim();
+    match essence {
+        "" | "text/plain" | "text/markdown" => None,
+        "application/json" => Some("json"),
+        "application/xml" | "text/xml" => Some("xml"),
+        "application/javascript" | "text/javascript" => Some("javascript"),
+        "application/yaml" | "text/yaml" => Some("yaml"),
+        "application/toml" => Some("toml"),
+        _ => essence
+            .strip_prefix("text/x-")
+            .or_else(|| essence.strip_prefix("application/x-"))
+            .or_else(|| essence.strip_prefix("text/")),
+    }
+}
+
+/// Render embedded resource contents as message text. Code and structured text is fenced
+/// with a language hint; binary contents are referenced by URI rather than inlined.
+fn format_resource_contents(contents: &ResourceContents) -> String {
+    match contents {
+        ResourceContents::TextResourceContents {
+            text, mime_type, ..
+        } => match mime_type.as_deref().and_then(fence_language) {
+            Some(language) => format!("```{}\n{}\n```", language, text),
+            None => text.clone(),
+        },
+        ResourceContents::BlobResourceContents { uri, mime_type, .. } => format!(
+            "[Binary resource: {} ({})]",
+            uri,
+            mime_type.as_deref().unwrap_or("unknown type")
+        ),
+    }
+}
+
 impl From<Content> for MessageContent {
     fn from(content: Content) -> Self {
         match content.raw {
@@ -339,13 +375,7 @@ impl From<Content> for MessageContent {
             }
             RawContent::ResourceLink(_link) => MessageContent::text("[Resource link]"),
             RawContent::Resource(resource) => {
-                let text = match &resource.resource {
-                    ResourceContents::TextResourceContents { text, .. } => text.clone(),
-                    ResourceContents::BlobResourceContents { blob, .. } => {
-                        format!("[Binary content: {}]", blob.clone())
-                    }
-                };
-                MessageContent::text(text)
+                MessageContent::text(format_resource_contents(&resource.resource))
             }
             RawContent::Audio(_) => {
                 MessageContent::text("[Audio content: not supported]".to_string())
@@ -370,15 +400,7 @@ impl From<PromptMessage> for Message {
             }
             PromptMessageContent::ResourceLink { .. } => MessageContent::text("[Resource link]"),
             PromptMessageContent::Resource { resource } => {
-                // For resources, convert to text content with the resource text
-                match &resource.resource {
-                    ResourceContents::TextResourceContents { text, .. } => {
-                        MessageContent::text(text.clone())
-                    }
-                    ResourceContents::BlobResourceContents { blob, .. } => {
-                        MessageContent::text(format!("[Binary content: {}]", blob.clone()))
-                    }
-                }
+                MessageContent::text(format_resource_contents(&resource.resource))
             }
         };
 
@@ -494,7 +516,7 @@ impl Message {
         Message {
             id: None,
             role: Role::User,
-            created: Utc::now().timestamp(),
+            created: crate::clock::now().timestamp(),
             content: Vec::new(),
             metadata: MessageMetadata::default(),
         }
@@ -505,7 +527,7 @@ impl Message {
         Message {
             id: None,
             role: Role::Assistant,
-            created: Utc::now().timestamp(),
+            created: crate::clock::now().timestamp(),
             content: Vec::new(),
             metadata: MessageMetadata::default(),
         }
@@ -709,6 +731,49 @@ impl Message {
     pub fn is_agent_visible(&self) -> bool {
         self.metadata.agent_visible
     }
+
+    /// Check that messages alternate between user and assistant, starting with the user,
+    /// and repair the sequence where possible: consecutive messages from the same role are
+    /// merged and a placeholder user message is inserted before a leading assistant message.
+    /// A merged message keeps the first message's id, with any tool results ahead of the
+    /// other content. Messages with different visibility are not merged.
+    pub fn validate_alternation(messages: &[Message]) -> Result<Vec<Message>, String> {
+        let mut repaired: Vec<Message> = Vec::with_capacity(messages.len());
+        for message in messages.iter().filter(|m| !m.content.is_empty()) {
+            match repaired.last_mut() {
+                Some(last) if last.role == message.role => {
+                    if last.metadata != message.metadata {
+                        return Err(format!(
+                            "consecutive {:?} messages with different visibility can't be merged",
+                            message.role
+                        ));
+                    }
+                    if last.id.is_none() {
+                        last.id.clone_from(&message.id);
+                    }
+                    // Tool results have to open the turn that answers the tool calls
+                    let (mut content, rest): (Vec<_>, Vec<_>) = last
+                        .content
+                        .drain(..)
+                        .chain(message.content.iter().cloned())
+                        .partition(|content| matches!(content, MessageContent::ToolResponse(_)));
+                    content.extend(rest);
+                    last.content = content;
+                }
+                _ => repaired.push(message.clone()),
+            }
+        }
+
+        match repaired.first() {
+            None => return Err("conversation has no messages with content".to_string()),
+            Some(first) if first.role == Role::Assistant => {
+                repaired.insert(0, Message::user().with_text(ALTERNATION_PLACEHOLDER));
+            }
+            _ => {}
+        }
+
+        Ok(repaired)
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
@@ -728,7 +793,7 @@ mod tests {
     use crate::conversation::*;
     use rmcp::model::{
         AnnotateAble, CallToolRequestParam, PromptMessage, PromptMessageContent, PromptMessageRole,
-        RawEmbeddedResource, RawImageContent, ResourceContents,
+        RawEmbeddedResource, RawImageContent, ResourceContents, Role,
     };
     use rmcp::model::{ErrorCode, ErrorData};
     use rmcp::object;
@@ -969,12 +1034,39 @@ mod tests {
         let message = Message::from(prompt_message);
 
         if let MessageContent::Text(text_content) = &message.content[0] {
-            assert_eq!(text_content.text, "[Binary content: binary_data]");
+            assert_eq!(
+                text_content.text,
+                "[Binary resource: file:///test.bin (application/octet-stream)]"
+            );
         } else {
             panic!("Expected MessageContent::Text");
         }
     }
 
+    #[test]
+    fn test_resource_contents_formatting_by_mime_type() {
+        let code = ResourceContents::TextResourceContents {
+            uri: "file:///src/main.rs".to_string(),
+            mime_type: Some("text/x-rust; charset=utf-8".to_string()),
+            text: "fn main() {}".to_string(),
+            meta: None,
+        };
+        assert_eq!(
+            format_resource_contents(&code),
+            "```rust\nfn main() {}\n```"
+        );
+
+        let image = ResourceContents::BlobResourceContents {
+            uri: "file:///logo.png".to_string(),
+            mime_type: Some("image/png".to_string()),
+            blob: "iVBORw0KGgo=".to_string(),
+            meta: None,
+        };
+        let text = format_resource_contents(&image);
+        assert_eq!(text, "[Binary resource: file:///logo.png (image/png)]");
+        assert!(!text.contains("iVBORw0KGgo="));
+    }
+
     #[test]
     fn test_from_prompt_message() {
         // Test user message conversion
@@ -1183,6 +1275,73 @@ mod tests {
         assert!(!invisible_msg.is_agent_visible());
     }
 
+    #[test]
+    fn test_validate_alternation_accepts_valid_sequence() {
+        let messages = vec![
+            Message::user().with_text("Hi"),
+            Message::assistant().with_text("Hello"),
+            Message::user().with_text("How are you?"),
+        ];
+
+        let validated = Message::validate_alternation(&messages).unwrap();
+        assert_eq!(validated, messages);
+    }
+
+    #[test]
+    fn test_validate_alternation_repairs_invalid_sequence() {
+        let messages = vec![
+            Message::assistant().with_text("Leading assistant"),
+            Message::user().with_text("First"),
+            Message::user().with_text("Second"),
+            Message::assistant(),
+            Message::assistant().with_text("Reply"),
+        ];
+
+        let validated = Message::validate_alternation(&messages).unwrap();
+        let roles: Vec<Role> = validated.iter().map(|m| m.role.clone()).collect();
+        assert_eq!(
+            roles,
+            vec![Role::User, Role::Assistant, Role::User, Role::Assistant]
+        );
+        assert_eq!(validated[2].as_concat_text(), "First\nSecond");
+        assert_eq!(validated[3].as_concat_text(), "Reply");
+
+        assert!(Message::validate_alternation(&[Message::user()]).is_err());
+    }
+
+    #[test]
+    fn test_validate_alternation_keeps_tool_results_first() {
+        let messages = vec![
+            Message::user().with_text("Run it"),
+            Message::assistant().with_tool_request(
+                "call_1",
+                Ok(CallToolRequestParam {
+                    name: "shell".into(),
+                    arguments: None,
+                }),
+            ),
+            Message::user().with_text("Also this").with_id("text"),
+            Message::user()
+                .with_tool_response("call_1", Ok(vec![]))
+                .with_id("result"),
+        ];
+
+        let validated = Message::validate_alternation(&messages).unwrap();
+        assert_eq!(validated.len(), 3);
+        assert_eq!(validated[2].id.as_deref(), Some("text"));
+        assert!(matches!(
+            validated[2].content.as_slice(),
+            [MessageContent::ToolResponse(_), MessageContent::Text(_)]
+        ));
+
+        let hidden = Message::user()
+            .with_text("Hidden")
+            .with_metadata(MessageMetadata::default().with_user_invisible());
+        assert!(
+            Message::validate_alternation(&[Message::user().with_text("Shown"), hidden]).is_err()
+        );
+    }
+
     #[test]
     fn test_message_metadata_builder_methods() {
         // Test with_agent_invisible
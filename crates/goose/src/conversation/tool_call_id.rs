@@ -0,0 +1,177 @@
+use crate::conversation::message::{Message, MessageContent};
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Prefix reserved for tool-call ids that goose assigns itself. Providers don't produce ids
+/// in this form, so an assigned id can't be mistaken for one a provider sent.
+pub const SYNTHETIC_TOOL_CALL_ID_PREFIX: &str = "__goose_call_";
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+// Distinguishes ids from earlier runs that may still be in a resumed session's history
+static RUN_TAG: Lazy<String> =
+    Lazy::new(|| uuid::Uuid::new_v4().simple().to_string()[..8].to_string());
+
+/// A new, sequential tool-call id for a call the provider did not give an id.
+pub fn synthetic_tool_call_id() -> String {
+    format!(
+        "{}{}_{}",
+        SYNTHETIC_TOOL_CALL_ID_PREFIX,
+        *RUN_TAG,
+        NEXT_ID.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// The provider's tool-call id, kept verbatim, or a synthetic one when it sent none.
+pub fn tool_call_id_or_synthetic(id: Option<&str>) -> String {
+    match id {
+        Some(id) if !id.is_empty() => id.to_string(),
+        _ => synthetic_tool_call_id(),
+    }
+}
+
+pub fn is_synthetic_tool_call_id(id: &str) -> bool {
+    id.starts_with(SYNTHETIC_TOOL_CALL_ID_PREFIX)
+}
+
+/// Give every tool request in a provider's `message` its own id, so each response can be
+/// matched to its call. Returns each renamed id as `(new id, id the provider sent)`.
+pub fn disambiguate_tool_call_ids(message: &mut Message) -> Vec<(String, String)> {
+    ToolCallIds::default().disambiguate(message)
+}
+
+/// The tool-call ids used so far in one provider response, so that calls streamed in separate
+/// messages are told apart too
+#[derive(Debug, Default)]
+pub struct ToolCallIds {
+    seen: HashSet<String>,
+}
+
+impl ToolCallIds {
+    /// Fill in missing ids with synthetic ones, give a repeated id a numeric suffix and
+    /// replace an id the provider sent in the reserved synthetic form. Returns each renamed
+    /// id as `(new id, id the provider sent)`; filled-in ids are not renames.
+    pub fn disambiguate(&mut self, message: &mut Message) -> Vec<(String, String)> {
+        let provider_ids: HashSet<String> = message
+            .content
+            .iter()
+            .filter_map(tool_request_id)
+            .map(str::to_string)
+            .collect();
+        let mut renamed = Vec::new();
+
+        for content in &mut message.content {
+            let id = match content {
+                MessageContent::ToolRequest(request) => &mut request.id,
+                MessageContent::FrontendToolRequest(request) => &mut request.id,
+                _ => continue,
+            };
+            if id.is_empty() {
+                *id = synthetic_tool_call_id();
+                self.seen.insert(id.clone());
+                continue;
+            }
+            let new_id = if is_synthetic_tool_call_id(id) {
+                synthetic_tool_call_id()
+            } else if self.seen.contains(id.as_str()) {
+                (2..)
+                    .map(|n| format!("{}_{}", id, n))
+                    .find(|candidate| {
+                        !self.seen.contains(candidate) && !provider_ids.contains(candidate)
+                    })
+                    .expect("an unused suffix exists")
+            } else {
+                self.seen.insert(id.clone());
+                continue;
+            };
+            tracing::warn!(
+                "Provider sent tool call id {:?} more than once or in reserved form, using {:?}",
+                id,
+                new_id
+            );
+            self.seen.insert(new_id.clone());
+            renamed.push((new_id.clone(), std::mem::replace(id, new_id)));
+        }
+        renamed
+    }
+}
+
+fn tool_request_id(content: &MessageContent) -> Option<&str> {
+    match content {
+        MessageContent::ToolRequest(request) => Some(&request.id),
+        MessageContent::FrontendToolRequest(request) => Some(&request.id),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_ids_are_unique_and_prefixed() {
+        let first = synthetic_tool_call_id();
+        let second = synthetic_tool_call_id();
+        assert_ne!(first, second);
+        assert!(is_synthetic_tool_call_id(&first));
+        assert!(is_synthetic_tool_call_id(&second));
+
+        assert_eq!(tool_call_id_or_synthetic(Some("000")), "000");
+        assert!(!is_synthetic_tool_call_id("000"));
+        assert!(is_synthetic_tool_call_id(&tool_call_id_or_synthetic(Some(
+            ""
+        ))));
+        assert!(is_synthetic_tool_call_id(&tool_call_id_or_synthetic(None)));
+    }
+
+    #[test]
+    fn test_duplicate_and_reserved_ids_are_disambiguated() {
+        let call = || {
+            Ok(rmcp::model::CallToolRequestParam {
+                name: "developer__shell".into(),
+                arguments: None,
+            })
+        };
+        let reserved = format!("{}from_provider", SYNTHETIC_TOOL_CALL_ID_PREFIX);
+        let mut message = Message::assistant()
+            .with_tool_request("call_1", call())
+            .with_tool_request("call_1", call())
+            .with_tool_request("call_1_2", call())
+            .with_tool_request(reserved.clone(), call());
+
+        let renamed = disambiguate_tool_call_ids(&mut message);
+
+        let ids: Vec<&str> = message.content.iter().filter_map(tool_request_id).collect();
+        assert_eq!(ids[..3], ["call_1", "call_1_3", "call_1_2"]);
+        assert!(is_synthetic_tool_call_id(ids[3]) && ids[3] != reserved);
+        assert_eq!(renamed[0], ("call_1_3".to_string(), "call_1".to_string()));
+        assert_eq!(renamed[1].1, reserved);
+
+        // Unique ids are left alone, and missing ones are filled in without counting as renames
+        let mut message = Message::assistant()
+            .with_tool_request("a", call())
+            .with_tool_request("b", call())
+            .with_tool_request("", call());
+        assert!(disambiguate_tool_call_ids(&mut message).is_empty());
+        let ids: Vec<&str> = message.content.iter().filter_map(tool_request_id).collect();
+        assert_eq!(ids[..2], ["a", "b"]);
+        assert!(is_synthetic_tool_call_id(ids[2]));
+    }
+
+    #[test]
+    fn test_repeats_across_streamed_messages_are_disambiguated() {
+        let call = || {
+            Ok(rmcp::model::CallToolRequestParam {
+                name: "developer__shell".into(),
+                arguments: None,
+            })
+        };
+        let mut ids = ToolCallIds::default();
+        let mut first = Message::assistant().with_tool_request("call_1", call());
+        let mut second = Message::assistant().with_tool_request("call_1", call());
+
+        assert!(ids.disambiguate(&mut first).is_empty());
+        let renamed = ids.disambiguate(&mut second);
+        assert_eq!(renamed, [("call_1_2".to_string(), "call_1".to_string())]);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod agents;
+pub mod clock;
 pub mod config;
 pub mod context_mgmt;
 pub mod conversation;
@@ -13,6 +14,7 @@ pub mod prompt_template;
 pub mod providers;
 pub mod recipe;
 pub mod recipe_deeplink;
+pub mod retry_budget;
 pub mod scheduler;
 pub mod scheduler_trait;
 pub mod security;
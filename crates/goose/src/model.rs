@@ -1,5 +1,7 @@
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
 use thiserror::Error;
 
 const DEFAULT_CONTEXT_LIMIT: usize = 128_000;
@@ -67,6 +69,44 @@ static MODEL_SPECIFIC_LIMITS: Lazy<Vec<(&'static str, usize)>> = Lazy::new(|| {
     ]
 });
 
+/// How the text parts of a tool result are sent to OpenAI-style chat APIs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolResultJoinStrategy {
+    /// One string with the parts joined by spaces. Accepted by every OpenAI-compatible API.
+    #[default]
+    Concatenated,
+    /// An array of text content blocks, one per part, keeping their boundaries. Some
+    /// endpoints reject arrays in tool messages.
+    SeparateBlocks,
+}
+
+/// OpenAI processing tier for a request, trading latency and availability against price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceTier {
+    /// Let OpenAI pick: uses the project's scale tier credits when available, else default
+    Auto,
+    /// Standard pricing and latency
+    Default,
+    /// Cheaper, slower processing with a higher chance of capacity errors; suited to
+    /// background work that can tolerate waiting
+    Flex,
+    /// Faster, more consistent latency at a premium price
+    Priority,
+}
+
+impl ServiceTier {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServiceTier::Auto => "auto",
+            ServiceTier::Default => "default",
+            ServiceTier::Flex => "flex",
+            ServiceTier::Priority => "priority",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelConfig {
     pub model_name: String,
@@ -76,6 +116,47 @@ pub struct ModelConfig {
     pub toolshim: bool,
     pub toolshim_model: Option<String>,
     pub fast_model: Option<String>,
+    /// Model to switch to when the provider reports `model_name` as not found
+    #[serde(default)]
+    pub fallback_model: Option<String>,
+    /// Whether the model may issue several tool calls in one turn; `None` keeps the provider default
+    pub parallel_tool_calls: Option<bool>,
+    /// Send tool schemas in OpenAI strict function-calling form
+    #[serde(default)]
+    pub strict_tools: bool,
+    /// Per-token sampling bias (-100 to 100) keyed by token id, for OpenAI-compatible providers
+    #[serde(default)]
+    pub logit_bias: Option<HashMap<String, f32>>,
+    #[serde(default)]
+    pub frequency_penalty: Option<f32>,
+    #[serde(default)]
+    pub presence_penalty: Option<f32>,
+    /// How tool result text is sent to OpenAI-compatible providers; `None` keeps the provider default
+    #[serde(default)]
+    pub tool_result_join: Option<ToolResultJoinStrategy>,
+    /// OpenAI `service_tier` to request; `None` omits it so the account default applies
+    #[serde(default)]
+    pub service_tier: Option<ServiceTier>,
+    /// Number of completions to request from OpenAI-compatible providers; `None` asks for
+    /// one. Only the first choice is used as the reply.
+    #[serde(default)]
+    pub n: Option<u32>,
+    /// Partial assistant reply the model continues from, e.g. `{` to force JSON. Only
+    /// Anthropic supports it; the returned reply starts with the prefill.
+    #[serde(default)]
+    pub prefill: Option<String>,
+    /// Timeout for each provider request; `None` keeps the provider's default
+    #[serde(default)]
+    pub request_timeout: Option<Duration>,
+    /// Cap on requests per minute sent to the provider; `None` sends them as they come
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    /// Nucleus sampling cutoff (0 to 1), for OpenAI-compatible providers and Anthropic
+    #[serde(default)]
+    pub top_p: Option<f32>,
+    /// Sequences that end the reply when generated; sent as `stop_sequences` to Anthropic
+    #[serde(default)]
+    pub stop: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,6 +178,18 @@ impl ModelConfig {
         let temperature = Self::parse_temperature()?;
         let toolshim = Self::parse_toolshim()?;
         let toolshim_model = Self::parse_toolshim_model()?;
+        let fallback_model = Self::parse_fallback_model()?;
+        let strict_tools = Self::parse_bool_env("GOOSE_STRICT_TOOLS")?;
+        let frequency_penalty = Self::parse_penalty("GOOSE_FREQUENCY_PENALTY")?;
+        let presence_penalty = Self::parse_penalty("GOOSE_PRESENCE_PENALTY")?;
+        let tool_result_join = Self::parse_tool_result_join()?;
+        let service_tier = Self::parse_service_tier()?;
+        let request_timeout = Self::parse_request_timeout()?;
+        let requests_per_minute = Self::parse_requests_per_minute()?;
+        let top_p = Self::parse_top_p()?;
+        let prefill = std::env::var("GOOSE_PREFILL")
+            .ok()
+            .filter(|prefill| !prefill.is_empty());
 
         Ok(Self {
             model_name,
@@ -106,6 +199,20 @@ impl ModelConfig {
             toolshim,
             toolshim_model,
             fast_model: None,
+            fallback_model,
+            parallel_tool_calls: None,
+            strict_tools,
+            logit_bias: None,
+            frequency_penalty,
+            presence_penalty,
+            tool_result_join,
+            service_tier,
+            n: None,
+            prefill,
+            request_timeout,
+            requests_per_minute,
+            top_p,
+            stop: None,
         })
     }
 
@@ -183,13 +290,113 @@ impl ModelConfig {
         }
     }
 
+    fn parse_penalty(var: &str) -> Result<Option<f32>, ConfigError> {
+        if let Ok(val) = std::env::var(var) {
+            let penalty = val.parse::<f32>().map_err(|_| {
+                ConfigError::InvalidValue(
+                    var.to_string(),
+                    val.clone(),
+                    "must be a valid number".to_string(),
+                )
+            })?;
+            if !(-2.0..=2.0).contains(&penalty) {
+                return Err(ConfigError::InvalidRange(var.to_string(), val));
+            }
+            Ok(Some(penalty))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn parse_tool_result_join() -> Result<Option<ToolResultJoinStrategy>, ConfigError> {
+        match std::env::var("GOOSE_TOOL_RESULT_JOIN") {
+            Ok(val) => match val.to_lowercase().as_str() {
+                "concatenated" => Ok(Some(ToolResultJoinStrategy::Concatenated)),
+                "separate_blocks" => Ok(Some(ToolResultJoinStrategy::SeparateBlocks)),
+                _ => Err(ConfigError::InvalidValue(
+                    "GOOSE_TOOL_RESULT_JOIN".to_string(),
+                    val,
+                    "must be one of: concatenated, separate_blocks".to_string(),
+                )),
+            },
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn parse_service_tier() -> Result<Option<ServiceTier>, ConfigError> {
+        match std::env::var("GOOSE_SERVICE_TIER") {
+            Ok(val) => match val.to_lowercase().as_str() {
+                "auto" => Ok(Some(ServiceTier::Auto)),
+                "default" => Ok(Some(ServiceTier::Default)),
+                "flex" => Ok(Some(ServiceTier::Flex)),
+                "priority" => Ok(Some(ServiceTier::Priority)),
+                _ => Err(ConfigError::InvalidValue(
+                    "GOOSE_SERVICE_TIER".to_string(),
+                    val,
+                    "must be one of: auto, default, flex, priority".to_string(),
+                )),
+            },
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn parse_request_timeout() -> Result<Option<Duration>, ConfigError> {
+        match std::env::var("GOOSE_REQUEST_TIMEOUT") {
+            Ok(val) => match val.parse::<u64>() {
+                Ok(secs) if secs > 0 => Ok(Some(Duration::from_secs(secs))),
+                _ => Err(ConfigError::InvalidValue(
+                    "GOOSE_REQUEST_TIMEOUT".to_string(),
+                    val,
+                    "must be a positive number of seconds".to_string(),
+                )),
+            },
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn parse_requests_per_minute() -> Result<Option<u32>, ConfigError> {
+        match std::env::var("GOOSE_REQUESTS_PER_MINUTE") {
+            Ok(val) => match val.parse::<u32>() {
+                Ok(rpm) if rpm > 0 => Ok(Some(rpm)),
+                _ => Err(ConfigError::InvalidValue(
+                    "GOOSE_REQUESTS_PER_MINUTE".to_string(),
+                    val,
+                    "must be a positive integer".to_string(),
+                )),
+            },
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn parse_top_p() -> Result<Option<f32>, ConfigError> {
+        if let Ok(val) = std::env::var("GOOSE_TOP_P") {
+            let top_p = val.parse::<f32>().map_err(|_| {
+                ConfigError::InvalidValue(
+                    "GOOSE_TOP_P".to_string(),
+                    val.clone(),
+                    "must be a valid number".to_string(),
+                )
+            })?;
+            if !(0.0..=1.0).contains(&top_p) {
+                return Err(ConfigError::InvalidRange("GOOSE_TOP_P".to_string(), val));
+            }
+            Ok(Some(top_p))
+        } else {
+            Ok(None)
+        }
+    }
+
     fn parse_toolshim() -> Result<bool, ConfigError> {
-        if let Ok(val) = std::env::var("GOOSE_TOOLSHIM") {
+        Self::parse_bool_env("GOOSE_TOOLSHIM")
+    }
+
+    fn parse_bool_env(var: &str) -> Result<bool, ConfigError> {
+        if let Ok(val) = std::env::var(var) {
             match val.to_lowercase().as_str() {
                 "1" | "true" | "yes" | "on" => Ok(true),
                 "0" | "false" | "no" | "off" => Ok(false),
                 _ => Err(ConfigError::InvalidValue(
-                    "GOOSE_TOOLSHIM".to_string(),
+                    var.to_string(),
                     val,
                     "must be one of: 1, true, yes, on, 0, false, no, off".to_string(),
                 )),
@@ -211,6 +418,18 @@ impl ModelConfig {
         }
     }
 
+    fn parse_fallback_model() -> Result<Option<String>, ConfigError> {
+        match std::env::var("GOOSE_FALLBACK_MODEL") {
+            Ok(val) if val.trim().is_empty() => Err(ConfigError::InvalidValue(
+                "GOOSE_FALLBACK_MODEL".to_string(),
+                val,
+                "cannot be empty if set".to_string(),
+            )),
+            Ok(val) => Ok(Some(val)),
+            Err(_) => Ok(None),
+        }
+    }
+
     fn get_model_specific_limit(model_name: &str) -> Option<usize> {
         MODEL_SPECIFIC_LIMITS
             .iter()
@@ -245,6 +464,15 @@ impl ModelConfig {
         self
     }
 
+    /// Shrink `max_tokens` so a request of `input_tokens` plus its reply fits in the context
+    /// window. An unset `max_tokens` is left to the provider's default.
+    pub fn fit_max_tokens(mut self, input_tokens: usize) -> Self {
+        let remaining = self.context_limit().saturating_sub(input_tokens).max(1);
+        let remaining = i32::try_from(remaining).unwrap_or(i32::MAX);
+        self.max_tokens = self.max_tokens.map(|max_tokens| max_tokens.min(remaining));
+        self
+    }
+
     pub fn with_toolshim(mut self, toolshim: bool) -> Self {
         self.toolshim = toolshim;
         self
@@ -260,6 +488,81 @@ impl ModelConfig {
         self
     }
 
+    pub fn with_fallback_model(mut self, fallback_model: String) -> Self {
+        self.fallback_model = Some(fallback_model);
+        self
+    }
+
+    pub fn with_parallel_tool_calls(mut self, enabled: bool) -> Self {
+        self.parallel_tool_calls = Some(enabled);
+        self
+    }
+
+    pub fn with_strict_tools(mut self, strict: bool) -> Self {
+        self.strict_tools = strict;
+        self
+    }
+
+    pub fn with_logit_bias(mut self, logit_bias: Option<HashMap<String, f32>>) -> Self {
+        self.logit_bias = logit_bias;
+        self
+    }
+
+    pub fn with_frequency_penalty(mut self, penalty: Option<f32>) -> Self {
+        self.frequency_penalty = penalty;
+        self
+    }
+
+    pub fn with_presence_penalty(mut self, penalty: Option<f32>) -> Self {
+        self.presence_penalty = penalty;
+        self
+    }
+
+    pub fn with_tool_result_join(mut self, strategy: Option<ToolResultJoinStrategy>) -> Self {
+        self.tool_result_join = strategy;
+        self
+    }
+
+    pub fn with_service_tier(mut self, service_tier: Option<ServiceTier>) -> Self {
+        self.service_tier = service_tier;
+        self
+    }
+
+    pub fn with_n(mut self, n: Option<u32>) -> Self {
+        self.n = n;
+        self
+    }
+
+    pub fn with_prefill(mut self, prefill: Option<String>) -> Self {
+        self.prefill = prefill;
+        self
+    }
+
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    pub fn with_requests_per_minute(mut self, requests_per_minute: Option<u32>) -> Self {
+        self.requests_per_minute = requests_per_minute;
+        self
+    }
+
+    pub fn with_top_p(mut self, top_p: Option<f32>) -> Self {
+        self.top_p = top_p;
+        self
+    }
+
+    pub fn with_stop(mut self, stop: Option<Vec<String>>) -> Self {
+        self.stop = stop;
+        self
+    }
+
+    /// The request timeout, or `default` when none is configured
+    pub fn request_timeout_or(&self, default: Duration) -> Duration {
+        self.request_timeout.unwrap_or(default)
+    }
+
     pub fn use_fast_model(&self) -> Self {
         if let Some(fast_model) = &self.fast_model {
             let mut config = self.clone();
@@ -270,6 +573,19 @@ impl ModelConfig {
         }
     }
 
+    /// Config for the fallback model, if one is set and differs from the current model. The
+    /// result has no fallback of its own, so a switch happens at most once.
+    pub fn use_fallback_model(&self) -> Option<Self> {
+        let fallback_model = self.fallback_model.as_ref()?;
+        if *fallback_model == self.model_name {
+            return None;
+        }
+        let mut config = self.clone();
+        config.model_name = fallback_model.clone();
+        config.fallback_model = None;
+        Some(config)
+    }
+
     pub fn context_limit(&self) -> usize {
         // If we have an explicit context limit set, use it
         if let Some(limit) = self.context_limit {
@@ -5,7 +5,6 @@ use crate::conversation::message::{Message, MessageContent, ToolRequest};
 use crate::conversation::Conversation;
 use crate::prompt_template::render_global_file;
 use crate::providers::base::Provider;
-use chrono::Utc;
 use indoc::indoc;
 use rmcp::model::{Tool, ToolAnnotations};
 use rmcp::object;
@@ -89,7 +88,7 @@ fn create_check_messages(tool_requests: Vec<&ToolRequest>) -> Conversation {
     let mut check_messages = vec![];
     check_messages.push(Message::new(
         rmcp::model::Role::User,
-        Utc::now().timestamp(),
+        crate::clock::now().timestamp(),
         vec![MessageContent::text(format!(
                 "Here are the tool requests: {:?}\n\nAnalyze the tool requests and list the tools that perform read-only operations. \
                 \n\nGuidelines for Read-Only Operations: \
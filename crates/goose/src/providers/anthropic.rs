@@ -8,18 +8,22 @@ use std::io;
 use tokio::pin;
 use tokio_util::io::StreamReader;
 
-use super::api_client::{ApiClient, ApiResponse, AuthMethod};
+use super::api_client::{ApiClient, ApiResponse, AuthMethod, DEFAULT_REQUEST_TIMEOUT};
 use super::base::{ConfigKey, MessageStream, ModelInfo, Provider, ProviderMetadata, ProviderUsage};
 use super::errors::ProviderError;
 use super::formats::anthropic::{
-    create_request, get_usage, response_to_message, response_to_streaming_message,
+    create_request, get_usage, prefill_text, prepend_prefill, response_to_message,
+    response_to_streaming_message,
+};
+use super::utils::{
+    get_model, map_http_error_to_provider_error, retry_after_from_headers, validate_response_shape,
+    ResponseShape,
 };
-use super::utils::{get_model, map_http_error_to_provider_error};
 use crate::config::declarative_providers::DeclarativeProviderConfig;
 use crate::conversation::message::Message;
 use crate::model::ModelConfig;
 use crate::providers::retry::ProviderRetry;
-use crate::providers::utils::RequestLog;
+use crate::providers::utils::{strip_empty_content, RequestLog};
 use rmcp::model::Tool;
 
 pub const ANTHROPIC_DEFAULT_MODEL: &str = "claude-sonnet-4-0";
@@ -61,8 +65,12 @@ impl AnthropicProvider {
             key: api_key,
         };
 
-        let api_client =
-            ApiClient::new(host, auth)?.with_header("anthropic-version", ANTHROPIC_API_VERSION)?;
+        let api_client = ApiClient::with_timeout(
+            host,
+            auth,
+            model.request_timeout_or(DEFAULT_REQUEST_TIMEOUT),
+        )?
+        .with_header("anthropic-version", ANTHROPIC_API_VERSION)?;
 
         Ok(Self {
             api_client,
@@ -86,8 +94,12 @@ impl AnthropicProvider {
             key: api_key,
         };
 
-        let api_client = ApiClient::new(config.base_url, auth)?
-            .with_header("anthropic-version", ANTHROPIC_API_VERSION)?;
+        let api_client = ApiClient::with_timeout(
+            config.base_url,
+            auth,
+            model.request_timeout_or(DEFAULT_REQUEST_TIMEOUT),
+        )?
+        .with_header("anthropic-version", ANTHROPIC_API_VERSION)?;
 
         Ok(Self {
             api_client,
@@ -121,6 +133,17 @@ impl AnthropicProvider {
         Ok(request.api_post(payload).await?)
     }
 
+    /// Strips empty content first, since dropping an emptied message can leave two turns of
+    /// the same role next to each other
+    fn validated_messages(messages: &[Message]) -> Result<Vec<Message>, ProviderError> {
+        Message::validate_alternation(&strip_empty_content(messages)).map_err(|e| {
+            ProviderError::ExecutionError(format!(
+                "Messages could not be arranged into alternating user/assistant turns: {}",
+                e
+            ))
+        })
+    }
+
     fn anthropic_api_call_result(response: ApiResponse) -> Result<Value, ProviderError> {
         match response.status {
             StatusCode::OK => response.payload.ok_or_else(|| {
@@ -139,14 +162,14 @@ impl AnthropicProvider {
                         if msg.to_lowercase().contains("too long")
                             || msg.to_lowercase().contains("too many")
                         {
-                            return Err(ProviderError::ContextLengthExceeded(msg));
+                            return Err(ProviderError::context_length_exceeded(msg));
                         }
                     }
                 }
-                Err(map_http_error_to_provider_error(
-                    response.status,
-                    response.payload,
-                ))
+                Err(
+                    map_http_error_to_provider_error(response.status, response.payload)
+                        .with_retry_delay(response.retry_after),
+                )
             }
         }
     }
@@ -198,7 +221,8 @@ impl Provider for AnthropicProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<(Message, ProviderUsage), ProviderError> {
-        let payload = create_request(model_config, system, messages, tools)?;
+        let messages = Self::validated_messages(messages)?;
+        let payload = create_request(model_config, system, &messages, tools)?;
 
         let response = self
             .with_retry(|| async { self.post(&payload).await })
@@ -206,7 +230,11 @@ impl Provider for AnthropicProvider {
 
         let json_response = Self::anthropic_api_call_result(response)?;
 
-        let message = response_to_message(&json_response)?;
+        validate_response_shape(self.get_name(), ResponseShape::Anthropic, &json_response)?;
+        let mut message = response_to_message(&json_response)?;
+        if let Some(prefill) = prefill_text(model_config) {
+            prepend_prefill(&mut message, prefill);
+        }
         let usage = get_usage(&json_response)?;
         tracing::debug!("🔍 Anthropic non-streaming parsed usage: input_tokens={:?}, output_tokens={:?}, total_tokens={:?}",
                 usage.input_tokens, usage.output_tokens, usage.total_tokens);
@@ -260,7 +288,8 @@ impl Provider for AnthropicProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<MessageStream, ProviderError> {
-        let mut payload = create_request(&self.model, system, messages, tools)?;
+        let messages = Self::validated_messages(messages)?;
+        let mut payload = create_request(&self.model, system, &messages, tools)?;
         payload
             .as_object_mut()
             .unwrap()
@@ -273,19 +302,23 @@ impl Provider for AnthropicProvider {
             request = request.header(key, value)?;
         }
 
+        self.wait_for_rate_limit().await;
         let response = request.response_post(&payload).await.inspect_err(|e| {
             let _ = log.error(e);
         })?;
         if !response.status().is_success() {
             let status = response.status();
+            let retry_after = retry_after_from_headers(response.headers());
             let error_text = response.text().await.unwrap_or_default();
             let error_json = serde_json::from_str::<Value>(&error_text).ok();
-            let error = map_http_error_to_provider_error(status, error_json);
+            let error =
+                map_http_error_to_provider_error(status, error_json).with_retry_delay(retry_after);
             let _ = log.error(&error);
             return Err(error);
         }
 
         let stream = response.bytes_stream().map_err(io::Error::other);
+        let mut prefill = prefill_text(&self.model).map(str::to_string);
 
         Ok(Box::pin(try_stream! {
             let stream_reader = StreamReader::new(stream);
@@ -294,7 +327,12 @@ impl Provider for AnthropicProvider {
             let message_stream = response_to_streaming_message(framed);
             pin!(message_stream);
             while let Some(message) = futures::StreamExt::next(&mut message_stream).await {
-                let (message, usage) = message.map_err(|e| ProviderError::RequestFailed(format!("Stream decode error: {}", e)))?;
+                let (mut message, usage) = message.map_err(|e| ProviderError::RequestFailed(format!("Stream decode error: {}", e)))?;
+                if let (Some(text), Some(message)) = (prefill.as_deref(), message.as_mut()) {
+                    if prepend_prefill(message, text) {
+                        prefill = None;
+                    }
+                }
                 log.write(&message, usage.as_ref().map(|f| f.usage).as_ref())?;
                 yield (message, usage);
             }
@@ -305,3 +343,31 @@ impl Provider for AnthropicProvider {
         self.supports_streaming
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_prompt_too_long_maps_to_context_length_exceeded() {
+        let response = ApiResponse {
+            status: StatusCode::BAD_REQUEST,
+            payload: Some(json!({
+                "type": "error",
+                "error": {
+                    "type": "invalid_request_error",
+                    "message": "prompt is too long: 215426 tokens > 200000 maximum"
+                }
+            })),
+            retry_after: None,
+        };
+        assert_eq!(
+            AnthropicProvider::anthropic_api_call_result(response),
+            Err(ProviderError::ContextLengthExceeded {
+                details: "prompt is too long: 215426 tokens > 200000 maximum".to_string(),
+                limit: Some(200000),
+            })
+        );
+    }
+}
@@ -1,18 +1,30 @@
+use super::utils::retry_after_from_headers;
 use crate::session_context::SESSION_ID_HEADER;
 use anyhow::Result;
 use async_trait::async_trait;
+use once_cell::sync::Lazy;
 use reqwest::{
-    header::{HeaderMap, HeaderName, HeaderValue},
+    header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT},
     Certificate, Client, Identity, Response, StatusCode,
 };
 use serde_json::Value;
+use std::collections::HashMap;
 use std::fmt;
 use std::fs::read_to_string;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+/// Request timeout used when neither the model config nor the provider sets one
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Clients reused by every `ApiClient` without custom TLS or default headers, keyed by
+/// timeout and User-Agent, so providers share connection pools
+static SHARED_CLIENTS: Lazy<Mutex<HashMap<(Duration, HeaderValue), Arc<Client>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
 pub struct ApiClient {
-    client: Client,
+    client: Arc<Client>,
     host: String,
     auth: AuthMethod,
     default_headers: HeaderMap,
@@ -164,9 +176,21 @@ pub trait AuthProvider: Send + Sync {
     async fn get_auth_header(&self) -> Result<(String, String)>;
 }
 
+/// Auth for local servers that don't check credentials; sends a placeholder header.
+pub struct NoAuth;
+
+#[async_trait]
+impl AuthProvider for NoAuth {
+    async fn get_auth_header(&self) -> Result<(String, String)> {
+        Ok(("X-No-Auth".to_string(), "true".to_string()))
+    }
+}
+
 pub struct ApiResponse {
     pub status: StatusCode,
     pub payload: Option<Value>,
+    /// Wait requested by the server's `Retry-After` header
+    pub retry_after: Option<Duration>,
 }
 
 impl fmt::Debug for AuthMethod {
@@ -187,8 +211,13 @@ impl fmt::Debug for AuthMethod {
 impl ApiResponse {
     pub async fn from_response(response: Response) -> Result<Self> {
         let status = response.status();
+        let retry_after = retry_after_from_headers(response.headers());
         let payload = response.json().await.ok();
-        Ok(Self { status, payload })
+        Ok(Self {
+            status,
+            payload,
+            retry_after,
+        })
     }
 }
 
@@ -200,41 +229,87 @@ pub struct ApiRequestBuilder<'a> {
 
 impl ApiClient {
     pub fn new(host: String, auth: AuthMethod) -> Result<Self> {
-        Self::with_timeout(host, auth, Duration::from_secs(600))
+        Self::with_timeout(host, auth, DEFAULT_REQUEST_TIMEOUT)
     }
 
     pub fn with_timeout(host: String, auth: AuthMethod, timeout: Duration) -> Result<Self> {
-        let mut client_builder = Client::builder().timeout(timeout);
+        let mut default_headers = HeaderMap::new();
+        default_headers.insert(USER_AGENT, HeaderValue::from_str(&Self::user_agent())?);
 
-        // Configure TLS if needed
         let tls_config = TlsConfig::from_config()?;
-        if let Some(ref config) = tls_config {
-            client_builder = Self::configure_tls(client_builder, config)?;
-        }
-
-        let client = client_builder.build()?;
+        let client = match tls_config {
+            None if Self::share_clients() => Self::shared_client(timeout, &default_headers)?,
+            _ => Arc::new(Self::build_client(
+                timeout,
+                &default_headers,
+                tls_config.as_ref(),
+            )?),
+        };
 
         Ok(Self {
             client,
             host,
             auth,
-            default_headers: HeaderMap::new(),
+            default_headers,
             timeout,
             tls_config,
         })
     }
 
-    fn rebuild_client(&mut self) -> Result<()> {
+    /// The User-Agent sent with every provider request, `goose/{version}` unless
+    /// overridden with GOOSE_USER_AGENT
+    fn user_agent() -> String {
+        crate::config::Config::global()
+            .get_param::<String>("GOOSE_USER_AGENT")
+            .unwrap_or_else(|_| format!("goose/{}", env!("CARGO_PKG_VERSION")))
+    }
+
+    /// Whether clients with default settings are shared, unless GOOSE_SHARE_HTTP_CLIENT is false
+    fn share_clients() -> bool {
+        crate::config::Config::global()
+            .get_param::<bool>("GOOSE_SHARE_HTTP_CLIENT")
+            .unwrap_or(true)
+    }
+
+    fn shared_client(timeout: Duration, default_headers: &HeaderMap) -> Result<Arc<Client>> {
+        let user_agent = default_headers
+            .get(USER_AGENT)
+            .cloned()
+            .unwrap_or_else(|| HeaderValue::from_static(""));
+        let mut clients = SHARED_CLIENTS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(client) = clients.get(&(timeout, user_agent.clone())) {
+            return Ok(client.clone());
+        }
+        let client = Arc::new(Self::build_client(timeout, default_headers, None)?);
+        clients.insert((timeout, user_agent), client.clone());
+        Ok(client)
+    }
+
+    fn build_client(
+        timeout: Duration,
+        default_headers: &HeaderMap,
+        tls_config: Option<&TlsConfig>,
+    ) -> Result<Client> {
         let mut client_builder = Client::builder()
-            .timeout(self.timeout)
-            .default_headers(self.default_headers.clone());
+            .timeout(timeout)
+            .default_headers(default_headers.clone());
 
         // Configure TLS if needed
-        if let Some(ref tls_config) = self.tls_config {
+        if let Some(tls_config) = tls_config {
             client_builder = Self::configure_tls(client_builder, tls_config)?;
         }
 
-        self.client = client_builder.build()?;
+        Ok(client_builder.build()?)
+    }
+
+    fn rebuild_client(&mut self) -> Result<()> {
+        self.client = Arc::new(Self::build_client(
+            self.timeout,
+            &self.default_headers,
+            self.tls_config.as_ref(),
+        )?);
         Ok(())
     }
 
@@ -259,7 +334,7 @@ impl ApiClient {
     }
 
     pub fn with_headers(mut self, headers: HeaderMap) -> Result<Self> {
-        self.default_headers = headers;
+        self.default_headers.extend(headers);
         self.rebuild_client()?;
         Ok(self)
     }
@@ -296,7 +371,7 @@ impl ApiClient {
         self.request(path).response_get().await
     }
 
-    fn build_url(&self, path: &str) -> Result<url::Url> {
+    pub(crate) fn build_url(&self, path: &str) -> Result<url::Url> {
         use url::Url;
         let mut base_url =
             Url::parse(&self.host).map_err(|e| anyhow::anyhow!("Invalid base URL: {}", e))?;
@@ -367,7 +442,7 @@ impl<'a> ApiRequestBuilder<'a> {
         F: FnOnce(url::Url, &Client) -> reqwest::RequestBuilder,
     {
         let url = self.client.build_url(self.path)?;
-        let mut request = request_builder(url, &self.client.client);
+        let mut request = request_builder(url, self.client.client.as_ref());
         request = request.headers(self.headers.clone());
 
         if let Some(session_id) = crate::session_context::current_session_id() {
@@ -435,6 +510,48 @@ mod tests {
         .await;
     }
 
+    #[test]
+    fn test_user_agent_header() {
+        let client = ApiClient::new(
+            "http://localhost:8080".to_string(),
+            AuthMethod::BearerToken("test-token".to_string()),
+        )
+        .unwrap();
+
+        let user_agent = client.default_headers.get(USER_AGENT).unwrap();
+        assert!(user_agent.to_str().unwrap().starts_with("goose"));
+
+        let client = client.with_header("User-Agent", "custom-agent").unwrap();
+        assert_eq!(
+            client.default_headers.get(USER_AGENT).unwrap(),
+            "custom-agent"
+        );
+    }
+
+    #[test]
+    fn test_default_clients_share_connection_pool() {
+        let openai = ApiClient::new(
+            "https://api.openai.com".to_string(),
+            AuthMethod::BearerToken("openai-token".to_string()),
+        )
+        .unwrap();
+        let anthropic = ApiClient::new(
+            "https://api.anthropic.com".to_string(),
+            AuthMethod::ApiKey {
+                header_name: "x-api-key".to_string(),
+                key: "anthropic-key".to_string(),
+            },
+        )
+        .unwrap();
+        assert!(Arc::ptr_eq(&openai.client, &anthropic.client));
+
+        // Custom default headers need a client of their own
+        let custom = anthropic
+            .with_header("anthropic-version", "2023-06-01")
+            .unwrap();
+        assert!(!Arc::ptr_eq(&openai.client, &custom.client));
+    }
+
     #[tokio::test]
     async fn test_no_session_id_header_when_absent() {
         let client = ApiClient::new(
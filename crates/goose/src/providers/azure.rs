@@ -3,7 +3,7 @@ use async_trait::async_trait;
 use serde::Serialize;
 use serde_json::Value;
 
-use super::api_client::{ApiClient, AuthMethod, AuthProvider};
+use super::api_client::{ApiClient, AuthMethod, AuthProvider, DEFAULT_REQUEST_TIMEOUT};
 use super::azureauth::{AuthError, AzureAuth};
 use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::errors::ProviderError;
@@ -73,7 +73,7 @@ impl AzureProvider {
     pub async fn from_env(model: ModelConfig) -> Result<Self> {
         let config = crate::config::Config::global();
         let endpoint: String = config.get_param("AZURE_OPENAI_ENDPOINT")?;
-        let deployment_name: String = config.get_param("AZURE_OPENAI_DEPLOYMENT_NAME")?;
+        let deployment_name = deployment_name(config)?;
         let api_version: String = config
             .get_param("AZURE_OPENAI_API_VERSION")
             .unwrap_or_else(|_| AZURE_DEFAULT_API_VERSION.to_string());
@@ -88,7 +88,11 @@ impl AzureProvider {
         })?;
 
         let auth_provider = AzureAuthProvider { auth };
-        let api_client = ApiClient::new(endpoint, AuthMethod::Custom(Box::new(auth_provider)))?;
+        let api_client = ApiClient::with_timeout(
+            endpoint,
+            AuthMethod::Custom(Box::new(auth_provider)),
+            model.request_timeout_or(DEFAULT_REQUEST_TIMEOUT),
+        )?;
 
         Ok(Self {
             api_client,
@@ -100,17 +104,29 @@ impl AzureProvider {
     }
 
     async fn post(&self, payload: &Value) -> Result<Value, ProviderError> {
-        // Build the path for Azure OpenAI
-        let path = format!(
-            "openai/deployments/{}/chat/completions?api-version={}",
-            self.deployment_name, self.api_version
-        );
-
+        let path = chat_completions_path(&self.deployment_name, &self.api_version);
         let response = self.api_client.response_post(&path, payload).await?;
         handle_response_openai_compat(response).await
     }
 }
 
+/// The deployment to route requests to, from AZURE_OPENAI_DEPLOYMENT_NAME or its shorter
+/// alias AZURE_OPENAI_DEPLOYMENT
+fn deployment_name(config: &crate::config::Config) -> Result<String> {
+    config
+        .get_param::<String>("AZURE_OPENAI_DEPLOYMENT_NAME")
+        .or_else(|_| config.get_param::<String>("AZURE_OPENAI_DEPLOYMENT"))
+        .map_err(Into::into)
+}
+
+/// Azure routes by deployment rather than by the model in the request body
+fn chat_completions_path(deployment_name: &str, api_version: &str) -> String {
+    format!(
+        "openai/deployments/{}/chat/completions?api-version={}",
+        deployment_name, api_version
+    )
+}
+
 #[async_trait]
 impl Provider for AzureProvider {
     fn metadata() -> ProviderMetadata {
@@ -168,3 +184,43 @@ impl Provider for AzureProvider {
         Ok((message, ProviderUsage::new(response_model, usage)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chat_completions_url_from_env() {
+        temp_env::with_vars(
+            [
+                ("AZURE_OPENAI_DEPLOYMENT_NAME", None),
+                ("AZURE_OPENAI_DEPLOYMENT", Some("gpt-4o-prod")),
+            ],
+            || {
+                let deployment = deployment_name(crate::config::Config::global()).unwrap();
+                assert_eq!(deployment, "gpt-4o-prod");
+
+                for endpoint in [
+                    "https://contoso.openai.azure.com",
+                    "https://contoso.openai.azure.com/",
+                ] {
+                    let client = ApiClient::new(
+                        endpoint.to_string(),
+                        AuthMethod::ApiKey {
+                            header_name: "api-key".to_string(),
+                            key: "test-key".to_string(),
+                        },
+                    )
+                    .unwrap();
+                    let url = client
+                        .build_url(&chat_completions_path(&deployment, "2024-10-21"))
+                        .unwrap();
+                    assert_eq!(
+                        url.as_str(),
+                        "https://contoso.openai.azure.com/openai/deployments/gpt-4o-prod/chat/completions?api-version=2024-10-21"
+                    );
+                }
+            },
+        );
+    }
+}
@@ -3,11 +3,13 @@ use futures::Stream;
 use serde::{Deserialize, Serialize};
 
 use super::errors::ProviderError;
+use super::pricing::PricingInfo;
 use super::retry::RetryConfig;
 use crate::config::base::ConfigValue;
 use crate::conversation::message::Message;
 use crate::conversation::Conversation;
 use crate::model::ModelConfig;
+use crate::token_counter::create_token_counter;
 use crate::utils::safe_truncate;
 use rmcp::model::Tool;
 use utoipa::ToSchema;
@@ -15,7 +17,7 @@ use utoipa::ToSchema;
 use once_cell::sync::Lazy;
 use std::ops::{Add, AddAssign};
 use std::pin::Pin;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 /// A global store for the current model being used, we use this as when a provider returns, it tells us the real model, not an alias
 pub static CURRENT_MODEL: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
@@ -226,15 +228,61 @@ impl ConfigKey {
     }
 }
 
+/// Rate-limit headroom a provider reported in its `x-ratelimit-*` response headers
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitInfo {
+    pub limit_requests: Option<u64>,
+    pub remaining_requests: Option<u64>,
+    /// Time until the request limit resets, as sent by the provider (e.g. "1s", "6m0s")
+    pub reset_requests: Option<String>,
+    pub limit_tokens: Option<u64>,
+    pub remaining_tokens: Option<u64>,
+    pub reset_tokens: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderUsage {
+    /// Name of the provider that served the request, see [`Provider::get_name`]
+    #[serde(default)]
+    pub provider: String,
     pub model: String,
     pub usage: Usage,
+    /// Rate-limit headroom after this request, for providers that report it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitInfo>,
+    /// Served from a response cache instead of the provider, so the tokens cost nothing
+    #[serde(default)]
+    pub cached: bool,
 }
 
 impl ProviderUsage {
     pub fn new(model: String, usage: Usage) -> Self {
-        Self { model, usage }
+        Self {
+            provider: String::new(),
+            model,
+            usage,
+            rate_limit: None,
+            cached: false,
+        }
+    }
+
+    pub fn with_rate_limit(mut self, rate_limit: Option<RateLimitInfo>) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// Mark this usage as served from a response cache
+    pub fn with_cached(mut self, cached: bool) -> Self {
+        self.cached = cached;
+        self
+    }
+
+    /// Attribute this usage to `provider` unless a provider is already recorded
+    pub fn with_provider(mut self, provider: &str) -> Self {
+        if self.provider.is_empty() {
+            self.provider = provider.to_string();
+        }
+        self
     }
 
     /// Ensures this ProviderUsage has token counts, estimating them if necessary
@@ -257,11 +305,14 @@ impl ProviderUsage {
     }
 
     /// Combine this ProviderUsage with another, adding their token counts
-    /// Uses the model from this ProviderUsage
+    /// Uses the model from this ProviderUsage and the latest rate-limit headroom
     pub fn combine_with(&self, other: &ProviderUsage) -> ProviderUsage {
         ProviderUsage {
+            provider: self.provider.clone(),
             model: self.model.clone(),
             usage: self.usage + other.usage,
+            rate_limit: other.rate_limit.clone().or_else(|| self.rate_limit.clone()),
+            cached: self.cached && other.cached,
         }
     }
 }
@@ -271,6 +322,15 @@ pub struct Usage {
     pub input_tokens: Option<i32>,
     pub output_tokens: Option<i32>,
     pub total_tokens: Option<i32>,
+    /// Reasoning tokens reported by the provider, always counted in `output_tokens` as well.
+    /// OpenAI and Anthropic include them in their output count; Google reports them
+    /// separately, so its usage parser adds them in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_tokens: Option<i32>,
+    /// Number of candidate completions the provider returned, when it returned more than
+    /// one; only the first becomes the reply but all of them are billed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub choices: Option<u32>,
 }
 
 fn sum_optionals<T>(a: Option<T>, b: Option<T>) -> Option<T>
@@ -294,6 +354,9 @@ impl Add for Usage {
             sum_optionals(self.output_tokens, other.output_tokens),
             sum_optionals(self.total_tokens, other.total_tokens),
         )
+        .with_reasoning_tokens(sum_optionals(self.reasoning_tokens, other.reasoning_tokens))
+        // A count of alternatives per response rather than tokens, so it is not summed
+        .with_choices(self.choices.max(other.choices))
     }
 }
 
@@ -324,12 +387,41 @@ impl Usage {
             input_tokens,
             output_tokens,
             total_tokens: calculated_total,
+            reasoning_tokens: None,
+            choices: None,
         }
     }
+
+    pub fn with_reasoning_tokens(mut self, reasoning_tokens: Option<i32>) -> Self {
+        self.reasoning_tokens = reasoning_tokens;
+        self
+    }
+
+    pub fn with_choices(mut self, choices: Option<u32>) -> Self {
+        self.choices = choices;
+        self
+    }
+
+    /// Estimated cost in the pricing currency. Reasoning tokens are billed at the output
+    /// rate as part of `output_tokens`.
+    pub fn cost(&self, pricing: &PricingInfo) -> f64 {
+        let input = self.input_tokens.unwrap_or(0) as f64;
+        let output = self.output_tokens.unwrap_or(0) as f64;
+        pricing.input_cost * input + pricing.output_cost * output
+    }
 }
 
 use async_trait::async_trait;
 
+/// Per-request hints from the caller; providers ignore what they have no use for
+#[derive(Debug, Clone, Default)]
+pub struct CompletionOptions {
+    /// The caller's estimate of the input tokens in the request, so it needn't be recounted
+    pub input_tokens: Option<usize>,
+    /// Use the non-streaming `complete` path even when the provider can stream
+    pub no_stream: bool,
+}
+
 /// Trait for LeadWorkerProvider-specific functionality
 pub trait LeadWorkerProviderTrait {
     /// Get information about the lead and worker models for logging
@@ -368,8 +460,39 @@ pub trait Provider: Send + Sync {
         tools: &[Tool],
     ) -> Result<(Message, ProviderUsage), ProviderError> {
         let model_config = self.get_model_config();
-        self.complete_with_model(&model_config, system, messages, tools)
-            .await
+        let (message, usage) = self
+            .complete_with_model(&model_config, system, messages, tools)
+            .await?;
+        Ok((message, usage.with_provider(self.get_name())))
+    }
+
+    /// Like `complete`, with hints from the caller. A configured `max_tokens` is shrunk to
+    /// the room left in the context window, using the caller's input token estimate or
+    /// counting the request when there is none.
+    async fn complete_with_options(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+        options: &CompletionOptions,
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let mut model_config = self.get_model_config();
+        if model_config.max_tokens.is_some() {
+            let input_tokens = match options.input_tokens {
+                Some(input_tokens) => Some(input_tokens),
+                None => create_token_counter()
+                    .await
+                    .ok()
+                    .map(|counter| counter.count_chat_tokens(system, messages, tools)),
+            };
+            if let Some(input_tokens) = input_tokens {
+                model_config = model_config.fit_max_tokens(input_tokens);
+            }
+        }
+        let (message, usage) = self
+            .complete_with_model(&model_config, system, messages, tools)
+            .await?;
+        Ok((message, usage.with_provider(self.get_name())))
     }
 
     // Check if a fast model is configured, otherwise fall back to regular model
@@ -379,10 +502,11 @@ pub trait Provider: Send + Sync {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<(Message, ProviderUsage), ProviderError> {
-        let model_config = self.get_model_config();
+        // Fast calls name sessions and summarize, so a prefill meant for replies doesn't apply
+        let model_config = self.get_model_config().with_prefill(None);
         let fast_config = model_config.use_fast_model();
 
-        match self
+        let result = match self
             .complete_with_model(&fast_config, system, messages, tools)
             .await
         {
@@ -401,14 +525,25 @@ pub trait Provider: Send + Sync {
                     Err(e)
                 }
             }
-        }
+        };
+        result.map(|(message, usage)| (message, usage.with_provider(self.get_name())))
     }
 
     /// Get the model config from the provider
     fn get_model_config(&self) -> ModelConfig;
 
+    /// A provider of the same kind using `model`, e.g. to switch to the fallback model.
+    ///
+    /// The default builds a fresh provider from the registry by name. Providers that wrap
+    /// others must override this to rebuild their inner providers, or the wrapping is lost.
+    async fn with_model(&self, model: ModelConfig) -> Result<Arc<dyn Provider>, ProviderError> {
+        crate::providers::create(self.get_name(), model)
+            .await
+            .map_err(|e| ProviderError::ExecutionError(e.to_string()))
+    }
+
     fn retry_config(&self) -> RetryConfig {
-        RetryConfig::default()
+        RetryConfig::from_config()
     }
 
     async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
@@ -558,6 +693,30 @@ mod tests {
         assert_eq!(usage.total_tokens, Some(30));
     }
 
+    #[test]
+    fn test_usage_cost_includes_reasoning_tokens() {
+        let pricing = PricingInfo {
+            input_cost: 0.001,
+            output_cost: 0.002,
+            context_length: None,
+        };
+
+        // Reasoning is part of the output tokens, so it is not billed twice
+        let usage = Usage::new(Some(100), Some(50), None).with_reasoning_tokens(Some(30));
+        assert!((usage.cost(&pricing) - 0.2).abs() < 1e-9);
+
+        let combined = usage + Usage::new(Some(1), Some(1), None).with_reasoning_tokens(Some(2));
+        assert_eq!(combined.reasoning_tokens, Some(32));
+    }
+
+    #[test]
+    fn test_usage_choices_are_not_summed() {
+        let first = Usage::new(Some(10), Some(20), None).with_choices(Some(3));
+        let second = Usage::new(Some(5), Some(5), None).with_choices(Some(2));
+        assert_eq!((first + second).choices, Some(3));
+        assert_eq!((first + Usage::default()).choices, Some(3));
+    }
+
     #[test]
     fn test_usage_serialization() -> Result<()> {
         let usage = Usage::new(Some(10), Some(20), Some(30));
@@ -672,4 +831,86 @@ mod tests {
         assert_eq!(info.output_token_cost, Some(0.00001));
         assert_eq!(info.currency, Some("$".to_string()));
     }
+
+    struct MaxTokensRecorder {
+        model_config: ModelConfig,
+        max_tokens: std::sync::Mutex<Option<Option<i32>>>,
+    }
+
+    #[async_trait]
+    impl Provider for MaxTokensRecorder {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        fn get_name(&self) -> &str {
+            "recorder"
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            self.model_config.clone()
+        }
+
+        async fn complete_with_model(
+            &self,
+            model_config: &ModelConfig,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            *self.max_tokens.lock().unwrap() = Some(model_config.max_tokens);
+            Ok((
+                Message::assistant().with_text("ok"),
+                ProviderUsage::new("test-model".to_string(), Usage::default()),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_complete_with_options_sizes_max_tokens_from_hint() {
+        let mut model_config = ModelConfig::new_or_fail("test-model").with_max_tokens(Some(4096));
+        model_config.context_limit = Some(10_000);
+        let provider = MaxTokensRecorder {
+            model_config,
+            max_tokens: std::sync::Mutex::new(None),
+        };
+
+        let options = CompletionOptions {
+            input_tokens: Some(8_000),
+            ..Default::default()
+        };
+        let (_, usage) = provider
+            .complete_with_options("system", &[], &[], &options)
+            .await
+            .unwrap();
+        assert_eq!(*provider.max_tokens.lock().unwrap(), Some(Some(2_000)));
+        // The hint only sizes the request; it is not reported as usage
+        assert_eq!(usage.usage.input_tokens, None);
+
+        // A hint that leaves enough room keeps the configured limit
+        let options = CompletionOptions {
+            input_tokens: Some(1_000),
+            ..Default::default()
+        };
+        provider
+            .complete_with_options("system", &[], &[], &options)
+            .await
+            .unwrap();
+        assert_eq!(*provider.max_tokens.lock().unwrap(), Some(Some(4_096)));
+
+        provider
+            .complete_with_options("system", &[], &[], &CompletionOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(*provider.max_tokens.lock().unwrap(), Some(Some(4_096)));
+
+        // Without a hint the request is counted
+        let long = Message::user().with_text("word ".repeat(9_000));
+        provider
+            .complete_with_options("system", &[long], &[], &CompletionOptions::default())
+            .await
+            .unwrap();
+        let max_tokens = provider.max_tokens.lock().unwrap().unwrap().unwrap();
+        assert!(max_tokens < 4_096);
+    }
 }
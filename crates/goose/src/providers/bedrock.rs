@@ -5,7 +5,7 @@ use super::errors::ProviderError;
 use super::retry::{ProviderRetry, RetryConfig};
 use crate::conversation::message::Message;
 use crate::model::ModelConfig;
-use crate::providers::utils::RequestLog;
+use crate::providers::utils::{strip_empty_content, RequestLog};
 use anyhow::Result;
 use async_trait::async_trait;
 use aws_sdk_bedrockruntime::config::ProvideCredentials;
@@ -119,7 +119,7 @@ impl BedrockProvider {
             .system(bedrock::SystemContentBlock::Text(system.to_string()))
             .model_id(model_name.to_string())
             .set_messages(Some(
-                messages
+                strip_empty_content(messages)
                     .iter()
                     .filter(|m| m.is_agent_visible())
                     .map(to_bedrock_message)
@@ -149,7 +149,7 @@ impl BedrockProvider {
                         .unwrap_or_default()
                         .contains("Input is too long for requested model.") =>
                 {
-                    ProviderError::ContextLengthExceeded(format!(
+                    ProviderError::context_length_exceeded(format!(
                         "Failed to call Bedrock: {:?}",
                         err
                     ))
@@ -179,7 +179,12 @@ impl Provider for BedrockProvider {
             BEDROCK_DEFAULT_MODEL,
             BEDROCK_KNOWN_MODELS.to_vec(),
             BEDROCK_DOC_LINK,
-            vec![ConfigKey::new("AWS_PROFILE", true, false, Some("default"))],
+            vec![
+                ConfigKey::new("AWS_PROFILE", true, false, Some("default")),
+                ConfigKey::new("AWS_REGION", false, false, None),
+                ConfigKey::new("AWS_ACCESS_KEY_ID", false, true, None),
+                ConfigKey::new("AWS_SECRET_ACCESS_KEY", false, true, None),
+            ],
         )
     }
 
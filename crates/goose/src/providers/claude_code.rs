@@ -230,7 +230,7 @@ impl ClaudeCodeProvider {
 
         let response_message = Message::new(
             Role::Assistant,
-            chrono::Utc::now().timestamp(),
+            crate::clock::now().timestamp(),
             message_content,
         );
 
@@ -374,7 +374,7 @@ impl ClaudeCodeProvider {
 
         let message = Message::new(
             Role::Assistant,
-            chrono::Utc::now().timestamp(),
+            crate::clock::now().timestamp(),
             vec![MessageContent::text(description.clone())],
         );
 
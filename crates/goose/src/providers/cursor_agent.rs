@@ -147,7 +147,7 @@ impl CursorAgentProvider {
                         let message_content = vec![MessageContent::text(text_content)];
                         let response_message = Message::new(
                             Role::Assistant,
-                            chrono::Utc::now().timestamp(),
+                            crate::clock::now().timestamp(),
                             message_content,
                         );
 
@@ -165,7 +165,7 @@ impl CursorAgentProvider {
         let message_content = vec![MessageContent::text(response_text)];
         let response_message = Message::new(
             Role::Assistant,
-            chrono::Utc::now().timestamp(),
+            crate::clock::now().timestamp(),
             message_content,
         );
         let usage = Usage::default();
@@ -307,7 +307,7 @@ impl CursorAgentProvider {
 
         let message = Message::new(
             Role::Assistant,
-            chrono::Utc::now().timestamp(),
+            crate::clock::now().timestamp(),
             vec![MessageContent::text(description.clone())],
         );
 
@@ -17,8 +17,8 @@ use super::formats::databricks::{create_request, response_to_message};
 use super::oauth;
 use super::retry::ProviderRetry;
 use super::utils::{
-    get_model, handle_response_openai_compat, map_http_error_to_provider_error, ImageFormat,
-    RequestLog,
+    get_model, handle_response_openai_compat, map_http_error_to_provider_error,
+    retry_after_from_headers, validate_response_shape, ImageFormat, RequestLog, ResponseShape,
 };
 use crate::config::ConfigError;
 use crate::conversation::message::Message;
@@ -138,8 +138,11 @@ impl DatabricksProvider {
         let auth_method =
             AuthMethod::Custom(Box::new(DatabricksAuthProvider { auth: auth.clone() }));
 
-        let api_client =
-            ApiClient::with_timeout(host, auth_method, Duration::from_secs(DEFAULT_TIMEOUT_SECS))?;
+        let api_client = ApiClient::with_timeout(
+            host,
+            auth_method,
+            model.request_timeout_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS)),
+        )?;
 
         // Create the provider without the fast model first
         let mut provider = Self {
@@ -213,7 +216,11 @@ impl DatabricksProvider {
         let auth_method =
             AuthMethod::Custom(Box::new(DatabricksAuthProvider { auth: auth.clone() }));
 
-        let api_client = ApiClient::with_timeout(host, auth_method, Duration::from_secs(600))?;
+        let api_client = ApiClient::with_timeout(
+            host,
+            auth_method,
+            model.request_timeout_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS)),
+        )?;
 
         Ok(Self {
             api_client,
@@ -296,6 +303,7 @@ impl Provider for DatabricksProvider {
             .with_retry(|| self.post(payload.clone(), Some(&model_config.model_name)))
             .await?;
 
+        validate_response_shape(self.get_name(), ResponseShape::OpenAi, &response)?;
         let message = response_to_message(&response)?;
         let usage = response.get("usage").map(get_usage).unwrap_or_else(|| {
             tracing::debug!("Failed to get usage data");
@@ -334,11 +342,13 @@ impl Provider for DatabricksProvider {
                 let resp = self.api_client.response_post(&path, &payload).await?;
                 if !resp.status().is_success() {
                     let status = resp.status();
+                    let retry_after = retry_after_from_headers(resp.headers());
                     let error_text = resp.text().await.unwrap_or_default();
 
                     // Parse as JSON if possible to pass to map_http_error_to_provider_error
                     let json_payload = serde_json::from_str::<Value>(&error_text).ok();
-                    return Err(map_http_error_to_provider_error(status, json_payload));
+                    return Err(map_http_error_to_provider_error(status, json_payload)
+                        .with_retry_delay(retry_after));
                 }
                 Ok(resp)
             })
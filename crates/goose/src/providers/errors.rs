@@ -1,3 +1,5 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
 use reqwest::StatusCode;
 use std::time::Duration;
 use thiserror::Error;
@@ -7,8 +9,15 @@ pub enum ProviderError {
     #[error("Authentication error: {0}")]
     Authentication(String),
 
-    #[error("Context length exceeded: {0}")]
-    ContextLengthExceeded(String),
+    #[error("Context length exceeded: {details}")]
+    ContextLengthExceeded {
+        details: String,
+        /// The model's context window in tokens, when the provider reported it
+        limit: Option<usize>,
+    },
+
+    #[error("Model not found: {0}")]
+    ModelNotFound(String),
 
     #[error("Rate limit exceeded: {details}")]
     RateLimitExceeded {
@@ -19,12 +28,26 @@ pub enum ProviderError {
     #[error("Server error: {0}")]
     ServerError(String),
 
+    /// A server error whose response said when to retry, typically a 503 with Retry-After
+    #[error("Server error: {details}")]
+    ServiceUnavailable {
+        details: String,
+        retry_delay: Option<Duration>,
+    },
+
     #[error("Request failed: {0}")]
     RequestFailed(String),
 
+    /// The request timed out or could not connect, so it never got an answer
+    #[error("Network error: {0}")]
+    NetworkError(String),
+
     #[error("Execution error: {0}")]
     ExecutionError(String),
 
+    #[error("Retry budget for this turn exhausted: {0}")]
+    RetryBudgetExhausted(String),
+
     #[error("Usage data error: {0}")]
     UsageError(String),
 
@@ -32,6 +55,84 @@ pub enum ProviderError {
     NotImplemented(String),
 }
 
+impl ProviderError {
+    /// A context length error for a provider's message, with the token limit if the message
+    /// states it
+    pub fn context_length_exceeded(details: impl Into<String>) -> Self {
+        let details = details.into();
+        let limit = parse_context_limit(&details);
+        ProviderError::ContextLengthExceeded { details, limit }
+    }
+
+    /// Set how long to wait before retrying a rate limited or unavailable server, unless the
+    /// error already carries a delay taken from the response body
+    pub fn with_retry_delay(self, delay: Option<Duration>) -> Self {
+        match (self, delay) {
+            (
+                ProviderError::RateLimitExceeded {
+                    details,
+                    retry_delay: None,
+                },
+                delay,
+            ) => ProviderError::RateLimitExceeded {
+                details,
+                retry_delay: delay,
+            },
+            (ProviderError::ServerError(details), Some(delay)) => {
+                ProviderError::ServiceUnavailable {
+                    details,
+                    retry_delay: Some(delay),
+                }
+            }
+            (other, _) => other,
+        }
+    }
+
+    /// How long the provider asked to wait before retrying, if it said
+    pub fn retry_delay(&self) -> Option<Duration> {
+        match self {
+            ProviderError::RateLimitExceeded { retry_delay, .. }
+            | ProviderError::ServiceUnavailable { retry_delay, .. } => *retry_delay,
+            _ => None,
+        }
+    }
+
+    /// Whether the failure is likely to clear up on its own or with another provider: server
+    /// errors, rate limits and dropped connections. Context length, authentication and request
+    /// errors such as moderation blocks would fail the same way anywhere.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ProviderError::ServerError(_)
+            | ProviderError::ServiceUnavailable { .. }
+            | ProviderError::RateLimitExceeded { .. }
+            | ProviderError::NetworkError(_)
+            | ProviderError::RetryBudgetExhausted(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// How providers state the context window in their errors: OpenAI and compatible APIs,
+/// Anthropic and Gemini
+static CONTEXT_LIMIT_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r"maximum context length is (\d+)",
+        r"tokens > (\d+) maximum",
+        r"maximum number of tokens allowed \((\d+)\)",
+    ]
+    .iter()
+    .map(|pattern| Regex::new(pattern).unwrap())
+    .collect()
+});
+
+fn parse_context_limit(details: &str) -> Option<usize> {
+    CONTEXT_LIMIT_PATTERNS.iter().find_map(|pattern| {
+        pattern
+            .captures(details)
+            .and_then(|captures| captures[1].parse().ok())
+    })
+}
+
 impl From<anyhow::Error> for ProviderError {
     fn from(error: anyhow::Error) -> Self {
         if let Some(reqwest_err) = error.downcast_ref::<reqwest::Error>() {
@@ -63,6 +164,9 @@ impl From<anyhow::Error> for ProviderError {
             } else {
                 format!("{} ({})", reqwest_err, details.join(", "))
             };
+            if reqwest_err.is_timeout() || reqwest_err.is_connect() {
+                return ProviderError::NetworkError(msg);
+            }
             return ProviderError::RequestFailed(msg);
         }
         ProviderError::ExecutionError(error.to_string())
@@ -71,7 +175,11 @@ impl From<anyhow::Error> for ProviderError {
 
 impl From<reqwest::Error> for ProviderError {
     fn from(error: reqwest::Error) -> Self {
-        ProviderError::RequestFailed(error.to_string())
+        if error.is_timeout() || error.is_connect() {
+            ProviderError::NetworkError(error.to_string())
+        } else {
+            ProviderError::RequestFailed(error.to_string())
+        }
     }
 }
 
@@ -185,6 +293,10 @@ impl OpenAIError {
             false
         }
     }
+
+    pub fn is_model_not_found(&self) -> bool {
+        self.code.as_deref() == Some("model_not_found")
+    }
 }
 
 impl std::fmt::Display for OpenAIError {
@@ -215,3 +327,32 @@ impl std::fmt::Display for OpenAIError {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_length_exceeded_reads_the_limit() {
+        let limit = |details: &str| match ProviderError::context_length_exceeded(details) {
+            ProviderError::ContextLengthExceeded { limit, .. } => limit,
+            other => panic!("unexpected error {:?}", other),
+        };
+        assert_eq!(
+            limit("This model's maximum context length is 128000 tokens. However, your messages resulted in 130412 tokens."),
+            Some(128000)
+        );
+        assert_eq!(
+            limit("prompt is too long: 215426 tokens > 200000 maximum"),
+            Some(200000)
+        );
+        assert_eq!(
+            limit("The input token count (1200000) exceeds the maximum number of tokens allowed (1048576)."),
+            Some(1048576)
+        );
+        assert_eq!(
+            limit("Failed to call Bedrock: Input is too long for requested model."),
+            None
+        );
+    }
+}
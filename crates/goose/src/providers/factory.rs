@@ -8,12 +8,14 @@ use super::{
     claude_code::ClaudeCodeProvider,
     cursor_agent::CursorAgentProvider,
     databricks::DatabricksProvider,
+    fallback::FallbackProvider,
     gcpvertexai::GcpVertexAIProvider,
     gemini_cli::GeminiCliProvider,
     githubcopilot::GithubCopilotProvider,
     google::GoogleProvider,
     lead_worker::LeadWorkerProvider,
     litellm::LiteLLMProvider,
+    llamacpp::LlamaCppProvider,
     ollama::OllamaProvider,
     openai::OpenAiProvider,
     openrouter::OpenRouterProvider,
@@ -65,6 +67,8 @@ async fn init_registry() -> RwLock<ProviderRegistry> {
         );
         registry.register::<GoogleProvider, _>(|m| Box::pin(GoogleProvider::from_env(m)), true);
         registry.register::<LiteLLMProvider, _>(|m| Box::pin(LiteLLMProvider::from_env(m)), false);
+        registry
+            .register::<LlamaCppProvider, _>(|m| Box::pin(LlamaCppProvider::from_env(m)), false);
         registry.register::<OllamaProvider, _>(|m| Box::pin(OllamaProvider::from_env(m)), true);
         registry.register::<OpenAiProvider, _>(|m| Box::pin(OpenAiProvider::from_env(m)), true);
         registry
@@ -126,13 +130,43 @@ async fn get_from_registry(name: &str) -> Result<ProviderEntry> {
 pub async fn create(name: &str, model: ModelConfig) -> Result<Arc<dyn Provider>> {
     let config = crate::config::Config::global();
 
-    if let Ok(lead_model_name) = config.get_param::<String>("GOOSE_LEAD_MODEL") {
+    let primary = if let Ok(lead_model_name) = config.get_param::<String>("GOOSE_LEAD_MODEL") {
         tracing::info!("Creating lead/worker provider from environment variables");
-        return create_lead_worker_from_env(name, &model, &lead_model_name).await;
+        create_lead_worker_from_env(name, &model, &lead_model_name).await?
+    } else {
+        let constructor = get_from_registry(name).await?.constructor.clone();
+        constructor(model).await?
+    };
+
+    match config.get_param::<String>("GOOSE_FALLBACK_PROVIDERS") {
+        Ok(fallbacks) => create_fallback_from_env(primary, &fallbacks).await,
+        Err(_) => Ok(primary),
+    }
+}
+
+/// Wrap the primary provider with the comma-separated GOOSE_FALLBACK_PROVIDERS, each using
+/// its default model
+async fn create_fallback_from_env(
+    primary: Arc<dyn Provider>,
+    fallbacks: &str,
+) -> Result<Arc<dyn Provider>> {
+    let names = parse_provider_list(fallbacks);
+    if names.is_empty() {
+        return Ok(primary);
     }
 
-    let constructor = get_from_registry(name).await?.constructor.clone();
-    constructor(model).await
+    let mut providers = vec![primary];
+    for name in names {
+        providers.push(create_with_default_model(name).await?);
+    }
+    Ok(Arc::new(FallbackProvider::new(providers)?))
+}
+
+fn parse_provider_list(list: &str) -> Vec<&str> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect()
 }
 
 pub async fn create_with_default_model(name: impl AsRef<str>) -> Result<Arc<dyn Provider>> {
@@ -274,6 +308,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_fallback_provider_list() {
+        assert_eq!(
+            parse_provider_list(" anthropic, openai ,,ollama"),
+            vec!["anthropic", "openai", "ollama"]
+        );
+        assert!(parse_provider_list(" , ").is_empty());
+    }
+
     #[tokio::test]
     async fn test_create_lead_worker_provider() {
         let _guard = EnvVarGuard::new(&[
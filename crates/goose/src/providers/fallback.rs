@@ -0,0 +1,334 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::StreamExt;
+use std::sync::Arc;
+
+use super::base::{
+    stream_from_single_message, LeadWorkerProviderTrait, MessageStream, Provider, ProviderMetadata,
+    ProviderUsage,
+};
+use super::errors::ProviderError;
+use crate::conversation::message::Message;
+use crate::model::ModelConfig;
+use rmcp::model::Tool;
+
+/// A provider that tries each of its providers in order, moving on to the next only when
+/// a request fails with a transient error
+pub struct FallbackProvider {
+    providers: Vec<Arc<dyn Provider>>,
+}
+
+impl FallbackProvider {
+    /// Create a new FallbackProvider; the first provider is the primary
+    pub fn new(providers: Vec<Arc<dyn Provider>>) -> Result<Self> {
+        if providers.is_empty() {
+            anyhow::bail!("FallbackProvider needs at least one provider");
+        }
+        Ok(Self { providers })
+    }
+
+    fn primary(&self) -> &Arc<dyn Provider> {
+        &self.providers[0]
+    }
+}
+
+/// Start a reply from `provider`, streamed when it can stream. Waits for the first chunk, so a
+/// request that fails before anything was shown can still move on to the next provider.
+async fn start_stream(
+    provider: &dyn Provider,
+    system: &str,
+    messages: &[Message],
+    tools: &[Tool],
+) -> Result<MessageStream, ProviderError> {
+    if !provider.supports_streaming() {
+        let (message, usage) = provider.complete(system, messages, tools).await?;
+        return Ok(stream_from_single_message(message, usage));
+    }
+    // Name the provider here; whoever reads the stream only sees the fallback provider
+    let name = provider.get_name().to_string();
+    let mut stream = provider
+        .stream(system, messages, tools)
+        .await?
+        .map(move |chunk| {
+            chunk.map(|(message, usage)| (message, usage.map(|usage| usage.with_provider(&name))))
+        });
+    match stream.next().await {
+        Some(Err(e)) => Err(e),
+        Some(Ok(first)) => Ok(Box::pin(
+            futures::stream::once(async move { Ok(first) }).chain(stream),
+        )),
+        None => Ok(Box::pin(stream)),
+    }
+}
+
+#[async_trait]
+impl Provider for FallbackProvider {
+    fn metadata() -> ProviderMetadata {
+        // This is a wrapper provider, so we return minimal metadata
+        ProviderMetadata::new(
+            "fallback",
+            "Fallback Provider",
+            "Falls back to the next provider when a request fails transiently",
+            "",
+            vec![],
+            "",
+            vec![],
+        )
+    }
+
+    fn get_name(&self) -> &str {
+        self.primary().get_name()
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.primary().get_model_config()
+    }
+
+    async fn complete_with_model(
+        &self,
+        model_config: &ModelConfig,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let mut last_error = None;
+        for (i, provider) in self.providers.iter().enumerate() {
+            // The requested model config belongs to the primary; fallbacks use their own
+            let result = if i == 0 {
+                provider
+                    .complete_with_model(model_config, system, messages, tools)
+                    .await
+            } else {
+                provider.complete(system, messages, tools).await
+            };
+            match result {
+                Err(e) if e.is_transient() => {
+                    tracing::warn!("Provider {} failed: {}", provider.get_name(), e);
+                    last_error = Some(e);
+                }
+                result => return result,
+            }
+        }
+        Err(last_error.expect("at least one provider"))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.primary().supports_streaming()
+    }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let mut last_error = None;
+        for provider in &self.providers {
+            match start_stream(provider.as_ref(), system, messages, tools).await {
+                Err(e) if e.is_transient() => {
+                    tracing::warn!("Provider {} failed: {}", provider.get_name(), e);
+                    last_error = Some(e);
+                }
+                result => return result,
+            }
+        }
+        Err(last_error.expect("at least one provider"))
+    }
+
+    async fn with_model(&self, model: ModelConfig) -> Result<Arc<dyn Provider>, ProviderError> {
+        let mut providers = self.providers.clone();
+        providers[0] = self.primary().with_model(model).await?;
+        Ok(Arc::new(Self { providers }))
+    }
+
+    async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
+        self.primary().fetch_supported_models().await
+    }
+
+    fn supports_embeddings(&self) -> bool {
+        self.primary().supports_embeddings()
+    }
+
+    async fn supports_cache_control(&self) -> bool {
+        self.primary().supports_cache_control().await
+    }
+
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, ProviderError> {
+        self.primary().create_embeddings(texts).await
+    }
+
+    fn as_lead_worker(&self) -> Option<&dyn LeadWorkerProviderTrait> {
+        self.primary().as_lead_worker()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::Usage;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct MockProvider {
+        name: &'static str,
+        error: Option<fn() -> ProviderError>,
+        calls: AtomicUsize,
+        streaming: bool,
+    }
+
+    impl MockProvider {
+        fn new(name: &'static str, error: Option<fn() -> ProviderError>) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                error,
+                calls: AtomicUsize::new(0),
+                streaming: false,
+            })
+        }
+
+        /// Streams its reply, or its error as the first chunk
+        fn streaming(name: &'static str, error: Option<fn() -> ProviderError>) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                error,
+                calls: AtomicUsize::new(0),
+                streaming: true,
+            })
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl Provider for MockProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        fn get_name(&self) -> &str {
+            self.name
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            ModelConfig::new_or_fail(self.name)
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            match self.error {
+                Some(error) => Err(error()),
+                None => Ok((
+                    Message::assistant().with_text(format!("from {}", self.name)),
+                    ProviderUsage::new(self.name.to_string(), Usage::default()),
+                )),
+            }
+        }
+
+        fn supports_streaming(&self) -> bool {
+            self.streaming
+        }
+
+        async fn stream(
+            &self,
+            system: &str,
+            messages: &[Message],
+            tools: &[Tool],
+        ) -> Result<MessageStream, ProviderError> {
+            let reply = self
+                .complete_with_model(&self.get_model_config(), system, messages, tools)
+                .await
+                .map(|(message, usage)| (Some(message), Some(usage)));
+            Ok(Box::pin(futures::stream::once(async move { reply })))
+        }
+    }
+
+    async fn complete(provider: &FallbackProvider) -> Result<String, ProviderError> {
+        let messages = vec![Message::user().with_text("hello")];
+        let (message, _) = provider.complete("system", &messages, &[]).await?;
+        Ok(message.as_concat_text())
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_on_server_error() {
+        let primary = MockProvider::new(
+            "primary",
+            Some(|| ProviderError::ServerError("503 unavailable".into())),
+        );
+        let secondary = MockProvider::new("secondary", None);
+        let provider = FallbackProvider::new(vec![primary.clone(), secondary.clone()]).unwrap();
+
+        assert_eq!(provider.get_model_config().model_name, "primary");
+        assert_eq!(complete(&provider).await.unwrap(), "from secondary");
+        assert_eq!(primary.calls(), 1);
+        assert_eq!(secondary.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stream_falls_back_before_the_first_chunk() {
+        let primary = MockProvider::streaming(
+            "primary",
+            Some(|| ProviderError::ServerError("503 unavailable".into())),
+        );
+        let secondary = MockProvider::streaming("secondary", None);
+        let provider = FallbackProvider::new(vec![primary.clone(), secondary.clone()]).unwrap();
+        assert!(provider.supports_streaming());
+
+        let messages = vec![Message::user().with_text("hello")];
+        let chunks: Vec<_> = provider
+            .stream("system", &messages, &[])
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(chunks.len(), 1);
+        let (message, usage) = chunks.into_iter().next().unwrap().unwrap();
+        assert_eq!(message.unwrap().as_concat_text(), "from secondary");
+        assert_eq!(usage.unwrap().provider, "secondary");
+        assert_eq!(primary.calls(), 1);
+        assert_eq!(secondary.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_fall_back_on_permanent_errors() {
+        let errors: [fn() -> ProviderError; 2] = [
+            || ProviderError::context_length_exceeded("too long"),
+            || ProviderError::RequestFailed("Request blocked by content moderation".into()),
+        ];
+        for error in errors {
+            let primary = MockProvider::new("primary", Some(error));
+            let secondary = MockProvider::new("secondary", None);
+            let provider = FallbackProvider::new(vec![primary.clone(), secondary.clone()]).unwrap();
+
+            assert!(complete(&provider).await.is_err());
+            assert_eq!(secondary.calls(), 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_last_error_is_returned_when_all_fail() {
+        let primary = MockProvider::new(
+            "primary",
+            Some(|| {
+                ProviderError::NetworkError(
+                    "error sending request (failed to connect to localhost:1)".into(),
+                )
+            }),
+        );
+        let secondary = MockProvider::new(
+            "secondary",
+            Some(|| ProviderError::ServerError("secondary down".into())),
+        );
+        let provider = FallbackProvider::new(vec![primary.clone(), secondary.clone()]).unwrap();
+
+        let err = complete(&provider).await.unwrap_err();
+        assert_eq!(err, ProviderError::ServerError("secondary down".into()));
+        assert_eq!(primary.calls(), 1);
+    }
+}
@@ -1,9 +1,15 @@
 use crate::conversation::message::{Message, MessageContent};
+use crate::conversation::tool_call_id::{disambiguate_tool_call_ids, ToolCallIds};
 use crate::model::ModelConfig;
 use crate::providers::base::Usage;
 use crate::providers::errors::ProviderError;
+use crate::providers::utils::{
+    clamp_content_block, log_unsupported_sampling_params, strip_empty_content,
+};
 use anyhow::{anyhow, Result};
-use rmcp::model::{object, CallToolRequestParam, ErrorCode, ErrorData, JsonObject, Role, Tool};
+use rmcp::model::{
+    object, CallToolRequestParam, ErrorCode, ErrorData, JsonObject, RawImageContent, Role, Tool,
+};
 use rmcp::object as json_object;
 use serde_json::{json, Value};
 use std::collections::HashSet;
@@ -28,6 +34,20 @@ const TOOL_USE_ID_FIELD: &str = "tool_use_id";
 const IS_ERROR_FIELD: &str = "is_error";
 const SIGNATURE_FIELD: &str = "signature";
 const DATA_FIELD: &str = "data";
+const IMAGE_TYPE: &str = "image";
+const SOURCE_FIELD: &str = "source";
+
+/// Anthropic's base64 image block for `image`
+fn image_block(image: &RawImageContent) -> Value {
+    json!({
+        TYPE_FIELD: IMAGE_TYPE,
+        SOURCE_FIELD: {
+            TYPE_FIELD: "base64",
+            "media_type": image.mime_type,
+            DATA_FIELD: image.data
+        }
+    })
+}
 
 /// Convert internal Message format to Anthropic's API message specification
 pub fn format_messages(messages: &[Message]) -> Vec<Value> {
@@ -45,7 +65,7 @@ pub fn format_messages(messages: &[Message]) -> Vec<Value> {
                 MessageContent::Text(text) => {
                     content.push(json!({
                         TYPE_FIELD: TEXT_TYPE,
-                        TEXT_TYPE: text.text
+                        TEXT_TYPE: clamp_content_block(&text.text)
                     }));
                 }
                 MessageContent::ToolRequest(tool_request) => {
@@ -71,11 +91,30 @@ pub fn format_messages(messages: &[Message]) -> Vec<Value> {
                             .filter_map(|c| c.as_text().map(|t| t.text.clone()))
                             .collect::<Vec<_>>()
                             .join("\n");
+                        let images: Vec<Value> = result
+                            .iter()
+                            .filter_map(|c| c.as_image().map(image_block))
+                            .collect();
+
+                        // Tool results may hold image blocks, which then need the list form
+                        let result_content = if images.is_empty() {
+                            json!(clamp_content_block(&text))
+                        } else {
+                            let mut blocks = Vec::new();
+                            if !text.is_empty() {
+                                blocks.push(json!({
+                                    TYPE_FIELD: TEXT_TYPE,
+                                    TEXT_TYPE: clamp_content_block(&text)
+                                }));
+                            }
+                            blocks.extend(images);
+                            json!(blocks)
+                        };
 
                         content.push(json!({
                             TYPE_FIELD: TOOL_RESULT_TYPE,
                             TOOL_USE_ID_FIELD: tool_response.id,
-                            CONTENT_FIELD: text
+                            CONTENT_FIELD: result_content
                         }));
                     }
                     Err(tool_error) => {
@@ -106,7 +145,9 @@ pub fn format_messages(messages: &[Message]) -> Vec<Value> {
                         DATA_FIELD: redacted.data
                     }));
                 }
-                MessageContent::Image(_) => continue, // Anthropic doesn't support image content yet
+                MessageContent::Image(image) => {
+                    content.push(image_block(image));
+                }
                 MessageContent::FrontendToolRequest(tool_request) => {
                     if let Ok(tool_call) = &tool_request.tool_call {
                         content.push(json!({
@@ -212,6 +253,27 @@ pub fn format_system(system: &str) -> Value {
     }])
 }
 
+/// The prefill as sent to the API, which rejects trailing whitespace
+pub fn prefill_text(model_config: &ModelConfig) -> Option<&str> {
+    model_config
+        .prefill
+        .as_deref()
+        .map(str::trim_end)
+        .filter(|prefill| !prefill.is_empty())
+}
+
+/// Put the prefill back in front of the reply's first text, since the API returns only the
+/// continuation. Returns false when the message has no text to attach it to.
+pub fn prepend_prefill(message: &mut Message, prefill: &str) -> bool {
+    for content in message.content.iter_mut() {
+        if let MessageContent::Text(text) = content {
+            text.text = format!("{prefill}{}", text.text);
+            return true;
+        }
+    }
+    false
+}
+
 /// Convert Anthropic's API response to internal Message format
 pub fn response_to_message(response: &Value) -> Result<Message> {
     let content_blocks = response
@@ -271,6 +333,7 @@ pub fn response_to_message(response: &Value) -> Result<Message> {
         }
     }
 
+    disambiguate_tool_call_ids(&mut message);
     Ok(message)
 }
 
@@ -378,7 +441,7 @@ pub fn create_request(
     messages: &[Message],
     tools: &[Tool],
 ) -> Result<Value> {
-    let anthropic_messages = format_messages(messages);
+    let mut anthropic_messages = format_messages(&strip_empty_content(messages));
     let tool_specs = format_tools(tools);
     let system_spec = format_system(system);
 
@@ -387,6 +450,14 @@ pub fn create_request(
         return Err(anyhow!("No valid messages to send to Anthropic API"));
     }
 
+    // Seed the reply by ending with a partial assistant turn
+    if let Some(prefill) = prefill_text(model_config) {
+        anthropic_messages.push(json!({
+            "role": "assistant",
+            "content": [{"type": "text", "text": prefill}]
+        }));
+    }
+
     // https://docs.anthropic.com/en/docs/about-claude/models/all-models#model-comparison-table
     // Claude 3.7 supports max output tokens up to 8192
     let max_tokens = model_config.max_tokens.unwrap_or(8192);
@@ -412,6 +483,21 @@ pub fn create_request(
             .insert("tools".to_string(), json!(tool_specs));
     }
 
+    log_unsupported_sampling_params("Anthropic", model_config);
+
+    if let Some(top_p) = model_config.top_p {
+        payload
+            .as_object_mut()
+            .unwrap()
+            .insert("top_p".to_string(), json!(top_p));
+    }
+    if let Some(stop) = &model_config.stop {
+        payload
+            .as_object_mut()
+            .unwrap()
+            .insert("stop_sequences".to_string(), json!(stop));
+    }
+
     // Add temperature if specified and not using extended thinking model
     if let Some(temp) = model_config.temperature {
         // Claude 3.7 models with thinking enabled don't support temperature
@@ -479,6 +565,7 @@ where
         let mut current_tool_id: Option<String> = None;
         let mut final_usage: Option<crate::providers::base::ProviderUsage> = None;
         let mut message_id: Option<String> = None;
+        let mut tool_call_ids = ToolCallIds::default();
 
         while let Some(line_result) = stream.next().await {
             let line = line_result?;
@@ -552,7 +639,7 @@ where
                                 // Yield partial text message with the same ID from message_start
                                 let mut message = Message::new(
                                     Role::Assistant,
-                                    chrono::Utc::now().timestamp(),
+                                    crate::clock::now().timestamp(),
                                     vec![MessageContent::text(text)],
                                 );
                                 message.id = message_id.clone();
@@ -590,10 +677,11 @@ where
                                         );
                                         let mut message = Message::new(
                                             Role::Assistant,
-                                            chrono::Utc::now().timestamp(),
+                                            crate::clock::now().timestamp(),
                                             vec![MessageContent::tool_request(tool_id, Err(error))],
                                         );
                                         message.id = message_id.clone();
+                                        tool_call_ids.disambiguate(&mut message);
                                         yield (Some(message), None);
                                         continue;
                                     }
@@ -604,10 +692,11 @@ where
 
                             let mut message = Message::new(
                                 rmcp::model::Role::Assistant,
-                                chrono::Utc::now().timestamp(),
+                                crate::clock::now().timestamp(),
                                 vec![MessageContent::tool_request(tool_id, Ok(tool_call))],
                             );
                             message.id = message_id.clone();
+                            tool_call_ids.disambiguate(&mut message);
                             yield (Some(message), None);
                         }
                     }
@@ -868,6 +957,46 @@ mod tests {
         assert_eq!(spec[2]["content"][0]["text"], "How are you?");
     }
 
+    #[test]
+    fn test_image_message_to_anthropic_spec() {
+        let messages = vec![
+            Message::user()
+                .with_text("What is in this screenshot?")
+                .with_image("aGVsbG8=", "image/png"),
+            Message::assistant().with_tool_request(
+                "tool_1",
+                Ok(CallToolRequestParam {
+                    name: "screen_capture".into(),
+                    arguments: None,
+                }),
+            ),
+            Message::user().with_tool_response(
+                "tool_1",
+                Ok(vec![
+                    rmcp::model::Content::text("Captured"),
+                    rmcp::model::Content::image("d29ybGQ=", "image/jpeg"),
+                ]),
+            ),
+        ];
+
+        let spec = format_messages(&messages);
+
+        let image = &spec[0]["content"][1];
+        assert_eq!(image["type"], "image");
+        assert_eq!(image["source"]["type"], "base64");
+        assert_eq!(image["source"]["media_type"], "image/png");
+        assert_eq!(image["source"]["data"], "aGVsbG8=");
+
+        let tool_result = &spec[2]["content"][0];
+        assert_eq!(tool_result["type"], "tool_result");
+        assert_eq!(tool_result["content"][0]["text"], "Captured");
+        assert_eq!(tool_result["content"][1]["type"], "image");
+        assert_eq!(
+            tool_result["content"][1]["source"]["media_type"],
+            "image/jpeg"
+        );
+    }
+
     #[test]
     fn test_tools_to_anthropic_spec() {
         let tools = vec![
@@ -963,6 +1092,53 @@ mod tests {
         result
     }
 
+    #[test]
+    fn test_create_request_stop_sequences_and_top_p() -> Result<()> {
+        let messages = vec![Message::user().with_text("Hello")];
+        let model_config = ModelConfig::new_or_fail("claude-sonnet-4-20250514");
+        let payload = create_request(&model_config, "system", &messages, &[])?;
+        assert!(payload.get("stop_sequences").is_none());
+        assert!(payload.get("top_p").is_none());
+
+        let model_config = model_config
+            .with_stop(Some(vec!["\n\nHuman:".to_string()]))
+            .with_top_p(Some(0.5));
+        let payload = create_request(&model_config, "system", &messages, &[])?;
+        assert_eq!(payload["stop_sequences"], json!(["\n\nHuman:"]));
+        assert_eq!(payload["top_p"], json!(0.5));
+        assert!(payload.get("stop").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_request_with_prefill() -> Result<()> {
+        let model_config = ModelConfig::new_or_fail("claude-sonnet-4-20250514")
+            .with_prefill(Some("{\"answer\": ".to_string()));
+        let messages = vec![Message::user().with_text("Reply in JSON")];
+
+        let payload = create_request(&model_config, "system", &messages, &[])?;
+
+        let sent = payload["messages"].as_array().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1]["role"], "assistant");
+        assert_eq!(sent[1]["content"][0]["text"], "{\"answer\":");
+        Ok(())
+    }
+
+    #[test]
+    fn test_prepend_prefill_to_first_text() {
+        let mut message = Message::assistant()
+            .with_thinking("thinking", "signature")
+            .with_text(" \"yes\"}")
+            .with_text("more");
+
+        assert!(prepend_prefill(&mut message, "{\"answer\":"));
+        assert_eq!(message.as_concat_text(), "{\"answer\": \"yes\"}\nmore");
+
+        let mut no_text = Message::assistant().with_thinking("thinking", "signature");
+        assert!(!prepend_prefill(&mut no_text, "{"));
+    }
+
     #[test]
     fn test_cache_pricing_calculation() -> Result<()> {
         // Test realistic cache scenario: small fresh input, large cached content
@@ -1037,4 +1213,62 @@ mod tests {
         );
         assert_eq!(spec[1]["content"][0]["is_error"], true);
     }
+
+    #[test]
+    fn test_format_messages_clamps_oversized_tool_result() {
+        use crate::providers::utils::MAX_CONTENT_BLOCK_CHARS;
+        use rmcp::model::Content;
+
+        let huge = "y".repeat(MAX_CONTENT_BLOCK_CHARS * 2);
+        let messages = vec![
+            Message::assistant().with_tool_request(
+                "tool_1",
+                Ok(CallToolRequestParam {
+                    name: "read".into(),
+                    arguments: Some(object!({})),
+                }),
+            ),
+            Message::user().with_tool_response("tool_1", Ok(vec![Content::text(huge)])),
+        ];
+
+        let spec = format_messages(&messages);
+        let block = &spec[1]["content"][0];
+        assert_eq!(block["type"], "tool_result");
+        let text = block["content"].as_str().unwrap();
+        assert!(text.contains(&format!(
+            "[... {} characters truncated",
+            MAX_CONTENT_BLOCK_CHARS
+        )));
+        assert!(text.chars().count() < MAX_CONTENT_BLOCK_CHARS + 200);
+    }
+
+    #[tokio::test]
+    async fn test_streamed_usage_merges_message_delta() {
+        use futures::StreamExt;
+
+        let response_lines = r#"
+data: {"type":"message_start","message":{"id":"msg_1","model":"claude-sonnet-4","usage":{"input_tokens":25,"cache_read_input_tokens":5,"output_tokens":1}}}
+data: {"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}
+data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hello"}}
+data: {"type":"content_block_stop","index":0}
+data: {"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":15}}
+data: {"type":"message_stop"}
+"#;
+        let lines: Vec<_> = response_lines.lines().map(|l| Ok(l.to_string())).collect();
+        let stream = response_to_streaming_message(futures::stream::iter(lines));
+        futures::pin_mut!(stream);
+
+        let mut usages = Vec::new();
+        while let Some(item) = stream.next().await {
+            if let (_, Some(usage)) = item.unwrap() {
+                usages.push(usage);
+            }
+        }
+
+        assert_eq!(usages.len(), 1);
+        assert_eq!(usages[0].model, "claude-sonnet-4");
+        assert_eq!(usages[0].usage.input_tokens, Some(30));
+        assert_eq!(usages[0].usage.output_tokens, Some(15));
+        assert_eq!(usages[0].usage.total_tokens, Some(45));
+    }
 }
@@ -7,7 +7,6 @@ use anyhow::{anyhow, bail, Result};
 use aws_sdk_bedrockruntime::types as bedrock;
 use aws_smithy_types::{Document, Number};
 use base64::Engine;
-use chrono::Utc;
 use rmcp::model::{
     object, CallToolRequestParam, Content, ErrorCode, ErrorData, RawContent, ResourceContents,
     Role, Tool,
@@ -286,7 +285,7 @@ pub fn from_bedrock_message(message: &bedrock::Message) -> Result<Message> {
         .iter()
         .map(from_bedrock_content_block)
         .collect::<Result<Vec<_>>>()?;
-    let created = Utc::now().timestamp();
+    let created = crate::clock::now().timestamp();
 
     Ok(Message::new(role, created, content))
 }
@@ -384,6 +383,87 @@ mod tests {
     // Base64 encoded 1x1 PNG image for testing
     const TEST_IMAGE_BASE64: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mP8/5+hHgAHggJ/PchI7wAAAABJRU5ErkJggg==";
 
+    #[test]
+    fn test_to_bedrock_message_with_tool_request() -> Result<()> {
+        let message = Message::assistant()
+            .with_text("Let me check.")
+            .with_tool_request(
+                "tool_1",
+                Ok(CallToolRequestParam {
+                    name: "developer__shell".into(),
+                    arguments: Some(object(serde_json::json!({"command": "ls"}))),
+                }),
+            );
+
+        let bedrock_message = to_bedrock_message(&message)?;
+
+        assert_eq!(
+            bedrock_message.role(),
+            &bedrock::ConversationRole::Assistant
+        );
+        let content = bedrock_message.content();
+        assert!(
+            matches!(&content[0], bedrock::ContentBlock::Text(text) if text == "Let me check.")
+        );
+        let bedrock::ContentBlock::ToolUse(tool_use) = &content[1] else {
+            panic!("expected a tool use block, got {:?}", content[1]);
+        };
+        assert_eq!(tool_use.tool_use_id(), "tool_1");
+        assert_eq!(tool_use.name(), "developer__shell");
+        assert_eq!(
+            from_bedrock_json(tool_use.input())?,
+            serde_json::json!({"command": "ls"})
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_bedrock_message_with_tool_use() -> Result<()> {
+        let tool_use = bedrock::ToolUseBlock::builder()
+            .tool_use_id("tool_1")
+            .name("developer__shell")
+            .input(to_bedrock_json(&serde_json::json!({"command": "ls"})))
+            .build()?;
+        let bedrock_message = bedrock::Message::builder()
+            .role(bedrock::ConversationRole::Assistant)
+            .content(bedrock::ContentBlock::Text("Running it.".to_string()))
+            .content(bedrock::ContentBlock::ToolUse(tool_use))
+            .build()?;
+
+        let message = from_bedrock_message(&bedrock_message)?;
+
+        assert_eq!(message.role, Role::Assistant);
+        assert_eq!(message.as_concat_text(), "Running it.");
+        let MessageContent::ToolRequest(request) = &message.content[1] else {
+            panic!("expected a tool request, got {:?}", message.content[1]);
+        };
+        assert_eq!(request.id, "tool_1");
+        let call = request.tool_call.as_ref().unwrap();
+        assert_eq!(call.name, "developer__shell");
+        assert_eq!(
+            call.arguments,
+            Some(object(serde_json::json!({"command": "ls"})))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_bedrock_usage() {
+        let usage = bedrock::TokenUsage::builder()
+            .input_tokens(12)
+            .output_tokens(30)
+            .total_tokens(42)
+            .build()
+            .unwrap();
+
+        let usage = from_bedrock_usage(&usage);
+        assert_eq!(usage.input_tokens, Some(12));
+        assert_eq!(usage.output_tokens, Some(30));
+        assert_eq!(usage.total_tokens, Some(42));
+    }
+
     #[test]
     fn test_to_bedrock_image_supported_formats() -> Result<()> {
         let supported_formats = [
@@ -1,9 +1,11 @@
 use crate::conversation::message::{Message, MessageContent};
-use crate::model::ModelConfig;
+use crate::conversation::tool_call_id::tool_call_id_or_synthetic;
+use crate::model::{ModelConfig, ToolResultJoinStrategy};
 use crate::providers::formats::google as gemini_schema;
 use crate::providers::utils::{
-    convert_image, detect_image_path, is_valid_function_name, load_image_file, safely_parse_json,
-    sanitize_function_name, ImageFormat,
+    convert_image, detect_image_path, is_valid_function_name, join_tool_result_texts,
+    load_image_file, log_unsupported_sampling_params, safely_parse_json, sanitize_function_name,
+    strip_empty_content, ImageFormat,
 };
 use anyhow::{anyhow, Error};
 use rmcp::model::{
@@ -28,7 +30,11 @@ struct DatabricksMessage {
 ///   Databricks is mostly OpenAI compatible, but has some differences (reasoning type, etc)
 ///   some openai compatible endpoints use the anthropic image spec at the content level
 ///   even though the message structure is otherwise following openai, the enum switches this
-fn format_messages(messages: &[Message], image_format: &ImageFormat) -> Vec<DatabricksMessage> {
+fn format_messages(
+    messages: &[Message],
+    image_format: &ImageFormat,
+    tool_result_join: ToolResultJoinStrategy,
+) -> Vec<DatabricksMessage> {
     let mut result = Vec::new();
     for message in messages.iter().filter(|m| m.is_agent_visible()) {
         let mut converted = DatabricksMessage {
@@ -178,11 +184,12 @@ fn format_messages(messages: &[Message], image_format: &ImageFormat) -> Vec<Data
                                     }
                                 }
                             }
-                            let tool_response_content: Value = json!(tool_content
+                            let tool_response_texts = tool_content
                                 .iter()
                                 .filter_map(|content| content.as_text().map(|t| t.text.clone()))
-                                .collect::<Vec<String>>()
-                                .join(" "));
+                                .collect::<Vec<String>>();
+                            let tool_response_content =
+                                join_tool_result_texts(&tool_response_texts, tool_result_join);
 
                             result.push(DatabricksMessage {
                                 content: tool_response_content,
@@ -344,7 +351,7 @@ pub fn response_to_message(response: &Value) -> anyhow::Result<Message> {
     if let Some(tool_calls) = original.get("tool_calls") {
         if let Some(tool_calls_array) = tool_calls.as_array() {
             for tool_call in tool_calls_array {
-                let id = tool_call["id"].as_str().unwrap_or_default().to_string();
+                let id = tool_call_id_or_synthetic(tool_call["id"].as_str());
                 let function_name = tool_call["function"]["name"]
                     .as_str()
                     .unwrap_or_default()
@@ -403,7 +410,7 @@ pub fn response_to_message(response: &Value) -> anyhow::Result<Message> {
 
     Ok(Message::new(
         Role::Assistant,
-        chrono::Utc::now().timestamp(),
+        crate::clock::now().timestamp(),
         content,
     ))
 }
@@ -545,7 +552,11 @@ pub fn create_request(
         tool_call_id: None,
     };
 
-    let messages_spec = format_messages(messages, image_format);
+    let messages_spec = format_messages(
+        &strip_empty_content(messages),
+        image_format,
+        model_config.tool_result_join.unwrap_or_default(),
+    );
     let mut tools_spec = if !tools.is_empty() {
         format_tools(tools, &model_config.model_name)?
     } else {
@@ -577,6 +588,8 @@ pub fn create_request(
             .insert("tools".to_string(), json!(tools_spec));
     }
 
+    log_unsupported_sampling_params("Databricks", model_config);
+
     // Add thinking parameters for Claude 3.7 Sonnet model when requested
     let is_thinking_enabled = std::env::var("CLAUDE_THINKING_ENABLED").is_ok();
     if is_claude_sonnet && is_thinking_enabled {
@@ -665,7 +678,7 @@ mod tests {
     #[test]
     fn test_format_messages() -> anyhow::Result<()> {
         let message = Message::user().with_text("Hello");
-        let spec = format_messages(&[message], &ImageFormat::OpenAi);
+        let spec = format_messages(&[message], &ImageFormat::OpenAi, Default::default());
 
         assert_eq!(spec.len(), 1);
         assert_eq!(spec[0].role, "user");
@@ -673,6 +686,49 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_create_request_tool_result_join_strategy() -> anyhow::Result<()> {
+        let messages = vec![
+            Message::assistant().with_tool_request(
+                "tool1",
+                Ok(CallToolRequestParam {
+                    name: "example".into(),
+                    arguments: None,
+                }),
+            ),
+            Message::user().with_tool_response(
+                "tool1",
+                Ok(vec![Content::text("first"), Content::text("second")]),
+            ),
+        ];
+        let tool_content = |model_config: &ModelConfig| -> anyhow::Result<Value> {
+            let request =
+                create_request(model_config, "system", &messages, &[], &ImageFormat::OpenAi)?;
+            let tool_message = request["messages"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|m| m["role"] == "tool")
+                .cloned()
+                .unwrap();
+            Ok(tool_message["content"].clone())
+        };
+
+        let default_config = ModelConfig::new_or_fail("databricks-meta-llama");
+        assert_eq!(tool_content(&default_config)?, json!("first second"));
+
+        let separate =
+            default_config.with_tool_result_join(Some(ToolResultJoinStrategy::SeparateBlocks));
+        assert_eq!(
+            tool_content(&separate)?,
+            json!([
+                {"type": "text", "text": "first"},
+                {"type": "text", "text": "second"}
+            ])
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_format_tools() -> anyhow::Result<()> {
         let tool = Tool::new(
@@ -727,8 +783,12 @@ mod tests {
         messages
             .push(Message::user().with_tool_response(tool_id, Ok(vec![Content::text("Result")])));
 
-        let as_value =
-            serde_json::to_value(format_messages(&messages, &ImageFormat::OpenAi)).unwrap();
+        let as_value = serde_json::to_value(format_messages(
+            &messages,
+            &ImageFormat::OpenAi,
+            Default::default(),
+        ))
+        .unwrap();
         let spec = as_value.as_array().unwrap();
 
         assert_eq!(spec.len(), 4);
@@ -764,8 +824,12 @@ mod tests {
         messages
             .push(Message::user().with_tool_response(tool_id, Ok(vec![Content::text("Result")])));
 
-        let as_value =
-            serde_json::to_value(format_messages(&messages, &ImageFormat::OpenAi)).unwrap();
+        let as_value = serde_json::to_value(format_messages(
+            &messages,
+            &ImageFormat::OpenAi,
+            Default::default(),
+        ))
+        .unwrap();
         let spec = as_value.as_array().unwrap();
 
         assert_eq!(spec.len(), 2);
@@ -834,8 +898,12 @@ mod tests {
 
         // Create message with image path
         let message = Message::user().with_text(format!("Here is an image: {}", png_path_str));
-        let as_value =
-            serde_json::to_value(format_messages(&[message], &ImageFormat::OpenAi)).unwrap();
+        let as_value = serde_json::to_value(format_messages(
+            &[message],
+            &ImageFormat::OpenAi,
+            Default::default(),
+        ))
+        .unwrap();
         let spec = as_value.as_array().unwrap();
 
         assert_eq!(spec.len(), 1);
@@ -982,6 +1050,20 @@ mod tests {
             toolshim: false,
             toolshim_model: None,
             fast_model: None,
+            fallback_model: None,
+            parallel_tool_calls: None,
+            strict_tools: false,
+            logit_bias: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            tool_result_join: None,
+            service_tier: None,
+            n: None,
+            prefill: None,
+            request_timeout: None,
+            requests_per_minute: None,
+            top_p: None,
+            stop: None,
         };
         let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
         let obj = request.as_object().unwrap();
@@ -1014,6 +1096,20 @@ mod tests {
             toolshim: false,
             toolshim_model: None,
             fast_model: None,
+            fallback_model: None,
+            parallel_tool_calls: None,
+            strict_tools: false,
+            logit_bias: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            tool_result_join: None,
+            service_tier: None,
+            n: None,
+            prefill: None,
+            request_timeout: None,
+            requests_per_minute: None,
+            top_p: None,
+            stop: None,
         };
         let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
         let obj = request.as_object().unwrap();
@@ -1047,6 +1143,20 @@ mod tests {
             toolshim: false,
             toolshim_model: None,
             fast_model: None,
+            fallback_model: None,
+            parallel_tool_calls: None,
+            strict_tools: false,
+            logit_bias: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            tool_result_join: None,
+            service_tier: None,
+            n: None,
+            prefill: None,
+            request_timeout: None,
+            requests_per_minute: None,
+            top_p: None,
+            stop: None,
         };
         let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
         let obj = request.as_object().unwrap();
@@ -1174,7 +1284,7 @@ mod tests {
             }),
         );
 
-        let spec = format_messages(&[message], &ImageFormat::OpenAi);
+        let spec = format_messages(&[message], &ImageFormat::OpenAi, Default::default());
         let as_value = serde_json::to_value(spec)?;
         let spec_array = as_value.as_array().unwrap();
 
@@ -1203,7 +1313,7 @@ mod tests {
             }),
         );
 
-        let spec = format_messages(&[message], &ImageFormat::OpenAi);
+        let spec = format_messages(&[message], &ImageFormat::OpenAi, Default::default());
         let as_value = serde_json::to_value(spec)?;
         let spec_array = as_value.as_array().unwrap();
 
@@ -1,15 +1,18 @@
 use crate::model::ModelConfig;
 use crate::providers::base::Usage;
 use crate::providers::errors::ProviderError;
-use crate::providers::utils::{is_valid_function_name, sanitize_function_name};
+use crate::providers::utils::{
+    is_valid_function_name, log_unsupported_sampling_params, sanitize_function_name,
+    strip_empty_content,
+};
 use anyhow::Result;
-use rand::{distributions::Alphanumeric, Rng};
 use rmcp::model::{
     object, AnnotateAble, CallToolRequestParam, ErrorCode, ErrorData, RawContent, Role, Tool,
 };
 use std::borrow::Cow;
 
 use crate::conversation::message::{Message, MessageContent};
+use crate::conversation::tool_call_id::tool_call_id_or_synthetic;
 use serde_json::{json, Map, Value};
 use std::ops::Deref;
 
@@ -257,7 +260,7 @@ pub fn response_to_message(response: Value) -> Result<Message> {
         .unwrap_or(&binding);
     let candidate = candidates.first();
     let role = Role::Assistant;
-    let created = chrono::Utc::now().timestamp();
+    let created = crate::clock::now().timestamp();
     if candidate.is_none() {
         return Ok(Message::new(role, created, content));
     }
@@ -272,11 +275,7 @@ pub fn response_to_message(response: Value) -> Result<Message> {
         if let Some(text) = part.get("text").and_then(|v| v.as_str()) {
             content.push(MessageContent::text(text.to_string()));
         } else if let Some(function_call) = part.get("functionCall") {
-            let id: String = rand::thread_rng()
-                .sample_iter(&Alphanumeric)
-                .take(8)
-                .map(char::from)
-                .collect();
+            let id = tool_call_id_or_synthetic(function_call.get("id").and_then(|v| v.as_str()));
             let name = function_call["name"]
                 .as_str()
                 .unwrap_or_default()
@@ -315,15 +314,25 @@ pub fn get_usage(data: &Value) -> Result<Usage> {
             .get("promptTokenCount")
             .and_then(|v| v.as_u64())
             .map(|v| v as i32);
-        let output_tokens = usage_meta_data
+        let candidate_tokens = usage_meta_data
             .get("candidatesTokenCount")
             .and_then(|v| v.as_u64())
             .map(|v| v as i32);
+        // Thinking is reported apart from the candidates but billed as output
+        let reasoning_tokens = usage_meta_data
+            .get("thoughtsTokenCount")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as i32);
+        let output_tokens = match (candidate_tokens, reasoning_tokens) {
+            (Some(candidates), Some(thoughts)) => Some(candidates + thoughts),
+            (candidates, thoughts) => candidates.or(thoughts),
+        };
         let total_tokens = usage_meta_data
             .get("totalTokenCount")
             .and_then(|v| v.as_u64())
             .map(|v| v as i32);
-        Ok(Usage::new(input_tokens, output_tokens, total_tokens))
+        Ok(Usage::new(input_tokens, output_tokens, total_tokens)
+            .with_reasoning_tokens(reasoning_tokens))
     } else {
         tracing::debug!(
             "Failed to get usage data: {}",
@@ -346,13 +355,17 @@ pub fn create_request(
         "system_instruction".to_string(),
         json!({"parts": [{"text": system}]}),
     );
-    payload.insert("contents".to_string(), json!(format_messages(messages)));
+    payload.insert(
+        "contents".to_string(),
+        json!(format_messages(&strip_empty_content(messages))),
+    );
     if !tools.is_empty() {
         payload.insert(
             "tools".to_string(),
             json!({"functionDeclarations": format_tools(tools)}),
         );
     }
+    log_unsupported_sampling_params("Google", model_config);
     let mut generation_config = Map::new();
     if let Some(temp) = model_config.temperature {
         generation_config.insert("temperature".to_string(), json!(temp as f64));
@@ -424,6 +437,23 @@ mod tests {
         assert_eq!(usage.input_tokens, Some(1));
         assert_eq!(usage.output_tokens, Some(2));
         assert_eq!(usage.total_tokens, Some(3));
+        assert_eq!(usage.reasoning_tokens, None);
+    }
+
+    #[test]
+    fn test_get_usage_adds_thoughts_to_output() {
+        let data = json!({
+            "usageMetadata": {
+                "promptTokenCount": 10,
+                "candidatesTokenCount": 20,
+                "thoughtsTokenCount": 30,
+                "totalTokenCount": 60
+            }
+        });
+        let usage = get_usage(&data).unwrap();
+        assert_eq!(usage.output_tokens, Some(50));
+        assert_eq!(usage.reasoning_tokens, Some(30));
+        assert_eq!(usage.total_tokens, Some(60));
     }
 
     #[test]
@@ -1,9 +1,12 @@
 use crate::conversation::message::{Message, MessageContent};
-use crate::model::ModelConfig;
+use crate::conversation::tool_call_id::{disambiguate_tool_call_ids, ToolCallIds};
+use crate::model::{ModelConfig, ToolResultJoinStrategy};
 use crate::providers::base::{ProviderUsage, Usage};
 use crate::providers::utils::{
-    convert_image, detect_image_path, is_valid_function_name, load_image_file, safely_parse_json,
-    sanitize_function_name, ImageFormat,
+    clamp_content_block, convert_image, detect_image_path, insert_openai_sampling_params,
+    is_valid_function_name, join_tool_result_texts, load_image_file,
+    log_unsupported_sampling_params, safely_parse_json, sanitize_function_name,
+    strip_empty_content, ImageFormat,
 };
 use anyhow::{anyhow, Error};
 use async_stream::try_stream;
@@ -55,7 +58,11 @@ struct StreamingChunk {
     model: Option<String>,
 }
 
-pub fn format_messages(messages: &[Message], image_format: &ImageFormat) -> Vec<Value> {
+pub fn format_messages(
+    messages: &[Message],
+    image_format: &ImageFormat,
+    tool_result_join: ToolResultJoinStrategy,
+) -> Vec<Value> {
     let mut messages_spec = Vec::new();
     for message in messages.iter().filter(|m| m.is_agent_visible()) {
         let mut converted = json!({
@@ -78,10 +85,10 @@ pub fn format_messages(messages: &[Message], image_format: &ImageFormat) -> Vec<
                                 ]);
                             } else {
                                 // If image loading fails, just use the text
-                                converted["content"] = json!(text.text);
+                                converted["content"] = json!(clamp_content_block(&text.text));
                             }
                         } else {
-                            converted["content"] = json!(text.text);
+                            converted["content"] = json!(clamp_content_block(&text.text));
                         }
                     }
                 }
@@ -173,14 +180,15 @@ pub fn format_messages(messages: &[Message], image_format: &ImageFormat) -> Vec<
                                     }
                                 }
                             }
-                            let tool_response_content: Value = json!(tool_content
+                            let tool_response_texts = tool_content
                                 .iter()
                                 .map(|content| match content.deref() {
                                     RawContent::Text(text) => text.text.clone(),
                                     _ => String::new(),
                                 })
-                                .collect::<Vec<String>>()
-                                .join(" "));
+                                .collect::<Vec<String>>();
+                            let tool_response_content =
+                                join_tool_result_texts(&tool_response_texts, tool_result_join);
 
                             // First add the tool response with all content
                             output.push(json!({
@@ -275,8 +283,121 @@ pub fn format_tools(tools: &[Tool]) -> anyhow::Result<Vec<Value>> {
     Ok(result)
 }
 
-/// Convert OpenAI's API response to internal Message format
+/// Rewrite a JSON schema into the form OpenAI's strict function calling accepts.
+///
+/// Every object gets `additionalProperties: false` and lists all of its properties as
+/// required; properties that were optional become nullable so the model can still omit
+/// a value by sending `null`.
+pub fn to_strict_schema(schema: &Value) -> Value {
+    let mut schema = schema.clone();
+    make_strict(&mut schema);
+    schema
+}
+
+fn make_strict(schema: &mut Value) {
+    let Some(obj) = schema.as_object_mut() else {
+        return;
+    };
+
+    if let Some(props) = obj.get_mut("properties").and_then(|p| p.as_object_mut()) {
+        for prop in props.values_mut() {
+            make_strict(prop);
+        }
+    }
+    for key in ["items", "additionalItems"] {
+        if let Some(items) = obj.get_mut(key) {
+            make_strict(items);
+        }
+    }
+    for key in ["anyOf", "oneOf", "allOf"] {
+        if let Some(variants) = obj.get_mut(key).and_then(|v| v.as_array_mut()) {
+            variants.iter_mut().for_each(make_strict);
+        }
+    }
+    for key in ["$defs", "definitions"] {
+        if let Some(defs) = obj.get_mut(key).and_then(|d| d.as_object_mut()) {
+            defs.values_mut().for_each(make_strict);
+        }
+    }
+
+    let is_object = obj.get("type").and_then(|t| t.as_str()) == Some("object")
+        || obj.contains_key("properties");
+    if !is_object {
+        return;
+    }
+
+    let required: std::collections::HashSet<String> = obj
+        .get("required")
+        .and_then(|r| r.as_array())
+        .map(|r| {
+            r.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let props = obj
+        .entry("properties")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .expect("properties is an object");
+    for (name, prop) in props.iter_mut() {
+        if !required.contains(name) {
+            make_nullable(prop);
+        }
+    }
+    let all_names: Vec<Value> = props.keys().map(|k| json!(k)).collect();
+
+    obj.insert("required".to_string(), Value::Array(all_names));
+    obj.insert("additionalProperties".to_string(), json!(false));
+}
+
+fn make_nullable(prop: &mut Value) {
+    let Some(obj) = prop.as_object_mut() else {
+        return;
+    };
+    match obj.get_mut("type") {
+        Some(Value::String(t)) if t != "null" => {
+            let t = t.clone();
+            obj.insert("type".to_string(), json!([t, "null"]));
+        }
+        Some(Value::Array(types)) if !types.iter().any(|t| t == "null") => {
+            types.push(json!("null"));
+        }
+        Some(_) => {}
+        None => {
+            let inner = Value::Object(std::mem::take(obj));
+            obj.insert("anyOf".to_string(), json!([inner, {"type": "null"}]));
+        }
+    }
+}
+
+/// Number of choices in a response, when there is more than one (`n > 1`, or a provider
+/// that returns several candidates on its own)
+pub fn choice_count(response: &Value) -> Option<u32> {
+    response
+        .get("choices")
+        .and_then(|c| c.as_array())
+        .map(|choices| choices.len() as u32)
+        .filter(|&count| count > 1)
+}
+
+/// Convert OpenAI's API response to internal Message format, using the first choice
 pub fn response_to_message(response: &Value) -> anyhow::Result<Message> {
+    if let Some(alternatives) = response
+        .get("choices")
+        .and_then(|c| c.as_array())
+        .and_then(|choices| choices.get(1..))
+    {
+        for (index, choice) in alternatives.iter().enumerate() {
+            tracing::debug!(
+                choice = index + 1,
+                alternative = %choice.get("message").unwrap_or(&serde_json::Value::Null),
+                "Ignoring alternative choice in provider response"
+            );
+        }
+    }
+
     let Some(original) = response
         .get("choices")
         .and_then(|c| c.get(0))
@@ -284,7 +405,7 @@ pub fn response_to_message(response: &Value) -> anyhow::Result<Message> {
     else {
         return Ok(Message::new(
             Role::Assistant,
-            chrono::Utc::now().timestamp(),
+            crate::clock::now().timestamp(),
             Vec::new(),
         ));
     };
@@ -300,6 +421,7 @@ pub fn response_to_message(response: &Value) -> anyhow::Result<Message> {
     if let Some(tool_calls) = original.get("tool_calls") {
         if let Some(tool_calls_array) = tool_calls.as_array() {
             for tool_call in tool_calls_array {
+                // A missing id is filled in when the ids are disambiguated below
                 let id = tool_call["id"].as_str().unwrap_or_default().to_string();
                 let function_name = tool_call["function"]["name"]
                     .as_str()
@@ -357,11 +479,9 @@ pub fn response_to_message(response: &Value) -> anyhow::Result<Message> {
         }
     }
 
-    Ok(Message::new(
-        Role::Assistant,
-        chrono::Utc::now().timestamp(),
-        content,
-    ))
+    let mut message = Message::new(Role::Assistant, crate::clock::now().timestamp(), content);
+    disambiguate_tool_call_ids(&mut message);
+    Ok(message)
 }
 
 pub fn get_usage(usage: &Value) -> Usage {
@@ -384,7 +504,13 @@ pub fn get_usage(usage: &Value) -> Usage {
             _ => None,
         });
 
-    Usage::new(input_tokens, output_tokens, total_tokens)
+    let reasoning_tokens = usage
+        .get("completion_tokens_details")
+        .and_then(|d| d.get("reasoning_tokens"))
+        .and_then(|v| v.as_i64())
+        .map(|v| v as i32);
+
+    Usage::new(input_tokens, output_tokens, total_tokens).with_reasoning_tokens(reasoning_tokens)
 }
 
 /// Validates and fixes tool schemas to ensure they have proper parameter structure.
@@ -437,6 +563,14 @@ fn strip_data_prefix(line: &str) -> Option<&str> {
     line.strip_prefix("data: ").map(|s| s.trim())
 }
 
+/// Usage reported on a streamed chunk; with `include_usage` OpenAI sends it on a final
+/// chunk with no choices, while some compatible servers report it on every chunk
+fn chunk_usage(chunk: &StreamingChunk) -> Option<ProviderUsage> {
+    let usage = chunk.usage.as_ref()?;
+    let model = chunk.model.as_ref()?;
+    Some(ProviderUsage::new(model.clone(), get_usage(usage)))
+}
+
 pub fn response_to_streaming_message<S>(
     mut stream: S,
 ) -> impl Stream<Item = anyhow::Result<(Option<Message>, Option<ProviderUsage>)>> + 'static
@@ -446,6 +580,7 @@ where
     try_stream! {
         use futures::StreamExt;
 
+        let mut tool_call_ids = ToolCallIds::default();
         'outer: while let Some(response) = stream.next().await {
             if response.as_ref().is_ok_and(|s| s == "data: [DONE]") {
                 break 'outer;
@@ -461,14 +596,7 @@ where
                 .ok_or_else(|| anyhow!("unexpected stream format"))?)
                 .map_err(|e| anyhow!("Failed to parse streaming chunk: {}: {:?}", e, &line))?;
 
-            let usage = chunk.usage.as_ref().and_then(|u| {
-                chunk.model.as_ref().map(|model| {
-                    ProviderUsage {
-                        usage: get_usage(u),
-                        model: model.clone(),
-                    }
-                })
-            });
+            let mut usage = chunk_usage(&chunk);
 
             if chunk.choices.is_empty() {
                 yield (None, usage)
@@ -497,6 +625,10 @@ where
                             if let Some(line) = strip_data_prefix(&response_str) {
                                 let tool_chunk: StreamingChunk = serde_json::from_str(line)
                                     .map_err(|e| anyhow!("Failed to parse streaming chunk: {}: {:?}", e, &line))?;
+                                // Usage is cumulative, so the latest chunk that reports it wins
+                                if let Some(tool_usage) = chunk_usage(&tool_chunk) {
+                                    usage = Some(tool_usage);
+                                }
 
                                 if !tool_chunk.choices.is_empty() {
                                     if let Some(delta_tool_calls) = &tool_chunk.choices[0].delta.tool_calls {
@@ -532,6 +664,7 @@ where
 
                 for index in sorted_indices {
                     if let Some((id, function_name, arguments)) = tool_call_data.get(&index) {
+                        let id = id.clone();
                         let parsed = if arguments.is_empty() {
                             Ok(json!({}))
                         } else {
@@ -541,7 +674,7 @@ where
                         let content = match parsed {
                             Ok(params) => {
                                 MessageContent::tool_request(
-                                    id.clone(),
+                                    id,
                                     Ok(CallToolRequestParam { name: function_name.clone().into(), arguments: Some(object(params)) }),
                                 )
                             },
@@ -554,7 +687,7 @@ where
                                     )),
                                     data: None,
                                 };
-                                MessageContent::tool_request(id.clone(), Err(error))
+                                MessageContent::tool_request(id, Err(error))
                             }
                         };
                         contents.push(content);
@@ -563,9 +696,10 @@ where
 
                 let mut msg = Message::new(
                     Role::Assistant,
-                    chrono::Utc::now().timestamp(),
+                    crate::clock::now().timestamp(),
                     contents,
                 );
+                tool_call_ids.disambiguate(&mut msg);
 
                 // Add ID if present
                 if let Some(id) = chunk.id {
@@ -580,7 +714,7 @@ where
                 let text = chunk.choices[0].delta.content.as_ref().unwrap();
                 let mut msg = Message::new(
                     Role::Assistant,
-                    chrono::Utc::now().timestamp(),
+                    crate::clock::now().timestamp(),
                     vec![MessageContent::text(text)],
                 );
 
@@ -648,7 +782,11 @@ pub fn create_request(
         "content": system
     });
 
-    let messages_spec = format_messages(messages, image_format);
+    let messages_spec = format_messages(
+        &strip_empty_content(messages),
+        image_format,
+        model_config.tool_result_join.unwrap_or_default(),
+    );
     let mut tools_spec = if !tools.is_empty() {
         format_tools(tools)?
     } else {
@@ -658,6 +796,17 @@ pub fn create_request(
     // Validate tool schemas
     validate_tool_schemas(&mut tools_spec);
 
+    if model_config.strict_tools {
+        for tool in tools_spec.iter_mut() {
+            if let Some(function) = tool.get_mut("function") {
+                if let Some(parameters) = function.get("parameters") {
+                    function["parameters"] = to_strict_schema(parameters);
+                }
+                function["strict"] = json!(true);
+            }
+        }
+    }
+
     let mut messages_array = vec![system_message];
     messages_array.extend(messages_spec);
 
@@ -673,13 +822,27 @@ pub fn create_request(
             .insert("reasoning_effort".to_string(), json!(effort));
     }
 
+    if let Some(n) = model_config.n {
+        payload
+            .as_object_mut()
+            .unwrap()
+            .insert("n".to_string(), json!(n));
+    }
+
     if !tools_spec.is_empty() {
         payload
             .as_object_mut()
             .unwrap()
             .insert("tools".to_string(), json!(tools_spec));
+
+        if let Some(parallel) = model_config.parallel_tool_calls {
+            payload
+                .as_object_mut()
+                .unwrap()
+                .insert("parallel_tool_calls".to_string(), json!(parallel));
+        }
     }
-    // o1, o3 models currently don't support temperature
+    // o1, o3 models currently don't support temperature or sampling penalties
     if !is_ox_model {
         if let Some(temp) = model_config.temperature {
             payload
@@ -687,6 +850,15 @@ pub fn create_request(
                 .unwrap()
                 .insert("temperature".to_string(), json!(temp));
         }
+        insert_openai_sampling_params(&mut payload, model_config);
+    } else {
+        log_unsupported_sampling_params(&model_config.model_name, model_config);
+    }
+
+    if model_config.prefill.is_some() {
+        tracing::warn!(
+            "Assistant prefill is not supported by OpenAI-compatible providers; ignoring it"
+        );
     }
 
     // o1 models use max_completion_tokens instead of max_tokens
@@ -812,7 +984,7 @@ mod tests {
     #[test]
     fn test_format_messages() -> anyhow::Result<()> {
         let message = Message::user().with_text("Hello");
-        let spec = format_messages(&[message], &ImageFormat::OpenAi);
+        let spec = format_messages(&[message], &ImageFormat::OpenAi, Default::default());
 
         assert_eq!(spec.len(), 1);
         assert_eq!(spec[0]["role"], "user");
@@ -820,6 +992,45 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_format_messages_tool_result_join_strategies() {
+        let messages = vec![
+            Message::assistant().with_tool_request(
+                "tool1",
+                Ok(CallToolRequestParam {
+                    name: "example".into(),
+                    arguments: None,
+                }),
+            ),
+            Message::user().with_tool_response(
+                "tool1",
+                Ok(vec![Content::text("first"), Content::text("second")]),
+            ),
+        ];
+
+        let spec = format_messages(
+            &messages,
+            &ImageFormat::OpenAi,
+            ToolResultJoinStrategy::Concatenated,
+        );
+        assert_eq!(spec[1]["role"], "tool");
+        assert_eq!(spec[1]["content"], "first second");
+
+        let spec = format_messages(
+            &messages,
+            &ImageFormat::OpenAi,
+            ToolResultJoinStrategy::SeparateBlocks,
+        );
+        assert_eq!(spec[1]["role"], "tool");
+        assert_eq!(
+            spec[1]["content"],
+            json!([
+                {"type": "text", "text": "first"},
+                {"type": "text", "text": "second"}
+            ])
+        );
+    }
+
     #[test]
     fn test_format_tools() -> anyhow::Result<()> {
         let tool = Tool::new(
@@ -869,7 +1080,7 @@ mod tests {
         messages
             .push(Message::user().with_tool_response(tool_id, Ok(vec![Content::text("Result")])));
 
-        let spec = format_messages(&messages, &ImageFormat::OpenAi);
+        let spec = format_messages(&messages, &ImageFormat::OpenAi, Default::default());
 
         assert_eq!(spec.len(), 4);
         assert_eq!(spec[0]["role"], "assistant");
@@ -905,7 +1116,7 @@ mod tests {
         messages
             .push(Message::user().with_tool_response(tool_id, Ok(vec![Content::text("Result")])));
 
-        let spec = format_messages(&messages, &ImageFormat::OpenAi);
+        let spec = format_messages(&messages, &ImageFormat::OpenAi, Default::default());
 
         assert_eq!(spec.len(), 2);
         assert_eq!(spec[0]["role"], "assistant");
@@ -981,7 +1192,7 @@ mod tests {
 
         // Create message with image path
         let message = Message::user().with_text(format!("Here is an image: {}", png_path_str));
-        let spec = format_messages(&[message], &ImageFormat::OpenAi);
+        let spec = format_messages(&[message], &ImageFormat::OpenAi, Default::default());
 
         assert_eq!(spec.len(), 1);
         assert_eq!(spec[0]["role"], "user");
@@ -1045,6 +1256,102 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_response_to_message_keeps_real_ids_and_fills_missing_ones() -> anyhow::Result<()> {
+        use crate::conversation::tool_call_id::is_synthetic_tool_call_id;
+        use crate::conversation::Conversation;
+
+        let response = json!({
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "tool_calls": [
+                        {"id": "000", "type": "function",
+                         "function": {"name": "read_file", "arguments": "{}"}},
+                        {"type": "function",
+                         "function": {"name": "list_dir", "arguments": "{}"}}
+                    ]
+                }
+            }]
+        });
+        let message = response_to_message(&response)?;
+
+        let ids: Vec<String> = message
+            .content
+            .iter()
+            .filter_map(|c| c.as_tool_request().map(|r| r.id.clone()))
+            .collect();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(ids[0], "000");
+        assert!(is_synthetic_tool_call_id(&ids[1]));
+
+        // Each response routes back to its own request
+        let conversation = Conversation::new(vec![
+            Message::user().with_text("look around"),
+            message,
+            Message::user()
+                .with_tool_response(ids[0].clone(), Ok(vec![]))
+                .with_tool_response(ids[1].clone(), Ok(vec![])),
+        ])?;
+        let spec = format_messages(
+            conversation.messages(),
+            &ImageFormat::OpenAi,
+            Default::default(),
+        );
+        assert_eq!(spec[1]["tool_calls"][0]["id"], "000");
+        assert_eq!(spec[1]["tool_calls"][1]["id"], json!(ids[1]));
+        assert_eq!(spec[2]["tool_call_id"], "000");
+        assert_eq!(spec[3]["tool_call_id"], json!(ids[1]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_to_message_disambiguates_duplicate_ids() -> anyhow::Result<()> {
+        use crate::conversation::Conversation;
+
+        let response = json!({
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "tool_calls": [
+                        {"id": "call_1", "type": "function",
+                         "function": {"name": "read_file", "arguments": "{\"path\": \"a\"}"}},
+                        {"id": "call_1", "type": "function",
+                         "function": {"name": "read_file", "arguments": "{\"path\": \"b\"}"}}
+                    ]
+                }
+            }]
+        });
+        let message = response_to_message(&response)?;
+
+        let ids: Vec<String> = message
+            .content
+            .iter()
+            .filter_map(|c| c.as_tool_request().map(|r| r.id.clone()))
+            .collect();
+        assert_eq!(ids, ["call_1", "call_1_2"]);
+
+        // Both calls get their own response in a valid conversation
+        let conversation = Conversation::new(vec![
+            Message::user().with_text("read both"),
+            message,
+            Message::user()
+                .with_tool_response(ids[0].clone(), Ok(vec![]))
+                .with_tool_response(ids[1].clone(), Ok(vec![])),
+        ])?;
+        let spec = format_messages(
+            conversation.messages(),
+            &ImageFormat::OpenAi,
+            Default::default(),
+        );
+        assert_eq!(
+            spec[1]["tool_calls"][1]["function"]["arguments"],
+            "{\"path\":\"b\"}"
+        );
+        assert_eq!(spec[3]["tool_call_id"], "call_1_2");
+        Ok(())
+    }
+
     #[test]
     fn test_response_to_message_invalid_func_name() -> anyhow::Result<()> {
         let mut response: Value = serde_json::from_str(OPENAI_TOOL_USE_RESPONSE)?;
@@ -1127,7 +1434,7 @@ mod tests {
             }),
         );
 
-        let spec = format_messages(&[message], &ImageFormat::OpenAi);
+        let spec = format_messages(&[message], &ImageFormat::OpenAi, Default::default());
 
         assert_eq!(spec.len(), 1);
         assert_eq!(spec[0]["role"], "assistant");
@@ -1154,7 +1461,7 @@ mod tests {
             }),
         );
 
-        let spec = format_messages(&[message], &ImageFormat::OpenAi);
+        let spec = format_messages(&[message], &ImageFormat::OpenAi, Default::default());
 
         assert_eq!(spec.len(), 1);
         assert_eq!(spec[0]["role"], "assistant");
@@ -1184,7 +1491,7 @@ mod tests {
             }),
         );
 
-        let spec = format_messages(&[message], &ImageFormat::OpenAi);
+        let spec = format_messages(&[message], &ImageFormat::OpenAi, Default::default());
 
         assert_eq!(spec.len(), 1);
         assert_eq!(spec[0]["role"], "assistant");
@@ -1211,7 +1518,7 @@ mod tests {
             }),
         );
 
-        let spec = format_messages(&[message], &ImageFormat::OpenAi);
+        let spec = format_messages(&[message], &ImageFormat::OpenAi, Default::default());
 
         assert_eq!(spec.len(), 1);
         assert_eq!(spec[0]["role"], "assistant");
@@ -1241,6 +1548,20 @@ mod tests {
             toolshim: false,
             toolshim_model: None,
             fast_model: None,
+            fallback_model: None,
+            parallel_tool_calls: None,
+            strict_tools: false,
+            logit_bias: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            tool_result_join: None,
+            service_tier: None,
+            n: None,
+            prefill: None,
+            request_timeout: None,
+            requests_per_minute: None,
+            top_p: None,
+            stop: None,
         };
         let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
         let obj = request.as_object().unwrap();
@@ -1262,6 +1583,57 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_create_request_drops_empty_text() -> anyhow::Result<()> {
+        let model_config = ModelConfig::new_or_fail("gpt-4o");
+        let messages = vec![
+            Message::user().with_text("hi"),
+            Message::assistant().with_text("  "),
+            Message::user().with_text("again"),
+        ];
+
+        let request = create_request(
+            &model_config,
+            "system",
+            &messages,
+            &[],
+            &ImageFormat::OpenAi,
+        )?;
+
+        let sent = request["messages"].as_array().unwrap();
+        let roles: Vec<&str> = sent.iter().map(|m| m["role"].as_str().unwrap()).collect();
+        assert_eq!(roles, ["system", "user", "user"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_request_parallel_tool_calls() -> anyhow::Result<()> {
+        let tool = Tool::new(
+            "test_tool",
+            "A test tool",
+            object!({
+                "type": "object",
+                "properties": {}
+            }),
+        );
+
+        let model_config = ModelConfig::new_or_fail("gpt-4o");
+        let request = create_request(
+            &model_config,
+            "system",
+            &[],
+            &[tool.clone()],
+            &ImageFormat::OpenAi,
+        )?;
+        assert!(request.get("parallel_tool_calls").is_none());
+
+        let model_config = model_config.with_parallel_tool_calls(false);
+        let request = create_request(&model_config, "system", &[], &[tool], &ImageFormat::OpenAi)?;
+        assert_eq!(request["parallel_tool_calls"], json!(false));
+
+        Ok(())
+    }
+
     #[test]
     fn test_create_request_o1_default() -> anyhow::Result<()> {
         // Test default medium reasoning effort for O1 model
@@ -1273,6 +1645,20 @@ mod tests {
             toolshim: false,
             toolshim_model: None,
             fast_model: None,
+            fallback_model: None,
+            parallel_tool_calls: None,
+            strict_tools: false,
+            logit_bias: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            tool_result_join: None,
+            service_tier: None,
+            n: None,
+            prefill: None,
+            request_timeout: None,
+            requests_per_minute: None,
+            top_p: None,
+            stop: None,
         };
         let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
         let obj = request.as_object().unwrap();
@@ -1306,6 +1692,20 @@ mod tests {
             toolshim: false,
             toolshim_model: None,
             fast_model: None,
+            fallback_model: None,
+            parallel_tool_calls: None,
+            strict_tools: false,
+            logit_bias: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            tool_result_join: None,
+            service_tier: None,
+            n: None,
+            prefill: None,
+            request_timeout: None,
+            requests_per_minute: None,
+            top_p: None,
+            stop: None,
         };
         let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
         let obj = request.as_object().unwrap();
@@ -1377,4 +1777,279 @@ data: [DONE]
 
         panic!("Expected tool call message with two calls, but did not see it");
     }
+
+    async fn collect_streamed_usage(response_lines: &str) -> Vec<ProviderUsage> {
+        let lines: Vec<_> = response_lines.lines().map(|l| Ok(l.to_string())).collect();
+        let messages = response_to_streaming_message(tokio_stream::iter(lines));
+        pin!(messages);
+
+        let mut usages = Vec::new();
+        while let Some(item) = messages.next().await {
+            if let (_, Some(usage)) = item.unwrap() {
+                usages.push(usage);
+            }
+        }
+        usages
+    }
+
+    #[tokio::test]
+    async fn test_streamed_tool_calls_get_distinct_ids() {
+        let response_lines = r#"
+data: {"id":"chatcmpl-1","model":"gpt-4o","choices":[{"index":0,"delta":{"role":"assistant","tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"developer__shell","arguments":"{}"}},{"index":1,"id":"call_1","type":"function","function":{"name":"developer__shell","arguments":"{}"}},{"index":2,"id":"","type":"function","function":{"name":"developer__shell","arguments":"{}"}}]},"finish_reason":"tool_calls"}],"usage":null}
+data: [DONE]
+"#;
+
+        let messages = response_to_streaming_message(tokio_stream::iter(
+            response_lines.lines().map(|line| Ok(line.to_string())),
+        ));
+        pin!(messages);
+
+        let mut ids = Vec::new();
+        while let Some(Ok((message, _))) = messages.next().await {
+            for content in message.iter().flat_map(|m| m.content.iter()) {
+                if let MessageContent::ToolRequest(request) = content {
+                    ids.push(request.id.clone());
+                }
+            }
+        }
+        assert_eq!(ids.len(), 3);
+        assert_eq!(ids[..2], ["call_1", "call_1_2"]);
+        assert!(crate::conversation::tool_call_id::is_synthetic_tool_call_id(&ids[2]));
+    }
+
+    #[tokio::test]
+    async fn test_streamed_terminal_usage_chunk() {
+        let response_lines = r#"
+data: {"id":"chatcmpl-1","model":"gpt-4o","choices":[{"index":0,"delta":{"role":"assistant","content":"Hi"},"finish_reason":null}],"usage":null}
+data: {"id":"chatcmpl-1","model":"gpt-4o","choices":[{"index":0,"delta":{},"finish_reason":"stop"}],"usage":null}
+data: {"id":"chatcmpl-1","model":"gpt-4o","choices":[],"usage":{"prompt_tokens":12,"completion_tokens":3,"total_tokens":15}}
+data: [DONE]
+"#;
+
+        let usages = collect_streamed_usage(response_lines).await;
+        assert_eq!(usages.len(), 1);
+        assert_eq!(usages[0].model, "gpt-4o");
+        assert_eq!(usages[0].usage.input_tokens, Some(12));
+        assert_eq!(usages[0].usage.output_tokens, Some(3));
+        assert_eq!(usages[0].usage.total_tokens, Some(15));
+    }
+
+    #[tokio::test]
+    async fn test_streamed_tool_call_keeps_final_usage() {
+        let response_lines = r#"
+data: {"id":"chatcmpl-2","model":"gpt-4o","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"developer__shell","arguments":""}}]},"finish_reason":null}],"usage":{"prompt_tokens":20,"completion_tokens":null,"total_tokens":null}}
+data: {"id":"chatcmpl-2","model":"gpt-4o","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"command\": \"ls\"}"}}]},"finish_reason":null}],"usage":{"prompt_tokens":20,"completion_tokens":null,"total_tokens":null}}
+data: {"id":"chatcmpl-2","model":"gpt-4o","choices":[{"index":0,"delta":{},"finish_reason":"tool_calls"}],"usage":{"prompt_tokens":20,"completion_tokens":7,"total_tokens":27}}
+data: [DONE]
+"#;
+
+        let usages = collect_streamed_usage(response_lines).await;
+        assert_eq!(usages.len(), 1);
+        assert_eq!(usages[0].usage.input_tokens, Some(20));
+        assert_eq!(usages[0].usage.output_tokens, Some(7));
+        assert_eq!(usages[0].usage.total_tokens, Some(27));
+    }
+
+    #[test]
+    fn test_get_usage_with_reasoning_tokens() {
+        let usage = get_usage(&json!({
+            "prompt_tokens": 25,
+            "completion_tokens": 120,
+            "total_tokens": 145,
+            "completion_tokens_details": {"reasoning_tokens": 96}
+        }));
+        assert_eq!(usage.input_tokens, Some(25));
+        assert_eq!(usage.output_tokens, Some(120));
+        assert_eq!(usage.total_tokens, Some(145));
+        assert_eq!(usage.reasoning_tokens, Some(96));
+
+        let usage = get_usage(&json!({"prompt_tokens": 1, "completion_tokens": 2}));
+        assert_eq!(usage.reasoning_tokens, None);
+    }
+
+    #[test]
+    fn test_response_with_multiple_choices() -> anyhow::Result<()> {
+        let model_config = ModelConfig::new_or_fail("gpt-4o").with_n(Some(2));
+        let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
+        assert_eq!(request["n"], json!(2));
+
+        let response = json!({
+            "choices": [
+                {"index": 0, "finish_reason": "stop",
+                 "message": {"role": "assistant", "content": "First answer"}},
+                {"index": 1, "finish_reason": "stop",
+                 "message": {"role": "assistant", "content": "Second answer"}}
+            ],
+            "usage": {"prompt_tokens": 10, "completion_tokens": 8, "total_tokens": 18}
+        });
+
+        let message = response_to_message(&response)?;
+        assert_eq!(message.as_concat_text(), "First answer");
+
+        let usage = get_usage(&response["usage"]).with_choices(choice_count(&response));
+        assert_eq!(usage.choices, Some(2));
+        assert_eq!(usage.output_tokens, Some(8));
+
+        let single = json!({"choices": [{"message": {"role": "assistant", "content": "hi"}}]});
+        assert_eq!(choice_count(&single), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_strict_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "path": {"type": "string"},
+                "limit": {"type": "integer"},
+                "filter": {
+                    "type": "object",
+                    "properties": {"pattern": {"type": "string"}},
+                    "required": ["pattern"]
+                },
+                "tags": {
+                    "type": "array",
+                    "items": {"type": "object", "properties": {"name": {"type": "string"}}}
+                }
+            },
+            "required": ["path"]
+        });
+
+        let strict = to_strict_schema(&schema);
+        assert_eq!(strict["additionalProperties"], json!(false));
+        let required: std::collections::HashSet<&str> = strict["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect();
+        assert_eq!(
+            required,
+            ["path", "limit", "filter", "tags"].into_iter().collect()
+        );
+        assert_eq!(strict["properties"]["path"]["type"], json!("string"));
+        assert_eq!(
+            strict["properties"]["limit"]["type"],
+            json!(["integer", "null"])
+        );
+
+        let filter = &strict["properties"]["filter"];
+        assert_eq!(filter["type"], json!(["object", "null"]));
+        assert_eq!(filter["additionalProperties"], json!(false));
+        assert_eq!(filter["properties"]["pattern"]["type"], json!("string"));
+
+        let item = &strict["properties"]["tags"]["items"];
+        assert_eq!(item["additionalProperties"], json!(false));
+        assert_eq!(item["required"], json!(["name"]));
+        assert_eq!(
+            item["properties"]["name"]["type"],
+            json!(["string", "null"])
+        );
+    }
+
+    #[test]
+    fn test_create_request_strict_tools() -> anyhow::Result<()> {
+        let tool = Tool::new(
+            "read",
+            "Read a file",
+            object!({
+                "type": "object",
+                "properties": {"path": {"type": "string"}, "offset": {"type": "integer"}},
+                "required": ["path"]
+            }),
+        );
+
+        let model_config = ModelConfig::new_or_fail("gpt-4o");
+        let request = create_request(
+            &model_config,
+            "system",
+            &[],
+            std::slice::from_ref(&tool),
+            &ImageFormat::OpenAi,
+        )?;
+        assert!(request["tools"][0]["function"].get("strict").is_none());
+
+        let request = create_request(
+            &model_config.with_strict_tools(true),
+            "system",
+            &[],
+            &[tool],
+            &ImageFormat::OpenAi,
+        )?;
+        let function = &request["tools"][0]["function"];
+        assert_eq!(function["strict"], json!(true));
+        assert_eq!(function["parameters"]["additionalProperties"], json!(false));
+        let required = function["parameters"]["required"].as_array().unwrap();
+        assert_eq!(required.len(), 2);
+        assert!(required.contains(&json!("offset")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_request_sampling_params() -> anyhow::Result<()> {
+        let model_config = ModelConfig::new_or_fail("gpt-4o");
+        let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
+        assert!(request.get("logit_bias").is_none());
+        assert!(request.get("frequency_penalty").is_none());
+        assert!(request.get("presence_penalty").is_none());
+
+        let model_config = model_config
+            .with_logit_bias(Some(std::collections::HashMap::from([(
+                "50256".to_string(),
+                -100.0,
+            )])))
+            .with_frequency_penalty(Some(0.5))
+            .with_presence_penalty(Some(-0.25));
+        let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
+        assert_eq!(request["logit_bias"], json!({"50256": -100.0}));
+        assert_eq!(request["frequency_penalty"], json!(0.5));
+        assert_eq!(request["presence_penalty"], json!(-0.25));
+
+        // Reasoning models reject these parameters
+        let mut o3_config = model_config.clone();
+        o3_config.model_name = "o3-mini".to_string();
+        let request = create_request(&o3_config, "system", &[], &[], &ImageFormat::OpenAi)?;
+        assert!(request.get("frequency_penalty").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_request_stop_and_top_p() -> anyhow::Result<()> {
+        let model_config = ModelConfig::new_or_fail("gpt-4o");
+        let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
+        assert!(request.get("stop").is_none());
+        assert!(request.get("top_p").is_none());
+
+        let model_config = model_config
+            .with_stop(Some(vec!["\n\n".to_string()]))
+            .with_top_p(Some(0.9));
+        let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
+        assert_eq!(request["stop"], json!(["\n\n"]));
+        assert_eq!(request["top_p"], json!(0.9_f32));
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_messages_clamps_oversized_tool_result() {
+        use crate::providers::utils::MAX_CONTENT_BLOCK_CHARS;
+
+        let huge = "x".repeat(MAX_CONTENT_BLOCK_CHARS + 1_000);
+        let messages = vec![
+            Message::assistant().with_tool_request(
+                "tool1",
+                Ok(CallToolRequestParam {
+                    name: "read".into(),
+                    arguments: Some(object!({})),
+                }),
+            ),
+            Message::user().with_tool_response("tool1", Ok(vec![Content::text(huge)])),
+        ];
+
+        let spec = format_messages(&messages, &ImageFormat::OpenAi, Default::default());
+        let content = spec[1]["content"].as_str().unwrap();
+        assert_eq!(spec[1]["role"], "tool");
+        assert!(content.contains("[... 1000 characters truncated"));
+        assert!(content.chars().count() < MAX_CONTENT_BLOCK_CHARS + 200);
+    }
 }
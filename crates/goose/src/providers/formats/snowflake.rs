@@ -2,6 +2,7 @@ use crate::conversation::message::{Message, MessageContent};
 use crate::model::ModelConfig;
 use crate::providers::base::Usage;
 use crate::providers::errors::ProviderError;
+use crate::providers::utils::strip_empty_content;
 use anyhow::{anyhow, Result};
 use rmcp::model::{object, CallToolRequestParam, Role, Tool};
 use rmcp::object;
@@ -321,7 +322,7 @@ pub fn create_request(
     messages: &[Message],
     tools: &[Tool],
 ) -> Result<Value> {
-    let mut snowflake_messages = format_messages(messages);
+    let mut snowflake_messages = format_messages(&strip_empty_content(messages));
     let system_spec = format_system(system);
 
     // Add system message to the beginning of the messages
@@ -20,7 +20,7 @@ use crate::providers::formats::gcpvertexai::{
 
 use crate::providers::formats::gcpvertexai::GcpLocation::Iowa;
 use crate::providers::gcpauth::GcpAuth;
-use crate::providers::retry::RetryConfig;
+use crate::providers::retry::{ProviderRetry, RetryConfig};
 use crate::providers::utils::RequestLog;
 use rmcp::model::Tool;
 
@@ -95,7 +95,7 @@ impl GcpVertexAIProvider {
         let host = format!("https://{}-aiplatform.googleapis.com", location);
 
         let client = Client::builder()
-            .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+            .timeout(model.request_timeout_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS)))
             .build()?;
 
         let auth = GcpAuth::new().await?;
@@ -252,6 +252,8 @@ impl GcpVertexAIProvider {
                 }));
             }
 
+            self.wait_for_rate_limit().await;
+
             // Get a fresh auth token for each attempt
             let auth_header = self
                 .get_auth_header()
@@ -170,7 +170,7 @@ impl GeminiCliProvider {
 
         let message = Message::new(
             Role::Assistant,
-            chrono::Utc::now().timestamp(),
+            crate::clock::now().timestamp(),
             vec![MessageContent::text(response_text)],
         );
 
@@ -212,7 +212,7 @@ impl GeminiCliProvider {
 
         let message = Message::new(
             Role::Assistant,
-            chrono::Utc::now().timestamp(),
+            crate::clock::now().timestamp(),
             vec![MessageContent::text(description.clone())],
         );
 
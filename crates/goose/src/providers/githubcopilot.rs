@@ -9,8 +9,8 @@ use serde_json::Value;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::time::Duration;
 
+use super::api_client::DEFAULT_REQUEST_TIMEOUT;
 use super::base::{Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::errors::ProviderError;
 use super::formats::openai::{create_request, get_usage, response_to_message};
@@ -157,7 +157,7 @@ impl GithubCopilotProvider {
 
     pub async fn from_env(model: ModelConfig) -> Result<Self> {
         let client = Client::builder()
-            .timeout(Duration::from_secs(600))
+            .timeout(model.request_timeout_or(DEFAULT_REQUEST_TIMEOUT))
             .build()?;
         let cache = DiskCache::new();
         let mu = tokio::sync::Mutex::new(RefCell::new(None));
@@ -1,7 +1,10 @@
-use super::api_client::{ApiClient, AuthMethod};
+use super::api_client::{ApiClient, AuthMethod, DEFAULT_REQUEST_TIMEOUT};
 use super::errors::ProviderError;
 use super::retry::ProviderRetry;
-use super::utils::{handle_response_google_compat, unescape_json_values, RequestLog};
+use super::utils::{
+    handle_response_google_compat, unescape_json_values, validate_response_shape, RequestLog,
+    ResponseShape,
+};
 use crate::conversation::message::Message;
 
 use crate::model::ModelConfig;
@@ -58,8 +61,12 @@ impl GoogleProvider {
             key: api_key,
         };
 
-        let api_client =
-            ApiClient::new(host, auth)?.with_header("Content-Type", "application/json")?;
+        let api_client = ApiClient::with_timeout(
+            host,
+            auth,
+            model.request_timeout_or(DEFAULT_REQUEST_TIMEOUT),
+        )?
+        .with_header("Content-Type", "application/json")?;
 
         Ok(Self {
             api_client,
@@ -121,6 +128,7 @@ impl Provider for GoogleProvider {
             })
             .await?;
 
+        validate_response_shape(self.get_name(), ResponseShape::Google, &response)?;
         let message = response_to_message(unescape_json_values(&response))?;
         let usage = get_usage(&response)?;
         let response_model = match response.get("modelVersion") {
@@ -415,6 +415,17 @@ impl Provider for LeadWorkerProvider {
         final_result
     }
 
+    async fn with_model(&self, model: ModelConfig) -> Result<Arc<dyn Provider>, ProviderError> {
+        // The model config is the lead's, so the lead is the one that switches
+        Ok(Arc::new(Self::new_with_settings(
+            self.lead_provider.with_model(model).await?,
+            Arc::clone(&self.worker_provider),
+            self.lead_turns,
+            self.max_failures_before_fallback,
+            self.fallback_turns,
+        )))
+    }
+
     async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
         // Combine models from both providers
         let lead_models = self.lead_provider.fetch_supported_models().await?;
@@ -462,7 +473,6 @@ mod tests {
     use super::*;
     use crate::conversation::message::{Message, MessageContent};
     use crate::providers::base::{ProviderMetadata, ProviderUsage, Usage};
-    use chrono::Utc;
     use rmcp::model::{AnnotateAble, RawTextContent, Role};
 
     #[derive(Clone)]
@@ -478,7 +488,7 @@ mod tests {
         }
 
         fn get_name(&self) -> &str {
-            "mock-lead"
+            &self.name
         }
 
         fn get_model_config(&self) -> ModelConfig {
@@ -495,7 +505,7 @@ mod tests {
             Ok((
                 Message::new(
                     Role::Assistant,
-                    Utc::now().timestamp(),
+                    crate::clock::now().timestamp(),
                     vec![MessageContent::Text(
                         RawTextContent {
                             text: format!("Response from {}", self.name),
@@ -527,6 +537,7 @@ mod tests {
         for i in 0..3 {
             let (_message, usage) = provider.complete("system", &[], &[]).await.unwrap();
             assert_eq!(usage.model, "lead");
+            assert_eq!(usage.provider, "lead");
             assert_eq!(provider.get_turn_count().await, i + 1);
             assert!(!provider.is_in_fallback_mode().await);
         }
@@ -535,6 +546,7 @@ mod tests {
         for i in 3..6 {
             let (_message, usage) = provider.complete("system", &[], &[]).await.unwrap();
             assert_eq!(usage.model, "worker");
+            assert_eq!(usage.provider, "worker");
             assert_eq!(provider.get_turn_count().await, i + 1);
             assert!(!provider.is_in_fallback_mode().await);
         }
@@ -666,7 +678,7 @@ mod tests {
                 Ok((
                     Message::new(
                         Role::Assistant,
-                        Utc::now().timestamp(),
+                        crate::clock::now().timestamp(),
                         vec![MessageContent::Text(
                             RawTextContent {
                                 text: format!("Response from {}", self.name),
@@ -3,7 +3,7 @@ use async_trait::async_trait;
 use serde_json::{json, Value};
 use std::collections::HashMap;
 
-use super::api_client::{ApiClient, AuthMethod};
+use super::api_client::{ApiClient, AuthMethod, NoAuth};
 use super::base::{ConfigKey, ModelInfo, Provider, ProviderMetadata, ProviderUsage};
 use super::embedding::EmbeddingCapable;
 use super::errors::ProviderError;
@@ -52,8 +52,11 @@ impl LiteLLMProvider {
             AuthMethod::BearerToken(api_key)
         };
 
-        let mut api_client =
-            ApiClient::with_timeout(host, auth, std::time::Duration::from_secs(timeout_secs))?;
+        let mut api_client = ApiClient::with_timeout(
+            host,
+            auth,
+            model.request_timeout_or(std::time::Duration::from_secs(timeout_secs)),
+        )?;
 
         if let Some(headers) = custom_headers {
             let mut header_map = reqwest::header::HeaderMap::new();
@@ -121,17 +124,6 @@ impl LiteLLMProvider {
     }
 }
 
-// No authentication provider for LiteLLM when API key is not provided
-struct NoAuth;
-
-#[async_trait]
-impl super::api_client::AuthProvider for NoAuth {
-    async fn get_auth_header(&self) -> Result<(String, String)> {
-        // Return a dummy header that won't be used
-        Ok(("X-No-Auth".to_string(), "true".to_string()))
-    }
-}
-
 #[async_trait]
 impl Provider for LiteLLMProvider {
     fn metadata() -> ProviderMetadata {
@@ -240,6 +232,7 @@ impl EmbeddingCapable for LiteLLMProvider {
             "encoding_format": "float"
         });
 
+        self.wait_for_rate_limit().await;
         let response = self
             .api_client
             .response_post("v1/embeddings", &payload)
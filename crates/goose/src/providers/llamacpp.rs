@@ -0,0 +1,253 @@
+use super::api_client::{ApiClient, AuthMethod, NoAuth};
+use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
+use super::errors::ProviderError;
+use super::retry::ProviderRetry;
+use super::utils::{get_model, handle_response_openai_compat, ImageFormat, RequestLog};
+use crate::conversation::message::Message;
+use crate::model::ModelConfig;
+use crate::providers::formats::openai::{create_request, get_usage, response_to_message};
+use anyhow::Result;
+use async_trait::async_trait;
+use rmcp::model::Tool;
+use serde_json::Value;
+use std::time::Duration;
+
+pub const LLAMACPP_HOST: &str = "http://localhost:8080";
+pub const LLAMACPP_TIMEOUT: u64 = 600;
+pub const LLAMACPP_DEFAULT_MODEL: &str = "default";
+pub const LLAMACPP_DOC_URL: &str = "https://github.com/ggml-org/llama.cpp/tree/master/tools/server";
+
+#[derive(serde::Serialize)]
+pub struct LlamaCppProvider {
+    #[serde(skip)]
+    api_client: ApiClient,
+    model: ModelConfig,
+    #[serde(skip)]
+    name: String,
+}
+
+impl LlamaCppProvider {
+    pub async fn from_env(model: ModelConfig) -> Result<Self> {
+        let config = crate::config::Config::global();
+        let host: String = config
+            .get_param("LLAMACPP_HOST")
+            .unwrap_or_else(|_| LLAMACPP_HOST.to_string());
+        let timeout: Duration = Duration::from_secs(
+            config
+                .get_param("LLAMACPP_TIMEOUT")
+                .unwrap_or(LLAMACPP_TIMEOUT),
+        );
+        // Many GGUF chat templates have no native tool calling; fall back to the
+        // interpreter-based toolshim when requested.
+        let emulate_tools: bool = config.get_param("LLAMACPP_TOOLSHIM").unwrap_or(false);
+        let model = if emulate_tools {
+            model.with_toolshim(true)
+        } else {
+            model
+        };
+
+        let auth = AuthMethod::Custom(Box::new(NoAuth));
+        let api_client = ApiClient::with_timeout(host, auth, model.request_timeout_or(timeout))?;
+
+        Ok(Self {
+            api_client,
+            model,
+            name: Self::metadata().name,
+        })
+    }
+
+    async fn post(&self, payload: &Value) -> Result<Value, ProviderError> {
+        let response = self
+            .api_client
+            .response_post("v1/chat/completions", payload)
+            .await?;
+        handle_response_openai_compat(response).await
+    }
+}
+
+/// Read usage from a llama.cpp response.
+///
+/// Prefers the OpenAI `usage` object and falls back to llama.cpp's native
+/// `tokens_evaluated`/`tokens_predicted` counters, which some server builds
+/// report instead (either at the top level or inside `usage`).
+pub fn get_llamacpp_usage(response: &Value) -> Usage {
+    let usage = response.get("usage");
+    if let Some(usage) = usage {
+        let parsed = get_usage(usage);
+        if parsed.input_tokens.is_some() || parsed.output_tokens.is_some() {
+            return parsed;
+        }
+    }
+
+    let native = |key: &str| {
+        usage
+            .and_then(|u| u.get(key))
+            .or_else(|| response.get(key))
+            .and_then(|v| v.as_i64())
+            .map(|v| v as i32)
+    };
+    let input_tokens = native("tokens_evaluated");
+    let output_tokens = native("tokens_predicted");
+    let total_tokens = match (input_tokens, output_tokens) {
+        (Some(input), Some(output)) => Some(input + output),
+        _ => None,
+    };
+
+    Usage::new(input_tokens, output_tokens, total_tokens)
+}
+
+#[async_trait]
+impl Provider for LlamaCppProvider {
+    fn metadata() -> ProviderMetadata {
+        ProviderMetadata::new(
+            "llamacpp",
+            "llama.cpp",
+            "Models served by a local llama.cpp server",
+            LLAMACPP_DEFAULT_MODEL,
+            vec![],
+            LLAMACPP_DOC_URL,
+            vec![
+                ConfigKey::new("LLAMACPP_HOST", true, false, Some(LLAMACPP_HOST)),
+                ConfigKey::new(
+                    "LLAMACPP_TIMEOUT",
+                    false,
+                    false,
+                    Some(&(LLAMACPP_TIMEOUT.to_string())),
+                ),
+                ConfigKey::new("LLAMACPP_TOOLSHIM", false, false, Some("false")),
+            ],
+        )
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.model.clone()
+    }
+
+    #[tracing::instrument(
+        skip(self, model_config, system, messages, tools),
+        fields(model_config, input, output, input_tokens, output_tokens, total_tokens)
+    )]
+    async fn complete_with_model(
+        &self,
+        model_config: &ModelConfig,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let payload = create_request(model_config, system, messages, tools, &ImageFormat::OpenAi)?;
+
+        let mut log = RequestLog::start(model_config, &payload)?;
+        let response = self
+            .with_retry(|| async {
+                let payload_clone = payload.clone();
+                self.post(&payload_clone).await
+            })
+            .await
+            .inspect_err(|e| {
+                let _ = log.error(e);
+            })?;
+
+        let message = response_to_message(&response)?;
+        let usage = get_llamacpp_usage(&response);
+        let response_model = get_model(&response);
+        log.write(&response, Some(&usage))?;
+        Ok((message, ProviderUsage::new(response_model, usage)))
+    }
+
+    async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
+        let response = self.api_client.response_get("v1/models").await?;
+        let json = handle_response_openai_compat(response).await?;
+
+        let mut models: Vec<String> = json
+            .get("data")
+            .and_then(|d| d.as_array())
+            .map(|data| {
+                data.iter()
+                    .filter_map(|m| m.get("id").and_then(|id| id.as_str()).map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        models.sort();
+
+        Ok(Some(models))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_usage_prefers_openai_fields() {
+        let response = json!({
+            "usage": {"prompt_tokens": 12, "completion_tokens": 5, "total_tokens": 17},
+            "tokens_evaluated": 99,
+            "tokens_predicted": 99
+        });
+        let usage = get_llamacpp_usage(&response);
+        assert_eq!(usage.input_tokens, Some(12));
+        assert_eq!(usage.output_tokens, Some(5));
+        assert_eq!(usage.total_tokens, Some(17));
+    }
+
+    #[test]
+    fn test_usage_falls_back_to_native_fields() {
+        let top_level = json!({"tokens_evaluated": 30, "tokens_predicted": 8});
+        let usage = get_llamacpp_usage(&top_level);
+        assert_eq!(usage.input_tokens, Some(30));
+        assert_eq!(usage.output_tokens, Some(8));
+        assert_eq!(usage.total_tokens, Some(38));
+
+        let nested = json!({"usage": {"tokens_evaluated": 4, "tokens_predicted": 2}});
+        let usage = get_llamacpp_usage(&nested);
+        assert_eq!(usage.input_tokens, Some(4));
+        assert_eq!(usage.output_tokens, Some(2));
+
+        let usage = get_llamacpp_usage(&json!({}));
+        assert_eq!(usage.input_tokens, None);
+        assert_eq!(usage.total_tokens, None);
+    }
+
+    #[tokio::test]
+    async fn test_requests_per_minute_limits_requests() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "model": "llama",
+                "choices": [{"message": {"role": "assistant", "content": "hi"}}],
+                "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2}
+            })))
+            .mount(&server)
+            .await;
+
+        let model = ModelConfig::new_or_fail("llama").with_requests_per_minute(Some(1));
+        let provider = LlamaCppProvider {
+            api_client: ApiClient::new(server.uri(), AuthMethod::Custom(Box::new(NoAuth))).unwrap(),
+            model: model.clone(),
+            name: "llamacpp-rate-limit-test".to_string(),
+        };
+        let messages = [Message::user().with_text("hello")];
+
+        provider
+            .complete_with_model(&model, "system", &messages, &[])
+            .await
+            .unwrap();
+        // One request per minute leaves no budget for a second request right away
+        let second = tokio::time::timeout(
+            Duration::from_millis(500),
+            provider.complete_with_model(&model, "system", &messages, &[]),
+        )
+        .await;
+
+        assert!(second.is_err());
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+}
@@ -10,6 +10,7 @@ pub mod databricks;
 pub mod embedding;
 pub mod errors;
 mod factory;
+pub mod fallback;
 pub mod formats;
 mod gcpauth;
 pub mod gcpvertexai;
@@ -18,6 +19,7 @@ pub mod githubcopilot;
 pub mod google;
 pub mod lead_worker;
 pub mod litellm;
+pub mod llamacpp;
 pub mod oauth;
 pub mod ollama;
 pub mod openai;
@@ -25,13 +27,16 @@ pub mod openrouter;
 pub mod pricing;
 pub mod provider_registry;
 pub mod provider_test;
-mod retry;
+pub mod response_cache;
+pub mod retry;
 pub mod sagemaker_tgi;
 pub mod snowflake;
 pub mod testprovider;
 pub mod tetrate;
 pub mod toolshim;
 pub mod usage_estimator;
+pub mod usage_metrics;
+pub mod usage_report;
 pub mod utils;
 pub mod utils_universal_openai_stream;
 pub mod venice;
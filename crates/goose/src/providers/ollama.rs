@@ -1,4 +1,4 @@
-use super::api_client::{ApiClient, AuthMethod};
+use super::api_client::{ApiClient, AuthMethod, NoAuth};
 use super::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::errors::ProviderError;
 use super::retry::ProviderRetry;
@@ -80,7 +80,11 @@ impl OllamaProvider {
         }
 
         let auth = AuthMethod::Custom(Box::new(NoAuth));
-        let api_client = ApiClient::with_timeout(base_url.to_string(), auth, timeout)?;
+        let api_client = ApiClient::with_timeout(
+            base_url.to_string(),
+            auth,
+            model.request_timeout_or(timeout),
+        )?;
 
         Ok(Self {
             api_client,
@@ -117,7 +121,11 @@ impl OllamaProvider {
         }
 
         let auth = AuthMethod::Custom(Box::new(NoAuth));
-        let api_client = ApiClient::with_timeout(base_url.to_string(), auth, timeout)?;
+        let api_client = ApiClient::with_timeout(
+            base_url.to_string(),
+            auth,
+            model.request_timeout_or(timeout),
+        )?;
 
         Ok(Self {
             api_client,
@@ -136,13 +144,42 @@ impl OllamaProvider {
     }
 }
 
-struct NoAuth;
+/// The chat request for Ollama's OpenAI-compatible endpoint. Tools are left out in chat
+/// mode, where the model should only talk.
+fn create_chat_request(
+    model_config: &ModelConfig,
+    system: &str,
+    messages: &[Message],
+    tools: &[Tool],
+    goose_mode: GooseMode,
+) -> Result<Value, ProviderError> {
+    let tools = if goose_mode == GooseMode::Chat {
+        &[]
+    } else {
+        tools
+    };
+    Ok(create_request(
+        model_config,
+        system,
+        messages,
+        tools,
+        &super::utils::ImageFormat::OpenAi,
+    )?)
+}
 
-#[async_trait]
-impl super::api_client::AuthProvider for NoAuth {
-    async fn get_auth_header(&self) -> Result<(String, String)> {
-        Ok(("X-No-Auth".to_string(), "true".to_string()))
-    }
+/// Model names from an Ollama `/api/tags` response, sorted
+pub fn parse_model_tags(response: &Value) -> Result<Vec<String>, ProviderError> {
+    let models = response
+        .get("models")
+        .and_then(|m| m.as_array())
+        .ok_or_else(|| ProviderError::RequestFailed("No models array in response".to_string()))?;
+
+    let mut model_names: Vec<String> = models
+        .iter()
+        .filter_map(|model| model.get("name").and_then(|n| n.as_str()).map(String::from))
+        .collect();
+    model_names.sort();
+    Ok(model_names)
 }
 
 #[async_trait]
@@ -186,21 +223,10 @@ impl Provider for OllamaProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<(Message, ProviderUsage), ProviderError> {
-        let config = crate::config::Config::global();
-        let goose_mode = config.get_goose_mode().unwrap_or(GooseMode::Auto);
-        let filtered_tools = if goose_mode == GooseMode::Chat {
-            &[]
-        } else {
-            tools
-        };
-
-        let payload = create_request(
-            model_config,
-            system,
-            messages,
-            filtered_tools,
-            &super::utils::ImageFormat::OpenAi,
-        )?;
+        let goose_mode = crate::config::Config::global()
+            .get_goose_mode()
+            .unwrap_or(GooseMode::Auto);
+        let payload = create_chat_request(model_config, system, messages, tools, goose_mode)?;
 
         let mut log = RequestLog::start(model_config, &payload)?;
         let response = self
@@ -254,13 +280,10 @@ impl Provider for OllamaProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<MessageStream, ProviderError> {
-        let mut payload = create_request(
-            &self.model,
-            system,
-            messages,
-            tools,
-            &super::utils::ImageFormat::OpenAi,
-        )?;
+        let goose_mode = crate::config::Config::global()
+            .get_goose_mode()
+            .unwrap_or(GooseMode::Auto);
+        let mut payload = create_chat_request(&self.model, system, messages, tools, goose_mode)?;
         payload["stream"] = json!(true);
         payload["stream_options"] = json!({
             "include_usage": true,
@@ -275,7 +298,8 @@ impl Provider for OllamaProvider {
                     .await?;
                 let status = resp.status();
                 if !status.is_success() {
-                    return Err(super::utils::map_http_error_to_provider_error(status, None));
+                    return Err(super::utils::map_http_error_to_provider_error(status, None)
+                        .with_retry_delay(super::utils::retry_after_from_headers(resp.headers())));
                 }
                 Ok(resp)
             })
@@ -318,21 +342,7 @@ impl Provider for OllamaProvider {
             ProviderError::RequestFailed(format!("Failed to parse response: {}", e))
         })?;
 
-        let models = json_response
-            .get("models")
-            .and_then(|m| m.as_array())
-            .ok_or_else(|| {
-                ProviderError::RequestFailed("No models array in response".to_string())
-            })?;
-
-        let mut model_names: Vec<String> = models
-            .iter()
-            .filter_map(|model| model.get("name").and_then(|n| n.as_str()).map(String::from))
-            .collect();
-
-        model_names.sort();
-
-        Ok(Some(model_names))
+        Ok(Some(parse_model_tags(&json_response)?))
     }
 }
 
@@ -375,3 +385,101 @@ impl OllamaProvider {
         filtered
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::object;
+    use std::time::Instant;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_request_timeout_rejects_slow_response() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(10)))
+                .mount(&server)
+                .await;
+            server
+        });
+
+        let uri = server.uri();
+        temp_env::with_vars(
+            [
+                ("OLLAMA_HOST", Some(uri.as_str())),
+                ("GOOSE_REQUEST_TIMEOUT", Some("1")),
+            ],
+            || {
+                rt.block_on(async {
+                    let model = ModelConfig::new_or_fail("qwen3");
+                    assert_eq!(model.request_timeout, Some(Duration::from_secs(1)));
+                    let provider = OllamaProvider::from_env(model).await.unwrap();
+
+                    let started = Instant::now();
+                    let err = provider
+                        .complete("system", &[Message::user().with_text("hi")], &[])
+                        .await
+                        .unwrap_err();
+                    assert!(matches!(err, ProviderError::NetworkError(_)));
+                    assert!(started.elapsed() < Duration::from_secs(10));
+                })
+            },
+        );
+    }
+
+    #[test]
+    fn test_create_request_sends_tools_as_functions() {
+        let model = ModelConfig::new_or_fail("qwen3");
+        let tool = Tool::new(
+            "developer__shell",
+            "Run a shell command",
+            object!({
+                "type": "object",
+                "properties": {"command": {"type": "string"}},
+                "required": ["command"]
+            }),
+        );
+        let messages = [Message::user().with_text("list files")];
+
+        let payload = create_chat_request(
+            &model,
+            "system",
+            &messages,
+            std::slice::from_ref(&tool),
+            GooseMode::Auto,
+        )
+        .unwrap();
+        assert_eq!(payload["model"], "qwen3");
+        assert_eq!(payload["messages"][0]["role"], "system");
+        assert_eq!(payload["messages"][1]["role"], "user");
+        assert_eq!(payload["tools"][0]["type"], "function");
+        assert_eq!(payload["tools"][0]["function"]["name"], "developer__shell");
+        assert_eq!(
+            payload["tools"][0]["function"]["parameters"]["required"],
+            json!(["command"])
+        );
+
+        let payload =
+            create_chat_request(&model, "system", &messages, &[tool], GooseMode::Chat).unwrap();
+        assert!(payload.get("tools").is_none());
+    }
+
+    #[test]
+    fn test_parse_model_tags() {
+        let response = json!({
+            "models": [
+                {"name": "qwen3:latest", "model": "qwen3:latest", "size": 5225388164u64},
+                {"name": "llama3.2:3b", "model": "llama3.2:3b", "size": 2019393189u64},
+                {"model": "unnamed"}
+            ]
+        });
+        assert_eq!(
+            parse_model_tags(&response).unwrap(),
+            vec!["llama3.2:3b", "qwen3:latest"]
+        );
+        assert!(parse_model_tags(&json!({"error": "not found"})).is_err());
+    }
+}
@@ -12,13 +12,16 @@ use tokio_util::codec::{FramedRead, LinesCodec};
 use tokio_util::io::StreamReader;
 
 use super::api_client::{ApiClient, AuthMethod};
-use super::base::{ConfigKey, ModelInfo, Provider, ProviderMetadata, ProviderUsage, Usage};
+use super::base::{
+    ConfigKey, ModelInfo, Provider, ProviderMetadata, ProviderUsage, RateLimitInfo, Usage,
+};
 use super::embedding::{EmbeddingCapable, EmbeddingRequest, EmbeddingResponse};
 use super::errors::ProviderError;
-use super::formats::openai::{create_request, get_usage, response_to_message};
+use super::formats::openai::{choice_count, create_request, get_usage, response_to_message};
 use super::retry::ProviderRetry;
 use super::utils::{
-    get_model, handle_response_openai_compat, handle_status_openai_compat, ImageFormat,
+    get_model, handle_response_openai_compat, handle_status_openai_compat, rate_limit_from_headers,
+    validate_response_shape, ImageFormat, ResponseShape,
 };
 use crate::config::declarative_providers::DeclarativeProviderConfig;
 use crate::conversation::message::Message;
@@ -78,10 +81,18 @@ impl OpenAiProvider {
             .ok()
             .map(parse_custom_headers);
         let timeout_secs: u64 = config.get_param("OPENAI_TIMEOUT").unwrap_or(600);
+        let requests_per_minute: Option<u32> = config
+            .get_param("OPENAI_RPM")
+            .ok()
+            .or(model.requests_per_minute);
+        let model = model.with_requests_per_minute(requests_per_minute);
 
         let auth = AuthMethod::BearerToken(api_key);
-        let mut api_client =
-            ApiClient::with_timeout(host, auth, std::time::Duration::from_secs(timeout_secs))?;
+        let mut api_client = ApiClient::with_timeout(
+            host,
+            auth,
+            model.request_timeout_or(std::time::Duration::from_secs(timeout_secs)),
+        )?;
 
         if let Some(org) = &organization {
             api_client = api_client.with_header("OpenAI-Organization", org)?;
@@ -158,8 +169,11 @@ impl OpenAiProvider {
 
         let timeout_secs = config.timeout_seconds.unwrap_or(600);
         let auth = AuthMethod::BearerToken(api_key);
-        let mut api_client =
-            ApiClient::with_timeout(host, auth, std::time::Duration::from_secs(timeout_secs))?;
+        let mut api_client = ApiClient::with_timeout(
+            host,
+            auth,
+            model.request_timeout_or(std::time::Duration::from_secs(timeout_secs)),
+        )?;
 
         // Add custom headers if present
         if let Some(headers) = &config.headers {
@@ -184,12 +198,13 @@ impl OpenAiProvider {
         })
     }
 
-    async fn post(&self, payload: &Value) -> Result<Value, ProviderError> {
+    async fn post(&self, payload: &Value) -> Result<(Value, Option<RateLimitInfo>), ProviderError> {
         let response = self
             .api_client
             .response_post(&self.base_path, payload)
             .await?;
-        handle_response_openai_compat(response).await
+        let rate_limit = read_rate_limit(&response);
+        Ok((handle_response_openai_compat(response).await?, rate_limit))
     }
 }
 
@@ -215,6 +230,7 @@ impl Provider for OpenAiProvider {
                 ConfigKey::new("OPENAI_PROJECT", false, false, None),
                 ConfigKey::new("OPENAI_CUSTOM_HEADERS", false, true, None),
                 ConfigKey::new("OPENAI_TIMEOUT", false, false, Some("600")),
+                ConfigKey::new("OPENAI_RPM", false, false, None),
             ],
         )
     }
@@ -238,10 +254,12 @@ impl Provider for OpenAiProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<(Message, ProviderUsage), ProviderError> {
-        let payload = create_request(model_config, system, messages, tools, &ImageFormat::OpenAi)?;
+        let mut payload =
+            create_request(model_config, system, messages, tools, &ImageFormat::OpenAi)?;
+        apply_service_tier(&mut payload, model_config);
 
         let mut log = RequestLog::start(&self.model, &payload)?;
-        let json_response = self
+        let (json_response, rate_limit) = self
             .with_retry(|| async {
                 let payload_clone = payload.clone();
                 self.post(&payload_clone).await
@@ -251,6 +269,7 @@ impl Provider for OpenAiProvider {
                 let _ = log.error(e);
             })?;
 
+        validate_response_shape(self.get_name(), ResponseShape::OpenAi, &json_response)?;
         let message = response_to_message(&json_response)?;
         let usage = json_response
             .get("usage")
@@ -258,11 +277,15 @@ impl Provider for OpenAiProvider {
             .unwrap_or_else(|| {
                 tracing::debug!("Failed to get usage data");
                 Usage::default()
-            });
+            })
+            .with_choices(choice_count(&json_response));
 
         let model = get_model(&json_response);
         log.write(&json_response, Some(&usage))?;
-        Ok((message, ProviderUsage::new(model, usage)))
+        Ok((
+            message,
+            ProviderUsage::new(model, usage).with_rate_limit(rate_limit),
+        ))
     }
 
     async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
@@ -321,6 +344,7 @@ impl Provider for OpenAiProvider {
     ) -> Result<MessageStream, ProviderError> {
         let mut payload =
             create_request(&self.model, system, messages, tools, &ImageFormat::OpenAi)?;
+        apply_service_tier(&mut payload, &self.model);
         payload["stream"] = serde_json::Value::Bool(true);
         payload["stream_options"] = json!({
             "include_usage": true,
@@ -337,7 +361,8 @@ impl Provider for OpenAiProvider {
                 if !status.is_success() {
                     return Err(super::utils::map_http_error_to_provider_error(
                         status, None, // We'll let handle_status_openai_compat parse the error
-                    ));
+                    )
+                    .with_retry_delay(super::utils::retry_after_from_headers(resp.headers())));
                 }
                 Ok(resp)
             })
@@ -346,6 +371,7 @@ impl Provider for OpenAiProvider {
                 let _ = log.error(e);
             })?;
         let response = handle_status_openai_compat(response).await?;
+        let rate_limit = read_rate_limit(&response);
 
         let stream = response.bytes_stream().map_err(io::Error::other);
 
@@ -357,6 +383,7 @@ impl Provider for OpenAiProvider {
             pin!(message_stream);
             while let Some(message) = message_stream.next().await {
                 let (message, usage) = message.map_err(|e| ProviderError::RequestFailed(format!("Stream decode error: {}", e)))?;
+                let usage = usage.map(|usage| usage.with_rate_limit(rate_limit.clone()));
                 log.write(&message, usage.as_ref().map(|f| f.usage).as_ref())?;
                 yield (message, usage);
             }
@@ -364,6 +391,17 @@ impl Provider for OpenAiProvider {
     }
 }
 
+/// Rate-limit headroom from `response`'s headers, also logged for monitoring
+fn read_rate_limit(response: &reqwest::Response) -> Option<RateLimitInfo> {
+    let rate_limit = rate_limit_from_headers(response.headers())?;
+    tracing::debug!(
+        remaining_requests = rate_limit.remaining_requests,
+        remaining_tokens = rate_limit.remaining_tokens,
+        "Provider rate limit headroom"
+    );
+    Some(rate_limit)
+}
+
 fn parse_custom_headers(s: String) -> HashMap<String, String> {
     s.split(',')
         .filter_map(|header| {
@@ -427,3 +465,31 @@ impl EmbeddingCapable for OpenAiProvider {
             .collect())
     }
 }
+
+/// Ask for the model config's processing tier, if one is set. Only OpenAI's own API takes
+/// `service_tier`, so the shared request format leaves it out.
+fn apply_service_tier(payload: &mut Value, model_config: &ModelConfig) {
+    if let Some(tier) = model_config.service_tier {
+        payload["service_tier"] = json!(tier.as_str());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ServiceTier;
+
+    #[test]
+    fn test_apply_service_tier() -> anyhow::Result<()> {
+        let model_config = ModelConfig::new_or_fail("gpt-4o");
+        let mut request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
+        apply_service_tier(&mut request, &model_config);
+        assert!(request.get("service_tier").is_none());
+
+        let model_config = model_config.with_service_tier(Some(ServiceTier::Flex));
+        apply_service_tier(&mut request, &model_config);
+        assert_eq!(request["service_tier"], json!("flex"));
+
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::{Error, Result};
 use async_trait::async_trait;
 use serde_json::{json, Value};
 
-use super::api_client::{ApiClient, AuthMethod};
+use super::api_client::{ApiClient, AuthMethod, DEFAULT_REQUEST_TIMEOUT};
 use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::errors::ProviderError;
 use super::retry::ProviderRetry;
@@ -55,9 +55,13 @@ impl OpenRouterProvider {
             .unwrap_or_else(|_| "https://openrouter.ai".to_string());
 
         let auth = AuthMethod::BearerToken(api_key);
-        let api_client = ApiClient::new(host, auth)?
-            .with_header("HTTP-Referer", "https://block.github.io/goose")?
-            .with_header("X-Title", "goose")?;
+        let api_client = ApiClient::with_timeout(
+            host,
+            auth,
+            model.request_timeout_or(DEFAULT_REQUEST_TIMEOUT),
+        )?
+        .with_header("HTTP-Referer", "https://block.github.io/goose")?
+        .with_header("X-Title", "goose")?;
 
         Ok(Self {
             api_client,
@@ -102,7 +106,7 @@ impl OpenRouterProvider {
 
             // Check for context length errors in the error message
             if error_code == 400 && error_message.contains("maximum context length") {
-                return Err(ProviderError::ContextLengthExceeded(
+                return Err(ProviderError::context_length_exceeded(
                     error_message.to_string(),
                 ));
             }
@@ -272,6 +272,11 @@ pub async fn initialize_pricing_cache() -> Result<()> {
 
 /// Get pricing for a specific model
 pub async fn get_model_pricing(provider: &str, model: &str) -> Option<PricingInfo> {
+    // Local models cost nothing to run, so leave their cost unknown rather than borrowing a
+    // hosted price for the same model
+    if provider.eq_ignore_ascii_case("ollama") {
+        return None;
+    }
     PRICING_CACHE.get_model_pricing(provider, model).await
 }
 
@@ -309,7 +314,6 @@ pub fn parse_model_id(model_id: &str) -> Option<(String, String)> {
             "openai" => "openai",
             "anthropic" => "anthropic",
             "google" => "google",
-            "meta-llama" => "ollama", // Meta models often run via Ollama
             "mistralai" => "mistral",
             "cohere" => "cohere",
             "perplexity" => "perplexity",
@@ -369,6 +373,13 @@ mod tests {
         assert_eq!(convert_pricing("invalid"), None);
     }
 
+    #[tokio::test]
+    async fn test_local_models_have_no_pricing() {
+        assert!(get_model_pricing("ollama", "llama-3.3-70b-instruct")
+            .await
+            .is_none());
+    }
+
     #[tokio::test]
     async fn test_claude_sonnet_4_pricing_lookup() {
         // Initialize the cache to load from disk
@@ -0,0 +1,360 @@
+use anyhow::Result;
+use async_stream::try_stream;
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::base::{
+    stream_from_single_message, LeadWorkerProviderTrait, MessageStream, Provider, ProviderMetadata,
+    ProviderUsage,
+};
+use super::errors::ProviderError;
+use crate::conversation::message::{Message, MessageContent};
+use crate::model::ModelConfig;
+use rmcp::model::Tool;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedResponse {
+    message: Message,
+    usage: ProviderUsage,
+}
+
+/// Wraps a provider with an on-disk cache of complete responses, so repeated
+/// identical requests (common in CI and evals) skip the provider entirely.
+pub struct CachingProvider {
+    inner: Arc<dyn Provider>,
+    dir: PathBuf,
+}
+
+impl CachingProvider {
+    pub fn new(inner: Arc<dyn Provider>, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            dir: dir.into(),
+        }
+    }
+
+    /// Keys on the request content only; message ids and timestamps vary between runs.
+    fn cache_key(
+        model_config: &ModelConfig,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> String {
+        let stable_messages: Vec<_> = messages
+            .iter()
+            .map(|msg| (msg.role.clone(), msg.content.clone()))
+            .collect();
+        let serialized = serde_json::to_string(&json!({
+            "system": system,
+            "messages": stable_messages,
+            "tools": tools,
+            "model_config": model_config,
+        }))
+        .unwrap_or_default();
+        format!("{:x}", Sha256::digest(serialized.as_bytes()))
+    }
+
+    fn entry_path(dir: &Path, key: &str) -> PathBuf {
+        dir.join(format!("{}.json", key))
+    }
+
+    fn load(&self, key: &str) -> Option<CachedResponse> {
+        let content = fs::read_to_string(Self::entry_path(&self.dir, key)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn store(dir: &Path, key: &str, entry: &CachedResponse) -> Result<()> {
+        fs::create_dir_all(dir)?;
+        fs::write(Self::entry_path(dir, key), serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+}
+
+/// Add a streamed chunk to the reply assembled so far, joining consecutive text
+fn append_chunk(reply: &mut Option<Message>, chunk: &Message) {
+    match reply {
+        Some(reply) => {
+            for content in &chunk.content {
+                match (reply.content.last_mut(), content) {
+                    (Some(MessageContent::Text(last)), MessageContent::Text(text)) => {
+                        last.text.push_str(&text.text)
+                    }
+                    _ => reply.content.push(content.clone()),
+                }
+            }
+        }
+        None => *reply = Some(chunk.clone()),
+    }
+}
+
+#[async_trait]
+impl Provider for CachingProvider {
+    fn metadata() -> ProviderMetadata {
+        // This is a wrapper provider, so we return minimal metadata
+        ProviderMetadata::new(
+            "response_cache",
+            "Response Cache",
+            "Serves repeated identical requests from an on-disk cache",
+            "",
+            vec![],
+            "",
+            vec![],
+        )
+    }
+
+    fn get_name(&self) -> &str {
+        self.inner.get_name()
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.inner.get_model_config()
+    }
+
+    async fn complete_with_model(
+        &self,
+        model_config: &ModelConfig,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let key = Self::cache_key(model_config, system, messages, tools);
+
+        if let Some(cached) = self.load(&key) {
+            tracing::debug!("Response cache hit: {}", key);
+            // Report the original token counts, marked so they are not charged again
+            return Ok((cached.message, cached.usage.with_cached(true)));
+        }
+
+        let (message, usage) = self
+            .inner
+            .complete_with_model(model_config, system, messages, tools)
+            .await?;
+
+        let entry = CachedResponse {
+            message: message.clone(),
+            usage: usage.clone(),
+        };
+        if let Err(e) = Self::store(&self.dir, &key, &entry) {
+            tracing::warn!("Failed to write response cache entry {}: {}", key, e);
+        }
+
+        Ok((message, usage))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.inner.supports_streaming()
+    }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let key = Self::cache_key(&self.get_model_config(), system, messages, tools);
+
+        if let Some(cached) = self.load(&key) {
+            tracing::debug!("Response cache hit: {}", key);
+            return Ok(stream_from_single_message(
+                cached.message,
+                cached.usage.with_cached(true),
+            ));
+        }
+
+        // Pass the chunks through as they arrive and cache the whole reply once it completes
+        let mut inner = self.inner.stream(system, messages, tools).await?;
+        let dir = self.dir.clone();
+        Ok(Box::pin(try_stream! {
+            let mut reply = None;
+            let mut reply_usage = None;
+            while let Some(chunk) = inner.next().await {
+                let (message, usage) = chunk?;
+                if let Some(message) = &message {
+                    append_chunk(&mut reply, message);
+                }
+                if usage.is_some() {
+                    reply_usage = usage.clone();
+                }
+                yield (message, usage);
+            }
+            if let (Some(message), Some(usage)) = (reply, reply_usage) {
+                if let Err(e) = Self::store(&dir, &key, &CachedResponse { message, usage }) {
+                    tracing::warn!("Failed to write response cache entry {}: {}", key, e);
+                }
+            }
+        }))
+    }
+
+    async fn with_model(&self, model: ModelConfig) -> Result<Arc<dyn Provider>, ProviderError> {
+        let inner = self.inner.with_model(model).await?;
+        Ok(Arc::new(Self::new(inner, self.dir.clone())))
+    }
+
+    async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
+        self.inner.fetch_supported_models().await
+    }
+
+    fn supports_embeddings(&self) -> bool {
+        self.inner.supports_embeddings()
+    }
+
+    async fn supports_cache_control(&self) -> bool {
+        self.inner.supports_cache_control().await
+    }
+
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, ProviderError> {
+        self.inner.create_embeddings(texts).await
+    }
+
+    fn as_lead_worker(&self) -> Option<&dyn LeadWorkerProviderTrait> {
+        self.inner.as_lead_worker()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::Usage;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingProvider {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Provider for CountingProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        fn get_name(&self) -> &str {
+            "counting"
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            ModelConfig::new_or_fail("counting-model")
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok((
+                Message::assistant().with_text(format!("response {}", n)),
+                ProviderUsage::new(
+                    "counting-model".to_string(),
+                    Usage::new(Some(10), Some(5), None),
+                ),
+            ))
+        }
+
+        fn supports_streaming(&self) -> bool {
+            true
+        }
+
+        /// Streams "streamed " and the call count as two chunks, with usage on the last
+        async fn stream(
+            &self,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<MessageStream, ProviderError> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            let usage = ProviderUsage::new(
+                "counting-model".to_string(),
+                Usage::new(Some(10), Some(5), None),
+            );
+            let chunks: Vec<Result<_, ProviderError>> = vec![
+                Ok((Some(Message::assistant().with_text("streamed ")), None)),
+                Ok((
+                    Some(Message::assistant().with_text(n.to_string())),
+                    Some(usage),
+                )),
+            ];
+            Ok(Box::pin(futures::stream::iter(chunks)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_miss_then_hit() {
+        let dir =
+            std::env::temp_dir().join(format!("goose-response-cache-{}", uuid::Uuid::new_v4()));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = CachingProvider::new(
+            Arc::new(CountingProvider {
+                calls: calls.clone(),
+            }),
+            &dir,
+        );
+
+        let messages = vec![Message::user().with_text("hello")];
+        let (first, first_usage) = provider.complete("system", &messages, &[]).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(first_usage.usage.total_tokens, Some(15));
+
+        // Same content with a fresh message id and timestamp still hits
+        let messages = vec![Message::user().with_text("hello")];
+        let (second, second_usage) = provider.complete("system", &messages, &[]).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(second.as_concat_text(), first.as_concat_text());
+        assert_eq!(second_usage.usage.total_tokens, Some(15));
+        assert!(second_usage.cached);
+        assert!(!first_usage.cached);
+
+        let messages = vec![Message::user().with_text("something else")];
+        let (third, _) = provider.complete("system", &messages, &[]).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(third.as_concat_text(), "response 2");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_streamed_reply_is_cached() {
+        let dir =
+            std::env::temp_dir().join(format!("goose-response-cache-{}", uuid::Uuid::new_v4()));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = CachingProvider::new(
+            Arc::new(CountingProvider {
+                calls: calls.clone(),
+            }),
+            &dir,
+        );
+        assert!(provider.supports_streaming());
+
+        let messages = vec![Message::user().with_text("hello")];
+        let first: Vec<_> = provider
+            .stream("system", &messages, &[])
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(first.len(), 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let second: Vec<_> = provider
+            .stream("system", &messages, &[])
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let (message, usage) = second.into_iter().next().unwrap().unwrap();
+        assert_eq!(message.unwrap().as_concat_text(), "streamed 1");
+        let usage = usage.unwrap();
+        assert!(usage.cached);
+        assert_eq!(usage.usage.input_tokens, Some(10));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,4 +1,5 @@
 use super::errors::ProviderError;
+use super::utils::RateLimiter;
 use crate::providers::base::Provider;
 use async_trait::async_trait;
 use std::future::Future;
@@ -48,6 +49,22 @@ impl RetryConfig {
         }
     }
 
+    /// The default policy, with the retry count and first backoff interval taken from
+    /// `GOOSE_MAX_RETRIES` and `GOOSE_RETRY_INITIAL_INTERVAL_MS` when set
+    pub fn from_config() -> Self {
+        let config = crate::config::Config::global();
+        let defaults = Self::default();
+        Self {
+            max_retries: config
+                .get_param::<usize>("GOOSE_MAX_RETRIES")
+                .unwrap_or(defaults.max_retries),
+            initial_interval_ms: config
+                .get_param::<u64>("GOOSE_RETRY_INITIAL_INTERVAL_MS")
+                .unwrap_or(defaults.initial_interval_ms),
+            ..defaults
+        }
+    }
+
     pub fn delay_for_attempt(&self, attempt: usize) -> Duration {
         if attempt == 0 {
             return Duration::from_millis(0);
@@ -74,6 +91,18 @@ pub trait ProviderRetry {
         RetryConfig::default()
     }
 
+    /// Limiter every request is queued behind, if the provider has a requests-per-minute limit
+    fn rate_limiter(&self) -> Option<RateLimiter> {
+        None
+    }
+
+    /// Waits for the rate limiter, for requests sent outside [`ProviderRetry::with_retry`]
+    async fn wait_for_rate_limit(&self) {
+        if let Some(rate_limiter) = self.rate_limiter() {
+            rate_limiter.acquire().await;
+        }
+    }
+
     async fn with_retry<F, Fut, T>(&self, operation: F) -> Result<T, ProviderError>
     where
         F: Fn() -> Fut + Send,
@@ -82,17 +111,43 @@ pub trait ProviderRetry {
     {
         let mut attempts = 0;
         let config = self.retry_config();
+        let rate_limiter = self.rate_limiter();
 
         loop {
+            if let Some(rate_limiter) = &rate_limiter {
+                rate_limiter.acquire().await;
+            }
             return match operation().await {
                 Ok(result) => Ok(result),
                 Err(error) => {
                     let should_retry = matches!(
                         error,
-                        ProviderError::RateLimitExceeded { .. } | ProviderError::ServerError(_)
+                        ProviderError::RateLimitExceeded { .. }
+                            | ProviderError::ServerError(_)
+                            | ProviderError::ServiceUnavailable { .. }
                     );
 
                     if should_retry && attempts < config.max_retries {
+                        let delay = match error.retry_delay() {
+                            Some(provider_delay)
+                                if provider_delay
+                                    > Duration::from_millis(config.max_interval_ms) =>
+                            {
+                                tracing::warn!(
+                                    "Provider asked to wait {:?}, longer than the {}ms retry limit; not retrying",
+                                    provider_delay,
+                                    config.max_interval_ms
+                                );
+                                return Err(error);
+                            }
+                            Some(provider_delay) => provider_delay,
+                            None => config.delay_for_attempt(attempts + 1),
+                        };
+
+                        if !crate::retry_budget::try_consume() {
+                            tracing::warn!("Retry budget for this turn exhausted: {:?}", error);
+                            return Err(ProviderError::RetryBudgetExhausted(error.to_string()));
+                        }
                         attempts += 1;
                         tracing::warn!(
                             "Request failed, retrying ({}/{}): {:?}",
@@ -101,14 +156,6 @@ pub trait ProviderRetry {
                             error
                         );
 
-                        let delay = match &error {
-                            ProviderError::RateLimitExceeded {
-                                retry_delay: Some(provider_delay),
-                                ..
-                            } => *provider_delay,
-                            _ => config.delay_for_attempt(attempts),
-                        };
-
                         tracing::info!("Backing off for {:?} before retry", delay);
                         sleep(delay).await;
                         continue;
@@ -126,4 +173,102 @@ impl<P: Provider> ProviderRetry for P {
     fn retry_config(&self) -> RetryConfig {
         Provider::retry_config(self)
     }
+
+    fn rate_limiter(&self) -> Option<RateLimiter> {
+        self.get_model_config()
+            .requests_per_minute
+            .map(|requests| RateLimiter::shared_per_minute(self.get_name(), requests))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::utils::handle_response_openai_compat;
+    use serde_json::json;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    struct QuickRetry;
+
+    impl ProviderRetry for QuickRetry {
+        fn retry_config(&self) -> RetryConfig {
+            RetryConfig::new(3, 1, 2.0, 10)
+        }
+    }
+
+    async fn post_with_retry(server: &MockServer) -> Result<serde_json::Value, ProviderError> {
+        let client = reqwest::Client::new();
+        QuickRetry
+            .with_retry(|| async {
+                let response = client.post(server.uri()).json(&json!({})).send().await?;
+                handle_response_openai_compat(response).await
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_post_retries_until_success() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"ok": true})))
+            .mount(&server)
+            .await;
+
+        assert_eq!(post_with_retry(&server).await.unwrap(), json!({"ok": true}));
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_give_up_after_max_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let err = post_with_retry(&server).await.unwrap_err();
+        assert!(matches!(err, ProviderError::ServerError(_)));
+        assert_eq!(server.received_requests().await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_server_retry_after_is_honored() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503).insert_header("retry-after", "0"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"ok": true})))
+            .mount(&server)
+            .await;
+
+        assert_eq!(post_with_retry(&server).await.unwrap(), json!({"ok": true}));
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_beyond_the_limit_fails_without_waiting() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "3600"))
+            .mount(&server)
+            .await;
+
+        let err = post_with_retry(&server).await.unwrap_err();
+        assert_eq!(
+            err.retry_delay(),
+            Some(Duration::from_secs(3600)),
+            "unexpected error: {err:?}"
+        );
+        assert!(matches!(err, ProviderError::RateLimitExceeded { .. }));
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
 }
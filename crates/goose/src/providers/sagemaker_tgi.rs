@@ -16,7 +16,6 @@ use super::utils::RequestLog;
 use crate::conversation::message::{Message, MessageContent};
 
 use crate::model::ModelConfig;
-use chrono::Utc;
 use rmcp::model::Role;
 
 pub const SAGEMAKER_TGI_DOC_LINK: &str =
@@ -207,7 +206,7 @@ impl SageMakerTgiProvider {
 
         Ok(Message::new(
             Role::Assistant,
-            Utc::now().timestamp(),
+            crate::clock::now().timestamp(),
             vec![MessageContent::text(clean_text)],
         ))
     }
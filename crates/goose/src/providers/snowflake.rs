@@ -3,7 +3,7 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
-use super::api_client::{ApiClient, AuthMethod};
+use super::api_client::{ApiClient, AuthMethod, DEFAULT_REQUEST_TIMEOUT};
 use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage};
 use super::errors::ProviderError;
 use super::formats::snowflake::{create_request, get_usage, response_to_message};
@@ -97,7 +97,12 @@ impl SnowflakeProvider {
         };
 
         let auth = AuthMethod::BearerToken(token?);
-        let api_client = ApiClient::new(base_url, auth)?.with_header("User-Agent", "goose")?;
+        let api_client = ApiClient::with_timeout(
+            base_url,
+            auth,
+            model.request_timeout_or(DEFAULT_REQUEST_TIMEOUT),
+        )?
+        .with_header("User-Agent", "goose")?;
 
         Ok(Self {
             api_client,
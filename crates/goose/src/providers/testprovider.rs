@@ -165,6 +165,12 @@ impl Provider for TestProvider {
     fn get_model_config(&self) -> ModelConfig {
         ModelConfig::new_or_fail("test-model")
     }
+
+    async fn with_model(&self, _model: ModelConfig) -> Result<Arc<dyn Provider>, ProviderError> {
+        Err(ProviderError::ExecutionError(
+            "The test provider replays a fixed model and cannot switch models".to_string(),
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -172,7 +178,6 @@ mod tests {
     use super::*;
     use crate::conversation::message::{Message, MessageContent};
     use crate::providers::base::{ProviderUsage, Usage};
-    use chrono::Utc;
     use rmcp::model::{RawTextContent, Role, TextContent};
     use std::env;
 
@@ -210,7 +215,7 @@ mod tests {
             Ok((
                 Message::new(
                     Role::Assistant,
-                    Utc::now().timestamp(),
+                    crate::clock::now().timestamp(),
                     vec![MessageContent::Text(TextContent {
                         raw: RawTextContent {
                             text: self.response.clone(),
@@ -9,7 +9,7 @@ use tokio_stream::StreamExt;
 use tokio_util::codec::{FramedRead, LinesCodec};
 use tokio_util::io::StreamReader;
 
-use super::api_client::{ApiClient, AuthMethod};
+use super::api_client::{ApiClient, AuthMethod, DEFAULT_REQUEST_TIMEOUT};
 use super::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::errors::ProviderError;
 use super::formats::openai::response_to_streaming_message;
@@ -60,9 +60,13 @@ impl TetrateProvider {
             .unwrap_or_else(|_| "https://api.router.tetrate.ai".to_string());
 
         let auth = AuthMethod::BearerToken(api_key);
-        let api_client = ApiClient::new(host, auth)?
-            .with_header("HTTP-Referer", "https://block.github.io/goose")?
-            .with_header("X-Title", "goose")?;
+        let api_client = ApiClient::with_timeout(
+            host,
+            auth,
+            model.request_timeout_or(DEFAULT_REQUEST_TIMEOUT),
+        )?
+        .with_header("HTTP-Referer", "https://block.github.io/goose")?
+        .with_header("X-Title", "goose")?;
 
         Ok(Self {
             api_client,
@@ -107,7 +111,7 @@ impl TetrateProvider {
 
             // Check for context length errors in the error message
             if error_code == 400 && error_message.contains("maximum context length") {
-                return Err(ProviderError::ContextLengthExceeded(
+                return Err(ProviderError::context_length_exceeded(
                     error_message.to_string(),
                 ));
             }
@@ -220,6 +224,7 @@ impl Provider for TetrateProvider {
             "include_usage": true,
         });
 
+        self.wait_for_rate_limit().await;
         let response = self
             .api_client
             .response_post("v1/chat/completions", &payload)
@@ -34,6 +34,7 @@ use super::errors::ProviderError;
 use super::ollama::OLLAMA_DEFAULT_PORT;
 use super::ollama::OLLAMA_HOST;
 use crate::conversation::message::{Message, MessageContent};
+use crate::conversation::tool_call_id::synthetic_tool_call_id;
 use crate::conversation::Conversation;
 use crate::model::ModelConfig;
 use crate::providers::formats::openai::create_request;
@@ -43,7 +44,6 @@ use rmcp::model::{object, CallToolRequestParam, RawContent, Tool};
 use serde_json::{json, Value};
 use std::ops::Deref;
 use std::time::Duration;
-use uuid::Uuid;
 
 /// Default model to use for tool interpretation
 pub const DEFAULT_INTERPRETER_MODEL_OLLAMA: &str = "mistral-nemo";
@@ -434,7 +434,7 @@ pub async fn augment_message_with_tool_calls<T: ToolInterpreter>(
     for tool_call in tool_calls {
         if tool_call.name != "noop" {
             // do not actually execute noop tool
-            let id = Uuid::new_v4().to_string();
+            let id = synthetic_tool_call_id();
             final_message = final_message.with_tool_request(id, Ok(tool_call));
         }
     }
@@ -0,0 +1,166 @@
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+use super::base::ProviderUsage;
+use super::pricing::get_model_pricing;
+
+/// Tokens used by one (provider, model) pair since the process started
+#[derive(Debug, Clone, Copy, Default)]
+struct UsageCounters {
+    requests: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    /// Cost at the pricing in effect when each response arrived; `None` until a response
+    /// from a model with known pricing is recorded
+    cost_usd: Option<f64>,
+}
+
+static USAGE_COUNTERS: Lazy<Mutex<BTreeMap<(String, String), UsageCounters>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Add a provider response's usage to the process-wide counters. Its cost is priced now, so
+/// the cost counter never moves backwards when pricing changes; responses served from the
+/// response cache add no cost.
+pub async fn record(usage: &ProviderUsage) {
+    let cost = if usage.cached {
+        Some(0.0)
+    } else {
+        get_model_pricing(&usage.provider, &usage.model)
+            .await
+            .map(|pricing| usage.usage.cost(&pricing))
+    };
+
+    let mut counters = USAGE_COUNTERS.lock().unwrap();
+    let entry = counters
+        .entry((usage.provider.clone(), usage.model.clone()))
+        .or_default();
+    entry.requests += 1;
+    entry.input_tokens += usage.usage.input_tokens.unwrap_or(0).max(0) as u64;
+    entry.output_tokens += usage.usage.output_tokens.unwrap_or(0).max(0) as u64;
+    if let Some(cost) = cost {
+        entry.cost_usd = Some(entry.cost_usd.unwrap_or(0.0) + cost);
+    }
+}
+
+/// The counters in the Prometheus text exposition format. Cost is only reported for models
+/// with known pricing.
+pub fn render_prometheus() -> String {
+    let counters: Vec<_> = USAGE_COUNTERS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(key, counters)| (key.clone(), *counters))
+        .collect();
+
+    let mut out = String::new();
+    let mut family = |name: &str, help: &str, samples: Vec<(String, String)>| {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} counter", name);
+        for (labels, value) in samples {
+            let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+        }
+    };
+
+    let samples = |value: fn(&UsageCounters) -> u64| {
+        counters
+            .iter()
+            .map(|((provider, model), counters)| {
+                (labels(provider, model), value(counters).to_string())
+            })
+            .collect()
+    };
+    family(
+        "goose_provider_requests_total",
+        "Provider responses that reported usage",
+        samples(|c| c.requests),
+    );
+    family(
+        "goose_input_tokens_total",
+        "Input tokens sent to the provider",
+        samples(|c| c.input_tokens),
+    );
+    family(
+        "goose_output_tokens_total",
+        "Output tokens returned by the provider",
+        samples(|c| c.output_tokens),
+    );
+    family(
+        "goose_cost_usd_total",
+        "Estimated cost in US dollars at current pricing",
+        counters
+            .iter()
+            .filter_map(|((provider, model), counters)| {
+                let cost = counters.cost_usd?;
+                Some((labels(provider, model), cost.to_string()))
+            })
+            .collect(),
+    );
+    out
+}
+
+fn labels(provider: &str, model: &str) -> String {
+    format!(
+        "provider=\"{}\",model=\"{}\"",
+        escape_label(provider),
+        escape_label(model)
+    )
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::Usage;
+
+    #[tokio::test]
+    async fn test_render_accumulates_per_provider_and_model() {
+        let usage = ProviderUsage::new(
+            "metrics-test-model".to_string(),
+            Usage::new(Some(100), Some(20), Some(120)),
+        )
+        .with_provider("metrics-test");
+        record(&usage).await;
+        record(&usage).await;
+
+        let rendered = render_prometheus();
+        assert!(rendered.contains("# TYPE goose_input_tokens_total counter"));
+        assert!(rendered.contains(
+            "goose_input_tokens_total{provider=\"metrics-test\",model=\"metrics-test-model\"} 200"
+        ));
+        assert!(rendered.contains(
+            "goose_output_tokens_total{provider=\"metrics-test\",model=\"metrics-test-model\"} 40"
+        ));
+        assert!(rendered.contains(
+            "goose_provider_requests_total{provider=\"metrics-test\",model=\"metrics-test-model\"} 2"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cached_usage_adds_no_cost() {
+        let usage = ProviderUsage::new(
+            "metrics-cached-model".to_string(),
+            Usage::new(Some(100), Some(20), Some(120)),
+        )
+        .with_provider("metrics-cached")
+        .with_cached(true);
+        record(&usage).await;
+
+        let rendered = render_prometheus();
+        assert!(rendered.contains(
+            "goose_cost_usd_total{provider=\"metrics-cached\",model=\"metrics-cached-model\"} 0"
+        ));
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
@@ -0,0 +1,266 @@
+use std::fmt;
+
+use super::base::{ProviderUsage, Usage};
+use super::pricing::PricingInfo;
+
+const COST_UNKNOWN: &str = "cost unknown";
+
+/// Token usage of a single (provider, model) pair
+#[derive(Debug, Clone)]
+pub struct UsageLineItem {
+    pub provider: String,
+    pub model: String,
+    pub usage: Usage,
+    /// Pricing for the model, `None` when it could not be looked up
+    pub pricing: Option<PricingInfo>,
+    /// Responses served from the response cache, which cost nothing
+    pub cached: bool,
+}
+
+impl UsageLineItem {
+    pub fn cost(&self) -> Option<f64> {
+        if self.cached {
+            return Some(0.0);
+        }
+        self.pricing
+            .as_ref()
+            .map(|pricing| self.usage.cost(pricing))
+    }
+}
+
+/// Combined usage across every provider and model that served a session, e.g. when a
+/// lead/worker or fallback setup switches models. Displays as a table with a grand total.
+#[derive(Debug, Clone, Default)]
+pub struct UsageReport {
+    pub items: Vec<UsageLineItem>,
+}
+
+impl UsageReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `usage` to the line item for its provider and model, creating it if needed. Cached
+    /// responses get a line item of their own.
+    pub fn record(&mut self, usage: &ProviderUsage) {
+        match self.items.iter_mut().find(|item| {
+            item.provider == usage.provider
+                && item.model == usage.model
+                && item.cached == usage.cached
+        }) {
+            Some(item) => item.usage += usage.usage,
+            None => self.items.push(UsageLineItem {
+                provider: usage.provider.clone(),
+                model: usage.model.clone(),
+                usage: usage.usage,
+                pricing: None,
+                cached: usage.cached,
+            }),
+        }
+    }
+
+    /// Rebuild a report from usage saved with [`UsageReport::to_usage`]
+    pub fn from_usage(usage: &[ProviderUsage]) -> Self {
+        let mut report = Self::new();
+        for item in usage {
+            report.record(item);
+        }
+        report
+    }
+
+    /// One entry per line item, without pricing
+    pub fn to_usage(&self) -> Vec<ProviderUsage> {
+        self.items
+            .iter()
+            .map(|item| {
+                ProviderUsage::new(item.model.clone(), item.usage)
+                    .with_provider(&item.provider)
+                    .with_cached(item.cached)
+            })
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn total_usage(&self) -> Usage {
+        self.items
+            .iter()
+            .fold(Usage::default(), |total, item| total + item.usage)
+    }
+
+    /// Sum of the known costs, and whether any line item is missing pricing
+    pub fn total_cost(&self) -> (f64, bool) {
+        self.items
+            .iter()
+            .fold((0.0, false), |(total, unknown), item| match item.cost() {
+                Some(cost) => (total + cost, unknown),
+                None => (total, true),
+            })
+    }
+}
+
+fn format_cost(cost: f64) -> String {
+    format!("${:.4}", cost)
+}
+
+impl fmt::Display for UsageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let row = |provider: &str, model: &str, usage: &Usage, cost: String| {
+            [
+                provider.to_string(),
+                model.to_string(),
+                usage.input_tokens.unwrap_or(0).to_string(),
+                usage.output_tokens.unwrap_or(0).to_string(),
+                usage.total_tokens.unwrap_or(0).to_string(),
+                cost,
+            ]
+        };
+
+        let mut rows =
+            vec![["Provider", "Model", "Input", "Output", "Total", "Cost"].map(String::from)];
+        for item in &self.items {
+            let cost = item
+                .cost()
+                .map(format_cost)
+                .unwrap_or_else(|| COST_UNKNOWN.to_string());
+            let model = if item.cached {
+                format!("{} (cached)", item.model)
+            } else {
+                item.model.clone()
+            };
+            rows.push(row(&item.provider, &model, &item.usage, cost));
+        }
+        let total_cost = match self.total_cost() {
+            (_, true) if self.items.iter().all(|item| item.pricing.is_none()) => {
+                COST_UNKNOWN.to_string()
+            }
+            (cost, true) => format!(">= {} (some {})", format_cost(cost), COST_UNKNOWN),
+            (cost, false) => format_cost(cost),
+        };
+        rows.push(row("Total", "", &self.total_usage(), total_cost));
+
+        let mut widths = [0; 6];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        for (index, row) in rows.iter().enumerate() {
+            if index == rows.len() - 1 {
+                let rule_width = widths.iter().sum::<usize>() + 2 * (widths.len() - 1);
+                writeln!(f, "{}", "-".repeat(rule_width))?;
+            }
+            let line = format!(
+                "{:<w0$}  {:<w1$}  {:>w2$}  {:>w3$}  {:>w4$}  {}",
+                row[0],
+                row[1],
+                row[2],
+                row[3],
+                row[4],
+                row[5],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3],
+                w4 = widths[4],
+            );
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(provider: &str, model: &str, input: i32, output: i32) -> ProviderUsage {
+        ProviderUsage::new(
+            model.to_string(),
+            Usage::new(Some(input), Some(output), None),
+        )
+        .with_provider(provider)
+    }
+
+    fn pricing(input_cost: f64, output_cost: f64) -> Option<PricingInfo> {
+        Some(PricingInfo {
+            input_cost,
+            output_cost,
+            context_length: None,
+        })
+    }
+
+    #[test]
+    fn test_usage_report_across_providers() {
+        let mut report = UsageReport::new();
+        report.record(&usage("anthropic", "claude-sonnet-4", 1000, 200));
+        report.record(&usage("openai", "gpt-4o", 600, 100));
+        report.record(&usage("anthropic", "claude-sonnet-4", 3000, 300));
+        report.record(&usage("openai", "in-house-model", 400, 50));
+
+        assert_eq!(report.items.len(), 3);
+        for item in &mut report.items {
+            item.pricing = match item.model.as_str() {
+                "claude-sonnet-4" => pricing(0.000003, 0.000015),
+                "gpt-4o" => pricing(0.0000025, 0.00001),
+                _ => None,
+            };
+        }
+
+        let claude = &report.items[0];
+        assert_eq!(claude.usage.input_tokens, Some(4000));
+        assert_eq!(claude.usage.output_tokens, Some(500));
+
+        let total = report.total_usage();
+        assert_eq!(total.input_tokens, Some(5000));
+        assert_eq!(total.output_tokens, Some(650));
+        assert_eq!(total.total_tokens, Some(5650));
+
+        let (cost, unknown) = report.total_cost();
+        assert!((cost - 0.022).abs() < 1e-9, "unexpected cost {cost}");
+        assert!(unknown);
+
+        let table = report.to_string();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].starts_with("Provider"));
+        assert!(lines[1].starts_with("anthropic") && lines[1].ends_with("$0.0195"));
+        assert!(lines[2].starts_with("openai") && lines[2].ends_with("$0.0025"));
+        assert!(lines[3].contains("in-house-model") && lines[3].ends_with("cost unknown"));
+        assert!(lines[4].chars().all(|c| c == '-'));
+        assert!(lines[5].starts_with("Total"));
+        assert!(lines[5].contains("5650"));
+        assert!(lines[5].ends_with(">= $0.0220 (some cost unknown)"));
+    }
+
+    #[test]
+    fn test_cached_usage_costs_nothing() {
+        let mut report = UsageReport::new();
+        report.record(&usage("openai", "gpt-4o", 600, 100));
+        report.record(&usage("openai", "gpt-4o", 600, 100).with_cached(true));
+
+        assert_eq!(report.items.len(), 2);
+        for item in &mut report.items {
+            item.pricing = pricing(0.0000025, 0.00001);
+        }
+
+        assert_eq!(report.total_usage().input_tokens, Some(1200));
+        let (cost, unknown) = report.total_cost();
+        assert!((cost - 0.0025).abs() < 1e-9, "unexpected cost {cost}");
+        assert!(!unknown);
+        assert!(report.to_string().contains("gpt-4o (cached)"));
+    }
+
+    #[test]
+    fn test_usage_report_without_pricing() {
+        let mut report = UsageReport::new();
+        report.record(&usage("ollama", "qwen3", 10, 5));
+
+        let table = report.to_string();
+        assert!(table.lines().last().unwrap().ends_with("cost unknown"));
+        assert!(!table.contains('$'));
+    }
+}
@@ -1,19 +1,27 @@
-use super::base::Usage;
+use super::base::{RateLimitInfo, Usage};
 use super::errors::GoogleErrorCode;
 use crate::config::paths::Paths;
-use crate::model::ModelConfig;
+use crate::config::Config;
+use crate::conversation::message::{Message, MessageContent};
+use crate::model::{ModelConfig, ToolResultJoinStrategy};
 use crate::providers::errors::{OpenAIError, ProviderError};
+use crate::utils::{close_open_code_fence, open_code_fence};
 use anyhow::{anyhow, Result};
 use base64::Engine;
+use once_cell::sync::Lazy;
 use regex::Regex;
+use reqwest::header::HeaderMap;
 use reqwest::{Response, StatusCode};
 use rmcp::model::{AnnotateAble, ImageContent, RawImageContent};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::fs::File;
 use std::io::{BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 use uuid::Uuid;
 
@@ -93,6 +101,14 @@ fn check_context_length_exceeded(text: &str) -> bool {
         .any(|phrase| text_lower.contains(phrase))
 }
 
+/// Whether an error body says the requested model does not exist (retired or misspelled)
+fn check_model_not_found(text: &str) -> bool {
+    let text_lower = text.to_lowercase();
+    text_lower.contains("model_not_found")
+        || (text_lower.contains("model")
+            && (text_lower.contains("does not exist") || text_lower.contains("not found")))
+}
+
 fn format_server_error_message(status_code: StatusCode, payload: Option<&Value>) -> String {
     match payload {
         Some(Value::Null) | None => format!(
@@ -124,14 +140,14 @@ pub fn map_http_error_to_provider_error(
             } else {
                 "Payload is too large.".to_string()
             };
-            ProviderError::ContextLengthExceeded(payload_str)
+            ProviderError::context_length_exceeded(payload_str)
         }
         StatusCode::BAD_REQUEST => {
             let base_msg = format!("Request failed with status: {}", status);
             if let Some(payload) = &payload {
                 let payload_str = payload.to_string();
                 if check_context_length_exceeded(&payload_str) {
-                    ProviderError::ContextLengthExceeded(payload_str)
+                    ProviderError::context_length_exceeded(payload_str)
                 } else {
                     ProviderError::RequestFailed(
                         payload
@@ -147,6 +163,13 @@ pub fn map_http_error_to_provider_error(
                 ProviderError::RequestFailed(base_msg)
             }
         }
+        StatusCode::NOT_FOUND
+            if payload
+                .as_ref()
+                .is_some_and(|payload| check_model_not_found(&payload.to_string())) =>
+        {
+            ProviderError::ModelNotFound(payload.as_ref().map(Value::to_string).unwrap_or_default())
+        }
         StatusCode::TOO_MANY_REQUESTS => ProviderError::RateLimitExceeded {
             details: format!("{:?}", payload),
             retry_delay: None,
@@ -169,6 +192,96 @@ pub fn map_http_error_to_provider_error(
     error
 }
 
+/// How long a `Retry-After` header asks us to wait, given either as seconds or as an HTTP date
+pub fn retry_after_from_headers(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Limiters handed out by [`RateLimiter::shared_per_minute`], keyed by provider name and
+/// requests per minute
+static SHARED_RATE_LIMITERS: Lazy<std::sync::Mutex<HashMap<(String, u32), RateLimiter>>> =
+    Lazy::new(|| std::sync::Mutex::new(HashMap::new()));
+
+/// Token bucket limiting how often a provider sends requests. Clones share one bucket, so
+/// concurrent calls through the same provider queue behind each other.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    bucket: Arc<tokio::sync::Mutex<TokenBucket>>,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    capacity: f64,
+    refill_per_sec: f64,
+    last_refill: tokio::time::Instant,
+}
+
+impl TokenBucket {
+    fn refill(&mut self) {
+        let now = tokio::time::Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+}
+
+impl RateLimiter {
+    /// Allows `requests` per `period`, with bursts of up to one second's worth
+    pub fn new(requests: u32, period: Duration) -> Self {
+        let refill_per_sec = f64::from(requests.max(1)) / period.as_secs_f64();
+        let capacity = refill_per_sec.max(1.0);
+        Self {
+            bucket: Arc::new(tokio::sync::Mutex::new(TokenBucket {
+                tokens: capacity,
+                capacity,
+                refill_per_sec,
+                last_refill: tokio::time::Instant::now(),
+            })),
+        }
+    }
+
+    pub fn per_minute(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(60))
+    }
+
+    /// The per-minute limiter for `provider`, shared by every instance of that provider with
+    /// the same limit, so a provider rebuilt for another model keeps counting against the
+    /// same budget.
+    pub fn shared_per_minute(provider: &str, requests: u32) -> Self {
+        SHARED_RATE_LIMITERS
+            .lock()
+            .unwrap()
+            .entry((provider.to_string(), requests))
+            .or_insert_with(|| Self::per_minute(requests))
+            .clone()
+    }
+
+    /// Waits until a request may be sent. The bucket stays locked while waiting, so callers
+    /// that arrive later queue behind this one, and a caller cancelled mid-wait takes no
+    /// token.
+    pub async fn acquire(&self) {
+        let mut bucket = self.bucket.lock().await;
+        bucket.refill();
+        if bucket.tokens < 1.0 {
+            let wait = (1.0 - bucket.tokens) / bucket.refill_per_sec;
+            tokio::time::sleep(Duration::from_secs_f64(wait)).await;
+            bucket.refill();
+        }
+        bucket.tokens -= 1.0;
+    }
+}
+
 /// Handles HTTP responses from OpenAI-compatible endpoints.
 ///
 /// Returns the response if status is OK; otherwise, reads the body and maps to a `ProviderError`,
@@ -189,6 +302,7 @@ pub async fn handle_status_openai_compat(response: Response) -> Result<Response,
     if status == StatusCode::OK {
         return Ok(response);
     }
+    let retry_after = retry_after_from_headers(response.headers());
 
     let body_str = response
         .text()
@@ -199,7 +313,11 @@ pub async fn handle_status_openai_compat(response: Response) -> Result<Response,
         if let Ok(err_resp) = serde_json::from_str::<OpenAIErrorResponse>(&body_str) {
             let err = err_resp.error;
             if err.is_context_length_exceeded() {
-                return Err(ProviderError::ContextLengthExceeded(
+                return Err(ProviderError::context_length_exceeded(
+                    err.message.unwrap_or("Unknown error".to_string()),
+                ));
+            } else if err.is_model_not_found() {
+                return Err(ProviderError::ModelNotFound(
                     err.message.unwrap_or("Unknown error".to_string()),
                 ));
             } else {
@@ -213,7 +331,7 @@ pub async fn handle_status_openai_compat(response: Response) -> Result<Response,
     }
 
     let payload = serde_json::from_str::<Value>(&body_str).ok();
-    Err(map_http_error_to_provider_error(status, payload))
+    Err(map_http_error_to_provider_error(status, payload).with_retry_delay(retry_after))
 }
 
 pub async fn handle_response_openai_compat(response: Response) -> Result<Value, ProviderError> {
@@ -224,6 +342,27 @@ pub async fn handle_response_openai_compat(response: Response) -> Result<Value,
     })
 }
 
+/// Read the OpenAI-style `x-ratelimit-*` headers, `None` when the response has none
+pub fn rate_limit_from_headers(headers: &HeaderMap) -> Option<RateLimitInfo> {
+    let text = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_string())
+    };
+    let number = |name: &str| text(name).and_then(|v| v.parse::<u64>().ok());
+
+    let info = RateLimitInfo {
+        limit_requests: number("x-ratelimit-limit-requests"),
+        remaining_requests: number("x-ratelimit-remaining-requests"),
+        reset_requests: text("x-ratelimit-reset-requests"),
+        limit_tokens: number("x-ratelimit-limit-tokens"),
+        remaining_tokens: number("x-ratelimit-remaining-tokens"),
+        reset_tokens: text("x-ratelimit-reset-tokens"),
+    };
+    (info != RateLimitInfo::default()).then_some(info)
+}
+
 /// Check if the model is a Google model based on the "model" field in the payload.
 ///
 /// ### Arguments
@@ -300,24 +439,48 @@ fn parse_google_retry_delay(payload: &Value) -> Option<Duration> {
 /// - `Err(ProviderError)`: Describes the failure reason.
 pub async fn handle_response_google_compat(response: Response) -> Result<Value, ProviderError> {
     let status = response.status();
+    let retry_after = retry_after_from_headers(response.headers());
     let payload: Option<Value> = response.json().await.ok();
+    classify_google_response(status, payload, retry_after)
+}
+
+/// Map a Gemini status and body to the response payload or a typed `ProviderError`:
+/// rate limits and server errors are retryable, while safety blocks, unknown models and
+/// oversized prompts are not.
+pub fn classify_google_response(
+    status: StatusCode,
+    payload: Option<Value>,
+    retry_after: Option<Duration>,
+) -> Result<Value, ProviderError> {
     let final_status = get_google_final_status(status, payload.as_ref());
 
     match final_status {
-        StatusCode::OK =>  payload.ok_or_else( || ProviderError::RequestFailed("Response body is not valid JSON".to_string()) ),
+        StatusCode::OK => {
+            let payload = payload.ok_or_else(|| {
+                ProviderError::RequestFailed("Response body is not valid JSON".to_string())
+            })?;
+            match google_block_reason(&payload) {
+                Some(reason) => Err(ProviderError::RequestFailed(format!(
+                    "Request blocked by Gemini safety filters (reason: {})",
+                    reason
+                ))),
+                None => Ok(payload),
+            }
+        }
         StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
             Err(ProviderError::Authentication(format!("Authentication failed. Please ensure your API keys are valid and have the required permissions. \
                 Status: {}. Response: {:?}", final_status, payload )))
         }
         StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND => {
             let mut error_msg = "Unknown error".to_string();
-            if let Some(payload) = &payload {
-                if let Some(error) = payload.get("error") {
-                    error_msg = error.get("message").and_then(|m| m.as_str()).unwrap_or("Unknown error").to_string();
-                    let error_status = error.get("status").and_then(|s| s.as_str()).unwrap_or("Unknown status");
-                    if error_status == "INVALID_ARGUMENT" && error_msg.to_lowercase().contains("exceeds") {
-                        return Err(ProviderError::ContextLengthExceeded(error_msg.to_string()));
-                    }
+            if let Some(error) = payload.as_ref().and_then(|p| p.get("error")) {
+                error_msg = error.get("message").and_then(|m| m.as_str()).unwrap_or("Unknown error").to_string();
+                let error_status = error.get("status").and_then(|s| s.as_str()).unwrap_or("Unknown status");
+                if error_status == "INVALID_ARGUMENT" && error_msg.to_lowercase().contains("exceeds") {
+                    return Err(ProviderError::context_length_exceeded(error_msg));
+                }
+                if is_google_model_not_found(error_status, &error_msg) {
+                    return Err(ProviderError::ModelNotFound(error_msg));
                 }
             }
             tracing::debug!(
@@ -326,7 +489,10 @@ pub async fn handle_response_google_compat(response: Response) -> Result<Value,
             Err(ProviderError::RequestFailed(format!("Request failed with status: {}. Message: {}", final_status, error_msg)))
         }
         StatusCode::TOO_MANY_REQUESTS => {
-            let retry_delay = payload.as_ref().and_then(parse_google_retry_delay);
+            let retry_delay = payload
+                .as_ref()
+                .and_then(parse_google_retry_delay)
+                .or(retry_after);
             Err(ProviderError::RateLimitExceeded {
                 details: format!("{:?}", payload),
                 retry_delay,
@@ -334,7 +500,8 @@ pub async fn handle_response_google_compat(response: Response) -> Result<Value,
         }
         _ if final_status.is_server_error() => Err(ProviderError::ServerError(
             format_server_error_message(final_status, payload.as_ref()),
-        )),
+        )
+        .with_retry_delay(retry_after)),
         _ => {
             tracing::debug!(
                 "{}", format!("Provider request failed with status: {}. Payload: {:?}", final_status, payload)
@@ -344,6 +511,219 @@ pub async fn handle_response_google_compat(response: Response) -> Result<Value,
     }
 }
 
+/// Why Gemini refused to answer, when the prompt or the only candidate was blocked
+fn google_block_reason(payload: &Value) -> Option<String> {
+    if let Some(reason) = payload
+        .get("promptFeedback")
+        .and_then(|feedback| feedback.get("blockReason"))
+        .and_then(|reason| reason.as_str())
+    {
+        return Some(reason.to_string());
+    }
+
+    const BLOCKED_FINISH_REASONS: &[&str] = &["SAFETY", "PROHIBITED_CONTENT", "BLOCKLIST", "SPII"];
+    let candidate = payload.get("candidates")?.as_array()?.first()?;
+    let reason = candidate.get("finishReason")?.as_str()?;
+    let has_parts = candidate
+        .get("content")
+        .and_then(|content| content.get("parts"))
+        .and_then(|parts| parts.as_array())
+        .is_some_and(|parts| !parts.is_empty());
+    (BLOCKED_FINISH_REASONS.contains(&reason) && !has_parts).then(|| reason.to_string())
+}
+
+fn is_google_model_not_found(error_status: &str, message: &str) -> bool {
+    let message = message.to_lowercase();
+    (error_status == "NOT_FOUND" && message.contains("models/"))
+        || message.contains("is not supported for generatecontent")
+}
+
+/// Add `logit_bias`, `frequency_penalty`, `presence_penalty`, `top_p` and `stop` to an
+/// OpenAI-style payload when they are set on the model config.
+pub fn insert_openai_sampling_params(payload: &mut Value, model_config: &ModelConfig) {
+    let Some(payload) = payload.as_object_mut() else {
+        return;
+    };
+    if let Some(logit_bias) = &model_config.logit_bias {
+        payload.insert("logit_bias".to_string(), json!(logit_bias));
+    }
+    if let Some(penalty) = model_config.frequency_penalty {
+        payload.insert("frequency_penalty".to_string(), json!(penalty));
+    }
+    if let Some(penalty) = model_config.presence_penalty {
+        payload.insert("presence_penalty".to_string(), json!(penalty));
+    }
+    if let Some(top_p) = model_config.top_p {
+        payload.insert("top_p".to_string(), json!(top_p));
+    }
+    if let Some(stop) = &model_config.stop {
+        payload.insert("stop".to_string(), json!(stop));
+    }
+}
+
+/// Note that sampling controls set on the model config are not sent for `target`.
+pub fn log_unsupported_sampling_params(target: &str, model_config: &ModelConfig) {
+    if model_config.logit_bias.is_some()
+        || model_config.frequency_penalty.is_some()
+        || model_config.presence_penalty.is_some()
+    {
+        tracing::debug!(
+            "logit_bias, frequency_penalty and presence_penalty are not supported by {}; ignoring them",
+            target
+        );
+    }
+}
+
+/// The response layout a provider's non-streaming completion is expected to follow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseShape {
+    OpenAi,
+    Anthropic,
+    Google,
+}
+
+impl ResponseShape {
+    fn required_fields(self) -> &'static [&'static str] {
+        match self {
+            ResponseShape::OpenAi => &["choices", "usage"],
+            ResponseShape::Anthropic => &["content", "usage"],
+            ResponseShape::Google => &["candidates", "usageMetadata"],
+        }
+    }
+}
+
+/// Top-level fields `shape` requires that are absent or null in `response`.
+pub fn missing_response_fields(shape: ResponseShape, response: &Value) -> Vec<&'static str> {
+    shape
+        .required_fields()
+        .iter()
+        .copied()
+        .filter(|field| response.get(*field).is_none_or(Value::is_null))
+        .collect()
+}
+
+/// Replace string values with their length so a payload can be logged without its content.
+fn redact_payload(value: &Value) -> Value {
+    match value {
+        Value::String(s) => Value::String(format!("<redacted: {} chars>", s.chars().count())),
+        Value::Array(items) => Value::Array(items.iter().map(redact_payload).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), redact_payload(value)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn response_validation_enabled() -> bool {
+    match Config::global().get_param::<Value>("GOOSE_VALIDATE_RESPONSES") {
+        Ok(Value::Bool(enabled)) => enabled,
+        Ok(Value::Number(n)) => n.as_i64() == Some(1),
+        Ok(Value::String(s)) => matches!(s.to_lowercase().as_str(), "1" | "true" | "yes"),
+        _ => false,
+    }
+}
+
+/// Debugging aid enabled with `GOOSE_VALIDATE_RESPONSES=1`: when a response lacks the fields
+/// its format requires, log a warning with the redacted payload and fail the request instead
+/// of letting parsing produce an empty message.
+pub fn validate_response_shape(
+    provider: &str,
+    shape: ResponseShape,
+    response: &Value,
+) -> Result<(), ProviderError> {
+    if !response_validation_enabled() {
+        return Ok(());
+    }
+    let missing = missing_response_fields(shape, response);
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    tracing::warn!(
+        provider,
+        missing = ?missing,
+        payload = %redact_payload(response),
+        "provider response is missing expected fields"
+    );
+    Err(ProviderError::RequestFailed(format!(
+        "{} response is missing expected fields: {}",
+        provider,
+        missing.join(", ")
+    )))
+}
+
+/// Largest text block, in characters, sent to a provider as a single content item.
+/// The agent already moves large tool output to files; this catches history loaded from
+/// disk or other paths that bypass it, so one block can't get the whole request rejected.
+pub const MAX_CONTENT_BLOCK_CHARS: usize = 400_000;
+
+/// Clamp a text block to `MAX_CONTENT_BLOCK_CHARS`, keeping its start and end around a marker.
+pub fn clamp_content_block(text: &str) -> Cow<'_, str> {
+    clamp_text_to(text, MAX_CONTENT_BLOCK_CHARS)
+}
+
+fn clamp_text_to(text: &str, max_chars: usize) -> Cow<'_, str> {
+    let char_count = text.chars().count();
+    if char_count <= max_chars {
+        return Cow::Borrowed(text);
+    }
+
+    let omitted = char_count - max_chars;
+    let mut head: String = text.chars().take(max_chars / 2).collect();
+    close_open_code_fence(&mut head);
+
+    let tail_start = text
+        .char_indices()
+        .nth(char_count - (max_chars - max_chars / 2))
+        .map_or(text.len(), |(idx, _)| idx);
+    let tail = match open_code_fence(&text[..tail_start]) {
+        Some(opening) => format!("{}\n{}", opening, &text[tail_start..]),
+        None => text[tail_start..].to_string(),
+    };
+    Cow::Owned(format!(
+        "{}\n\n[... {} characters truncated to fit provider content limits ...]\n\n{}",
+        head, omitted, tail
+    ))
+}
+
+/// Build the `content` of an OpenAI-style tool message from the text parts of a tool result.
+/// Separate blocks skip blank parts, since some APIs reject empty text blocks.
+pub fn join_tool_result_texts(texts: &[String], strategy: ToolResultJoinStrategy) -> Value {
+    match strategy {
+        ToolResultJoinStrategy::SeparateBlocks => {
+            let blocks: Vec<_> = texts
+                .iter()
+                .filter(|text| !text.trim().is_empty())
+                .map(|text| json!({"type": "text", "text": clamp_content_block(text)}))
+                .collect();
+            if blocks.is_empty() {
+                json!("")
+            } else {
+                json!(blocks)
+            }
+        }
+        ToolResultJoinStrategy::Concatenated => json!(clamp_content_block(&texts.join(" "))),
+    }
+}
+
+/// Remove blank text blocks before a request is built, dropping any message left without
+/// content. Some APIs reject empty text (e.g. an assistant turn that only made tool calls).
+pub fn strip_empty_content(messages: &[Message]) -> Vec<Message> {
+    messages
+        .iter()
+        .filter_map(|message| {
+            let mut message = message.clone();
+            message.content.retain(|content| match content {
+                MessageContent::Text(text) => !text.text.trim().is_empty(),
+                _ => true,
+            });
+            (!message.content.is_empty()).then_some(message)
+        })
+        .collect()
+}
+
 pub fn sanitize_function_name(name: &str) -> String {
     let re = Regex::new(r"[^a-zA-Z0-9_-]").unwrap();
     re.replace_all(name, "_").to_string()
@@ -491,6 +871,20 @@ pub struct RequestLog {
 
 pub const LOGS_TO_KEEP: usize = 10;
 
+/// Record token usage on the current provider span so tracing layers can report it.
+fn record_usage_on_span(usage: &Usage) {
+    let span = tracing::Span::current();
+    if let Some(input) = usage.input_tokens {
+        span.record("input_tokens", input);
+    }
+    if let Some(output) = usage.output_tokens {
+        span.record("output_tokens", output);
+    }
+    if let Some(total) = usage.total_tokens {
+        span.record("total_tokens", total);
+    }
+}
+
 impl RequestLog {
     pub fn start<Payload>(model_config: &ModelConfig, payload: &Payload) -> Result<Self>
     where
@@ -544,6 +938,9 @@ impl RequestLog {
     where
         Payload: Serialize,
     {
+        if let Some(usage) = usage {
+            record_usage_on_span(usage);
+        }
         self.write_json(&serde_json::json!({
             "data": data,
             "usage": usage,
@@ -647,6 +1044,186 @@ mod tests {
     use serde_json::json;
     use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
 
+    #[test]
+    fn test_rate_limit_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(rate_limit_from_headers(&headers), None);
+
+        for (name, value) in [
+            ("x-ratelimit-limit-requests", "500"),
+            ("x-ratelimit-remaining-requests", "499"),
+            ("x-ratelimit-reset-requests", "120ms"),
+            ("x-ratelimit-limit-tokens", "30000"),
+            ("x-ratelimit-remaining-tokens", "28500"),
+            ("x-ratelimit-reset-tokens", "3s"),
+            ("content-type", "application/json"),
+        ] {
+            headers.insert(name, value.parse().unwrap());
+        }
+
+        assert_eq!(
+            rate_limit_from_headers(&headers),
+            Some(RateLimitInfo {
+                limit_requests: Some(500),
+                remaining_requests: Some(499),
+                reset_requests: Some("120ms".to_string()),
+                limit_tokens: Some(30000),
+                remaining_tokens: Some(28500),
+                reset_tokens: Some("3s".to_string()),
+            })
+        );
+
+        let mut partial = HeaderMap::new();
+        partial.insert(
+            "x-ratelimit-remaining-tokens",
+            "not-a-number".parse().unwrap(),
+        );
+        partial.insert("x-ratelimit-remaining-requests", "7".parse().unwrap());
+        let info = rate_limit_from_headers(&partial).unwrap();
+        assert_eq!(info.remaining_requests, Some(7));
+        assert_eq!(info.remaining_tokens, None);
+    }
+
+    #[test]
+    fn test_clamp_text_to() {
+        assert_eq!(clamp_text_to("short", 10), "short");
+
+        let text = format!("{}{}", "a".repeat(50), "b".repeat(50));
+        let clamped = clamp_text_to(&text, 20);
+        assert!(clamped.starts_with(&"a".repeat(10)));
+        assert!(clamped.ends_with(&"b".repeat(10)));
+        assert!(clamped.contains("[... 80 characters truncated"));
+    }
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn validate_with_logs(shape: ResponseShape, response: &Value) -> (bool, String) {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let result = temp_env::with_var("GOOSE_VALIDATE_RESPONSES", Some("1"), || {
+            tracing::subscriber::with_default(subscriber, || {
+                validate_response_shape("test-provider", shape, response)
+            })
+        });
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        (result.is_ok(), output)
+    }
+
+    #[test]
+    fn test_validate_response_shape_warns_on_malformed_responses() {
+        let (ok, logs) = validate_with_logs(
+            ResponseShape::OpenAi,
+            &json!({"id": "chatcmpl-1", "output": [{"text": "secret answer"}]}),
+        );
+        assert!(!ok);
+        assert!(logs.contains("WARN"));
+        assert!(logs.contains("provider response is missing expected fields"));
+        assert!(logs.contains("choices"));
+        assert!(logs.contains("usage"));
+        assert!(!logs.contains("secret answer"));
+
+        let (ok, logs) = validate_with_logs(
+            ResponseShape::Google,
+            &json!({"candidates": null, "usageMetadata": {"totalTokenCount": 3}}),
+        );
+        assert!(!ok);
+        assert!(logs.contains("candidates"));
+
+        let (ok, logs) = validate_with_logs(
+            ResponseShape::Anthropic,
+            &json!({"content": [{"type": "text", "text": "hi"}], "usage": {}}),
+        );
+        assert!(ok);
+        assert!(logs.is_empty());
+    }
+
+    #[test]
+    fn test_validate_response_shape_disabled_by_default() {
+        temp_env::with_var("GOOSE_VALIDATE_RESPONSES", None::<&str>, || {
+            assert!(
+                validate_response_shape("test-provider", ResponseShape::OpenAi, &json!({})).is_ok()
+            );
+        });
+    }
+
+    #[test]
+    fn test_clamp_text_to_keeps_code_fences_balanced() {
+        let text = format!("```rust\n{}\n```\ndone", "x".repeat(100));
+        let clamped = clamp_text_to(&text, 20);
+        let (head, tail) = clamped.split_once("\n\n[...").unwrap();
+        assert_eq!(head, format!("```rust\n{}\n```", "x".repeat(2)));
+        assert!(tail.contains("```rust\n"));
+        assert!(tail.ends_with("x\n```\ndone"));
+        assert!(crate::utils::open_code_fence(&clamped).is_none());
+    }
+
+    #[test]
+    fn test_join_tool_result_texts() {
+        let texts = vec!["line one".to_string(), "line two".to_string()];
+        assert_eq!(
+            join_tool_result_texts(&texts, ToolResultJoinStrategy::Concatenated),
+            json!("line one line two")
+        );
+        assert_eq!(
+            join_tool_result_texts(&texts, ToolResultJoinStrategy::SeparateBlocks),
+            json!([
+                {"type": "text", "text": "line one"},
+                {"type": "text", "text": "line two"}
+            ])
+        );
+        assert_eq!(
+            join_tool_result_texts(&[], ToolResultJoinStrategy::SeparateBlocks),
+            json!("")
+        );
+
+        let with_blank = vec!["line one".to_string(), " ".to_string(), String::new()];
+        assert_eq!(
+            join_tool_result_texts(&with_blank, ToolResultJoinStrategy::SeparateBlocks),
+            json!([{"type": "text", "text": "line one"}])
+        );
+    }
+
+    #[test]
+    fn test_strip_empty_content() {
+        let messages = vec![
+            Message::user().with_text("hello"),
+            Message::assistant().with_text("").with_tool_request(
+                "call_1",
+                Ok(rmcp::model::CallToolRequestParam {
+                    name: "shell".into(),
+                    arguments: None,
+                }),
+            ),
+            Message::assistant().with_text("  \n"),
+            Message::user().with_text("").with_text("still here"),
+        ];
+
+        let stripped = strip_empty_content(&messages);
+
+        assert_eq!(stripped.len(), 3);
+        assert_eq!(stripped[0], messages[0]);
+        assert_eq!(stripped[1].content.len(), 1);
+        assert!(stripped[1].is_tool_call());
+        assert_eq!(stripped[2].content.len(), 1);
+        assert_eq!(stripped[2].as_concat_text(), "still here");
+    }
+
     #[test]
     fn test_detect_image_path() {
         // Create a temporary PNG file with valid PNG magic numbers
@@ -953,6 +1530,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_retry_after_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after_from_headers(&headers), None);
+
+        headers.insert(reqwest::header::RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(
+            retry_after_from_headers(&headers),
+            Some(Duration::from_secs(7))
+        );
+
+        // A date in the past means retry right away
+        headers.insert(
+            reqwest::header::RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after_from_headers(&headers), Some(Duration::ZERO));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter_spreads_out_bursts() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(1));
+        let started = tokio::time::Instant::now();
+
+        let calls = (0..10).map(|_| {
+            let limiter = limiter.clone();
+            tokio::spawn(async move {
+                limiter.acquire().await;
+                started.elapsed()
+            })
+        });
+        let mut finished = Vec::new();
+        for call in calls {
+            finished.push(call.await.unwrap());
+        }
+        finished.sort();
+
+        // Two go through at once, then one every half second
+        assert!(finished[1] < Duration::from_millis(250));
+        assert!(finished[2] >= Duration::from_millis(450));
+        assert!(finished[9] >= Duration::from_millis(3900));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter_cancelled_wait_keeps_token() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(1));
+        limiter.acquire().await;
+
+        // Give up half way to the next token
+        let waited = tokio::time::timeout(Duration::from_millis(500), limiter.acquire()).await;
+        assert!(waited.is_err());
+
+        let started = tokio::time::Instant::now();
+        limiter.acquire().await;
+        assert!(started.elapsed() < Duration::from_millis(600));
+    }
+
+    #[test]
+    fn test_shared_rate_limiter_is_reused() {
+        let limiter = RateLimiter::shared_per_minute("shared-limiter-test", 60);
+        let same = RateLimiter::shared_per_minute("shared-limiter-test", 60);
+        let other = RateLimiter::shared_per_minute("shared-limiter-test", 30);
+        assert!(Arc::ptr_eq(&limiter.bucket, &same.bucket));
+        assert!(!Arc::ptr_eq(&limiter.bucket, &other.bucket));
+    }
+
     #[test]
     fn test_parse_google_retry_delay() {
         let payload = json!({
@@ -971,6 +1614,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_classify_google_response() {
+        let error = |code: u16, status: &str, message: &str| json!({"error": {"code": code, "status": status, "message": message}});
+        let classify = |status: u16, payload: Value| {
+            classify_google_response(StatusCode::from_u16(status).unwrap(), Some(payload), None)
+        };
+
+        let rate_limited = classify(
+            429,
+            json!({"error": {
+                "code": 429,
+                "status": "RESOURCE_EXHAUSTED",
+                "message": "Quota exceeded for metric: generate_content_free_tier_requests",
+                "details": [{"@type": "type.googleapis.com/google.rpc.RetryInfo", "retryDelay": "7s"}]
+            }}),
+        );
+        assert!(matches!(
+            rate_limited,
+            Err(ProviderError::RateLimitExceeded { retry_delay: Some(d), .. }) if d == Duration::from_secs(7)
+        ));
+
+        assert!(matches!(
+            classify(
+                503,
+                error(
+                    503,
+                    "UNAVAILABLE",
+                    "The model is overloaded. Please try again later."
+                )
+            ),
+            Err(ProviderError::ServerError(_))
+        ));
+        assert!(matches!(
+            classify(
+                500,
+                error(500, "INTERNAL", "An internal error has occurred.")
+            ),
+            Err(ProviderError::ServerError(_))
+        ));
+
+        assert!(matches!(
+            classify(
+                404,
+                error(404, "NOT_FOUND", "models/gemini-9.0-pro is not found for API version v1beta, or is not supported for generateContent.")
+            ),
+            Err(ProviderError::ModelNotFound(_))
+        ));
+        assert!(matches!(
+            classify(
+                400,
+                error(400, "INVALID_ARGUMENT", "The input token count (1200000) exceeds the maximum number of tokens allowed (1048576).")
+            ),
+            Err(ProviderError::ContextLengthExceeded {
+                limit: Some(1048576),
+                ..
+            })
+        ));
+        assert!(matches!(
+            classify(
+                400,
+                error(400, "INVALID_ARGUMENT", "Invalid JSON payload received.")
+            ),
+            Err(ProviderError::RequestFailed(_))
+        ));
+
+        let blocked_prompt = classify(
+            200,
+            json!({"promptFeedback": {"blockReason": "SAFETY"}, "usageMetadata": {"promptTokenCount": 8}}),
+        );
+        assert_eq!(
+            blocked_prompt,
+            Err(ProviderError::RequestFailed(
+                "Request blocked by Gemini safety filters (reason: SAFETY)".to_string()
+            ))
+        );
+        assert!(matches!(
+            classify(200, json!({"candidates": [{"finishReason": "PROHIBITED_CONTENT"}]})),
+            Err(ProviderError::RequestFailed(msg)) if msg.contains("PROHIBITED_CONTENT")
+        ));
+
+        let answered = json!({"candidates": [{
+            "content": {"parts": [{"text": "Hi"}], "role": "model"},
+            "finishReason": "STOP"
+        }]});
+        assert_eq!(classify(200, answered.clone()), Ok(answered));
+    }
+
     #[tokio::test]
     async fn test_handle_status_openai_compat() {
         let test_cases = vec![
@@ -1023,11 +1753,12 @@ mod tests {
                     "type": "invalid_request_error"
                 }
             })),
-                Err(ProviderError::ContextLengthExceeded(
-                    "This model's maximum context length is 4096 tokens.".to_string(),
-                )),
+                Err(ProviderError::ContextLengthExceeded {
+                    details: "This model's maximum context length is 4096 tokens.".to_string(),
+                    limit: Some(4096),
+                }),
             ),
-            // 404 Not Found with OpenAI-formatted error (directly handled like 400)
+            // 404 Not Found with OpenAI-formatted model_not_found error
             (
                 404,
                 Some(json!({
@@ -1036,18 +1767,33 @@ mod tests {
                     "message": "The model 'gpt-5' does not exist",
                     "type": "invalid_request_error"
                 }
+            })),
+                Err(ProviderError::ModelNotFound(
+                    "The model 'gpt-5' does not exist".to_string(),
+                )),
+            ),
+            // 404 Not Found with another OpenAI-formatted error (directly handled like 400)
+            (
+                404,
+                Some(json!({
+                "error": {
+                    "code": "unknown_url",
+                    "message": "Unknown request URL: GET /v1/foo",
+                    "type": "invalid_request_error"
+                }
             })),
                 Err(ProviderError::RequestFailed(
-                    "The model 'gpt-5' does not exist (code: model_not_found, type: invalid_request_error) (status 404)".to_string(),
+                    "Unknown request URL: GET /v1/foo (code: unknown_url, type: invalid_request_error) (status 404)".to_string(),
                 )),
             ),
             // Non-JSON body error (tests 413 PAYLOAD_TOO_LARGE -> ContextLengthExceeded)
             (
                 413,
                 Some(Value::String("Payload Too Large".to_string())),
-                Err(ProviderError::ContextLengthExceeded(
-                    "Payload is too large.".to_string(),
-                )),
+                Err(ProviderError::ContextLengthExceeded {
+                    details: "Payload is too large.".to_string(),
+                    limit: None,
+                }),
             ),
         ];
 
@@ -1109,9 +1855,10 @@ mod tests {
             (
                 StatusCode::BAD_REQUEST,
                 Some(json!({"error": {"message": "context_length_exceeded"}})),
-                ProviderError::ContextLengthExceeded(
-                    "{\"error\":{\"message\":\"context_length_exceeded\"}}".to_string(),
-                ),
+                ProviderError::ContextLengthExceeded {
+                    details: "{\"error\":{\"message\":\"context_length_exceeded\"}}".to_string(),
+                    limit: None,
+                },
             ),
             (
                 StatusCode::BAD_REQUEST,
@@ -1127,6 +1874,13 @@ mod tests {
                     "Request failed with status: 400 Bad Request".to_string(),
                 ),
             ),
+            (
+                StatusCode::NOT_FOUND,
+                Some(json!({"error": {"message": "model: claude-2 not found"}})),
+                ProviderError::ModelNotFound(
+                    "{\"error\":{\"message\":\"model: claude-2 not found\"}}".to_string(),
+                ),
+            ),
             (
                 StatusCode::TOO_MANY_REQUESTS,
                 Some(json!({"retry_after": 60})),
@@ -1,15 +1,15 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use chrono::Utc;
 use serde::Serialize;
 use serde_json::{json, Value};
 
-use super::api_client::{ApiClient, AuthMethod};
+use super::api_client::{ApiClient, AuthMethod, DEFAULT_REQUEST_TIMEOUT};
 use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::errors::ProviderError;
 use super::retry::ProviderRetry;
-use super::utils::map_http_error_to_provider_error;
+use super::utils::{map_http_error_to_provider_error, strip_empty_content};
 use crate::conversation::message::{Message, MessageContent};
+use crate::conversation::tool_call_id::tool_call_id_or_synthetic;
 
 use crate::mcp_utils::ToolResult;
 use crate::model::ModelConfig;
@@ -100,7 +100,11 @@ impl VeniceProvider {
         model.model_name = strip_flags(&model.model_name).to_string();
 
         let auth = AuthMethod::BearerToken(api_key);
-        let api_client = ApiClient::new(host, auth)?;
+        let api_client = ApiClient::with_timeout(
+            host,
+            auth,
+            model.request_timeout_or(DEFAULT_REQUEST_TIMEOUT),
+        )?;
 
         let instance = Self {
             api_client,
@@ -268,7 +272,7 @@ impl Provider for VeniceProvider {
         }
 
         // Format regular messages according to Venice API requirements
-        for msg in messages {
+        for msg in &strip_empty_content(messages) {
             // Venice API expects 'content' to be a string, not an array of MessageContent
             let content = match msg.role {
                 Role::User => {
@@ -448,7 +452,7 @@ impl Provider for VeniceProvider {
                 let mut content = Vec::new();
 
                 for tool_call in tool_calls {
-                    let id = tool_call["id"].as_str().unwrap_or("unknown").to_string();
+                    let id = tool_call_id_or_synthetic(tool_call["id"].as_str());
                     let function = tool_call["function"].clone();
                     let name = function["name"].as_str().unwrap_or("unknown").to_string();
 
@@ -510,7 +514,7 @@ impl Provider for VeniceProvider {
         );
 
         Ok((
-            Message::new(Role::Assistant, Utc::now().timestamp(), content),
+            Message::new(Role::Assistant, crate::clock::now().timestamp(), content),
             ProviderUsage::new(strip_flags(&self.model.model_name).to_string(), usage),
         ))
     }
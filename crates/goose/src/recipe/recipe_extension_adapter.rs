@@ -1,4 +1,4 @@
-use crate::agents::extension::{Envs, ExtensionConfig};
+use crate::agents::extension::{Envs, ExtensionConfig, StdioFraming};
 use rmcp::model::Tool;
 use serde::de::Deserializer;
 use serde::Deserialize;
@@ -39,6 +39,8 @@ enum RecipeExtensionConfigInternal {
         bundled: Option<bool>,
         #[serde(default)]
         available_tools: Vec<String>,
+        #[serde(default)]
+        framing: StdioFraming,
     },
     #[serde(rename = "builtin")]
     Builtin {
@@ -143,7 +145,8 @@ impl From<RecipeExtensionConfigInternal> for ExtensionConfig {
                 env_keys,
                 timeout,
                 bundled,
-                available_tools
+                available_tools,
+                framing
             },
             Builtin {
                 display_name,
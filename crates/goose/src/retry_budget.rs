@@ -0,0 +1,94 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::task_local;
+
+/// Caps the retries spent in one agent turn, shared by provider request retries and the
+/// agent's own recovery (context compaction, empty responses, model fallback, recipe
+/// retries), so a bad turn fails predictably instead of compounding retries.
+#[derive(Debug)]
+pub struct RetryBudget {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+impl RetryBudget {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    /// Take one retry from the budget, returning false once it is spent
+    pub fn try_consume(&self) -> bool {
+        self.used
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |used| {
+                (used < self.limit).then_some(used + 1)
+            })
+            .is_ok()
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::SeqCst)
+    }
+}
+
+task_local! {
+    static RETRY_BUDGET: Arc<RetryBudget>;
+}
+
+/// Run `f` drawing retries from `budget`; without a budget retries are not capped
+pub async fn with_retry_budget<F>(budget: Option<Arc<RetryBudget>>, f: F) -> F::Output
+where
+    F: std::future::Future,
+{
+    if let Some(budget) = budget {
+        RETRY_BUDGET.scope(budget, f).await
+    } else {
+        f.await
+    }
+}
+
+/// Take one retry from the budget in scope; always allowed outside a scope
+pub fn try_consume() -> bool {
+    RETRY_BUDGET
+        .try_with(|budget| budget.try_consume())
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_runs_out() {
+        let budget = RetryBudget::new(2);
+        assert!(budget.try_consume());
+        assert!(budget.try_consume());
+        assert!(!budget.try_consume());
+        assert_eq!(budget.used(), 2);
+        assert_eq!(budget.limit(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_budget_shared_within_scope() {
+        assert!(try_consume());
+
+        let budget = Arc::new(RetryBudget::new(1));
+        with_retry_budget(Some(budget.clone()), async {
+            assert!(try_consume());
+            assert!(!try_consume());
+        })
+        .await;
+        assert_eq!(budget.used(), 1);
+
+        with_retry_budget(None, async {
+            assert!(try_consume());
+        })
+        .await;
+    }
+}
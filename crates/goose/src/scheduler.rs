@@ -692,6 +692,8 @@ async fn execute_job(
         schedule_id: Some(job.id.clone()),
         max_turns: None,
         retry_config: None,
+        compact_threshold: None,
+        turn_budget: None,
     };
 
     let session_id = session_config.id.clone();
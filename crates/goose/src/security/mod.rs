@@ -5,7 +5,8 @@ pub mod security_inspector;
 use crate::conversation::message::{Message, ToolRequest};
 use crate::permission::permission_judge::PermissionCheckResult;
 use anyhow::Result;
-use scanner::PromptInjectionScanner;
+use patterns::ThreatCategory;
+use scanner::{PromptInjectionScanner, ScanResult};
 use std::sync::OnceLock;
 use uuid::Uuid;
 
@@ -19,10 +20,44 @@ pub struct SecurityResult {
     pub confidence: f32,
     pub explanation: String,
     pub should_ask_user: bool,
+    /// Flagged below the threshold in flag mode: runs, but is reported as flagged-allowed
+    pub flagged_allowed: bool,
+    pub categories: Vec<ThreatCategory>,
     pub finding_id: String,
     pub tool_request_id: String,
 }
 
+/// How findings at or below `security_prompt_threshold` are handled, from `security_prompt_mode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityMode {
+    /// Only log them (the default)
+    LogOnly,
+    /// Allow them but report them as flagged-allowed with their categories
+    Flag,
+}
+
+/// What happens to a scanned tool call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityVerdict {
+    Pass,
+    FlagAllow,
+    Block,
+}
+
+/// Findings above `threshold` always need the user's approval; those at or below it are
+/// flagged but allowed in [`SecurityMode::Flag`] and pass otherwise.
+pub fn classify_finding(scan: &ScanResult, threshold: f32, mode: SecurityMode) -> SecurityVerdict {
+    if !scan.is_malicious {
+        SecurityVerdict::Pass
+    } else if scan.confidence > threshold {
+        SecurityVerdict::Block
+    } else if mode == SecurityMode::Flag {
+        SecurityVerdict::FlagAllow
+    } else {
+        SecurityVerdict::Pass
+    }
+}
+
 impl SecurityManager {
     pub fn new() -> Self {
         Self {
@@ -40,6 +75,17 @@ impl SecurityManager {
             .unwrap_or(false)
     }
 
+    pub fn mode(&self) -> SecurityMode {
+        use crate::config::Config;
+        match Config::global()
+            .get_param::<String>("security_prompt_mode")
+            .as_deref()
+        {
+            Ok("flag") => SecurityMode::Flag,
+            _ => SecurityMode::LogOnly,
+        }
+    }
+
     /// New method for tool inspection framework - works directly with tool requests
     pub async fn analyze_tool_requests(
         &self,
@@ -63,6 +109,7 @@ impl SecurityManager {
         });
 
         let mut results = Vec::new();
+        let mode = self.mode();
 
         tracing::info!(
             "🔍 Starting security analysis - {} tool requests, {} messages",
@@ -81,44 +128,53 @@ impl SecurityManager {
                 let config_threshold = scanner.get_threshold_from_config();
                 let sanitized_explanation = analysis_result.explanation.replace('\n', " | ");
 
-                if analysis_result.is_malicious {
-                    let above_threshold = analysis_result.confidence > config_threshold;
-                    let finding_id = format!("SEC-{}", Uuid::new_v4().simple());
-
-                    tracing::warn!(
-                        counter.goose.prompt_injection_finding = 1,
-                        above_threshold = above_threshold,
+                let verdict = classify_finding(&analysis_result, config_threshold, mode);
+                if !analysis_result.is_malicious {
+                    tracing::info!(
                         tool_name = %tool_call.name,
                         tool_request_id = %tool_request.id,
                         confidence = analysis_result.confidence,
                         explanation = %sanitized_explanation,
-                        finding_id = %finding_id,
-                        threshold = config_threshold,
-                        "{}",
-                        if above_threshold {
+                        "✅ Current tool call passed security analysis"
+                    );
+                    continue;
+                }
+
+                let finding_id = format!("SEC-{}", Uuid::new_v4().simple());
+                tracing::warn!(
+                    counter.goose.prompt_injection_finding = 1,
+                    above_threshold = verdict == SecurityVerdict::Block,
+                    tool_name = %tool_call.name,
+                    tool_request_id = %tool_request.id,
+                    confidence = analysis_result.confidence,
+                    explanation = %sanitized_explanation,
+                    categories = ?analysis_result.categories,
+                    finding_id = %finding_id,
+                    threshold = config_threshold,
+                    "{}",
+                    match verdict {
+                        SecurityVerdict::Block => {
                             "Current tool call flagged as malicious after security analysis (above threshold)"
-                        } else {
+                        }
+                        SecurityVerdict::FlagAllow => {
+                            "Security finding below threshold - flagged-allowed"
+                        }
+                        SecurityVerdict::Pass => {
                             "Security finding below threshold - logged but not blocking execution"
                         }
-                    );
-                    if above_threshold {
-                        results.push(SecurityResult {
-                            is_malicious: analysis_result.is_malicious,
-                            confidence: analysis_result.confidence,
-                            explanation: analysis_result.explanation,
-                            should_ask_user: true, // Always ask user for threats above threshold
-                            finding_id,
-                            tool_request_id: tool_request.id.clone(),
-                        });
                     }
-                } else {
-                    tracing::info!(
-                        tool_name = %tool_call.name,
-                        tool_request_id = %tool_request.id,
-                        confidence = analysis_result.confidence,
-                        explanation = %sanitized_explanation,
-                        "✅ Current tool call passed security analysis"
-                    );
+                );
+                if verdict != SecurityVerdict::Pass {
+                    results.push(SecurityResult {
+                        is_malicious: analysis_result.is_malicious,
+                        confidence: analysis_result.confidence,
+                        explanation: analysis_result.explanation,
+                        should_ask_user: verdict == SecurityVerdict::Block,
+                        flagged_allowed: verdict == SecurityVerdict::FlagAllow,
+                        categories: analysis_result.categories,
+                        finding_id,
+                        tool_request_id: tool_request.id.clone(),
+                    });
                 }
             }
         }
@@ -163,3 +219,45 @@ impl Default for SecurityManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(is_malicious: bool, confidence: f32) -> ScanResult {
+        ScanResult {
+            is_malicious,
+            confidence,
+            explanation: String::new(),
+            categories: vec![ThreatCategory::NetworkAccess],
+        }
+    }
+
+    #[test]
+    fn test_classify_finding_block_flag_and_pass() {
+        let threshold = 0.7;
+
+        // Above the threshold needs approval in either mode
+        for mode in [SecurityMode::LogOnly, SecurityMode::Flag] {
+            assert_eq!(
+                classify_finding(&scan(true, 0.85), threshold, mode),
+                SecurityVerdict::Block
+            );
+        }
+
+        // Borderline findings are only flagged in flag mode
+        assert_eq!(
+            classify_finding(&scan(true, 0.55), threshold, SecurityMode::Flag),
+            SecurityVerdict::FlagAllow
+        );
+        assert_eq!(
+            classify_finding(&scan(true, 0.55), threshold, SecurityMode::LogOnly),
+            SecurityVerdict::Pass
+        );
+
+        assert_eq!(
+            classify_finding(&scan(false, 0.0), threshold, SecurityMode::Flag),
+            SecurityVerdict::Pass
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use crate::conversation::message::Message;
-use crate::security::patterns::{PatternMatcher, RiskLevel};
+use crate::security::patterns::{PatternMatcher, RiskLevel, ThreatCategory};
 use anyhow::Result;
 use rmcp::model::CallToolRequestParam;
 use serde_json::Value;
@@ -9,6 +9,8 @@ pub struct ScanResult {
     pub is_malicious: bool,
     pub confidence: f32,
     pub explanation: String,
+    /// Distinct categories of the matched threat patterns
+    pub categories: Vec<ThreatCategory>,
 }
 
 pub struct PromptInjectionScanner {
@@ -66,6 +68,7 @@ impl PromptInjectionScanner {
                 is_malicious: false,
                 confidence: 0.0,
                 explanation: "No security threats detected".to_string(),
+                categories: Vec::new(),
             });
         }
 
@@ -111,10 +114,18 @@ impl PromptInjectionScanner {
             )
         };
 
+        let mut categories: Vec<ThreatCategory> = Vec::new();
+        for pattern_match in &matches {
+            if !categories.contains(&pattern_match.threat.category) {
+                categories.push(pattern_match.threat.category.clone());
+            }
+        }
+
         Ok(ScanResult {
             is_malicious,
             confidence,
             explanation,
+            categories,
         })
     }
 
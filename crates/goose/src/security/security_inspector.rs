@@ -39,10 +39,19 @@ impl SecurityInspector {
             InspectionAction::Allow
         };
 
+        let reason = if security_result.flagged_allowed {
+            format!(
+                "flagged-allowed {:?}: {}",
+                security_result.categories, security_result.explanation
+            )
+        } else {
+            security_result.explanation.clone()
+        };
+
         InspectionResult {
             tool_request_id,
             action,
-            reason: security_result.explanation.clone(),
+            reason,
             confidence: security_result.confidence,
             inspector_name: self.name().to_string(),
             finding_id: Some(security_result.finding_id.clone()),
@@ -138,6 +147,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_flagged_allowed_finding_is_allowed_and_annotated() {
+        let inspector = SecurityInspector::new();
+        let finding = SecurityResult {
+            is_malicious: true,
+            confidence: 0.55,
+            explanation: "Network request".to_string(),
+            should_ask_user: false,
+            flagged_allowed: true,
+            categories: vec![crate::security::patterns::ThreatCategory::NetworkAccess],
+            finding_id: "SEC-1".to_string(),
+            tool_request_id: "req".to_string(),
+        };
+
+        let result = inspector.convert_security_result(&finding, "req".to_string());
+        assert_eq!(result.action, InspectionAction::Allow);
+        assert_eq!(
+            result.reason,
+            "flagged-allowed [NetworkAccess]: Network request"
+        );
+
+        let blocked = SecurityResult {
+            confidence: 0.95,
+            should_ask_user: true,
+            flagged_allowed: false,
+            ..finding
+        };
+        let result = inspector.convert_security_result(&blocked, "req".to_string());
+        assert!(matches!(
+            result.action,
+            InspectionAction::RequireApproval(_)
+        ));
+    }
+
     #[test]
     fn test_security_inspector_name() {
         let inspector = SecurityInspector::new();
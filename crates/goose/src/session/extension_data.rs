@@ -2,6 +2,7 @@
 // Provides a simple way to store extension-specific data with versioned keys
 
 use crate::config::ExtensionConfig;
+use crate::providers::base::ProviderUsage;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -113,6 +114,42 @@ impl EnabledExtensionsState {
     }
 }
 
+/// Token usage per provider and model over the whole session, so a resumed session keeps
+/// its running totals
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct UsageState {
+    pub usage: Vec<ProviderUsage>,
+}
+
+impl ExtensionState for UsageState {
+    const EXTENSION_NAME: &'static str = "usage";
+    const VERSION: &'static str = "v0";
+}
+
+impl UsageState {
+    pub fn new(usage: Vec<ProviderUsage>) -> Self {
+        Self { usage }
+    }
+}
+
+/// The model a session switched to mid-run, e.g. after falling back from a missing model,
+/// so resuming the session keeps using it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelState {
+    pub model: String,
+}
+
+impl ExtensionState for ModelState {
+    const EXTENSION_NAME: &'static str = "model";
+    const VERSION: &'static str = "v0";
+}
+
+impl ModelState {
+    pub fn new(model: String) -> Self {
+        Self { model }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -5,5 +5,7 @@ mod legacy;
 pub mod session_manager;
 
 pub use diagnostics::generate_diagnostics;
-pub use extension_data::{EnabledExtensionsState, ExtensionData, ExtensionState, TodoState};
+pub use extension_data::{
+    EnabledExtensionsState, ExtensionData, ExtensionState, ModelState, TodoState, UsageState,
+};
 pub use session_manager::{Session, SessionInsights, SessionManager, SessionType};
@@ -18,7 +18,7 @@ use tokio::sync::OnceCell;
 use tracing::{info, warn};
 use utoipa::ToSchema;
 
-const CURRENT_SCHEMA_VERSION: i32 = 5;
+const CURRENT_SCHEMA_VERSION: i32 = 6;
 pub const SESSIONS_FOLDER: &str = "sessions";
 pub const DB_NAME: &str = "sessions.db";
 
@@ -290,6 +290,10 @@ impl SessionManager {
         Self::instance().await?.import_session(json).await
     }
 
+    pub async fn fork_session(id: &str, name: String) -> Result<Session> {
+        Self::instance().await?.fork_session(id, name).await
+    }
+
     pub async fn maybe_update_name(id: &str, provider: Arc<dyn Provider>) -> Result<()> {
         let session = Self::get_session(id, true).await?;
 
@@ -537,7 +541,8 @@ impl SessionStorage {
                 created_timestamp INTEGER NOT NULL,
                 timestamp TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
                 tokens INTEGER,
-                metadata_json TEXT
+                metadata_json TEXT,
+                message_id TEXT
             )
         "#,
         )
@@ -766,6 +771,15 @@ impl SessionStorage {
                     .execute(&self.pool)
                     .await?;
             }
+            6 => {
+                sqlx::query(
+                    r#"
+                    ALTER TABLE messages ADD COLUMN message_id TEXT
+                "#,
+                )
+                .execute(&self.pool)
+                .await?;
+            }
             _ => {
                 anyhow::bail!("Unknown migration version: {}", version);
             }
@@ -780,7 +794,7 @@ impl SessionStorage {
         name: String,
         session_type: SessionType,
     ) -> Result<Session> {
-        let today = chrono::Utc::now().format("%Y%m%d").to_string();
+        let today = crate::clock::now().format("%Y%m%d").to_string();
         Ok(sqlx::query_as(
             r#"
                 INSERT INTO sessions (id, name, user_set_name, session_type, working_dir, extension_data)
@@ -938,15 +952,16 @@ impl SessionStorage {
     }
 
     async fn get_conversation(&self, session_id: &str) -> Result<Conversation> {
-        let rows = sqlx::query_as::<_, (String, String, i64, Option<String>)>(
-            "SELECT role, content_json, created_timestamp, metadata_json FROM messages WHERE session_id = ? ORDER BY timestamp",
+        // Rows are read back in insertion order; `timestamp` only has second resolution
+        let rows = sqlx::query_as::<_, (String, String, i64, Option<String>, Option<String>)>(
+            "SELECT role, content_json, created_timestamp, metadata_json, message_id FROM messages WHERE session_id = ? ORDER BY id",
         )
             .bind(session_id)
             .fetch_all(&self.pool)
             .await?;
 
         let mut messages = Vec::new();
-        for (idx, (role_str, content_json, created_timestamp, metadata_json)) in
+        for (idx, (role_str, content_json, created_timestamp, metadata_json, message_id)) in
             rows.into_iter().enumerate()
         {
             let role = match role_str.as_str() {
@@ -962,7 +977,8 @@ impl SessionStorage {
 
             let mut message = Message::new(role, created_timestamp, content);
             message.metadata = metadata;
-            message = message.with_id(format!("msg_{}_{}", session_id, idx));
+            message = message
+                .with_id(message_id.unwrap_or_else(|| format!("msg_{}_{}", session_id, idx)));
             messages.push(message);
         }
 
@@ -976,8 +992,8 @@ impl SessionStorage {
 
         sqlx::query(
             r#"
-            INSERT INTO messages (session_id, role, content_json, created_timestamp, metadata_json)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT INTO messages (session_id, role, content_json, created_timestamp, metadata_json, message_id)
+            VALUES (?, ?, ?, ?, ?, ?)
         "#,
         )
         .bind(session_id)
@@ -985,6 +1001,7 @@ impl SessionStorage {
         .bind(serde_json::to_string(&message.content)?)
         .bind(message.created)
         .bind(metadata_json)
+        .bind(message.id.as_deref())
         .execute(&mut *tx)
         .await?;
 
@@ -1014,8 +1031,8 @@ impl SessionStorage {
 
             sqlx::query(
                 r#"
-            INSERT INTO messages (session_id, role, content_json, created_timestamp, metadata_json)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT INTO messages (session_id, role, content_json, created_timestamp, metadata_json, message_id)
+            VALUES (?, ?, ?, ?, ?, ?)
         "#,
             )
             .bind(session_id)
@@ -1023,6 +1040,7 @@ impl SessionStorage {
             .bind(serde_json::to_string(&message.content)?)
             .bind(message.created)
             .bind(metadata_json)
+            .bind(message.id.as_deref())
             .execute(&mut *tx)
             .await?;
         }
@@ -1137,6 +1155,39 @@ impl SessionStorage {
         self.get_session(&session.id, true).await
     }
 
+    /// Copy a session's history and usage into a new, independent session
+    async fn fork_session(&self, id: &str, name: String) -> Result<Session> {
+        let source = self.get_session(id, true).await?;
+
+        let session = self
+            .create_session(
+                source.working_dir.clone(),
+                name.clone(),
+                source.session_type,
+            )
+            .await?;
+
+        let builder = SessionUpdateBuilder::new(session.id.clone())
+            .user_provided_name(name)
+            .extension_data(source.extension_data)
+            .total_tokens(source.total_tokens)
+            .input_tokens(source.input_tokens)
+            .output_tokens(source.output_tokens)
+            .accumulated_total_tokens(source.accumulated_total_tokens)
+            .accumulated_input_tokens(source.accumulated_input_tokens)
+            .accumulated_output_tokens(source.accumulated_output_tokens)
+            .recipe(source.recipe)
+            .user_recipe_values(source.user_recipe_values);
+        self.apply_update(builder).await?;
+
+        if let Some(conversation) = source.conversation {
+            self.replace_conversation(&session.id, &conversation)
+                .await?;
+        }
+
+        self.get_session(&session.id, true).await
+    }
+
     async fn search_chat_history(
         &self,
         query: &str,
@@ -1164,6 +1215,7 @@ impl SessionStorage {
 mod tests {
     use super::*;
     use crate::conversation::message::{Message, MessageContent};
+    use rmcp::model::{CallToolRequestParam, Content};
     use tempfile::TempDir;
 
     const NUM_CONCURRENT_SESSIONS: i32 = 10;
@@ -1341,6 +1393,146 @@ mod tests {
         assert_eq!(conversation.messages()[1].role, Role::Assistant);
     }
 
+    #[tokio::test]
+    async fn test_fork_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test_fork.db");
+        let storage = Arc::new(SessionStorage::create(&db_path).await.unwrap());
+
+        let original = storage
+            .create_session(
+                PathBuf::from("/tmp/test"),
+                "original".to_string(),
+                SessionType::User,
+            )
+            .await
+            .unwrap();
+        storage
+            .apply_update(SessionUpdateBuilder::new(original.id.clone()).total_tokens(Some(42)))
+            .await
+            .unwrap();
+        storage
+            .add_message(&original.id, &Message::user().with_text("hello"))
+            .await
+            .unwrap();
+        storage
+            .add_message(&original.id, &Message::assistant().with_text("hi there"))
+            .await
+            .unwrap();
+
+        let fork = storage
+            .fork_session(&original.id, "branch".to_string())
+            .await
+            .unwrap();
+
+        assert_ne!(fork.id, original.id);
+        assert_eq!(fork.name, "branch");
+        assert!(fork.user_set_name);
+        assert_eq!(fork.working_dir, PathBuf::from("/tmp/test"));
+        assert_eq!(fork.total_tokens, Some(42));
+
+        let original = storage.get_session(&original.id, true).await.unwrap();
+        let original_messages = original.conversation.unwrap();
+        let fork_messages = fork.conversation.unwrap();
+        assert_eq!(fork_messages.len(), 2);
+        for (a, b) in original_messages.iter().zip(fork_messages.iter()) {
+            assert_eq!(a.role, b.role);
+            assert_eq!(a.content, b.content);
+        }
+
+        storage
+            .add_message(&fork.id, &Message::user().with_text("only in the fork"))
+            .await
+            .unwrap();
+
+        let original = storage.get_session(&original.id, true).await.unwrap();
+        let fork = storage.get_session(&fork.id, true).await.unwrap();
+        assert_eq!(original.message_count, 2);
+        assert_eq!(fork.message_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_multi_part_messages_roundtrip_in_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test_roundtrip.db");
+        let storage = Arc::new(SessionStorage::create(&db_path).await.unwrap());
+        let session = storage
+            .create_session(
+                PathBuf::from("/tmp/test"),
+                "roundtrip".to_string(),
+                SessionType::User,
+            )
+            .await
+            .unwrap();
+
+        let read_file = |path: &str| {
+            Ok(CallToolRequestParam {
+                name: "read_file".into(),
+                arguments: Some(
+                    serde_json::json!({"path": path})
+                        .as_object()
+                        .unwrap()
+                        .clone(),
+                ),
+            })
+        };
+        let messages = vec![
+            Message::user().with_text("compare a.txt and b.txt"),
+            Message::assistant()
+                .with_id("msg_assistant")
+                .with_thinking("Read both files first", "sig")
+                .with_text("Reading a.txt")
+                .with_tool_request("call_a", read_file("a.txt"))
+                .with_text("and b.txt")
+                .with_tool_request("call_b", read_file("b.txt")),
+            Message::user()
+                .with_id("msg_results")
+                .with_tool_response("call_b", Ok(vec![Content::text("bbb")]))
+                .with_tool_response("call_a", Ok(vec![Content::text("aaa")]))
+                .with_text("both read")
+                .agent_only(),
+        ];
+
+        // Added within the same second, so order must not depend on the timestamp column
+        for message in &messages {
+            storage.add_message(&session.id, message).await.unwrap();
+        }
+        let loaded = storage.get_conversation(&session.id).await.unwrap();
+        let expected_ids = [
+            format!("msg_{}_0", session.id),
+            "msg_assistant".to_string(),
+            "msg_results".to_string(),
+        ];
+        assert_eq!(
+            loaded
+                .messages()
+                .iter()
+                .map(|m| m.id.clone().unwrap())
+                .collect::<Vec<_>>(),
+            expected_ids
+        );
+
+        let with_ids: Vec<Message> = messages
+            .into_iter()
+            .zip(expected_ids)
+            .map(|(message, id)| message.with_id(id))
+            .collect();
+        assert_eq!(
+            serde_json::to_string(loaded.messages()).unwrap(),
+            serde_json::to_string(&with_ids).unwrap()
+        );
+
+        storage
+            .replace_conversation(&session.id, &loaded)
+            .await
+            .unwrap();
+        let reloaded = storage.get_conversation(&session.id).await.unwrap();
+        assert_eq!(
+            serde_json::to_string(reloaded.messages()).unwrap(),
+            serde_json::to_string(&with_ids).unwrap()
+        );
+    }
+
     #[tokio::test]
     async fn test_import_session_with_description_field() {
         const OLD_FORMAT_JSON: &str = r#"{
@@ -280,6 +280,17 @@ pub fn get_security_finding_id_from_results(
         .and_then(|result| result.finding_id.clone())
 }
 
+/// Security findings that were allowed to run but flagged, as (tool request id, reason) pairs
+pub fn get_flagged_security_findings(inspection_results: &[InspectionResult]) -> Vec<(&str, &str)> {
+    inspection_results
+        .iter()
+        .filter(|result| {
+            result.inspector_name == "security" && result.action == InspectionAction::Allow
+        })
+        .map(|result| (result.tool_request_id.as_str(), result.reason.as_str()))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -319,4 +330,26 @@ mod tests {
         assert_eq!(updated_result.denied.len(), 1);
         assert_eq!(updated_result.denied[0].id, "req_1");
     }
+
+    #[test]
+    fn test_flagged_security_findings() {
+        let result = |id: &str, action: InspectionAction, inspector_name: &str| InspectionResult {
+            tool_request_id: id.to_string(),
+            action,
+            reason: format!("reason {id}"),
+            confidence: 0.5,
+            inspector_name: inspector_name.to_string(),
+            finding_id: None,
+        };
+        let inspection_results = vec![
+            result("req_1", InspectionAction::Allow, "security"),
+            result("req_2", InspectionAction::RequireApproval(None), "security"),
+            result("req_3", InspectionAction::Allow, "permission"),
+        ];
+
+        assert_eq!(
+            get_flagged_security_findings(&inspection_results),
+            vec![("req_1", "reason req_1")]
+        );
+    }
 }
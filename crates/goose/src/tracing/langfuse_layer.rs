@@ -1,5 +1,4 @@
 use crate::tracing::observation_layer::{BatchManager, ObservationLayer, SpanTracker};
-use chrono::Utc;
 use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
@@ -135,7 +134,7 @@ impl BatchManager for LangfuseBatchManager {
     fn add_event(&mut self, event_type: &str, body: Value) {
         self.batch.push(json!({
             "id": Uuid::new_v4().to_string(),
-            "timestamp": Utc::now().to_rfc3339(),
+            "timestamp": crate::clock::now().to_rfc3339(),
             "type": event_type,
             "body": body
         }));
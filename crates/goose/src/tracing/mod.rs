@@ -2,11 +2,10 @@ pub mod langfuse_layer;
 mod observation_layer;
 pub mod otlp_layer;
 pub mod rate_limiter;
+mod visitor;
 
 pub use langfuse_layer::{create_langfuse_observer, LangfuseBatchManager};
-pub use observation_layer::{
-    flatten_metadata, map_level, BatchManager, ObservationLayer, SpanData, SpanTracker,
-};
+pub use observation_layer::{BatchManager, ObservationLayer, SpanData, SpanTracker};
 pub use otlp_layer::{
     create_otlp_metrics_filter, create_otlp_tracing_filter, create_otlp_tracing_layer,
     init_otlp_metrics, init_otlp_tracing, init_otlp_tracing_only, shutdown_otlp, OtlpConfig,
@@ -14,3 +13,4 @@ pub use otlp_layer::{
 pub use rate_limiter::{
     MetricData, RateLimitedTelemetrySender, SpanData as RateLimitedSpanData, TelemetryEvent,
 };
+pub use visitor::{flatten_metadata, map_level};
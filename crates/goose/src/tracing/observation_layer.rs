@@ -1,16 +1,16 @@
-use chrono::Utc;
+use crate::clock;
 use serde_json::{json, Value};
-use std::collections::HashMap;
-use std::fmt;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::field::{Field, Visit};
-use tracing::{span, Event, Id, Level, Metadata, Subscriber};
+use tracing::{span, Event, Id, Metadata, Subscriber};
 use tracing_subscriber::layer::Context;
 use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::Layer;
 use uuid::Uuid;
 
+use super::visitor::{flatten_metadata, map_level, JsonVisitor};
+
 #[derive(Debug, Clone)]
 pub struct SpanData {
     pub observation_id: String, // Langfuse requires ids to be UUID v4 strings
@@ -21,39 +21,12 @@ pub struct SpanData {
     pub parent_span_id: Option<u64>,
 }
 
-pub fn map_level(level: &Level) -> &'static str {
-    match *level {
-        Level::ERROR => "ERROR",
-        Level::WARN => "WARNING",
-        Level::INFO => "DEFAULT",
-        Level::DEBUG => "DEBUG",
-        Level::TRACE => "DEBUG",
-    }
-}
-
-pub fn flatten_metadata(
-    metadata: serde_json::Map<String, Value>,
-) -> serde_json::Map<String, Value> {
-    let mut flattened = serde_json::Map::new();
-    for (key, value) in metadata {
-        match value {
-            Value::String(s) => {
-                flattened.insert(key, json!(s));
-            }
-            Value::Object(mut obj) => {
-                if let Some(text) = obj.remove("text") {
-                    flattened.insert(key, text);
-                } else {
-                    flattened.insert(key, json!(obj));
-                }
-            }
-            _ => {
-                flattened.insert(key, value);
-            }
-        }
-    }
-    flattened
-}
+/// Span fields carrying token usage, mapped to Langfuse's generation `usage` keys
+const USAGE_FIELDS: [(&str, &str); 3] = [
+    ("input_tokens", "input"),
+    ("output_tokens", "output"),
+    ("total_tokens", "total"),
+];
 
 pub trait BatchManager: Send + Sync + 'static {
     fn add_event(&mut self, event_type: &str, body: Value);
@@ -64,6 +37,7 @@ pub trait BatchManager: Send + Sync + 'static {
 #[derive(Debug)]
 pub struct SpanTracker {
     active_spans: HashMap<u64, String>, // span_id -> observation_id. span_id in Tracing is u64 whereas Langfuse requires UUID v4 strings
+    generation_spans: HashSet<u64>,
     current_trace_id: Option<String>,
 }
 
@@ -77,6 +51,7 @@ impl SpanTracker {
     pub fn new() -> Self {
         Self {
             active_spans: HashMap::new(),
+            generation_spans: HashSet::new(),
             current_trace_id: None,
         }
     }
@@ -90,8 +65,18 @@ impl SpanTracker {
     }
 
     pub fn remove_span(&mut self, span_id: u64) -> Option<String> {
+        self.generation_spans.remove(&span_id);
         self.active_spans.remove(&span_id)
     }
+
+    /// Mark a span as an LLM generation so later updates keep the GENERATION type
+    pub fn mark_generation(&mut self, span_id: u64) {
+        self.generation_spans.insert(span_id);
+    }
+
+    pub fn is_generation(&self, span_id: u64) -> bool {
+        self.generation_spans.contains(&span_id)
+    }
 }
 
 #[derive(Clone)]
@@ -137,9 +122,10 @@ impl ObservationLayer {
     }
 
     pub async fn handle_span_close(&self, span_id: u64) {
-        let observation_id = {
+        let (observation_id, is_generation) = {
             let mut spans = self.span_tracker.lock().await;
-            spans.remove_span(span_id)
+            let is_generation = spans.is_generation(span_id);
+            (spans.remove_span(span_id), is_generation)
         };
 
         if let Some(observation_id) = observation_id {
@@ -149,9 +135,9 @@ impl ObservationLayer {
                 "observation-update",
                 json!({
                     "id": observation_id,
-                    "type": "SPAN",
+                    "type": observation_type(is_generation),
                     "traceId": trace_id,
-                    "endTime": Utc::now().to_rfc3339()
+                    "endTime": clock::now().to_rfc3339()
                 }),
             );
         }
@@ -171,8 +157,8 @@ impl ObservationLayer {
             "trace-create",
             json!({
                 "id": trace_id,
-                "name": Utc::now().timestamp().to_string(),
-                "timestamp": Utc::now().to_rfc3339(),
+                "name": clock::now().timestamp().to_string(),
+                "timestamp": clock::now().to_rfc3339(),
                 "input": {},
                 "metadata": {},
                 "tags": [],
@@ -184,9 +170,17 @@ impl ObservationLayer {
     }
 
     pub async fn handle_record(&self, span_id: u64, metadata: serde_json::Map<String, Value>) {
-        let observation_id = {
-            let spans = self.span_tracker.lock().await;
-            spans.get_span(span_id).cloned()
+        let usage = usage_from_metadata(&metadata);
+        let (observation_id, is_generation) = {
+            let mut spans = self.span_tracker.lock().await;
+            // Provider spans record their model config and token usage; both mark a generation
+            if usage.is_some() || metadata.contains_key("model_config") {
+                spans.mark_generation(span_id);
+            }
+            (
+                spans.get_span(span_id).cloned(),
+                spans.is_generation(span_id),
+            )
         };
 
         if let Some(observation_id) = observation_id {
@@ -195,9 +189,13 @@ impl ObservationLayer {
             let mut update = json!({
                 "id": observation_id,
                 "traceId": trace_id,
-                "type": "SPAN"
+                "type": observation_type(is_generation)
             });
 
+            if let Some(usage) = usage {
+                update["usage"] = usage;
+            }
+
             // Handle special fields
             if let Some(val) = metadata.get("input") {
                 update["input"] = val.clone();
@@ -214,7 +212,10 @@ impl ObservationLayer {
             // Handle any remaining metadata
             let remaining_metadata: serde_json::Map<String, Value> = metadata
                 .iter()
-                .filter(|(k, _)| !["input", "output", "model_config"].contains(&k.as_str()))
+                .filter(|(k, _)| {
+                    !["input", "output", "model_config"].contains(&k.as_str())
+                        && !USAGE_FIELDS.iter().any(|(field, _)| field == k)
+                })
                 .map(|(k, v)| (k.clone(), v.clone()))
                 .collect();
 
@@ -234,11 +235,38 @@ impl ObservationLayer {
             }
 
             let mut batch = self.batch_manager.lock().await;
-            batch.add_event("span-update", update);
+            batch.add_event("observation-update", update);
         }
     }
 }
 
+fn observation_type(is_generation: bool) -> &'static str {
+    if is_generation {
+        "GENERATION"
+    } else {
+        "SPAN"
+    }
+}
+
+/// Build a Langfuse `usage` object from any token-count fields in the recorded metadata.
+fn usage_from_metadata(metadata: &serde_json::Map<String, Value>) -> Option<Value> {
+    let mut usage = serde_json::Map::new();
+    for (field, key) in USAGE_FIELDS {
+        if let Some(count) = metadata.get(field).and_then(|v| {
+            v.as_i64()
+                .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+        }) {
+            usage.insert(key.to_string(), json!(count));
+        }
+    }
+
+    if usage.is_empty() {
+        return None;
+    }
+    usage.insert("unit".to_string(), json!("TOKENS"));
+    Some(Value::Object(usage))
+}
+
 impl<S> Layer<S> for ObservationLayer
 where
     S: Subscriber + for<'a> LookupSpan<'a>,
@@ -261,7 +289,7 @@ where
         let span_data = SpanData {
             observation_id: Uuid::new_v4().to_string(),
             name: attrs.metadata().name().to_string(),
-            start_time: Utc::now().to_rfc3339(),
+            start_time: clock::now().to_rfc3339(),
             level: map_level(attrs.metadata().level()).to_owned(),
             metadata: visitor.recorded_fields,
             parent_span_id,
@@ -301,42 +329,6 @@ where
     }
 }
 
-#[derive(Debug)]
-struct JsonVisitor {
-    recorded_fields: serde_json::Map<String, Value>,
-}
-
-impl JsonVisitor {
-    fn new() -> Self {
-        Self {
-            recorded_fields: serde_json::Map::new(),
-        }
-    }
-
-    fn insert_value(&mut self, field: &Field, value: Value) {
-        self.recorded_fields.insert(field.name().to_string(), value);
-    }
-}
-
-macro_rules! record_field {
-    ($fn_name:ident, $type:ty) => {
-        fn $fn_name(&mut self, field: &Field, value: $type) {
-            self.insert_value(field, Value::from(value));
-        }
-    };
-}
-
-impl Visit for JsonVisitor {
-    record_field!(record_i64, i64);
-    record_field!(record_u64, u64);
-    record_field!(record_bool, bool);
-    record_field!(record_str, &str);
-
-    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
-        self.insert_value(field, Value::String(format!("{:?}", value)));
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -429,7 +421,7 @@ mod tests {
         SpanData {
             observation_id: Uuid::new_v4().to_string(),
             name: "test_span".to_string(),
-            start_time: Utc::now().to_rfc3339(),
+            start_time: clock::now().to_rfc3339(),
             level: "DEFAULT".to_string(),
             metadata: serde_json::Map::new(),
             parent_span_id: None,
@@ -493,29 +485,47 @@ mod tests {
         tokio::time::sleep(TEST_WAIT_DURATION).await;
 
         let events = fixture.get_events().await;
-        assert_eq!(events.len(), 3); // trace-create, observation-create, span-update
+        assert_eq!(events.len(), 3); // trace-create, observation-create, observation-update
 
         let (event_type, body) = &events[2];
-        assert_eq!(event_type, "span-update");
+        assert_eq!(event_type, "observation-update");
+        assert_eq!(body["type"], "SPAN");
         assert_eq!(body["input"], "test input");
         assert_eq!(body["output"], "test output");
         assert_eq!(body["metadata"]["custom_field"], "custom value");
+        assert!(body.get("usage").is_none());
     }
 
-    #[test]
-    fn test_flatten_metadata() {
-        let _fixture = TestFixture::new();
+    #[tokio::test]
+    async fn test_record_usage_produces_generation() {
+        let (fixture, layer) = TestFixture::new().with_test_layer();
+        let span_id = 1u64;
+        let span_data = create_test_span_data();
+
+        layer.handle_span(span_id, span_data.clone()).await;
+
         let mut metadata = serde_json::Map::new();
-        metadata.insert("simple".to_string(), json!("value"));
-        metadata.insert(
-            "complex".to_string(),
-            json!({
-                "text": "inner value"
-            }),
+        metadata.insert("input_tokens".to_string(), json!(120));
+        metadata.insert("output_tokens".to_string(), json!(30));
+        metadata.insert("total_tokens".to_string(), json!(150));
+
+        layer.handle_record(span_id, metadata).await;
+        layer.handle_span_close(span_id).await;
+        tokio::time::sleep(TEST_WAIT_DURATION).await;
+
+        let events = fixture.get_events().await;
+        assert_eq!(events.len(), 4); // trace-create, observation-create, 2x observation-update
+
+        let (event_type, body) = &events[2];
+        assert_eq!(event_type, "observation-update");
+        assert_eq!(body["type"], "GENERATION");
+        assert_eq!(
+            body["usage"],
+            json!({"input": 120, "output": 30, "total": 150, "unit": "TOKENS"})
         );
+        assert!(body.get("metadata").is_none());
 
-        let flattened = flatten_metadata(metadata);
-        assert_eq!(flattened["simple"], "value");
-        assert_eq!(flattened["complex"], "inner value");
+        let (_, close_body) = &events[3];
+        assert_eq!(close_body["type"], "GENERATION");
     }
 }
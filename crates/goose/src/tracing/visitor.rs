@@ -0,0 +1,141 @@
+use serde_json::{json, Value};
+use std::fmt;
+use tracing::field::{Field, Visit};
+use tracing::Level;
+
+pub fn map_level(level: &Level) -> &'static str {
+    match *level {
+        Level::ERROR => "ERROR",
+        Level::WARN => "WARNING",
+        Level::INFO => "DEFAULT",
+        Level::DEBUG => "DEBUG",
+        Level::TRACE => "DEBUG",
+    }
+}
+
+pub fn flatten_metadata(
+    metadata: serde_json::Map<String, Value>,
+) -> serde_json::Map<String, Value> {
+    let mut flattened = serde_json::Map::new();
+    for (key, value) in metadata {
+        match value {
+            Value::String(s) => {
+                flattened.insert(key, json!(s));
+            }
+            Value::Object(mut obj) => {
+                if let Some(text) = obj.remove("text") {
+                    flattened.insert(key, text);
+                } else {
+                    flattened.insert(key, json!(obj));
+                }
+            }
+            _ => {
+                flattened.insert(key, value);
+            }
+        }
+    }
+    flattened
+}
+
+/// Collects the fields of a span or event as JSON values keyed by field name
+#[derive(Debug)]
+pub(crate) struct JsonVisitor {
+    pub(crate) recorded_fields: serde_json::Map<String, Value>,
+}
+
+impl JsonVisitor {
+    pub(crate) fn new() -> Self {
+        Self {
+            recorded_fields: serde_json::Map::new(),
+        }
+    }
+
+    fn insert_value(&mut self, field: &Field, value: Value) {
+        self.recorded_fields.insert(field.name().to_string(), value);
+    }
+}
+
+macro_rules! record_field {
+    ($fn_name:ident, $type:ty) => {
+        fn $fn_name(&mut self, field: &Field, value: $type) {
+            self.insert_value(field, Value::from(value));
+        }
+    };
+}
+
+impl Visit for JsonVisitor {
+    record_field!(record_i64, i64);
+    record_field!(record_u64, u64);
+    record_field!(record_bool, bool);
+    record_field!(record_str, &str);
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert_value(field, Value::String(format!("{:?}", value)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::Layer;
+
+    #[test]
+    fn test_flatten_metadata() {
+        let mut metadata = serde_json::Map::new();
+        metadata.insert("simple".to_string(), json!("value"));
+        metadata.insert(
+            "complex".to_string(),
+            json!({
+                "text": "inner value"
+            }),
+        );
+
+        let flattened = flatten_metadata(metadata);
+        assert_eq!(flattened["simple"], "value");
+        assert_eq!(flattened["complex"], "inner value");
+    }
+
+    struct CaptureLayer(std::sync::Arc<std::sync::Mutex<serde_json::Map<String, Value>>>);
+
+    impl<S: tracing::Subscriber> Layer<S> for CaptureLayer {
+        fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+            let mut visitor = JsonVisitor::new();
+            event.record(&mut visitor);
+            *self.0.lock().unwrap() = visitor.recorded_fields;
+        }
+    }
+
+    #[test]
+    fn test_json_visitor_records_typed_fields() {
+        let fields = std::sync::Arc::new(std::sync::Mutex::new(serde_json::Map::new()));
+        let subscriber = tracing_subscriber::registry().with(CaptureLayer(fields.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(
+                count = 3u64,
+                delta = -2i64,
+                ok = true,
+                name = "goose",
+                path = ?std::path::Path::new("/tmp"),
+                "done"
+            );
+        });
+
+        let fields = fields.lock().unwrap();
+        assert_eq!(fields["count"], json!(3));
+        assert_eq!(fields["delta"], json!(-2));
+        assert_eq!(fields["ok"], json!(true));
+        assert_eq!(fields["name"], json!("goose"));
+        assert_eq!(fields["path"], json!("\"/tmp\""));
+        assert_eq!(fields["message"], json!("done"));
+    }
+
+    #[test]
+    fn test_map_level() {
+        assert_eq!(map_level(&Level::ERROR), "ERROR");
+        assert_eq!(map_level(&Level::WARN), "WARNING");
+        assert_eq!(map_level(&Level::INFO), "DEFAULT");
+        assert_eq!(map_level(&Level::TRACE), "DEBUG");
+    }
+}
@@ -41,6 +41,51 @@ pub fn safe_truncate(s: &str, max_chars: usize) -> String {
     }
 }
 
+/// The fence character and length when `line` opens or closes a fenced code block
+fn code_fence(line: &str) -> Option<(char, usize)> {
+    let line = line.trim_start();
+    let fence_char = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = line.chars().take_while(|c| *c == fence_char).count();
+    (len >= 3).then_some((fence_char, len))
+}
+
+/// The opening line of a fenced code block left open at the end of `text`, if any.
+pub fn open_code_fence(text: &str) -> Option<&str> {
+    let mut open: Option<(&str, char, usize)> = None;
+    for line in text.lines() {
+        let Some((fence_char, len)) = code_fence(line) else {
+            continue;
+        };
+        match open {
+            None => open = Some((line.trim(), fence_char, len)),
+            Some((_, open_char, open_len))
+                if fence_char == open_char
+                    && len >= open_len
+                    && line.trim().chars().all(|c| c == fence_char) =>
+            {
+                open = None
+            }
+            Some(_) => {}
+        }
+    }
+    open.map(|(line, _, _)| line)
+}
+
+/// Close a fenced code block left open at the end of `text`, so text clipped inside a
+/// code block still renders as valid markdown.
+pub fn close_open_code_fence(text: &mut String) {
+    let Some(closing) = open_code_fence(text).and_then(|line| {
+        let (fence_char, len) = code_fence(line)?;
+        Some(fence_char.to_string().repeat(len))
+    }) else {
+        return;
+    };
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(&closing);
+}
+
 pub fn is_token_cancelled(cancellation_token: &Option<CancellationToken>) -> bool {
     cancellation_token
         .as_ref()
@@ -125,4 +170,35 @@ mod tests {
         assert_eq!(safe_truncate(mixed, 20), mixed);
         assert_eq!(safe_truncate(mixed, 8), "Hello...");
     }
+
+    #[test]
+    fn test_close_open_code_fence() {
+        let mut text = "Here is the fix:\n\n```rust\nfn main() {\n    pr".to_string();
+        close_open_code_fence(&mut text);
+        assert_eq!(
+            text,
+            "Here is the fix:\n\n```rust\nfn main() {\n    pr\n```"
+        );
+        assert!(open_code_fence(&text).is_none());
+
+        let mut closed = text.clone();
+        close_open_code_fence(&mut closed);
+        assert_eq!(closed, text);
+    }
+
+    #[test]
+    fn test_open_code_fence_tracks_matching_closers() {
+        assert_eq!(open_code_fence("```python\nprint(1)\n"), Some("```python"));
+        assert_eq!(open_code_fence("```python\nprint(1)\n```\n"), None);
+        // A shorter or different fence inside a block does not close it
+        assert_eq!(
+            open_code_fence("````md\n```\nnested\n```\n"),
+            Some("````md")
+        );
+        assert_eq!(open_code_fence("~~~\n```\n"), Some("~~~"));
+
+        let mut text = "~~~~\nunfinished".to_string();
+        close_open_code_fence(&mut text);
+        assert_eq!(text, "~~~~\nunfinished\n~~~~");
+    }
 }
@@ -9,6 +9,218 @@ use goose::config::extensions::{set_extension, ExtensionEntry};
 mod tests {
     use super::*;
 
+    /// A configurable provider and the session plumbing shared by the reply tests
+    mod mock {
+        use async_trait::async_trait;
+        use futures::StreamExt;
+        use goose::agents::{Agent, AgentEvent, SessionConfig};
+        use goose::conversation::message::{Message, MessageContent};
+        use goose::conversation::Conversation;
+        use goose::model::ModelConfig;
+        use goose::providers::base::{
+            MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage,
+        };
+        use goose::providers::errors::ProviderError;
+        use goose::providers::retry::{ProviderRetry, RetryConfig};
+        use goose::session::session_manager::SessionType;
+        use goose::session::{Session, SessionManager};
+        use rmcp::model::Tool;
+        use std::path::PathBuf;
+        use std::sync::Arc;
+
+        type Respond =
+            dyn Fn(&ModelConfig, &[Message]) -> Result<Message, ProviderError> + Send + Sync;
+
+        /// Answers each request with whatever `respond` returns for the model and messages
+        /// sent, going through the provider retry loop like a real provider
+        pub struct MockProvider {
+            model_config: ModelConfig,
+            respond: Arc<Respond>,
+            usage: Usage,
+            retry_config: Option<RetryConfig>,
+            stream_chunks: Vec<Message>,
+        }
+
+        impl MockProvider {
+            pub fn new(
+                respond: impl Fn(&ModelConfig, &[Message]) -> Result<Message, ProviderError>
+                    + Send
+                    + Sync
+                    + 'static,
+            ) -> Self {
+                Self {
+                    model_config: ModelConfig::new("mock-model").unwrap(),
+                    respond: Arc::new(respond),
+                    usage: Usage::default(),
+                    retry_config: None,
+                    stream_chunks: Vec::new(),
+                }
+            }
+
+            /// Answer every request with the same text
+            pub fn replying(reply: &str) -> Self {
+                let reply = reply.to_string();
+                Self::new(move |_, _| Ok(Message::assistant().with_text(&reply)))
+            }
+
+            pub fn with_usage(mut self, usage: Usage) -> Self {
+                self.usage = usage;
+                self
+            }
+
+            pub fn with_model_config(mut self, model_config: ModelConfig) -> Self {
+                self.model_config = model_config;
+                self
+            }
+
+            pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+                self.retry_config = Some(retry_config);
+                self
+            }
+
+            /// Stream `chunks` when the agent streams, rather than the whole reply
+            pub fn with_stream(mut self, chunks: Vec<Message>) -> Self {
+                self.stream_chunks = chunks;
+                self
+            }
+        }
+
+        #[async_trait]
+        impl Provider for MockProvider {
+            async fn complete_with_model(
+                &self,
+                model_config: &ModelConfig,
+                _system_prompt: &str,
+                messages: &[Message],
+                _tools: &[Tool],
+            ) -> anyhow::Result<(Message, ProviderUsage), ProviderError> {
+                let message = self
+                    .with_retry(|| async { (self.respond)(model_config, messages) })
+                    .await?;
+                Ok((
+                    message,
+                    ProviderUsage::new(model_config.model_name.clone(), self.usage),
+                ))
+            }
+
+            async fn stream(
+                &self,
+                _system: &str,
+                _messages: &[Message],
+                _tools: &[Tool],
+            ) -> Result<MessageStream, ProviderError> {
+                let chunks: Vec<_> = self
+                    .stream_chunks
+                    .iter()
+                    .map(|chunk| Ok((Some(chunk.clone()), None)))
+                    .collect();
+                Ok(Box::pin(futures::stream::iter(chunks)))
+            }
+
+            fn supports_streaming(&self) -> bool {
+                !self.stream_chunks.is_empty()
+            }
+
+            async fn with_model(
+                &self,
+                model: ModelConfig,
+            ) -> Result<Arc<dyn Provider>, ProviderError> {
+                Ok(Arc::new(Self {
+                    model_config: model,
+                    respond: self.respond.clone(),
+                    usage: self.usage,
+                    retry_config: self.retry_config.clone(),
+                    stream_chunks: self.stream_chunks.clone(),
+                }))
+            }
+
+            async fn generate_session_name(
+                &self,
+                _messages: &Conversation,
+            ) -> Result<String, ProviderError> {
+                Ok("Mock session".to_string())
+            }
+
+            fn retry_config(&self) -> RetryConfig {
+                self.retry_config
+                    .clone()
+                    .unwrap_or_else(RetryConfig::from_config)
+            }
+
+            fn get_model_config(&self) -> ModelConfig {
+                self.model_config.clone()
+            }
+
+            fn metadata() -> ProviderMetadata {
+                ProviderMetadata::empty()
+            }
+
+            fn get_name(&self) -> &str {
+                "mock"
+            }
+        }
+
+        pub async fn hidden_session(name: &str) -> anyhow::Result<Session> {
+            SessionManager::create_session(
+                PathBuf::default(),
+                name.to_string(),
+                SessionType::Hidden,
+            )
+            .await
+        }
+
+        /// Settings for replying in session `id`, with nothing overridden
+        pub fn session_config(id: &str) -> SessionConfig {
+            SessionConfig {
+                id: id.to_string(),
+                schedule_id: None,
+                max_turns: None,
+                retry_config: None,
+                compact_threshold: None,
+                turn_budget: None,
+            }
+        }
+
+        /// Send `message` and collect the reply's events, stopping at the first error
+        pub async fn run_reply(
+            agent: &Agent,
+            message: Message,
+            config: SessionConfig,
+        ) -> anyhow::Result<Vec<AgentEvent>> {
+            let reply_stream = agent.reply(message, config, None).await?;
+            tokio::pin!(reply_stream);
+
+            let mut events = Vec::new();
+            while let Some(event) = reply_stream.next().await {
+                events.push(event?);
+            }
+            Ok(events)
+        }
+
+        /// The messages among `events`
+        pub fn messages(events: &[AgentEvent]) -> Vec<&Message> {
+            events
+                .iter()
+                .filter_map(|event| match event {
+                    AgentEvent::Message(message) => Some(message),
+                    _ => None,
+                })
+                .collect()
+        }
+
+        /// The text of the last message among `events` that opens with text
+        pub fn last_text(events: &[AgentEvent]) -> String {
+            messages(events)
+                .iter()
+                .rev()
+                .find_map(|message| match message.content.first() {
+                    Some(MessageContent::Text(text)) => Some(text.text.clone()),
+                    _ => None,
+                })
+                .unwrap_or_default()
+        }
+    }
+
     #[cfg(test)]
     mod schedule_tool_tests {
         use super::*;
@@ -278,101 +490,31 @@ mod tests {
 
     #[cfg(test)]
     mod max_turns_tests {
+        use super::mock::{hidden_session, session_config, MockProvider};
         use super::*;
-        use async_trait::async_trait;
-        use goose::agents::SessionConfig;
         use goose::conversation::message::{Message, MessageContent};
-        use goose::model::ModelConfig;
-        use goose::providers::base::{Provider, ProviderMetadata, ProviderUsage, Usage};
-        use goose::providers::errors::ProviderError;
-        use goose::session::session_manager::SessionType;
-        use goose::session::SessionManager;
-        use rmcp::model::{CallToolRequestParam, Tool};
+        use goose::providers::base::Usage;
+        use rmcp::model::CallToolRequestParam;
         use rmcp::object;
-        use std::path::PathBuf;
-
-        struct MockToolProvider {}
 
-        impl MockToolProvider {
-            fn new() -> Self {
-                Self {}
-            }
-        }
-
-        #[async_trait]
-        impl Provider for MockToolProvider {
-            async fn complete(
-                &self,
-                _system_prompt: &str,
-                _messages: &[Message],
-                _tools: &[Tool],
-            ) -> Result<(Message, ProviderUsage), ProviderError> {
+        #[tokio::test]
+        async fn test_max_turns_limit() -> Result<()> {
+            let agent = Agent::new();
+            let provider = MockProvider::new(|_, _| {
                 let tool_call = CallToolRequestParam {
                     name: "test_tool".into(),
                     arguments: Some(object!({"param": "value"})),
                 };
-                let message = Message::assistant().with_tool_request("call_123", Ok(tool_call));
-
-                let usage = ProviderUsage::new(
-                    "mock-model".to_string(),
-                    Usage::new(Some(10), Some(5), Some(15)),
-                );
-
-                Ok((message, usage))
-            }
-
-            async fn complete_with_model(
-                &self,
-                _model_config: &ModelConfig,
-                system_prompt: &str,
-                messages: &[Message],
-                tools: &[Tool],
-            ) -> anyhow::Result<(Message, ProviderUsage), ProviderError> {
-                self.complete(system_prompt, messages, tools).await
-            }
-
-            fn get_model_config(&self) -> ModelConfig {
-                ModelConfig::new("mock-model").unwrap()
-            }
-
-            fn metadata() -> ProviderMetadata {
-                ProviderMetadata {
-                    name: "mock".to_string(),
-                    display_name: "Mock Provider".to_string(),
-                    description: "Mock provider for testing".to_string(),
-                    default_model: "mock-model".to_string(),
-                    known_models: vec![],
-                    model_doc_link: "".to_string(),
-                    config_keys: vec![],
-                }
-            }
-
-            fn get_name(&self) -> &str {
-                "mock-test"
-            }
-        }
-
-        #[tokio::test]
-        async fn test_max_turns_limit() -> Result<()> {
-            let agent = Agent::new();
-            let provider = Arc::new(MockToolProvider::new());
-            agent.update_provider(provider).await?;
+                Ok(Message::assistant().with_tool_request("call_123", Ok(tool_call)))
+            })
+            .with_usage(Usage::new(Some(10), Some(5), Some(15)));
+            agent.update_provider(Arc::new(provider)).await?;
             let user_message = Message::user().with_text("Hello");
 
-            let session = SessionManager::create_session(
-                PathBuf::default(),
-                "max-turn-test".to_string(),
-                SessionType::Hidden,
-            )
-            .await?;
-            let session_config = SessionConfig {
-                id: session.id,
-                schedule_id: None,
-                max_turns: None,
-                retry_config: None,
-            };
-
-            let reply_stream = agent.reply(user_message, session_config, None).await?;
+            let session = hidden_session("max-turn-test").await?;
+            let reply_stream = agent
+                .reply(user_message, session_config(&session.id), None)
+                .await?;
             tokio::pin!(reply_stream);
 
             let mut responses = Vec::new();
@@ -423,6 +565,740 @@ mod tests {
         }
     }
 
+    #[cfg(test)]
+    mod empty_response_tests {
+        use super::mock::{hidden_session, messages, run_reply, session_config, MockProvider};
+        use super::*;
+        use goose::conversation::message::{Message, MessageContent};
+        use goose::providers::errors::ProviderError;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Answers with `respond`, counting each call in `calls`
+        fn counting_provider(
+            calls: &Arc<AtomicUsize>,
+            respond: fn() -> Result<Message, ProviderError>,
+        ) -> MockProvider {
+            let calls = calls.clone();
+            MockProvider::new(move |_, _| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                respond()
+            })
+        }
+
+        fn bad_request() -> Result<Message, ProviderError> {
+            Err(ProviderError::RequestFailed("bad request".to_string()))
+        }
+
+        #[tokio::test]
+        async fn test_empty_response_terminates_loop() -> Result<()> {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let agent = Agent::new();
+            agent
+                .update_provider(Arc::new(counting_provider(&calls, || {
+                    Ok(Message::assistant())
+                })))
+                .await?;
+
+            let session = hidden_session("empty-response-test").await?;
+            let events = run_reply(
+                &agent,
+                Message::user().with_text("Hello"),
+                session_config(&session.id),
+            )
+            .await?;
+
+            let responses = messages(&events);
+            assert_eq!(calls.load(Ordering::SeqCst), 2);
+            assert_eq!(responses.len(), 1);
+            match responses[0].content.first() {
+                Some(MessageContent::Text(text)) => {
+                    assert!(text.text.contains("empty response"))
+                }
+                other => panic!("Expected a text note, got {:?}", other),
+            }
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn test_provider_error_is_not_retried_as_empty_response() -> Result<()> {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let agent = Agent::new();
+            agent
+                .update_provider(Arc::new(counting_provider(&calls, bad_request)))
+                .await?;
+
+            let session = hidden_session("provider-error-test").await?;
+            let events = run_reply(
+                &agent,
+                Message::user().with_text("Hello"),
+                session_config(&session.id),
+            )
+            .await?;
+
+            let responses = messages(&events);
+            assert_eq!(calls.load(Ordering::SeqCst), 1);
+            assert_eq!(responses.len(), 1);
+            assert!(responses[0].as_concat_text().contains("bad request"));
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn test_provider_failure_error_carries_provider_error() -> Result<()> {
+            let agent = Agent::new();
+            agent
+                .update_provider(Arc::new(MockProvider::new(|_, _| bad_request())))
+                .await?;
+            agent.set_error_on_provider_failure(true);
+
+            let session = hidden_session("provider-error-typed-test").await?;
+            let failure = run_reply(
+                &agent,
+                Message::user().with_text("Hello"),
+                session_config(&session.id),
+            )
+            .await
+            .expect_err("the reply should end with an error");
+
+            assert_eq!(
+                failure.downcast_ref::<ProviderError>(),
+                Some(&ProviderError::RequestFailed("bad request".to_string()))
+            );
+            Ok(())
+        }
+    }
+
+    mod compact_threshold_tests {
+        use super::mock::{hidden_session, run_reply, session_config, MockProvider};
+        use super::*;
+        use goose::agents::SessionConfig;
+        use goose::conversation::message::Message;
+        use goose::model::ModelConfig;
+        use goose::providers::base::Usage;
+        use goose::session::SessionManager;
+
+        const CONTEXT_LIMIT: usize = 1000;
+
+        /// Reply once in a session already holding `used_tokens` of context and count how
+        /// often the history was replaced by a summary
+        async fn compactions_for(used_tokens: i32, compact_threshold: f64) -> Result<usize> {
+            let provider = MockProvider::replying("A short summary")
+                .with_usage(Usage::new(Some(50), Some(10), None))
+                .with_model_config(
+                    ModelConfig::new("mock-model")?.with_context_limit(Some(CONTEXT_LIMIT)),
+                );
+            let agent = Agent::new();
+            agent.update_provider(Arc::new(provider)).await?;
+
+            let session = hidden_session("compact-threshold-test").await?;
+            SessionManager::add_message(&session.id, &Message::user().with_text("Earlier")).await?;
+            SessionManager::add_message(
+                &session.id,
+                &Message::assistant().with_text("Earlier answer"),
+            )
+            .await?;
+            SessionManager::update_session(&session.id)
+                .total_tokens(Some(used_tokens))
+                .apply()
+                .await?;
+
+            let config = SessionConfig {
+                compact_threshold: Some(compact_threshold),
+                ..session_config(&session.id)
+            };
+            let events = run_reply(&agent, Message::user().with_text("Next"), config).await?;
+            Ok(events
+                .iter()
+                .filter(|event| matches!(event, AgentEvent::HistoryReplaced(_)))
+                .count())
+        }
+
+        #[tokio::test]
+        async fn test_crossing_compact_threshold_summarizes_once() -> Result<()> {
+            // 75% of the context is in use
+            assert_eq!(compactions_for(750, 0.7).await?, 1);
+            assert_eq!(compactions_for(750, 0.8).await?, 0);
+            Ok(())
+        }
+    }
+
+    mod context_recovery_tests {
+        use super::mock::{hidden_session, last_text, run_reply, session_config, MockProvider};
+        use super::*;
+        use goose::conversation::message::Message;
+        use goose::providers::base::Usage;
+        use goose::providers::errors::ProviderError;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Reply once against a provider rejecting the first `failures` replies as too long,
+        /// while always answering summarization requests; returns the reply calls made, the
+        /// number of compactions and the text of the last message
+        async fn reply_with_failures(failures: usize) -> Result<(usize, usize, String)> {
+            let failures = AtomicUsize::new(failures);
+            let reply_calls = Arc::new(AtomicUsize::new(0));
+            let provider = {
+                let reply_calls = reply_calls.clone();
+                MockProvider::new(move |_, messages| {
+                    let is_summary_request = messages
+                        .last()
+                        .is_some_and(|m| m.as_concat_text().starts_with("Please summarize"));
+                    if is_summary_request {
+                        return Ok(Message::assistant().with_text("A short summary"));
+                    }
+
+                    reply_calls.fetch_add(1, Ordering::SeqCst);
+                    if failures
+                        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                        .is_ok()
+                    {
+                        return Err(ProviderError::context_length_exceeded("prompt is too long"));
+                    }
+                    Ok(Message::assistant().with_text("Recovered answer"))
+                })
+                .with_usage(Usage::new(Some(10), Some(5), None))
+            };
+            let agent = Agent::new();
+            agent.update_provider(Arc::new(provider)).await?;
+
+            let session = hidden_session("context-recovery-test").await?;
+            let events = run_reply(
+                &agent,
+                Message::user().with_text("Hello"),
+                session_config(&session.id),
+            )
+            .await?;
+
+            let compactions = events
+                .iter()
+                .filter(|event| matches!(event, AgentEvent::HistoryReplaced(_)))
+                .count();
+            Ok((
+                reply_calls.load(Ordering::SeqCst),
+                compactions,
+                last_text(&events),
+            ))
+        }
+
+        #[tokio::test]
+        async fn test_context_length_error_compacts_and_retries_once() -> Result<()> {
+            let (calls, compactions, last_text) = reply_with_failures(1).await?;
+            assert_eq!(calls, 2);
+            assert_eq!(compactions, 1);
+            assert_eq!(last_text, "Recovered answer");
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn test_repeated_context_length_error_fails() -> Result<()> {
+            let (calls, compactions, last_text) = reply_with_failures(3).await?;
+            assert_eq!(calls, 2);
+            assert_eq!(compactions, 1);
+            assert!(last_text.contains("still too long"));
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod model_fallback_tests {
+        use super::mock::{hidden_session, last_text, run_reply, session_config, MockProvider};
+        use super::*;
+        use goose::conversation::message::Message;
+        use goose::model::ModelConfig;
+        use goose::providers::base::Usage;
+        use goose::providers::errors::ProviderError;
+        use goose::session::{ExtensionState, ModelState, SessionManager};
+        use std::sync::Mutex;
+
+        const RETIRED_MODEL: &str = "retired-model";
+
+        /// Reply once with `model_config` against a provider that reports `RETIRED_MODEL` as
+        /// not found and answers with any other model; returns the models requested, the
+        /// model changes announced, the text of the last message and the model saved with
+        /// the session
+        async fn reply_with_model(
+            model_config: ModelConfig,
+        ) -> Result<(Vec<String>, Vec<String>, String, Option<String>)> {
+            let models_called = Arc::new(Mutex::new(Vec::new()));
+            let provider = {
+                let models_called = models_called.clone();
+                MockProvider::new(move |model_config, _| {
+                    let model = model_config.model_name.clone();
+                    models_called.lock().unwrap().push(model.clone());
+                    if model == RETIRED_MODEL {
+                        return Err(ProviderError::ModelNotFound(format!(
+                            "The model '{}' does not exist",
+                            model
+                        )));
+                    }
+                    Ok(Message::assistant().with_text(format!("Answer from {}", model)))
+                })
+                .with_usage(Usage::new(Some(10), Some(5), None))
+                .with_model_config(model_config)
+            };
+            let agent = Agent::new();
+            agent.update_provider(Arc::new(provider)).await?;
+
+            let session = hidden_session("model-fallback-test").await?;
+            let events = run_reply(
+                &agent,
+                Message::user().with_text("Hello"),
+                session_config(&session.id),
+            )
+            .await?;
+
+            let mut model_changes = Vec::new();
+            for event in &events {
+                if let AgentEvent::ModelChange { model, mode } = event {
+                    assert_eq!(mode, "fallback");
+                    model_changes.push(model.clone());
+                }
+            }
+            let models_called = models_called.lock().unwrap().clone();
+            let saved_model = ModelState::from_extension_data(
+                &SessionManager::get_session(&session.id, false)
+                    .await?
+                    .extension_data,
+            )
+            .map(|state| state.model);
+            Ok((
+                models_called,
+                model_changes,
+                last_text(&events),
+                saved_model,
+            ))
+        }
+
+        #[tokio::test]
+        async fn test_model_not_found_switches_to_fallback() -> Result<()> {
+            let model_config =
+                ModelConfig::new(RETIRED_MODEL)?.with_fallback_model("current-model".to_string());
+            let (models_called, model_changes, last_text, saved_model) =
+                reply_with_model(model_config).await?;
+
+            assert_eq!(model_changes, vec!["current-model"]);
+            assert_eq!(saved_model.as_deref(), Some("current-model"));
+            assert_eq!(last_text, "Answer from current-model");
+            let first_fallback_call = models_called
+                .iter()
+                .position(|model| model == "current-model")
+                .unwrap();
+            assert!(models_called[..first_fallback_call]
+                .iter()
+                .all(|model| model == RETIRED_MODEL));
+            Ok(())
+        }
+
+        #[tokio::test]
+        async fn test_model_not_found_without_fallback_fails() -> Result<()> {
+            let mut model_config = ModelConfig::new(RETIRED_MODEL)?;
+            model_config.fallback_model = None;
+            let (models_called, model_changes, last_text, saved_model) =
+                reply_with_model(model_config).await?;
+
+            assert!(model_changes.is_empty());
+            assert_eq!(saved_model, None);
+            assert!(models_called.iter().all(|model| model == RETIRED_MODEL));
+            assert!(last_text.contains("Model not found"));
+            assert!(last_text.contains("GOOSE_FALLBACK_MODEL"));
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod retry_budget_tests {
+        use super::mock::{hidden_session, last_text, run_reply, session_config, MockProvider};
+        use super::*;
+        use goose::agents::SessionConfig;
+        use goose::conversation::message::Message;
+        use goose::providers::errors::ProviderError;
+        use goose::providers::retry::RetryConfig;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[tokio::test]
+        async fn test_transient_failures_exhaust_turn_budget() -> Result<()> {
+            // Always overloaded; retries generously with no backoff so only the turn budget
+            // stops it
+            let attempts = Arc::new(AtomicUsize::new(0));
+            let provider = {
+                let attempts = attempts.clone();
+                MockProvider::new(move |_, _| {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    Err(ProviderError::ServerError("overloaded".to_string()))
+                })
+                .with_retry_config(RetryConfig::new(100, 0, 1.0, 0))
+            };
+            let agent = Agent::new();
+            agent.update_provider(Arc::new(provider)).await?;
+
+            let session = hidden_session("retry-budget-test").await?;
+            let config = SessionConfig {
+                turn_budget: Some(3),
+                ..session_config(&session.id)
+            };
+            let events = run_reply(&agent, Message::user().with_text("Hello"), config).await?;
+
+            // The first attempt plus the three retries the budget allows
+            assert_eq!(attempts.load(Ordering::SeqCst), 4);
+            assert!(last_text(&events).contains("retry budget of 3 retries"));
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod no_stream_tests {
+        use super::mock::{hidden_session, messages, run_reply, session_config, MockProvider};
+        use super::*;
+        use goose::conversation::message::Message;
+        use goose::session::SessionManager;
+        use rmcp::model::Role;
+
+        async fn final_history(no_stream: bool) -> Result<(usize, Vec<(Role, String)>)> {
+            // Streams the reply in two chunks, or returns it whole from `complete`
+            let chunks = ["Hello", " there"]
+                .map(|text| Message::assistant().with_id("reply").with_text(text))
+                .to_vec();
+            let provider = MockProvider::new(|_, _| {
+                Ok(Message::assistant()
+                    .with_id("reply")
+                    .with_text("Hello there"))
+            })
+            .with_stream(chunks);
+            let agent = Agent::new();
+            agent.set_no_stream(no_stream);
+            agent.update_provider(Arc::new(provider)).await?;
+
+            let session = hidden_session("no-stream-test").await?;
+            let events = run_reply(
+                &agent,
+                Message::user().with_text("Hi"),
+                session_config(&session.id),
+            )
+            .await?;
+
+            let conversation = SessionManager::get_session(&session.id, true)
+                .await?
+                .conversation
+                .unwrap_or_default();
+            let history = conversation
+                .messages()
+                .iter()
+                .map(|m| (m.role.clone(), m.as_concat_text()))
+                .collect();
+            Ok((messages(&events).len(), history))
+        }
+
+        #[tokio::test]
+        async fn test_no_stream_matches_streamed_history() -> Result<()> {
+            let (streamed_events, streamed) = final_history(false).await?;
+            let (whole_events, whole) = final_history(true).await?;
+
+            assert_eq!(streamed_events, 2);
+            assert_eq!(whole_events, 1);
+            assert_eq!(streamed, whole);
+            assert_eq!(
+                whole.last(),
+                Some(&(Role::Assistant, "Hello there".to_string()))
+            );
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod usage_persistence_tests {
+        use super::mock::{hidden_session, run_reply, session_config, MockProvider};
+        use super::*;
+        use goose::conversation::message::Message;
+        use goose::providers::base::Usage;
+        use goose::providers::pricing::PricingInfo;
+        use goose::providers::usage_report::UsageReport;
+        use goose::session::SessionManager;
+
+        fn priced(mut report: UsageReport) -> UsageReport {
+            for item in &mut report.items {
+                item.pricing = Some(PricingInfo {
+                    input_cost: 0.000_01,
+                    output_cost: 0.000_03,
+                    context_length: None,
+                });
+            }
+            report
+        }
+
+        #[tokio::test]
+        async fn test_usage_carries_over_when_session_is_resumed() -> Result<()> {
+            let session = hidden_session("usage-persistence-test").await?;
+
+            let provider = MockProvider::replying("Done").with_usage(Usage::new(
+                Some(100),
+                Some(20),
+                Some(120),
+            ));
+            let agent = Agent::new();
+            agent.update_provider(Arc::new(provider)).await?;
+            run_reply(
+                &agent,
+                Message::user().with_text("Hi"),
+                session_config(&session.id),
+            )
+            .await?;
+            let original = priced(agent.usage().await);
+            assert!(!original.is_empty());
+
+            let resumed = Agent::new();
+            assert!(resumed.usage().await.is_empty());
+            resumed
+                .restore_usage(&SessionManager::get_session(&session.id, false).await?)
+                .await;
+            let restored = priced(resumed.usage().await);
+
+            assert_eq!(restored.items.len(), original.items.len());
+            assert_eq!(restored.items[0].provider, "mock");
+            assert_eq!(restored.items[0].model, "mock-model");
+            assert_eq!(
+                restored.total_usage().total_tokens,
+                original.total_usage().total_tokens
+            );
+            assert_eq!(restored.total_cost(), original.total_cost());
+            Ok(())
+        }
+    }
+
+    mod post_processor_tests {
+        use super::mock::{hidden_session, messages, run_reply, session_config, MockProvider};
+        use super::*;
+        use goose::agents::{PostProcessorRule, PostProcessors};
+        use goose::conversation::message::Message;
+        use goose::session::SessionManager;
+
+        const REPLY: &str = "The token is sk-live-1234abcd, keep it safe.";
+
+        /// The assistant text yielded by one reply, and the assistant text then saved
+        async fn reply(redact_history: bool) -> Result<(String, String)> {
+            let session = hidden_session("post-processor-test").await?;
+
+            let agent = Agent::new();
+            agent
+                .update_provider(Arc::new(MockProvider::replying(REPLY)))
+                .await?;
+            let rule = PostProcessorRule {
+                pattern: r"sk-live-\w+".to_string(),
+                replacement: "[redacted]".to_string(),
+            };
+            agent
+                .set_post_processors(PostProcessors::new(&[rule], redact_history)?)
+                .await;
+
+            let events = run_reply(
+                &agent,
+                Message::user().with_text("What is the token?"),
+                session_config(&session.id),
+            )
+            .await?;
+            let shown: String = messages(&events)
+                .iter()
+                .map(|message| message.as_concat_text())
+                .collect();
+
+            let saved = SessionManager::get_session(&session.id, true)
+                .await?
+                .conversation
+                .unwrap_or_default();
+            let saved = saved.messages().last().unwrap().as_concat_text();
+            SessionManager::delete_session(&session.id).await?;
+            Ok((shown, saved))
+        }
+
+        #[tokio::test]
+        async fn test_redaction_rule_masks_shown_output() -> Result<()> {
+            let masked = "The token is [redacted], keep it safe.";
+
+            let (shown, saved) = reply(false).await?;
+            assert_eq!(shown, masked);
+            assert_eq!(saved, REPLY);
+
+            let (shown, saved) = reply(true).await?;
+            assert_eq!(shown, masked);
+            assert_eq!(saved, masked);
+            Ok(())
+        }
+    }
+
+    mod duplicate_tool_call_tests {
+        use super::mock::{
+            hidden_session, last_text, messages, run_reply, session_config, MockProvider,
+        };
+        use super::*;
+        use goose::agents::extension::ExtensionConfig;
+        use goose::agents::mcp_client::{Error, McpClientTrait};
+        use goose::conversation::message::{Message, MessageContent};
+        use rmcp::model::{
+            CallToolRequestParam, CallToolResult, Content, GetPromptResult, InitializeResult,
+            JsonObject, ListPromptsResult, ListResourcesResult, ListToolsResult,
+            ReadResourceResult, ServerNotification, Tool,
+        };
+        use rmcp::object;
+        use serde_json::Value;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::sync::{mpsc, Mutex};
+        use tokio_util::sync::CancellationToken;
+
+        /// Offers a single `count` tool and counts how often it is called
+        struct CountingClient {
+            calls: Arc<AtomicUsize>,
+        }
+
+        #[async_trait::async_trait]
+        impl McpClientTrait for CountingClient {
+            async fn list_resources(
+                &self,
+                _next_cursor: Option<String>,
+                _cancel_token: CancellationToken,
+            ) -> Result<ListResourcesResult, Error> {
+                Err(Error::TransportClosed)
+            }
+
+            async fn read_resource(
+                &self,
+                _uri: &str,
+                _cancel_token: CancellationToken,
+            ) -> Result<ReadResourceResult, Error> {
+                Err(Error::TransportClosed)
+            }
+
+            async fn list_tools(
+                &self,
+                _next_cursor: Option<String>,
+                _cancel_token: CancellationToken,
+            ) -> Result<ListToolsResult, Error> {
+                Ok(ListToolsResult {
+                    tools: vec![Tool::new(
+                        "count".to_string(),
+                        "Count one call".to_string(),
+                        Arc::new(object!({"type": "object"})),
+                    )],
+                    next_cursor: None,
+                })
+            }
+
+            async fn call_tool(
+                &self,
+                _name: &str,
+                _arguments: Option<JsonObject>,
+                _cancel_token: CancellationToken,
+            ) -> Result<CallToolResult, Error> {
+                let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+                Ok(CallToolResult {
+                    content: vec![Content::text(format!("call {}", call))],
+                    is_error: None,
+                    structured_content: None,
+                    meta: None,
+                })
+            }
+
+            async fn list_prompts(
+                &self,
+                _next_cursor: Option<String>,
+                _cancel_token: CancellationToken,
+            ) -> Result<ListPromptsResult, Error> {
+                Err(Error::TransportClosed)
+            }
+
+            async fn get_prompt(
+                &self,
+                _name: &str,
+                _arguments: Value,
+                _cancel_token: CancellationToken,
+            ) -> Result<GetPromptResult, Error> {
+                Err(Error::TransportClosed)
+            }
+
+            async fn subscribe(&self) -> mpsc::Receiver<ServerNotification> {
+                mpsc::channel(1).1
+            }
+
+            fn get_info(&self) -> Option<&InitializeResult> {
+                None
+            }
+        }
+
+        #[tokio::test]
+        async fn test_identical_tool_requests_in_one_reply_dispatch_once() -> Result<()> {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let agent = Agent::new();
+            agent
+                .extension_manager
+                .add_client(
+                    "counter".to_string(),
+                    ExtensionConfig::Builtin {
+                        name: "counter".to_string(),
+                        display_name: None,
+                        description: "Counts tool calls".to_string(),
+                        timeout: None,
+                        bundled: None,
+                        available_tools: vec![],
+                    },
+                    Arc::new(Mutex::new(Box::new(CountingClient {
+                        calls: calls.clone(),
+                    }))),
+                    None,
+                    None,
+                )
+                .await;
+
+            // Asks for the same call twice in one message, then answers once results are in
+            let provider = MockProvider::new(|_, messages| {
+                let has_results = messages.last().is_some_and(|message| {
+                    message
+                        .content
+                        .iter()
+                        .any(|content| matches!(content, MessageContent::ToolResponse(_)))
+                });
+                if has_results {
+                    return Ok(Message::assistant().with_text("Counted"));
+                }
+                let count = || {
+                    Ok(CallToolRequestParam {
+                        name: "counter__count".into(),
+                        arguments: Some(object!({"step": 1})),
+                    })
+                };
+                Ok(Message::assistant()
+                    .with_tool_request("call_1", count())
+                    .with_tool_request("call_2", count()))
+            });
+            agent.update_provider(Arc::new(provider)).await?;
+
+            let session = hidden_session("duplicate-tool-call-test").await?;
+            let events = run_reply(
+                &agent,
+                Message::user().with_text("Count"),
+                session_config(&session.id),
+            )
+            .await?;
+
+            assert_eq!(calls.load(Ordering::SeqCst), 1);
+            let responses: Vec<_> = messages(&events)
+                .iter()
+                .flat_map(|message| message.content.iter())
+                .filter_map(|content| match content {
+                    MessageContent::ToolResponse(response) => {
+                        Some((response.id.clone(), response.tool_result.clone().ok()))
+                    }
+                    _ => None,
+                })
+                .collect();
+            let result = Some(vec![Content::text("call 1")]);
+            assert_eq!(
+                responses,
+                vec![
+                    ("call_1".to_string(), result.clone()),
+                    ("call_2".to_string(), result)
+                ]
+            );
+            assert_eq!(last_text(&events), "Counted");
+            Ok(())
+        }
+    }
+
     #[cfg(test)]
     mod extension_manager_tests {
         use super::*;
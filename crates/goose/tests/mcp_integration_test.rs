@@ -251,6 +251,7 @@ async fn test_replayed_session(
         timeout: Some(30),
         bundled: Some(false),
         available_tools: vec![],
+        framing: Default::default(),
     };
 
     let provider = Arc::new(tokio::sync::Mutex::new(Some(Arc::new(MockProvider {
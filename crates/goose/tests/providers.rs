@@ -243,7 +243,10 @@ impl ProviderTester {
             "Expected error when context window is exceeded"
         );
         assert!(
-            matches!(result.unwrap_err(), ProviderError::ContextLengthExceeded(_)),
+            matches!(
+                result.unwrap_err(),
+                ProviderError::ContextLengthExceeded { .. }
+            ),
             "Expected error to be ContextLengthExceeded"
         );
 